@@ -0,0 +1,187 @@
+use std::{net::IpAddr, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{docs::ConfigDocs, ConfigError};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AccessMode {
+    /// Every IP can connect, `ips` is ignored
+    #[default]
+    AllowAll,
+    /// Only IPs in `ips` can connect
+    AllowList,
+    /// IPs in `ips` can't connect
+    DenyList,
+}
+
+/// A static list of IPs which are allowed or denied to connect, independent of bans
+#[derive(Deserialize, Serialize, Default, ConfigDocs)]
+#[serde(default)]
+pub struct AccessControlConfig {
+    /// Whether `ips` is ignored (`AllowAll`), an allow list (`AllowList`) or a deny list (`DenyList`)
+    pub mode: AccessMode,
+    /// Single IPs (`192.168.0.5`) or CIDR ranges (`10.0.0.0/8`, `fd00::/8`)
+    pub ips: Vec<String>,
+}
+
+impl AccessControlConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for ip in &self.ips {
+            if let Err(err) = IpRange::from_str(ip) {
+                return Err(ConfigError::validation(
+                    "access_control.ips",
+                    format!("Invalid access_control IP {:?}: {}", ip, err),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_ip_allowed(&self, addr: IpAddr) -> bool {
+        let listed = || {
+            self.ips
+                .iter()
+                .filter_map(|ip| IpRange::from_str(ip).ok())
+                .any(|range| range.contains(addr))
+        };
+        match self.mode {
+            AccessMode::AllowAll => true,
+            AccessMode::AllowList => listed(),
+            AccessMode::DenyList => !listed(),
+        }
+    }
+}
+
+/// The address to compare with the configured IPs and bans.
+/// IPv4 clients on dual stack sockets show up as IPv4 mapped IPv6 addresses, they are turned back into IPv4
+pub fn canonical_ip(addr: IpAddr) -> IpAddr {
+    addr.to_canonical()
+}
+
+/// An IP network in CIDR notation, a single IP is a network with the full prefix length
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpRange {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.network, canonical_ip(addr)) {
+            (IpAddr::V4(network), IpAddr::V4(addr)) => prefix_matches(
+                network.to_bits().into(),
+                addr.to_bits().into(),
+                self.prefix,
+                32,
+            ),
+            (IpAddr::V6(network), IpAddr::V6(addr)) => {
+                prefix_matches(network.to_bits(), addr.to_bits(), self.prefix, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches(network: u128, addr: u128, prefix: u8, bits: u8) -> bool {
+    let shift = bits - prefix;
+    // Shifting by the full width would overflow, a prefix of 0 matches everything anyways
+    shift >= bits || network >> shift == addr >> shift
+}
+
+impl FromStr for IpRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ip, prefix) = match s.trim().split_once('/') {
+            Some((ip, prefix)) => (ip, Some(prefix)),
+            None => (s.trim(), None),
+        };
+        let network = IpAddr::from_str(ip).map_err(|err| err.to_string())?;
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .map_err(|err| format!("Invalid prefix length: {}", err))?,
+            None => max_prefix,
+        };
+        if prefix > max_prefix {
+            return Err(format!(
+                "Prefix length {} is larger than {}",
+                prefix, max_prefix
+            ));
+        }
+        Ok(Self { network, prefix })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{net::IpAddr, str::FromStr};
+
+    use super::{AccessControlConfig, AccessMode, IpRange};
+
+    fn ip(ip: &str) -> IpAddr {
+        IpAddr::from_str(ip).unwrap()
+    }
+
+    fn range(range: &str) -> IpRange {
+        IpRange::from_str(range).unwrap()
+    }
+
+    #[test]
+    fn test_ipv4_cidr() {
+        assert!(range("10.0.0.0/8").contains(ip("10.255.3.4")));
+        assert!(!range("10.0.0.0/8").contains(ip("11.0.0.1")));
+        assert!(range("192.168.1.0/24").contains(ip("192.168.1.200")));
+        assert!(!range("192.168.1.0/24").contains(ip("192.168.2.1")));
+        assert!(range("0.0.0.0/0").contains(ip("8.8.8.8")));
+        assert!(range("127.0.0.1").contains(ip("127.0.0.1")));
+        assert!(!range("127.0.0.1").contains(ip("127.0.0.2")));
+        // IPv4 mapped IPv6 addresses
+        assert!(range("10.0.0.0/8").contains(ip("::ffff:10.1.2.3")));
+        assert!(!range("10.0.0.0/8").contains(ip("fd00::1")));
+    }
+
+    #[test]
+    fn test_ipv6_cidr() {
+        assert!(range("fd00::/8").contains(ip("fd12:3456::1")));
+        assert!(!range("fd00::/8").contains(ip("fe80::1")));
+        assert!(range("2001:db8::/32").contains(ip("2001:db8:ffff::1")));
+        assert!(!range("2001:db8::/32").contains(ip("2001:db9::1")));
+        assert!(range("::/0").contains(ip("2001:db8::1")));
+        assert!(range("::1").contains(ip("::1")));
+        assert!(!range("::1/128").contains(ip("::2")));
+    }
+
+    #[test]
+    fn test_malformed() {
+        for malformed in [
+            "10.0.0.0/33",
+            "::/129",
+            "10.0.0/8",
+            "10.0.0.0/",
+            "hello",
+            "::1/-1",
+        ] {
+            assert!(IpRange::from_str(malformed).is_err(), "{}", malformed);
+        }
+    }
+
+    #[test]
+    fn test_modes() {
+        let mut config = AccessControlConfig {
+            mode: AccessMode::AllowAll,
+            ips: vec!["192.168.0.0/16".to_string()],
+        };
+        assert!(config.is_ip_allowed(ip("1.1.1.1")));
+
+        config.mode = AccessMode::AllowList;
+        assert!(config.is_ip_allowed(ip("192.168.4.4")));
+        assert!(!config.is_ip_allowed(ip("1.1.1.1")));
+
+        config.mode = AccessMode::DenyList;
+        assert!(!config.is_ip_allowed(ip("192.168.4.4")));
+        assert!(config.is_ip_allowed(ip("1.1.1.1")));
+    }
+}
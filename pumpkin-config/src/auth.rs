@@ -1,18 +1,30 @@
 use pumpkin_core::ProfileAction;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize)]
+use crate::{docs::ConfigDocs, ConfigError};
+
+#[derive(Deserialize, Serialize, ConfigDocs)]
 #[serde(default)]
 pub struct AuthenticationConfig {
     /// Whether to use Mojang authentication.
     pub enabled: bool,
-    pub auth_url: String,
+    /// The session server players are authenticated with.
+    /// Point it to another Yggdrasil implementation to run your own authentication, e.g. in a private network
+    pub session_server_url: String,
+    /// The authentication server belonging to the session server, Pumpkin itself only talks to the session server.
+    pub auth_server_url: String,
+    /// Allows plain `http` URLs for the servers above, anyone on the way can read and change the profiles then.
+    pub allow_insecure_auth: bool,
+    /// Whether players have to connect from the IP they authenticated with, like vanilla.
     pub prevent_proxy_connections: bool,
-    pub prevent_proxy_connection_auth_url: String,
     /// Player profile handling.
+    #[config_docs(nested)]
     pub player_profile: PlayerProfileConfig,
     /// Texture handling.
+    #[config_docs(nested)]
     pub textures: TextureConfig,
+    /// How player UUIDs are created in offline mode
+    pub offline_uuid_strategy: OfflineUuidStrategy,
 }
 
 impl Default for AuthenticationConfig {
@@ -22,13 +34,62 @@ impl Default for AuthenticationConfig {
             prevent_proxy_connections: false,
             player_profile: Default::default(),
             textures: Default::default(),
-            auth_url: "https://sessionserver.mojang.com/session/minecraft/hasJoined?username={username}&serverId={server_hash}".to_string(),
-            prevent_proxy_connection_auth_url: "https://sessionserver.mojang.com/session/minecraft/hasJoined?username={username}&serverId={server_hash}&ip={ip}".to_string(),
+            offline_uuid_strategy: OfflineUuidStrategy::default(),
+            session_server_url: DEFAULT_SESSION_SERVER_URL.to_string(),
+            auth_server_url: DEFAULT_AUTH_SERVER_URL.to_string(),
+            allow_insecure_auth: false,
+        }
+    }
+}
+
+/// Mojang's session server
+pub const DEFAULT_SESSION_SERVER_URL: &str = "https://sessionserver.mojang.com";
+
+/// Mojang's authentication server
+pub const DEFAULT_AUTH_SERVER_URL: &str = "https://authserver.mojang.com";
+
+/// The path of the session server which tells whether a player joined the server
+pub const HAS_JOINED_PATH: &str = "/session/minecraft/hasJoined";
+
+impl AuthenticationConfig {
+    /// The URL which tells whether a player joined, the query parameters still have to be added
+    #[must_use]
+    pub fn has_joined_url(&self) -> String {
+        format!(
+            "{}{HAS_JOINED_PATH}",
+            self.session_server_url.trim_end_matches('/')
+        )
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for (name, url) in [
+            ("session_server_url", &self.session_server_url),
+            ("auth_server_url", &self.auth_server_url),
+        ] {
+            let (scheme, host) = url.split_once("://").unwrap_or_default();
+            if host.is_empty()
+                || host.starts_with('/')
+                || host.contains(['?', '#'])
+                || url.contains(char::is_whitespace)
+                || !["http", "https"].contains(&scheme)
+            {
+                return Err(ConfigError::validation(
+                    format!("authentication.{name}"),
+                    format!("authentication.{name} {url:?} is no valid http(s) URL"),
+                ));
+            }
+            if scheme == "http" && !self.allow_insecure_auth {
+                return Err(ConfigError::validation(
+                    format!("authentication.{name}"),
+                    format!("authentication.{name} {url:?} doesn't use https, set allow_insecure_auth to allow it"),
+                ));
+            }
         }
+        Ok(())
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ConfigDocs)]
 #[serde(default)]
 pub struct PlayerProfileConfig {
     /// Allow players flagged by Mojang (banned, forced name change).
@@ -49,16 +110,19 @@ impl Default for PlayerProfileConfig {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ConfigDocs)]
 #[serde(default)]
 pub struct TextureConfig {
     /// Whether to use player textures.
     pub enabled: bool,
 
+    /// Texture URLs have to use one of these schemes.
     pub allowed_url_schemes: Vec<String>,
+    /// Texture URLs have to be on a domain ending with one of these.
     pub allowed_url_domains: Vec<String>,
 
     /// Specific texture types.
+    #[config_docs(nested)]
     pub types: TextureTypes,
 }
 
@@ -73,7 +137,7 @@ impl Default for TextureConfig {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ConfigDocs)]
 #[serde(default)]
 pub struct TextureTypes {
     /// Use player skins.
@@ -94,3 +158,76 @@ impl Default for TextureTypes {
         }
     }
 }
+
+/// How UUIDs of players are created in offline mode
+#[derive(Deserialize, Serialize, Clone, Copy, Default)]
+pub enum OfflineUuidStrategy {
+    /// Derived from the name like vanilla, so player data carries over
+    #[default]
+    Vanilla,
+    /// A new random UUID on every join
+    Random,
+}
+
+#[cfg(test)]
+mod test {
+    use super::AuthenticationConfig;
+
+    #[test]
+    fn test_has_joined_url() {
+        let mut config = AuthenticationConfig::default();
+        assert_eq!(
+            config.has_joined_url(),
+            "https://sessionserver.mojang.com/session/minecraft/hasJoined"
+        );
+        config.session_server_url = "https://auth.example.com/api/sessionserver/".to_string();
+        assert_eq!(
+            config.has_joined_url(),
+            "https://auth.example.com/api/sessionserver/session/minecraft/hasJoined"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let validate = |url: &str, allow_insecure_auth: bool| {
+            AuthenticationConfig {
+                session_server_url: url.to_string(),
+                allow_insecure_auth,
+                ..Default::default()
+            }
+            .validate()
+        };
+        assert!(AuthenticationConfig::default().validate().is_ok());
+        assert!(validate("https://auth.example.com/api", false).is_ok());
+        assert!(validate("http://10.0.0.2:8080", false)
+            .unwrap_err()
+            .to_string()
+            .contains("allow_insecure_auth"));
+        assert!(validate("http://10.0.0.2:8080", true).is_ok());
+        for url in [
+            "",
+            "sessionserver.mojang.com",
+            "ftp://example.com",
+            "https://",
+            "https:///path",
+            "https://example.com/has joined",
+            "https://example.com/?username=x",
+        ] {
+            assert!(validate(url, true).is_err(), "{url}");
+        }
+
+        let mut config = AuthenticationConfig {
+            auth_server_url: "http://10.0.0.2:8080".to_string(),
+            ..Default::default()
+        };
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("authentication.auth_server_url"));
+        config.allow_insecure_auth = true;
+        assert!(config.validate().is_ok());
+        config.auth_server_url = "authserver.mojang.com".to_string();
+        assert!(config.validate().is_err());
+    }
+}
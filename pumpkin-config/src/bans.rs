@@ -0,0 +1,398 @@
+use std::{
+    io,
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{access_control::canonical_ip, docs::ConfigDocs, json_list, ConfigError, PendingSave};
+
+/// Banned players and IPs, stored like vanilla's `banned-players.json` and `banned-ips.json`
+#[derive(Deserialize, Serialize, ConfigDocs)]
+#[serde(default)]
+pub struct BansConfig {
+    /// The file with the banned players
+    pub players_path: PathBuf,
+    /// The file with the banned IPs
+    pub ips_path: PathBuf,
+}
+
+impl Default for BansConfig {
+    fn default() -> Self {
+        Self {
+            players_path: "banned-players.json".into(),
+            ips_path: "banned-ips.json".into(),
+        }
+    }
+}
+
+impl BansConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for (field, path) in [
+            ("bans.players_path", &self.players_path),
+            ("bans.ips_path", &self.ips_path),
+        ] {
+            if path.as_os_str().is_empty() {
+                return Err(ConfigError::validation(
+                    field,
+                    "The ban list paths must not be empty",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Vanilla writes the expiry of permanent bans as `forever`
+const FOREVER: &str = "forever";
+
+fn format_date(date: OffsetDateTime) -> String {
+    date.format(time::macros::format_description!(
+        "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour sign:mandatory][offset_minute]"
+    ))
+    .unwrap_or_default()
+}
+
+fn parse_date(date: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(
+        date,
+        time::macros::format_description!(
+            "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour sign:mandatory][offset_minute]"
+        ),
+    )
+    .ok()
+}
+
+/// Why and how long someone is banned, the fields every vanilla ban entry has.
+/// Missing or unreadable values get the same defaults as in vanilla
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BanInfo {
+    #[serde(
+        default = "OffsetDateTime::now_utc",
+        serialize_with = "serialize_created",
+        deserialize_with = "deserialize_created"
+    )]
+    pub created: OffsetDateTime,
+    #[serde(default = "default_source")]
+    pub source: String,
+    /// `None` for permanent bans
+    #[serde(
+        default,
+        serialize_with = "serialize_expires",
+        deserialize_with = "deserialize_expires"
+    )]
+    pub expires: Option<OffsetDateTime>,
+    #[serde(default = "default_reason")]
+    pub reason: String,
+}
+
+fn default_source() -> String {
+    "(Unknown)".to_string()
+}
+
+fn default_reason() -> String {
+    "Banned by an operator.".to_string()
+}
+
+fn serialize_created<S: Serializer>(created: &OffsetDateTime, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&format_date(*created))
+}
+
+fn deserialize_created<'de, D: Deserializer<'de>>(d: D) -> Result<OffsetDateTime, D::Error> {
+    let created = String::deserialize(d)?;
+    Ok(parse_date(&created).unwrap_or_else(OffsetDateTime::now_utc))
+}
+
+fn serialize_expires<S: Serializer>(
+    expires: &Option<OffsetDateTime>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    match expires {
+        Some(expires) => s.serialize_str(&format_date(*expires)),
+        None => s.serialize_str(FOREVER),
+    }
+}
+
+fn deserialize_expires<'de, D: Deserializer<'de>>(
+    d: D,
+) -> Result<Option<OffsetDateTime>, D::Error> {
+    // Vanilla treats invalid dates as permanent bans too
+    let expires = String::deserialize(d)?;
+    Ok(parse_date(&expires))
+}
+
+impl BanInfo {
+    /// A ban created now, `expires` is `None` for permanent bans
+    pub fn new(source: &str, reason: &str, expires: Option<OffsetDateTime>) -> Self {
+        Self {
+            created: OffsetDateTime::now_utc(),
+            source: source.to_string(),
+            expires,
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// When the ban ends in the vanilla format, or `forever`
+    pub fn expires_display(&self) -> String {
+        self.expires
+            .map_or_else(|| FOREVER.to_string(), format_date)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PlayerBan {
+    pub uuid: Uuid,
+    pub name: String,
+    #[serde(flatten)]
+    pub info: BanInfo,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct IpBan {
+    pub ip: IpAddr,
+    #[serde(flatten)]
+    pub info: BanInfo,
+}
+
+/// The banned players and IPs. Every change is written to the files right away,
+/// lookups skip expired bans until [`BanList::remove_expired`] removes them
+#[derive(Debug)]
+pub struct BanList {
+    players_path: PathBuf,
+    ips_path: PathBuf,
+    players: Vec<PlayerBan>,
+    ips: Vec<IpBan>,
+}
+
+impl BanList {
+    /// Reads both files, missing or empty files contain no bans
+    pub fn load(players_path: &Path, ips_path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            players: json_list::read(players_path)?,
+            ips: json_list::read(ips_path)?,
+            players_path: players_path.to_path_buf(),
+            ips_path: ips_path.to_path_buf(),
+        })
+    }
+
+    /// A ban list without any bans, which gets saved to the paths once it changes
+    pub fn empty(players_path: &Path, ips_path: &Path) -> Self {
+        Self {
+            players_path: players_path.to_path_buf(),
+            ips_path: ips_path.to_path_buf(),
+            players: Vec::new(),
+            ips: Vec::new(),
+        }
+    }
+
+    pub fn players(&self) -> &[PlayerBan] {
+        &self.players
+    }
+
+    pub fn ips(&self) -> &[IpBan] {
+        &self.ips
+    }
+
+    /// The ban of the player, if they are banned
+    pub fn is_banned_player(&self, uuid: &Uuid) -> Option<&BanInfo> {
+        self.is_banned_player_at(uuid, OffsetDateTime::now_utc())
+    }
+
+    fn is_banned_player_at(&self, uuid: &Uuid, now: OffsetDateTime) -> Option<&BanInfo> {
+        self.players
+            .iter()
+            .find(|ban| ban.uuid == *uuid && !ban.info.is_expired(now))
+            .map(|ban| &ban.info)
+    }
+
+    /// The ban of the IP, if it is banned
+    pub fn is_banned_ip(&self, addr: IpAddr) -> Option<&BanInfo> {
+        self.is_banned_ip_at(addr, OffsetDateTime::now_utc())
+    }
+
+    fn is_banned_ip_at(&self, addr: IpAddr, now: OffsetDateTime) -> Option<&BanInfo> {
+        let addr = canonical_ip(addr);
+        self.ips
+            .iter()
+            .find(|ban| canonical_ip(ban.ip) == addr && !ban.info.is_expired(now))
+            .map(|ban| &ban.info)
+    }
+
+    /// Whether any ban has expired, checking it only needs shared access unlike [`BanList::remove_expired`]
+    pub fn has_expired(&self) -> bool {
+        let now = OffsetDateTime::now_utc();
+        self.players.iter().any(|ban| ban.info.is_expired(now))
+            || self.ips.iter().any(|ban| ban.info.is_expired(now))
+    }
+
+    /// Removes the expired bans. Returns the files to save, if any changed
+    pub fn remove_expired(&mut self) -> io::Result<Option<PendingSave>> {
+        self.remove_expired_at(OffsetDateTime::now_utc())
+    }
+
+    fn remove_expired_at(&mut self, now: OffsetDateTime) -> io::Result<Option<PendingSave>> {
+        let mut files = Vec::new();
+        if remove_expired(&mut self.players, |ban| &ban.info, now) {
+            files.push((
+                self.players_path.clone(),
+                json_list::to_json(&self.players)?,
+            ));
+        }
+        if remove_expired(&mut self.ips, |ban| &ban.info, now) {
+            files.push((self.ips_path.clone(), json_list::to_json(&self.ips)?));
+        }
+        Ok(PendingSave::new(files))
+    }
+
+    /// Bans the player and saves the file, replacing an earlier ban of them
+    pub fn add_player(&mut self, uuid: Uuid, name: &str, info: BanInfo) -> io::Result<()> {
+        self.players.retain(|ban| ban.uuid != uuid);
+        self.players.push(PlayerBan {
+            uuid,
+            name: name.to_string(),
+            info,
+        });
+        json_list::write(&self.players_path, &self.players)
+    }
+
+    /// Unbans the player and saves the file. Returns false if they weren't banned
+    pub fn remove_player(&mut self, uuid: &Uuid) -> io::Result<bool> {
+        let len = self.players.len();
+        self.players.retain(|ban| ban.uuid != *uuid);
+        if self.players.len() == len {
+            return Ok(false);
+        }
+        json_list::write(&self.players_path, &self.players)?;
+        Ok(true)
+    }
+
+    /// Bans the IP and saves the file, replacing an earlier ban of it
+    pub fn add_ip(&mut self, addr: IpAddr, info: BanInfo) -> io::Result<()> {
+        let addr = canonical_ip(addr);
+        self.ips.retain(|ban| canonical_ip(ban.ip) != addr);
+        self.ips.push(IpBan { ip: addr, info });
+        json_list::write(&self.ips_path, &self.ips)
+    }
+
+    /// Unbans the IP and saves the file. Returns false if it wasn't banned
+    pub fn remove_ip(&mut self, addr: IpAddr) -> io::Result<bool> {
+        let addr = canonical_ip(addr);
+        let len = self.ips.len();
+        self.ips.retain(|ban| canonical_ip(ban.ip) != addr);
+        if self.ips.len() == len {
+            return Ok(false);
+        }
+        json_list::write(&self.ips_path, &self.ips)?;
+        Ok(true)
+    }
+}
+
+/// Returns whether any ban was removed
+fn remove_expired<T>(
+    bans: &mut Vec<T>,
+    info: impl Fn(&T) -> &BanInfo,
+    now: OffsetDateTime,
+) -> bool {
+    let len = bans.len();
+    bans.retain(|ban| !info(ban).is_expired(now));
+    bans.len() != len
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, net::IpAddr, time::Duration};
+
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    use super::{BanInfo, BanList};
+    use crate::test::TestDir;
+
+    #[test]
+    fn test_vanilla_format() {
+        let dir = TestDir::new("bans");
+        let players = dir.join("banned-players.json");
+        let ips = dir.join("banned-ips.json");
+        // As written by vanilla
+        fs::write(
+            &players,
+            r#"[
+  {
+    "uuid": "069a79f4-44e9-4726-a5be-fca90e38aaf5",
+    "name": "Notch",
+    "created": "2024-01-01 12:00:00 +0000",
+    "source": "Server",
+    "expires": "forever",
+    "reason": "Griefing"
+  },
+  {
+    "uuid": "853c80ef-3c37-49fd-aa49-938b674adae6",
+    "name": "jeb_",
+    "created": "2024-01-01 12:00:00 +0100",
+    "source": "Server",
+    "expires": "2024-01-02 12:00:00 +0100",
+    "reason": "Cool down"
+  }
+]"#,
+        )
+        .unwrap();
+        fs::write(
+            &ips,
+            r#"[{"ip": "192.168.1.5", "created": "2024-01-01 12:00:00 +0000", "source": "Server", "expires": "forever", "reason": "Spam"}]"#,
+        )
+        .unwrap();
+
+        let notch = Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+        let jeb = Uuid::parse_str("853c80ef-3c37-49fd-aa49-938b674adae6").unwrap();
+        let mut bans = BanList::load(&players, &ips).unwrap();
+
+        // The temporary ban ended at 11:00 UTC
+        let before = bans.players()[1].info.expires.unwrap() - Duration::from_secs(1);
+        assert_eq!(
+            bans.is_banned_player_at(&jeb, before)
+                .unwrap()
+                .expires_display(),
+            "2024-01-02 12:00:00 +0100"
+        );
+        assert!(bans.is_banned_player(&jeb).is_none());
+        assert!(bans.remove_expired_at(before).unwrap().is_none());
+        assert!(bans.has_expired());
+        // The expired ban gets removed from the file
+        bans.remove_expired().unwrap().unwrap().write().unwrap();
+        assert!(!bans.has_expired());
+        assert!(bans.remove_expired().unwrap().is_none());
+        let reloaded = BanList::load(&players, &ips).unwrap();
+        assert_eq!(reloaded.players().len(), 1);
+
+        // Permanent bans are enforced no matter when
+        let far_future = OffsetDateTime::now_utc() + Duration::from_secs(100 * 365 * 24 * 60 * 60);
+        let ban = bans.is_banned_player_at(&notch, far_future).unwrap();
+        assert_eq!(ban.reason, "Griefing");
+        assert_eq!(ban.expires_display(), "forever");
+
+        let ip: IpAddr = "192.168.1.5".parse().unwrap();
+        assert_eq!(bans.is_banned_ip(ip).unwrap().reason, "Spam");
+        assert!(bans
+            .is_banned_ip("::ffff:192.168.1.5".parse().unwrap())
+            .is_some());
+        assert!(bans.is_banned_ip("192.168.1.6".parse().unwrap()).is_none());
+
+        bans.add_player(jeb, "jeb_", BanInfo::new("Console", "Again", None))
+            .unwrap();
+        assert!(bans.remove_ip(ip).unwrap());
+        assert!(!bans.remove_ip(ip).unwrap());
+        assert!(bans.remove_player(&notch).unwrap());
+
+        let reloaded = BanList::load(&players, &ips).unwrap();
+        assert_eq!(reloaded.is_banned_player(&jeb).unwrap().reason, "Again");
+        assert!(reloaded.is_banned_player(&notch).is_none());
+        assert!(reloaded.is_banned_ip(ip).is_none());
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+use crate::docs::ConfigDocs;
+
+/// Global offsets applied to the climate noise before biomes get selected.
+/// Offsets are limited to `[-1.0, 1.0]`, NaN counts as 0.
+/// Not applied yet, none of the generators select biomes by climate so far
+#[derive(Deserialize, Serialize, Default, ConfigDocs)]
+#[serde(default)]
+pub struct BiomeTuningConfig {
+    /// Positive values make the world hotter
+    pub temperature_offset: f64,
+    /// Positive values make the world wetter
+    pub humidity_offset: f64,
+    /// Positive values move the world further inland
+    pub continentalness_offset: f64,
+}
+
+impl BiomeTuningConfig {
+    /// Out of range offsets are clamped instead of rejected, NaN is replaced with 0
+    pub fn validation_warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = [
+            ("temperature_offset", self.temperature_offset),
+            ("humidity_offset", self.humidity_offset),
+            ("continentalness_offset", self.continentalness_offset),
+        ]
+        .into_iter()
+        .filter(|(_, offset)| !(-1.0..=1.0).contains(offset))
+        .map(|(name, offset)| {
+            if offset.is_nan() {
+                format!("biome_tuning.{} is NaN, using 0.0", name)
+            } else {
+                format!(
+                    "biome_tuning.{} is out of range ({}), clamping it to [-1.0, 1.0]",
+                    name, offset
+                )
+            }
+        })
+        .collect();
+        if self.temperature_offset != 0.0
+            || self.humidity_offset != 0.0
+            || self.continentalness_offset != 0.0
+        {
+            warnings.push(
+                "biome_tuning has no effect yet, none of the generators select biomes by climate so far"
+                    .to_string(),
+            );
+        }
+        warnings
+    }
+
+    pub fn temperature_offset(&self) -> f64 {
+        Self::clamp_offset(self.temperature_offset)
+    }
+
+    pub fn humidity_offset(&self) -> f64 {
+        Self::clamp_offset(self.humidity_offset)
+    }
+
+    pub fn continentalness_offset(&self) -> f64 {
+        Self::clamp_offset(self.continentalness_offset)
+    }
+
+    fn clamp_offset(offset: f64) -> f64 {
+        if offset.is_nan() {
+            0.0
+        } else {
+            offset.clamp(-1.0, 1.0)
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::net::SocketAddr;
+
+use pumpkin_core::{Difficulty, GameMode};
+
+use crate::{world_gen::WorldGenType, BasicConfiguration, ConfigError};
+
+/// Builds a [`BasicConfiguration`] without a config file, e.g. for tests or when embedding Pumpkin.
+///
+/// Starts from the defaults, every setter replaces one field. [`Self::build`] validates the result like
+/// a loaded config
+///
+/// ```
+/// use pumpkin_config::BasicConfiguration;
+///
+/// let config = BasicConfiguration::builder()
+///     .max_players(20)
+///     .view_distance(12)
+///     .online_mode(false)
+///     .build()
+///     .unwrap();
+/// assert_eq!(config.max_players, 20);
+/// ```
+#[must_use]
+pub struct BasicConfigurationBuilder {
+    config: BasicConfiguration,
+}
+
+impl Default for BasicConfigurationBuilder {
+    fn default() -> Self {
+        Self {
+            config: BasicConfiguration::default(),
+        }
+    }
+}
+
+macro_rules! setters {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            #[doc = concat!("Sets [`BasicConfiguration::", stringify!($field), "`]")]
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.config.$field = $field;
+                self
+            }
+        )*
+    };
+}
+
+macro_rules! string_setters {
+    ($($field:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("Sets [`BasicConfiguration::", stringify!($field), "`]")]
+            pub fn $field(mut self, $field: impl Into<String>) -> Self {
+                self.config.$field = $field.into();
+                self
+            }
+        )*
+    };
+}
+
+impl BasicConfigurationBuilder {
+    setters! {
+        server_address: SocketAddr,
+        max_players: u32,
+        view_distance: u8,
+        simulation_distance: u8,
+        default_difficulty: Difficulty,
+        allow_nether: bool,
+        allow_end: bool,
+        hardcore: bool,
+        online_mode: bool,
+        encryption: bool,
+        tps: f32,
+        default_gamemode: GameMode,
+        force_gamemode: bool,
+        spawn_protection_radius: u32,
+        spawn_radius: u32,
+        scrub_ips: bool,
+        use_favicon: bool,
+        world_type: WorldGenType,
+    }
+
+    string_setters! {
+        seed,
+        motd,
+        favicon_path,
+    }
+
+    /// Returns the config, or the first validation error if any value is invalid
+    pub fn build(self) -> Result<BasicConfiguration, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pumpkin_core::GameMode;
+
+    use crate::{BasicConfiguration, ConfigError};
+
+    #[test]
+    fn test_builder() {
+        let config = BasicConfiguration::builder()
+            .max_players(20)
+            .view_distance(12)
+            .motd("Built")
+            .default_gamemode(GameMode::Creative)
+            .online_mode(false)
+            .build()
+            .unwrap();
+        assert_eq!(config.max_players, 20);
+        assert_eq!(config.view_distance, 12);
+        assert_eq!(config.motd, "Built");
+        assert_eq!(config.default_gamemode, GameMode::Creative);
+        assert!(!config.online_mode);
+        // Everything else keeps the default
+        let default = BasicConfiguration::default();
+        assert_eq!(config.simulation_distance, default.simulation_distance);
+        assert_eq!(config.server_address, default.server_address);
+
+        let err = BasicConfiguration::builder()
+            .view_distance(1)
+            .simulation_distance(1)
+            .online_mode(true)
+            .encryption(false)
+            .build()
+            .err()
+            .unwrap();
+        let ConfigError::Validation { path, field, .. } = &err else {
+            panic!("Unexpected error {err}");
+        };
+        assert!(path.is_none());
+        assert_eq!(field, "view_distance");
+        assert_eq!(err.to_string(), "View distance must be at least 2");
+    }
+}
@@ -1,12 +1,24 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize)]
+use crate::{docs::ConfigDocs, ConfigError};
+
+/// The highest permission level, like vanilla's owner level
+pub const MAX_PERMISSION_LEVEL: u8 = 4;
+
+#[derive(Deserialize, Serialize, ConfigDocs)]
 #[serde(default)]
 pub struct CommandsConfig {
     /// Are commands from the Console accepted ?
     pub use_console: bool,
     /// Should be commands from players be logged in console?
     pub log_console: bool, // TODO: commands...
+    /// Overrides the permission level (0-4) a player needs to use a command, e.g. `kill = 3`.
+    /// Commands can also be named by their permission node, e.g. `"minecraft.command.kill" = 3`.
+    /// Commands which aren't listed use the vanilla levels, the console can use every command
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub permission_levels: BTreeMap<String, u8>,
 }
 
 impl Default for CommandsConfig {
@@ -14,6 +26,98 @@ impl Default for CommandsConfig {
         Self {
             use_console: true,
             log_console: true,
+            permission_levels: BTreeMap::new(),
+        }
+    }
+}
+
+impl CommandsConfig {
+    /// The permission level needed to use `command`, which is the primary name of the command
+    #[must_use]
+    pub fn required_level(&self, command: &str) -> u8 {
+        self.permission_levels
+            .get(command)
+            .or_else(|| {
+                self.permission_levels
+                    .get(&format!("minecraft.command.{command}"))
+            })
+            .copied()
+            .unwrap_or_else(|| default_level(command))
+    }
+
+    #[must_use]
+    pub fn is_allowed(&self, command: &str, player_level: u8) -> bool {
+        player_level >= self.required_level(command)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid: Vec<String> = self
+            .permission_levels
+            .iter()
+            .filter(|(_, level)| **level > MAX_PERMISSION_LEVEL)
+            .map(|(command, level)| format!("{command} = {level}"))
+            .collect();
+        if !invalid.is_empty() {
+            return Err(ConfigError::validation(
+                "commands.permission_levels",
+                format!(
+                    "Command permission levels must be between 0 and {MAX_PERMISSION_LEVEL}, got {}",
+                    invalid.join(", ")
+                ),
+            ));
         }
+        Ok(())
+    }
+}
+
+/// The levels vanilla requires, unknown commands need the same level as most cheats
+fn default_level(command: &str) -> u8 {
+    match command {
+        "help" | "list" | "me" | "msg" | "tell" | "w" | "teammsg" | "tm" | "trigger" | "random"
+        | "pumpkin" | "version" => 0,
+        "ban" | "ban-ip" | "banlist" | "compression" | "debug" | "deop" | "kick" | "op"
+        | "pardon" | "pardon-ip" | "transfer" | "whitelist" => 3,
+        "stop" | "reload" | "save-all" | "save-off" | "save-on" | "setidletimeout" => 4,
+        _ => 2,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CommandsConfig;
+
+    #[test]
+    fn test_required_level() {
+        let config = CommandsConfig::default();
+        assert_eq!(config.required_level("help"), 0);
+        assert_eq!(config.required_level("gamemode"), 2);
+        assert_eq!(config.required_level("kick"), 3);
+        assert_eq!(config.required_level("stop"), 4);
+        assert!(config.is_allowed("list", 0));
+        assert!(!config.is_allowed("give", 1));
+        assert!(config.is_allowed("give", 2));
+
+        let config: CommandsConfig = toml::from_str(
+            r#"
+            permission_levels = { stop = 3, help = 1, "minecraft.command.kill" = 4 }
+            "#,
+        )
+        .unwrap();
+        assert!(config.is_allowed("stop", 3));
+        assert!(!config.is_allowed("help", 0));
+        assert_eq!(config.required_level("kill"), 4);
+        // Untouched commands keep the vanilla level
+        assert_eq!(config.required_level("kick"), 3);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate() {
+        let config: CommandsConfig =
+            toml::from_str("permission_levels = { stop = 5, kick = 3, give = 9 }").unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("give = 9"));
+        assert!(err.contains("stop = 5"));
+        assert!(!err.contains("kick"));
     }
 }
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize)]
+use crate::{docs::ConfigDocs, ConfigError};
+
+#[derive(Deserialize, Serialize, ConfigDocs)]
 #[serde(default)]
 /// Packet compression
 pub struct CompressionConfig {
@@ -19,23 +21,192 @@ impl Default for CompressionConfig {
     }
 }
 
-#[derive(Deserialize, Serialize, Clone)]
+impl CompressionConfig {
+    /// Whether packets get compressed at all, a threshold of -1 disables it like vanilla
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled && self.compression_info.threshold != CompressionThreshold::Disabled
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.compression_info.level > 9 {
+            return Err(ConfigError::validation(
+                "packet_compression.level",
+                format!(
+                    "The compression level must be between 0 and 9, got {}",
+                    self.compression_info.level
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, ConfigDocs)]
 #[serde(default)]
 /// We have this in a Seperate struct so we can use it outside of the Config
 pub struct CompressionInfo {
     /// The compression threshold used when compression is enabled
-    pub threshold: u32,
+    pub threshold: CompressionThreshold,
     /// A value between 0..9
     /// 1 = Optimize for the best speed of encoding.
     /// 9 = Optimize for the size of data being encoded.
     pub level: u32,
+    /// How packets above the threshold are compressed
+    pub algorithm: CompressionAlgorithm,
 }
 
 impl Default for CompressionInfo {
     fn default() -> Self {
         Self {
-            threshold: 256,
+            threshold: CompressionThreshold::Threshold(256),
             level: 4,
+            algorithm: CompressionAlgorithm::default(),
+        }
+    }
+}
+
+/// The algorithm compressing packets, vanilla clients only understand [`CompressionAlgorithm::Zlib`]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CompressionAlgorithm {
+    /// Deflate with a zlib header like vanilla, `level` trades speed against size
+    #[default]
+    Zlib,
+    /// Sends every packet uncompressed, e.g. on a LAN where bandwidth is cheaper than CPU time.
+    /// The threshold still applies to the framing, packets above it are sent with a data length of 0 which vanilla accepts
+    None,
+}
+
+impl CompressionAlgorithm {
+    /// Whether packets above the threshold get compressed at all
+    #[must_use]
+    pub fn compresses(self) -> bool {
+        match self {
+            Self::Zlib => true,
+            Self::None => false,
+        }
+    }
+}
+
+/// Written as a number like in vanilla, packets of at least this many bytes are compressed.
+/// 0 compresses every packet and `-1` is [`CompressionThreshold::Disabled`]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(try_from = "i64", into = "i64")]
+pub enum CompressionThreshold {
+    Disabled,
+    /// The size in bytes from which packets are compressed
+    Threshold(u32),
+}
+
+impl CompressionThreshold {
+    /// Whether a packet with `len` bytes of data gets compressed
+    #[must_use]
+    pub fn compresses(self, len: usize) -> bool {
+        match self {
+            Self::Disabled => false,
+            Self::Threshold(threshold) => len >= threshold as usize,
+        }
+    }
+
+    /// The threshold sent to the client in the set compression packet
+    #[must_use]
+    pub fn packet_value(self) -> i32 {
+        match self {
+            Self::Disabled => -1,
+            Self::Threshold(threshold) => i32::try_from(threshold).unwrap_or(i32::MAX),
+        }
+    }
+}
+
+impl TryFrom<i64> for CompressionThreshold {
+    type Error = String;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        match value {
+            -1 => Ok(Self::Disabled),
+            _ => u32::try_from(value).map(Self::Threshold).map_err(|_| {
+                format!(
+                    "The compression threshold must be -1 to disable compression or between 0 and {}, got {value}",
+                    u32::MAX
+                )
+            }),
+        }
+    }
+}
+
+impl From<CompressionThreshold> for i64 {
+    fn from(threshold: CompressionThreshold) -> Self {
+        match threshold {
+            CompressionThreshold::Disabled => -1,
+            CompressionThreshold::Threshold(threshold) => threshold.into(),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{CompressionAlgorithm, CompressionConfig, CompressionThreshold};
+
+    fn parse(toml: &str) -> Result<CompressionConfig, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    #[test]
+    fn test_threshold() {
+        let config = parse("threshold = -1").unwrap();
+        assert_eq!(
+            config.compression_info.threshold,
+            CompressionThreshold::Disabled
+        );
+        assert!(!config.is_enabled());
+        assert!(!config.compression_info.threshold.compresses(100_000));
+        assert_eq!(config.compression_info.threshold.packet_value(), -1);
+
+        let config = parse("threshold = 0").unwrap();
+        assert!(config.is_enabled());
+        assert!(config.compression_info.threshold.compresses(0));
+        assert!(config.compression_info.threshold.compresses(1));
+
+        let config = parse("threshold = 256").unwrap();
+        assert!(!config.compression_info.threshold.compresses(255));
+        assert!(config.compression_info.threshold.compresses(256));
+        assert_eq!(config.compression_info.threshold.packet_value(), 256);
+        assert!(!parse("enabled = false\nthreshold = 256")
+            .unwrap()
+            .is_enabled());
+
+        let err = parse("threshold = -5").err().unwrap();
+        assert!(err.to_string().contains("got -5"));
+
+        // Written back as a number
+        let written = toml::to_string(&parse("threshold = -1").unwrap()).unwrap();
+        assert!(written.contains("threshold = -1"));
+    }
+
+    #[test]
+    fn test_validate_level() {
+        assert!(CompressionConfig::default().validate().is_ok());
+        assert!(parse("level = 9").unwrap().validate().is_ok());
+        assert!(parse("level = 10").unwrap().validate().is_err());
+    }
+
+    #[test]
+    fn test_algorithm() {
+        let config = CompressionConfig::default();
+        assert_eq!(
+            config.compression_info.algorithm,
+            CompressionAlgorithm::Zlib
+        );
+        assert!(parse("algorithm = \"Zlib\"").unwrap().validate().is_ok());
+        assert!(parse("algorithm = \"Brotli\"").is_err());
+
+        // Keeps the compressed framing with a threshold
+        let config = parse("algorithm = \"None\"").unwrap();
+        assert!(config.validate().is_ok());
+        assert!(config.is_enabled());
+        assert!(!config.compression_info.algorithm.compresses());
+        let config = parse("algorithm = \"None\"\nthreshold = -1").unwrap();
+        assert!(config.validate().is_ok());
+        assert!(!config.is_enabled());
+    }
+}
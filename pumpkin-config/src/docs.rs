@@ -0,0 +1,111 @@
+use serde::Serialize;
+
+pub use pumpkin_macros::ConfigDocs;
+
+/// Provides the doc comments of config fields, so they can be written into generated config files.
+/// Use `#[derive(ConfigDocs)]` instead of implementing it manually
+pub trait ConfigDocs {
+    /// Returns the doc comment of the field at `path`, e.g. `["proxy", "velocity", "enabled"]`
+    fn docs(path: &[&str]) -> Option<&'static str>;
+}
+
+/// Every entry of an array of tables has the same docs
+impl<T: ConfigDocs> ConfigDocs for Vec<T> {
+    fn docs(path: &[&str]) -> Option<&'static str> {
+        T::docs(path)
+    }
+}
+
+/// Serializes the config like `toml::to_string`, but with every documented field and table
+/// preceded by its doc comment
+pub fn to_commented_toml<T: Serialize + ConfigDocs>(config: &T) -> String {
+    let toml = toml::to_string(config).unwrap();
+    let mut result = String::with_capacity(toml.len() * 2);
+    let mut table: Vec<&str> = Vec::new();
+
+    for line in toml.lines() {
+        let path = if line.starts_with('[') {
+            table = line
+                .trim_matches(|c| c == '[' || c == ']')
+                .split('.')
+                .map(str::trim)
+                .collect();
+            Some(table.clone())
+        } else if let Some((key, _)) = line.split_once(" = ") {
+            let mut path = table.clone();
+            path.push(key.trim());
+            Some(path)
+        } else {
+            None
+        };
+
+        if let Some(doc) = path.and_then(|path| T::docs(&path)) {
+            for doc_line in doc.lines() {
+                result.push('#');
+                if !doc_line.is_empty() {
+                    result.push(' ');
+                    result.push_str(doc_line);
+                }
+                result.push('\n');
+            }
+        }
+        result.push_str(line);
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{AdvancedConfiguration, BasicConfiguration};
+
+    use super::{to_commented_toml, ConfigDocs};
+
+    #[test]
+    fn test_docs() {
+        assert_eq!(
+            BasicConfiguration::docs(&["server_address"]),
+            Some("The address to bind the server to.")
+        );
+        assert_eq!(
+            BasicConfiguration::docs(&["tps"]),
+            Some("The ticks per second the server runs at, vanilla uses 20.")
+        );
+        assert_eq!(BasicConfiguration::docs(&["unknown"]), None);
+
+        // Nested and flattened configs
+        assert_eq!(
+            AdvancedConfiguration::docs(&["packet_compression", "enabled"]),
+            Some("Wether compression is enabled")
+        );
+        assert_eq!(
+            AdvancedConfiguration::docs(&["packet_compression", "threshold"]),
+            Some("The compression threshold used when compression is enabled")
+        );
+        assert!(
+            AdvancedConfiguration::docs(&["packet_compression", "level"])
+                .unwrap()
+                .contains('\n')
+        );
+    }
+
+    #[test]
+    fn test_commented_toml() {
+        let commented = to_commented_toml(&BasicConfiguration::default());
+        assert!(commented.contains("# The address to bind the server to.\nserver_address = "));
+        assert_eq!(
+            toml::from_str::<toml::Value>(&commented).unwrap(),
+            toml::Value::try_from(BasicConfiguration::default()).unwrap()
+        );
+
+        let commented = to_commented_toml(&AdvancedConfiguration::default());
+        assert!(commented.contains("# Wether compression is enabled\nenabled = "));
+        assert!(commented.contains("# 9 = Optimize for the size of data being encoded.\nlevel = "));
+        // Tables are documented as well
+        assert!(commented.contains("# Velocity modern forwarding\n[proxy.velocity]\n"));
+        assert_eq!(
+            toml::from_str::<toml::Value>(&commented).unwrap(),
+            toml::Value::try_from(AdvancedConfiguration::default()).unwrap()
+        );
+    }
+}
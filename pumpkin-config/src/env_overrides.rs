@@ -0,0 +1,227 @@
+use log::{info, warn};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::docs::ConfigDocs;
+
+/// Prefix of the environment variables which override config fields,
+/// e.g. `PUMPKIN_MAX_PLAYERS` or `PUMPKIN_PROXY_VELOCITY_SECRET` for nested fields
+pub const CONFIG_ENV_PREFIX: &str = "PUMPKIN_";
+
+/// Overrides the fields of `config` with the matching environment variables.
+///
+/// Each variable is parsed like the value it replaces, variables which can't be parsed are logged
+/// and skipped, so the value from the file is kept
+pub(crate) fn apply_env_overrides<T, I>(config: T, vars: I) -> T
+where
+    T: Serialize + DeserializeOwned + ConfigDocs,
+    I: IntoIterator<Item = (String, String)>,
+{
+    let Ok(mut value) = toml::Value::try_from(&config) else {
+        return config;
+    };
+    let mut overridden = false;
+
+    for (name, raw) in vars {
+        let Some(suffix) = name.strip_prefix(CONFIG_ENV_PREFIX) else {
+            continue;
+        };
+        let Some(path) = resolve_path::<T>(&value, suffix) else {
+            continue;
+        };
+
+        let previous = get(&value, &path).cloned();
+        let Some(new) = parse_like(previous.as_ref(), &raw) else {
+            warn!(
+                "Ignoring environment variable {}, it isn't a valid value for {}",
+                name,
+                path.join(".")
+            );
+            continue;
+        };
+        set(&mut value, &path, new);
+
+        // Catch values which have the right TOML type but are still invalid, e.g. unknown enum variants
+        if value.clone().try_into::<T>().is_err() {
+            warn!(
+                "Ignoring environment variable {}, it isn't a valid value for {}",
+                name,
+                path.join(".")
+            );
+            match previous {
+                Some(previous) => set(&mut value, &path, previous),
+                None => remove(&mut value, &path),
+            }
+            continue;
+        }
+        info!(
+            "Using {} from the environment variable {}",
+            path.join("."),
+            name
+        );
+        overridden = true;
+    }
+
+    if !overridden {
+        return config;
+    }
+    value.try_into().unwrap_or(config)
+}
+
+/// Finds the field whose path matches `suffix` (e.g. `PROXY_VELOCITY_SECRET`), segments are joined by `_`.
+///
+/// Fields missing in the serialized config (unset optional values) are only found if they are documented
+fn resolve_path<T: ConfigDocs>(value: &toml::Value, suffix: &str) -> Option<Vec<String>> {
+    // The layout version is managed by the migrations
+    if suffix == "VERSION" {
+        return None;
+    }
+    resolve(value, suffix, Vec::new()).or_else(|| {
+        let key = suffix.to_ascii_lowercase();
+        T::docs(&[&key]).map(|_| vec![key])
+    })
+}
+
+fn resolve(value: &toml::Value, suffix: &str, parents: Vec<String>) -> Option<Vec<String>> {
+    for (key, value) in value.as_table()? {
+        let mut path = parents.clone();
+        path.push(key.clone());
+
+        let upper = key.to_ascii_uppercase();
+        if suffix == upper && !value.is_table() {
+            return Some(path);
+        }
+        let nested = suffix
+            .strip_prefix(&upper)
+            .and_then(|rest| rest.strip_prefix('_'))
+            .filter(|_| value.is_table())
+            .and_then(|rest| resolve(value, rest, path));
+        if nested.is_some() {
+            return nested;
+        }
+    }
+    None
+}
+
+/// Parses `raw` as the same type as `previous`, anything but strings uses the TOML syntax
+fn parse_like(previous: Option<&toml::Value>, raw: &str) -> Option<toml::Value> {
+    match previous {
+        Some(toml::Value::String(_)) => Some(toml::Value::String(raw.to_string())),
+        Some(toml::Value::Boolean(_)) => raw.parse().ok().map(toml::Value::Boolean),
+        Some(toml::Value::Integer(_)) => raw.parse().ok().map(toml::Value::Integer),
+        Some(toml::Value::Float(_)) => raw.parse().ok().map(toml::Value::Float),
+        // Unset optional values don't tell their type, so fall back to a string
+        None => Some(parse_toml(raw).unwrap_or_else(|| toml::Value::String(raw.to_string()))),
+        Some(_) => parse_toml(raw),
+    }
+}
+
+fn parse_toml(raw: &str) -> Option<toml::Value> {
+    let mut table: toml::Table = toml::from_str(&format!("value = {raw}")).ok()?;
+    table.remove("value")
+}
+
+fn get<'a>(value: &'a toml::Value, path: &[String]) -> Option<&'a toml::Value> {
+    path.iter().try_fold(value, |value, key| value.get(key))
+}
+
+fn set(value: &mut toml::Value, path: &[String], new: toml::Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let Some(table) = parents
+        .iter()
+        .try_fold(value, |value, key| value.get_mut(key))
+        .and_then(toml::Value::as_table_mut)
+    else {
+        return;
+    };
+    table.insert(last.clone(), new);
+}
+
+fn remove(value: &mut toml::Value, path: &[String]) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    if let Some(table) = parents
+        .iter()
+        .try_fold(value, |value, key| value.get_mut(key))
+        .and_then(toml::Value::as_table_mut)
+    {
+        table.remove(last);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{AdvancedConfiguration, BasicConfiguration, OfflineUuidStrategy};
+
+    use super::apply_env_overrides;
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_overrides() {
+        let config = apply_env_overrides(
+            BasicConfiguration::default(),
+            vars(&[
+                ("PUMPKIN_SERVER_ADDRESS", "127.0.0.1:25570"),
+                ("PUMPKIN_MAX_PLAYERS", "20"),
+                ("PUMPKIN_ONLINE_MODE", "false"),
+                ("PUMPKIN_TPS", "10"),
+                ("PUMPKIN_DEFAULT_GAMEMODE", "Creative"),
+                ("UNRELATED", "1"),
+            ]),
+        );
+        assert_eq!(config.server_address.to_string(), "127.0.0.1:25570");
+        assert_eq!(config.max_players, 20);
+        assert!(!config.online_mode);
+        assert_eq!(config.tps, 10.0);
+        assert_eq!(config.default_gamemode, pumpkin_core::GameMode::Creative);
+
+        let config = apply_env_overrides(
+            AdvancedConfiguration::default(),
+            vars(&[
+                ("PUMPKIN_PVP_DEFAULT_ENABLED", "false"),
+                ("PUMPKIN_PROXY_VELOCITY_SECRET", "secret"),
+                ("PUMPKIN_AUTHENTICATION_OFFLINE_UUID_STRATEGY", "Random"),
+                // Unset optional value
+                ("PUMPKIN_ENTITY_TRACKING_RANGE", "6"),
+                ("PUMPKIN_VERSION", "5"),
+            ]),
+        );
+        assert!(!config.pvp.default.enabled);
+        assert_eq!(config.proxy.velocity.secret, "secret");
+        assert!(matches!(
+            config.authentication.offline_uuid_strategy,
+            OfflineUuidStrategy::Random
+        ));
+        assert_eq!(config.entity_tracking_range, Some(6));
+        assert_eq!(config.version, AdvancedConfiguration::default().version);
+    }
+
+    #[test]
+    fn test_malformed_override() {
+        let config = apply_env_overrides(
+            BasicConfiguration::default(),
+            vars(&[
+                ("PUMPKIN_MAX_PLAYERS", "many"),
+                ("PUMPKIN_VIEW_DISTANCE", "300"),
+                ("PUMPKIN_SERVER_ADDRESS", "nowhere"),
+                ("PUMPKIN_DEFAULT_GAMEMODE", "Flying"),
+                ("PUMPKIN_HARDCORE", "true"),
+            ]),
+        );
+        let default = BasicConfiguration::default();
+        assert_eq!(config.max_players, default.max_players);
+        // Doesn't fit into the field
+        assert_eq!(config.view_distance, default.view_distance);
+        assert_eq!(config.server_address, default.server_address);
+        assert_eq!(config.default_gamemode, default.default_gamemode);
+        // Valid overrides still apply
+        assert!(config.hardcore);
+    }
+}
@@ -0,0 +1,244 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::{config_dir, config_readonly, AdvancedConfiguration, PendingSave};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum GameRuleValue {
+    Bool(bool),
+    Int(i32),
+}
+
+#[derive(Debug)]
+pub enum GameRuleError {
+    /// There is no game rule with this name
+    Unknown,
+    /// The game rule holds a different type of value
+    WrongType,
+    /// The changed rules couldn't be serialized to be saved, the new value is still applied
+    Serialize(toml::ser::Error),
+}
+
+/// The game rule starting at `AdvancedConfiguration::random_tick_speed`, so there is only one initial value
+pub const RANDOM_TICK_SPEED: &str = "randomTickSpeed";
+
+/// All known game rules and their vanilla defaults, except for `RANDOM_TICK_SPEED`
+const DEFAULT_RULES: &[(&str, GameRuleValue)] = &[
+    ("announceAdvancements", GameRuleValue::Bool(true)),
+    ("doDaylightCycle", GameRuleValue::Bool(true)),
+    ("doFireTick", GameRuleValue::Bool(true)),
+    ("doImmediateRespawn", GameRuleValue::Bool(false)),
+    ("doMobSpawning", GameRuleValue::Bool(true)),
+    ("doWeatherCycle", GameRuleValue::Bool(true)),
+    ("fallDamage", GameRuleValue::Bool(true)),
+    ("keepInventory", GameRuleValue::Bool(false)),
+    ("maxEntityCramming", GameRuleValue::Int(24)),
+    ("mobGriefing", GameRuleValue::Bool(true)),
+    ("naturalRegeneration", GameRuleValue::Bool(true)),
+    ("playersSleepingPercentage", GameRuleValue::Int(100)),
+    ("showDeathMessages", GameRuleValue::Bool(true)),
+    ("spawnRadius", GameRuleValue::Int(10)),
+];
+
+/// Runtime game rule state.
+///
+/// Starts with the vanilla defaults, overridden by the `[gamerules]` table in the features config,
+/// overridden by changes made at runtime, which are saved to `gamerules.toml`.
+/// `randomTickSpeed` starts at `random_tick_speed` from the features config instead
+pub struct GameRules {
+    rules: BTreeMap<String, GameRuleValue>,
+    path: Option<PathBuf>,
+}
+
+impl GameRules {
+    /// Creates the rules from the initial values in the config, without saving changes anywhere
+    pub fn new(config: &AdvancedConfiguration) -> Self {
+        let mut rules = Self {
+            rules: DEFAULT_RULES
+                .iter()
+                .map(|(name, value)| (name.to_string(), *value))
+                .collect(),
+            path: None,
+        };
+        // The validation limits it far below `i32::MAX`
+        let random_tick_speed = i32::try_from(config.random_tick_speed).unwrap_or(i32::MAX);
+        rules.rules.insert(
+            RANDOM_TICK_SPEED.to_string(),
+            GameRuleValue::Int(random_tick_speed),
+        );
+
+        // Setting it in `[gamerules]` is reported by the config validation
+        let mut initial = config.gamerules.clone();
+        initial.remove(RANDOM_TICK_SPEED);
+        rules.apply(&initial);
+        rules
+    }
+
+    /// Creates the rules from the config and the saved runtime changes, saving further changes
+    /// unless the config is read only
+    pub fn load(config: &AdvancedConfiguration) -> Self {
+        let mut rules = Self::new(config);
+        let path = config_dir().join("gamerules.toml");
+
+        if path.exists() {
+            match fs::read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|content| toml::from_str(&content).map_err(|err| err.to_string()))
+            {
+                Ok(saved) => rules.apply(&saved),
+                Err(err) => warn!(
+                    "Couldn't load saved game rules at {:?}. Reason: {}",
+                    path, err
+                ),
+            }
+        }
+
+        rules.path = (!config_readonly()).then_some(path);
+        rules
+    }
+
+    fn apply(&mut self, values: &BTreeMap<String, GameRuleValue>) {
+        for (name, value) in values {
+            match self.rules.get_mut(name) {
+                Some(rule) if std::mem::discriminant(rule) == std::mem::discriminant(value) => {
+                    *rule = *value;
+                }
+                Some(_) => warn!("Game rule {} has the wrong type, ignoring it", name),
+                None => warn!("Unknown game rule {}, ignoring it", name),
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<GameRuleValue> {
+        self.rules.get(name).copied()
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            GameRuleValue::Bool(value) => Some(value),
+            GameRuleValue::Int(_) => None,
+        }
+    }
+
+    pub fn get_int(&self, name: &str) -> Option<i32> {
+        match self.get(name)? {
+            GameRuleValue::Int(value) => Some(value),
+            GameRuleValue::Bool(_) => None,
+        }
+    }
+
+    /// Changes the rule. Returns the file to save, unless changes aren't saved
+    pub fn set_bool(
+        &mut self,
+        name: &str,
+        value: bool,
+    ) -> Result<Option<PendingSave>, GameRuleError> {
+        self.set(name, GameRuleValue::Bool(value))
+    }
+
+    /// Changes the rule. Returns the file to save, unless changes aren't saved
+    pub fn set_int(
+        &mut self,
+        name: &str,
+        value: i32,
+    ) -> Result<Option<PendingSave>, GameRuleError> {
+        self.set(name, GameRuleValue::Int(value))
+    }
+
+    fn set(
+        &mut self,
+        name: &str,
+        value: GameRuleValue,
+    ) -> Result<Option<PendingSave>, GameRuleError> {
+        let rule = self.rules.get_mut(name).ok_or(GameRuleError::Unknown)?;
+        if std::mem::discriminant(rule) != std::mem::discriminant(&value) {
+            return Err(GameRuleError::WrongType);
+        }
+        *rule = value;
+
+        let Some(path) = &self.path else {
+            return Ok(None);
+        };
+        let content = toml::to_string(&self.rules).map_err(GameRuleError::Serialize)?;
+        Ok(PendingSave::new(vec![(path.clone(), content)]))
+    }
+
+    /// All rules with their current values
+    pub fn iter(&self) -> impl Iterator<Item = (&str, GameRuleValue)> {
+        self.rules
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, fs};
+
+    use super::{GameRuleError, GameRuleValue, GameRules, RANDOM_TICK_SPEED};
+    use crate::{test::TestDir, AdvancedConfiguration};
+
+    #[test]
+    fn test_initial_values() {
+        let config = AdvancedConfiguration {
+            gamerules: toml::from_str(
+                "keepInventory = true\nspawnRadius = 5\ndoFireTick = 1\nnotARule = true\nrandomTickSpeed = 7\n",
+            )
+            .unwrap(),
+            random_tick_speed: 5,
+            ..Default::default()
+        };
+        let rules = GameRules::new(&config);
+
+        assert_eq!(rules.get_bool("keepInventory"), Some(true));
+        assert_eq!(rules.get_int("spawnRadius"), Some(5));
+        // Wrong types and unknown rules are ignored
+        assert_eq!(rules.get_bool("doFireTick"), Some(true));
+        assert_eq!(rules.get("notARule"), None);
+        // Only `random_tick_speed` sets the initial random tick speed
+        assert_eq!(rules.get_int(RANDOM_TICK_SPEED), Some(5));
+        // Everything else keeps the vanilla default
+        assert_eq!(rules.get_bool("doDaylightCycle"), Some(true));
+        assert_eq!(rules.get_int("keepInventory"), None);
+    }
+
+    #[test]
+    fn test_set_and_save() {
+        let dir = TestDir::new("gamerules");
+        let path = dir.join("gamerules.toml");
+        let mut rules = GameRules::new(&AdvancedConfiguration::default());
+        // Without a path nothing gets saved
+        assert!(rules.set_int("spawnRadius", 1).unwrap().is_none());
+        rules.path = Some(path.clone());
+
+        rules.set_bool("keepInventory", true).unwrap().unwrap();
+        // Nothing is written until the caller does
+        assert!(!path.exists());
+        rules
+            .set_int("spawnRadius", 0)
+            .unwrap()
+            .unwrap()
+            .write()
+            .unwrap();
+        assert!(matches!(
+            rules.set_int("keepInventory", 1),
+            Err(GameRuleError::WrongType)
+        ));
+        assert!(matches!(
+            rules.set_bool("notARule", true),
+            Err(GameRuleError::Unknown)
+        ));
+        assert_eq!(rules.get_bool("keepInventory"), Some(true));
+
+        // The saved values override the config on the next load
+        let saved: BTreeMap<String, GameRuleValue> =
+            toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let mut reloaded = GameRules::new(&AdvancedConfiguration::default());
+        reloaded.apply(&saved);
+        assert_eq!(reloaded.get_bool("keepInventory"), Some(true));
+        assert_eq!(reloaded.get_int("spawnRadius"), Some(0));
+    }
+}
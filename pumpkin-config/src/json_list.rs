@@ -0,0 +1,37 @@
+//! The JSON list files vanilla uses for the ban lists and the whitelist
+
+use std::{fs, io, path::Path};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Reads the list at `path`, a missing or empty file is an empty list
+pub(crate) fn read<T: DeserializeOwned>(path: &Path) -> io::Result<Vec<T>> {
+    match fs::read_to_string(path) {
+        Ok(content) if content.trim().is_empty() => Ok(Vec::new()),
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// The list as written to the file
+pub(crate) fn to_json<T: Serialize>(list: &[T]) -> io::Result<String> {
+    serde_json::to_string_pretty(list)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Writes the content of a file, creating its directory if needed
+pub(crate) fn write_file(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)
+}
+
+pub(crate) fn write<T: Serialize>(path: &Path, list: &[T]) -> io::Result<()> {
+    write_file(path, &to_json(list)?)
+}
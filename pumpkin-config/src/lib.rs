@@ -1,70 +1,712 @@
-use log::warn;
+use access_control::AccessControlConfig;
+use bans::BansConfig;
+use biome_tuning::BiomeTuningConfig;
+use docs::{to_commented_toml, ConfigDocs};
+use env_overrides::apply_env_overrides;
+use gamerules::GameRuleValue;
+use log::{info, warn};
 use logging::LoggingConfig;
-use pumpkin_core::{Difficulty, GameMode};
+use messages::MessagesConfig;
+use pumpkin_core::{text::legacy_to_json, Difficulty, GameMode};
 use query::QueryConfig;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use arc_swap::ArcSwap;
 use std::{
-    fs,
+    collections::BTreeMap,
+    env, fmt, fs, io,
     net::{Ipv4Addr, SocketAddr},
-    path::Path,
-    sync::LazyLock,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock},
+    time::{Duration, SystemTime},
 };
+use tokio::sync::watch;
 
+pub mod access_control;
 pub mod auth;
+pub mod bans;
+pub mod biome_tuning;
+pub mod builder;
+pub mod docs;
+pub mod gamerules;
 pub mod logging;
+pub mod messages;
 pub mod proxy;
 pub mod query;
 pub mod resource_pack;
+pub mod whitelist;
+pub mod world_gen;
 
-pub use auth::AuthenticationConfig;
+pub use auth::{AuthenticationConfig, OfflineUuidStrategy};
+pub use builder::BasicConfigurationBuilder;
 pub use commands::CommandsConfig;
 pub use compression::CompressionConfig;
-pub use pvp::PVPConfig;
+pub use env_overrides::CONFIG_ENV_PREFIX;
+pub use pending_save::PendingSave;
+pub use pvp::{PVPConfig, PVPWorldsConfig};
 pub use rcon::RCONConfig;
 
 mod commands;
 pub mod compression;
+mod env_overrides;
+mod json_list;
+mod pending_save;
 mod pvp;
 mod rcon;
 
 use proxy::ProxyConfig;
 use resource_pack::ResourcePackConfig;
+use whitelist::WhitelistConfig;
+use world_gen::{WorldGenConfig, WorldGenType};
 
-pub static ADVANCED_CONFIG: LazyLock<AdvancedConfiguration> =
-    LazyLock::new(AdvancedConfiguration::load);
+pub static ADVANCED_CONFIG: LazyLock<ReloadableConfig<AdvancedConfiguration>> =
+    LazyLock::new(|| ReloadableConfig::new(AdvancedConfiguration::load()));
 
-pub static BASIC_CONFIG: LazyLock<BasicConfiguration> = LazyLock::new(BasicConfiguration::load);
+pub static BASIC_CONFIG: LazyLock<ReloadableConfig<BasicConfiguration>> =
+    LazyLock::new(|| ReloadableConfig::new(BasicConfiguration::load()));
+
+/// Environment variable which overrides the directory the config files are stored in.
+pub const CONFIG_DIR_ENV: &str = "PUMPKIN_CONFIG_DIR";
+
+/// Returns the directory the config files are read from and written to.
+/// This is `PUMPKIN_CONFIG_DIR` if set, otherwise the current working directory.
+pub fn config_dir() -> PathBuf {
+    env::var_os(CONFIG_DIR_ENV).map_or_else(|| PathBuf::from("."), PathBuf::from)
+}
+
+/// A config which can be replaced at runtime, see `reload_all`.
+///
+/// Replaced configs are freed once the last `Arc` returned by `get` is dropped,
+/// so keep them only as long as the values have to stay consistent
+pub struct ReloadableConfig<T> {
+    current: ArcSwap<T>,
+    changes: watch::Sender<Arc<T>>,
+}
+
+impl<T> ReloadableConfig<T> {
+    fn new(config: T) -> Self {
+        let config = Arc::new(config);
+        Self {
+            current: ArcSwap::new(config.clone()),
+            changes: watch::channel(config).0,
+        }
+    }
+
+    /// The current config, later reloads don't change it
+    pub fn get(&self) -> Arc<T> {
+        self.current.load_full()
+    }
+
+    fn replace(&self, config: T) {
+        let config = Arc::new(config);
+        self.current.store(config.clone());
+        self.changes.send_replace(config);
+    }
+
+    /// Notifies about every reload, e.g. for state derived from the config.
+    /// The receiver starts with the current config, which counts as already seen
+    pub fn subscribe(&self) -> watch::Receiver<Arc<T>> {
+        self.changes.subscribe()
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The config file doesn't exist
+    Missing(PathBuf),
+    /// The config couldn't be read, with the config file if it was read from one
+    Read(Option<PathBuf>, io::Error),
+    /// The config isn't valid TOML or doesn't match the config structure, with the config file if it was read from one
+    Parse(Option<PathBuf>, toml::de::Error),
+    /// The value of `field`, e.g. `logging.max_size_mb`, failed validation.
+    /// `path` is the config file if the config was read from one
+    Validation {
+        path: Option<PathBuf>,
+        field: String,
+        message: String,
+    },
+}
+
+impl ConfigError {
+    pub fn validation(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Validation {
+            path: None,
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    /// The config file the error belongs to, if the config was read from one
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Missing(path) => Some(path),
+            Self::Read(path, _) | Self::Parse(path, _) | Self::Validation { path, .. } => {
+                path.as_deref()
+            }
+        }
+    }
+
+    /// Attaches the config file the error belongs to
+    fn in_file(self, file: &Path) -> Self {
+        let file = Some(file.to_path_buf());
+        match self {
+            Self::Missing(_) => self,
+            Self::Read(_, err) => Self::Read(file, err),
+            Self::Parse(_, err) => Self::Parse(file, err),
+            Self::Validation { field, message, .. } => Self::Validation {
+                path: file,
+                field,
+                message,
+            },
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        Self::Read(None, err)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(err: toml::de::Error) -> Self {
+        Self::Parse(None, err)
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = self.path() {
+            write!(f, "{:?}: ", path)?;
+        }
+        match self {
+            Self::Missing(_) => write!(f, "The config file doesn't exist"),
+            Self::Read(_, err) => write!(f, "Couldn't read the config: {}", err),
+            Self::Parse(_, err) => write!(f, "Couldn't parse the config: {}", err.message()),
+            // The messages already name the field
+            Self::Validation { message, .. } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Read(_, err) => Some(err),
+            Self::Parse(_, err) => Some(err),
+            Self::Missing(_) | Self::Validation { .. } => None,
+        }
+    }
+}
+
+/// Environment variable which, when set to `1` or `true`, stops Pumpkin from writing missing config files.
+/// The defaults are used instead, e.g. for read only container filesystems
+pub const CONFIG_READONLY_ENV: &str = "PUMPKIN_CONFIG_READONLY";
+
+/// Whether config files must not be written, see `CONFIG_READONLY_ENV`
+pub fn config_readonly() -> bool {
+    env::var(CONFIG_READONLY_ENV)
+        .is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+}
 
 /// The idea is that Pumpkin should very customizable.
 /// You can Enable or Disable Features depending on your needs.
 ///
 /// This also allows you get some Performance or Resource boosts.
 /// Important: The Configuration should match Vanilla by default
-#[derive(Deserialize, Serialize, Default)]
+#[derive(Deserialize, Serialize, ConfigDocs)]
 #[serde(default)]
 pub struct AdvancedConfiguration {
+    /// The layout version of this file, used to migrate older configs. Don't change it
+    pub version: u32,
+    /// Connecting through a proxy like Velocity or BungeeCord
+    #[config_docs(nested)]
     pub proxy: ProxyConfig,
+    /// Authentication of players with Mojang
+    #[config_docs(nested)]
     pub authentication: AuthenticationConfig,
+    /// Packet compression
+    #[config_docs(nested)]
     pub packet_compression: CompressionConfig,
+    /// The resource pack sent to joining players
+    #[config_docs(nested)]
     pub resource_pack: ResourcePackConfig,
+    /// Commands from the console and from players
+    #[config_docs(nested)]
     pub commands: CommandsConfig,
+    /// The remote console (RCON)
+    #[config_docs(nested)]
     pub rcon: RCONConfig,
-    pub pvp: PVPConfig,
+    /// Combat between players, per world. Worlds without their own table (e.g. `[pvp.arena]`) use `[pvp.default]`
+    #[config_docs(nested)]
+    pub pvp: PVPWorldsConfig,
+    /// The console log
+    #[config_docs(nested)]
     pub logging: LoggingConfig,
+    /// The query protocol (GameSpy4), used by server lists
+    #[config_docs(nested)]
     pub query: QueryConfig,
+    /// Messages sent to players, e.g. when they get kicked
+    #[config_docs(nested)]
+    pub messages: MessagesConfig,
+    /// Initial game rule values, e.g. `keepInventory = true`. Use `gamerules::GameRules::load` to get the runtime state.
+    /// `randomTickSpeed` starts at `random_tick_speed` instead
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub gamerules: BTreeMap<String, GameRuleValue>,
+    /// The maximum amount of chunks each world queues for generation at the same time. Defaults to the number of CPUs minus one.
+    /// Only `generation_threads` of them run at once, the rest wait for a free thread
+    pub max_concurrent_chunk_gen: usize,
+    /// The threads generating chunks, shared by all worlds. Lower it so generation can't starve the tick thread.
+    /// Defaults to the number of CPUs
+    pub generation_threads: Option<usize>,
+    /// Global offsets applied to the climate noise before biomes get selected. Not applied yet,
+    /// none of the generators select biomes by climate so far
+    #[config_docs(nested)]
+    pub biome_tuning: BiomeTuningConfig,
+    /// World generation
+    #[config_docs(nested)]
+    pub world_gen: WorldGenConfig,
+    /// A static list of IPs which are allowed or denied to connect, independent of bans
+    #[config_docs(nested)]
+    pub access_control: AccessControlConfig,
+    /// Only players on the whitelist can join
+    #[config_docs(nested)]
+    pub whitelist: WhitelistConfig,
+    /// Banned players and IPs
+    #[config_docs(nested)]
+    pub bans: BansConfig,
+    /// The distance in chunks in which entity updates are sent to players. Defaults to the view distance
+    pub entity_tracking_range: Option<u8>,
+    /// Blocks can only be placed below this height. Defaults to the top of the dimension
+    pub max_build_height: Option<i32>,
+    /// Random block ticks (e.g. crop growth) per chunk section and tick, within the simulation distance. `0` disables them.
+    /// Also the initial value of the `randomTickSpeed` game rule. Not applied yet, as blocks have no random tick behaviours so far
+    pub random_tick_speed: u32,
+    /// The radius in blocks in which an existing portal is searched when entering a Nether portal.
+    /// It gets divided by the Nether coordinate scale when searching in the Nether
+    pub portal_search_radius: i32,
+    /// The radius in blocks in which a new portal may be placed when no existing one was found
+    pub portal_creation_radius: i32,
+    /// Whether newly joined players start at `initial_view_distance`, which then grows to their full view distance
+    pub join_view_distance_rampup: bool,
+    /// The view distance newly joined players start with, see `join_view_distance_rampup`
+    pub initial_view_distance: u8,
+    /// Whether both config files are reloaded automatically when they change on disk
+    pub hot_reload: bool,
+    /// How long in milliseconds the serialized status response is reused for server list pings.
+    /// It is always rebuilt when the player count changed
+    pub status_cache_ttl_ms: u64,
+}
+
+impl Default for AdvancedConfiguration {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            proxy: ProxyConfig::default(),
+            authentication: AuthenticationConfig::default(),
+            packet_compression: CompressionConfig::default(),
+            resource_pack: ResourcePackConfig::default(),
+            commands: CommandsConfig::default(),
+            rcon: RCONConfig::default(),
+            pvp: PVPWorldsConfig::default(),
+            logging: LoggingConfig::default(),
+            query: QueryConfig::default(),
+            messages: MessagesConfig::default(),
+            gamerules: BTreeMap::new(),
+            max_concurrent_chunk_gen: default_chunk_gen_concurrency(),
+            generation_threads: None,
+            biome_tuning: BiomeTuningConfig::default(),
+            world_gen: WorldGenConfig::default(),
+            access_control: AccessControlConfig::default(),
+            whitelist: WhitelistConfig::default(),
+            bans: BansConfig::default(),
+            entity_tracking_range: None,
+            max_build_height: None,
+            random_tick_speed: 3,
+            portal_search_radius: 128,
+            portal_creation_radius: 16,
+            join_view_distance_rampup: false,
+            initial_view_distance: 2,
+            hot_reload: false,
+            status_cache_ttl_ms: 1000,
+        }
+    }
+}
+
+impl AdvancedConfiguration {
+    /// The key paths of the values which must not be shared
+    const SECRETS: &'static [&'static [&'static str]] =
+        &[&["rcon", "password"], &["proxy", "velocity", "secret"]];
+
+    /// Serializes the config with secrets (RCON password, Velocity secret) replaced by `<redacted>`,
+    /// so it can be shared e.g. in bug reports
+    pub fn to_sanitized_toml(&self) -> String {
+        to_sanitized_toml(self, Self::SECRETS, REDACTED)
+    }
+
+    /// Like `to_sanitized_toml`, but the secrets are replaced by `***`
+    pub fn redacted_dump(&self) -> String {
+        to_sanitized_toml(self, Self::SECRETS, "***")
+    }
+
+    /// Loads `features.toml` like on startup, but returns the errors instead of panicking.
+    /// Values depending on the basic config are checked against `BASIC_CONFIG`
+    pub fn try_load() -> Result<Self, Vec<ConfigError>> {
+        Self::try_load_from(&Self::get_path(), config_readonly())
+    }
+
+    /// Parses and validates the content of a `features.toml` without touching the disk, e.g. in tests.
+    /// Omitted values get their defaults and older layouts are migrated, environment overrides aren't applied.
+    /// Values depending on the basic config are checked against its defaults, an invalid value is returned as
+    /// [`ConfigError::Validation`] without a path
+    pub fn from_toml_str(toml: &str) -> Result<Self, ConfigError> {
+        let (config, _) = parse_versioned::<Self>(toml)?;
+        config.validate(&BasicConfiguration::default())?;
+        Ok(config)
+    }
+
+    /// The distance in chunks in which entity updates are sent to players
+    pub fn entity_tracking_range(&self) -> u8 {
+        self.entity_tracking_range
+            .unwrap_or_else(|| BASIC_CONFIG.get().view_distance)
+    }
+
+    /// How long the serialized status response is reused, see `status_cache_ttl_ms`
+    pub fn status_cache_ttl(&self) -> Duration {
+        Duration::from_millis(self.status_cache_ttl_ms)
+    }
+
+    /// The number of chunk generation threads, see `generation_threads`
+    pub fn generation_threads(&self) -> usize {
+        self.generation_threads.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+        })
+    }
+
+    /// Whether random block ticks are applied at all
+    pub fn random_ticks_enabled(&self) -> bool {
+        self.random_tick_speed > 0
+    }
+
+    /// The random ticks per tick for each section of a chunk `chunk_distance` chunks away from the closest player.
+    /// Chunks outside of `simulation_distance` get none
+    pub fn random_ticks_per_section(&self, chunk_distance: u32, simulation_distance: u8) -> u32 {
+        if chunk_distance > u32::from(simulation_distance) {
+            0
+        } else {
+            self.random_tick_speed
+        }
+    }
+
+    /// Whether blocks may be placed at `y`, in a dimension ranging from `bottom_y` (inclusive) to `top_y` (exclusive)
+    pub fn can_place_at(&self, y: i32, bottom_y: i32, top_y: i32) -> bool {
+        let top_y = self
+            .max_build_height
+            .map_or(top_y, |max_build_height| max_build_height.min(top_y));
+        (bottom_y..top_y).contains(&y)
+    }
+
+    /// The view distances to apply after a player joined, with the time since joining at which they apply.
+    /// The last one is always `view_distance`
+    pub fn view_distance_rampup(&self, view_distance: u8) -> Vec<(Duration, u8)> {
+        if !self.join_view_distance_rampup || self.initial_view_distance >= view_distance {
+            return vec![(Duration::ZERO, view_distance)];
+        }
+        (self.initial_view_distance..=view_distance)
+            .zip(0u32..)
+            .map(|(distance, step)| (VIEW_DISTANCE_RAMPUP_STEP * step, distance))
+            .collect()
+    }
+
+    /// Re-reads `features.toml` and replaces `ADVANCED_CONFIG` if the new config is valid,
+    /// `BASIC_CONFIG` is left untouched.
+    ///
+    /// Returns the names of the sections (e.g. `pvp`) which changed,
+    /// so the server can re-apply them
+    pub fn reload() -> Result<Vec<String>, Vec<ConfigError>> {
+        let path = Self::get_path();
+        let config: Self = read_config(&path).map_err(|err| vec![err])?;
+        let errors = config.validation_errors(&BASIC_CONFIG.get());
+        if !errors.is_empty() {
+            return Err(in_file(&path, errors));
+        }
+
+        let changed = changed_sections(&*ADVANCED_CONFIG.get(), &config);
+        if !changed.is_empty() {
+            warn_restart_required(&path, &changed, Self::RESTART_REQUIRED);
+            ADVANCED_CONFIG.replace(config);
+        }
+        Ok(changed)
+    }
+
+    /// Sections which are only read on startup, changing them requires a restart
+    const RESTART_REQUIRED: &'static [&'static str] = &[
+        "commands",
+        "generation_threads",
+        "hot_reload",
+        "logging",
+        "max_concurrent_chunk_gen",
+        "query",
+        "rcon",
+        "world_gen",
+    ];
+
+    /// Checks every value and returns the first invalid one, `basic` is needed as some values depend on it
+    pub fn validate(&self, basic: &BasicConfiguration) -> Result<(), ConfigError> {
+        self.validation_errors(basic)
+            .into_iter()
+            .next()
+            .map_or(Ok(()), Err)
+    }
+
+    /// Values which are valid but most likely not intended, `basic` is needed as some values depend on it
+    fn validation_warnings(&self, basic: &BasicConfiguration) -> Vec<String> {
+        let mut warnings = self.proxy.validation_warnings(basic.online_mode);
+        warnings.extend(self.biome_tuning.validation_warnings());
+        warnings.extend(self.resource_pack.validation_warnings());
+        if self.gamerules.contains_key(gamerules::RANDOM_TICK_SPEED) {
+            warnings.push(format!(
+                "gamerules.{} is ignored, its initial value is random_tick_speed",
+                gamerules::RANDOM_TICK_SPEED
+            ));
+        }
+        warnings
+    }
+
+    /// Like `validate`, but returns every invalid value
+    fn validation_errors(&self, basic: &BasicConfiguration) -> Vec<ConfigError> {
+        let mut errors: Vec<ConfigError> = [
+            self.resource_pack.validate(),
+            self.proxy.validate(),
+            self.world_gen.validate(),
+            self.access_control.validate(),
+            self.whitelist.validate(),
+            self.bans.validate(),
+            self.commands.validate(),
+            self.authentication.validate(),
+            self.logging.validate(),
+            self.packet_compression.validate(),
+        ]
+        .into_iter()
+        .filter_map(Result::err)
+        .collect();
+
+        if self.max_concurrent_chunk_gen < 1 {
+            errors.push(ConfigError::validation(
+                "max_concurrent_chunk_gen",
+                "max_concurrent_chunk_gen must be at least 1",
+            ));
+        }
+        if self.generation_threads == Some(0) {
+            errors.push(ConfigError::validation(
+                "generation_threads",
+                "generation_threads must be at least 1",
+            ));
+        }
+        if let Some(range) = self.entity_tracking_range {
+            if range > basic.view_distance {
+                errors.push(ConfigError::validation(
+                    "entity_tracking_range",
+                    "entity_tracking_range must not be larger than the view distance",
+                ));
+            }
+        }
+        if self.random_tick_speed > MAX_RANDOM_TICK_SPEED {
+            errors.push(ConfigError::validation(
+                "random_tick_speed",
+                format!("random_tick_speed must not be larger than {MAX_RANDOM_TICK_SPEED}"),
+            ));
+        }
+        if self.portal_search_radius < 1 {
+            errors.push(ConfigError::validation(
+                "portal_search_radius",
+                "portal_search_radius must be at least 1",
+            ));
+        }
+        if self.portal_creation_radius < 1 {
+            errors.push(ConfigError::validation(
+                "portal_creation_radius",
+                "portal_creation_radius must be at least 1",
+            ));
+        }
+        if !(2..=basic.view_distance).contains(&self.initial_view_distance) {
+            errors.push(ConfigError::validation(
+                "initial_view_distance",
+                "initial_view_distance must be between 2 and the view distance",
+            ));
+        }
+        if let Some(max_build_height) = self.max_build_height {
+            if !(WORLD_BOTTOM_Y..=WORLD_TOP_Y).contains(&max_build_height) {
+                errors.push(ConfigError::validation(
+                    "max_build_height",
+                    format!(
+                        "max_build_height must be within the world height ({WORLD_BOTTOM_Y}..={WORLD_TOP_Y})"
+                    ),
+                ));
+            }
+        }
+        errors
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+/// Re-reads both config files and replaces `BASIC_CONFIG` and `ADVANCED_CONFIG` if both are valid,
+/// if either is invalid nothing gets replaced.
+///
+/// Returns the names of the top level keys (e.g. `motd`, `pvp`) which changed in either file,
+/// so the server can re-apply them. Changes which require a restart are logged as warnings
+pub fn reload_all() -> Result<Vec<String>, Vec<ConfigError>> {
+    let basic_path = BasicConfiguration::get_path();
+    let advanced_path = AdvancedConfiguration::get_path();
+    let (basic, advanced) = read_files(&basic_path, &advanced_path)?;
+
+    let changed_basic = changed_sections(&*BASIC_CONFIG.get(), &basic);
+    let changed_advanced = changed_sections(&*ADVANCED_CONFIG.get(), &advanced);
+    warn_restart_required(
+        &basic_path,
+        &changed_basic,
+        BasicConfiguration::RESTART_REQUIRED,
+    );
+    warn_restart_required(
+        &advanced_path,
+        &changed_advanced,
+        AdvancedConfiguration::RESTART_REQUIRED,
+    );
+
+    // Unchanged configs are kept, so the subscribers aren't notified for nothing
+    if !changed_basic.is_empty() {
+        BASIC_CONFIG.replace(basic);
+    }
+    if !changed_advanced.is_empty() {
+        ADVANCED_CONFIG.replace(advanced);
+    }
+    Ok(changed_basic.into_iter().chain(changed_advanced).collect())
+}
+
+/// Returns the changed keys which are only read on startup
+fn restart_required<'a>(changed: &'a [String], restart_required: &[&str]) -> Vec<&'a str> {
+    changed
+        .iter()
+        .map(String::as_str)
+        .filter(|key| restart_required.contains(key))
+        .collect()
+}
+
+fn warn_restart_required(path: &Path, changed: &[String], keys: &[&str]) {
+    let keys = restart_required(changed, keys);
+    if !keys.is_empty() {
+        warn!(
+            "Changes to {} in {:?} only take effect after a restart",
+            keys.join(", "),
+            path
+        );
+    }
+}
+
+/// How often the config files are checked for changes when `hot_reload` is enabled
+pub const HOT_RELOAD_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Detects changes of the config files by comparing their modification times
+pub struct ConfigWatcher {
+    paths: Vec<PathBuf>,
+    modified: Vec<Option<SystemTime>>,
+}
+
+impl ConfigWatcher {
+    /// Watches `configuration.toml` and `features.toml`
+    pub fn new() -> Self {
+        Self::with_paths(vec![
+            BasicConfiguration::get_path(),
+            AdvancedConfiguration::get_path(),
+        ])
+    }
+
+    fn with_paths(paths: Vec<PathBuf>) -> Self {
+        let modified = paths.iter().map(|path| modified(path)).collect();
+        Self { paths, modified }
+    }
+
+    /// Whether any of the files changed since the last call
+    pub fn poll(&mut self) -> bool {
+        let modified: Vec<_> = self.paths.iter().map(|path| modified(path)).collect();
+        let changed = modified != self.modified;
+        self.modified = modified;
+        changed
+    }
+}
+
+impl Default for ConfigWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Returns the top level keys which differ between the two configs
+fn changed_sections<T: Serialize>(old: &T, new: &T) -> Vec<String> {
+    let old = toml::Value::try_from(old).unwrap();
+    let new = toml::Value::try_from(new).unwrap();
+    let (Some(old), Some(new)) = (old.as_table(), new.as_table()) else {
+        return Vec::new();
+    };
+
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect()
+}
+
+/// The time between two view distance increases, see `AdvancedConfiguration::view_distance_rampup`
+pub const VIEW_DISTANCE_RAMPUP_STEP: Duration = Duration::from_millis(250);
+
+/// The height limit of the overworld, from the lowest block (inclusive) to the top (exclusive).
+/// Blocks can't be placed above the top, so `max_build_height` may also be the top itself
+pub const WORLD_BOTTOM_Y: i32 = -64;
+pub const WORLD_TOP_Y: i32 = 320;
+
+/// More random ticks than blocks in a section are pointless and very expensive
+pub const MAX_RANDOM_TICK_SPEED: u32 = 4096;
+
+/// The largest allowed `spawn_protection_radius` and `spawn_radius`
+pub const MAX_SPAWN_RADIUS: u32 = 1000;
+
+/// The allowed range of `view_distance`
+pub const MIN_VIEW_DISTANCE: u8 = 2;
+pub const MAX_VIEW_DISTANCE: u8 = 32;
+/// Above this `view_distance` a warning is logged, the chunks to send and keep loaded grow quadratically
+pub const HIGH_VIEW_DISTANCE: u8 = 16;
+
+/// The allowed range of `tps`
+pub const MIN_TPS: f32 = 1.0;
+pub const MAX_TPS: f32 = 1000.0;
+/// Above this `tps` a warning is logged, most servers can't tick that fast
+pub const HIGH_TPS: f32 = 100.0;
+
+/// The number of CPUs minus one, leaving one for the tick thread, but at least 1
+pub fn default_chunk_gen_concurrency() -> usize {
+    std::thread::available_parallelism()
+        .map_or(1, |cpus| cpus.get() - 1)
+        .max(1)
+}
+
+#[derive(Serialize, Deserialize, ConfigDocs)]
 #[serde(default)]
 pub struct BasicConfiguration {
+    /// The layout version of this file, used to migrate older configs. Don't change it
+    pub version: u32,
     /// The address to bind the server to.
     pub server_address: SocketAddr,
-    /// The seed for world generation.
+    /// The seed for world generation. Numbers are used as is, other text gets hashed like in vanilla. Random if empty.
+    /// Text seeds of worlds created with older Pumpkin versions were hashed differently, new chunks don't match their terrain
     pub seed: String,
     /// The maximum number of players allowed on the server. Specifying `0` disables the limit.
     pub max_players: u32,
-    /// The maximum view distance for players.
+    /// The maximum view distance for players, from 2 to 32. Above 16 a warning is logged as it may hurt performance.
     pub view_distance: u8,
     /// The maximum simulated view distance.
     pub simulation_distance: u8,
@@ -72,28 +714,41 @@ pub struct BasicConfiguration {
     pub default_difficulty: Difficulty,
     /// Whether the Nether dimension is enabled.
     pub allow_nether: bool,
+    /// Whether the End dimension is enabled.
+    pub allow_end: bool,
     /// Whether the server is in hardcore mode.
     pub hardcore: bool,
     /// Whether online mode is enabled. Requires valid Minecraft accounts.
     pub online_mode: bool,
     /// Whether packet encryption is enabled. Required when online mode is enabled.
     pub encryption: bool,
-    /// The server's description displayed on the status screen.
+    /// The server's description displayed on the status screen. Either a JSON text component or text,
+    /// which may use `&` or `§` formatting codes. Only the first two lines are shown
     pub motd: String,
+    /// The ticks per second the server runs at, vanilla uses 20. Must be between 1 and 1000
     pub tps: f32,
     /// The default game mode for players.
     pub default_gamemode: GameMode,
-    /// Whether to remove IPs from logs or not
+    /// Whether players are set to the default game mode on every join, ignoring the mode they left with.
+    pub force_gamemode: bool,
+    /// The radius in blocks around the spawn point where only operators can build. `0` disables the protection
+    pub spawn_protection_radius: u32,
+    /// The radius in blocks around the spawn point where new players spawn
+    pub spawn_radius: u32,
+    /// Whether to remove IP addresses from the log messages, on the console and in the log file
     pub scrub_ips: bool,
     /// Whether to use a server favicon
     pub use_favicon: bool,
-    /// Path to server favicon
+    /// Path to server favicon, a PNG, JPEG or WebP image. It gets cropped to a square and resized to 64x64 pixels
     pub favicon_path: String,
+    /// The kind of world to generate. Types which are not implemented yet fall back to plains
+    pub world_type: WorldGenType,
 }
 
 impl Default for BasicConfiguration {
     fn default() -> Self {
         Self {
+            version: Self::VERSION,
             server_address: SocketAddr::new(Ipv4Addr::new(0, 0, 0, 0).into(), 25565),
             seed: "".to_string(),
             max_players: 100000,
@@ -101,41 +756,483 @@ impl Default for BasicConfiguration {
             simulation_distance: 10,
             default_difficulty: Difficulty::Normal,
             allow_nether: true,
+            allow_end: true,
             hardcore: false,
             online_mode: true,
             encryption: true,
             motd: "A Blazing fast Pumpkin Server!".to_string(),
             tps: 20.0,
             default_gamemode: GameMode::Survival,
+            force_gamemode: false,
+            spawn_protection_radius: 16,
+            spawn_radius: 10,
             scrub_ips: true,
             use_favicon: true,
             favicon_path: "icon.png".to_string(),
+            world_type: WorldGenType::default(),
+        }
+    }
+}
+
+impl BasicConfiguration {
+    /// Starts building a config from the defaults, without reading `configuration.toml`
+    pub fn builder() -> BasicConfigurationBuilder {
+        BasicConfigurationBuilder::default()
+    }
+
+    /// Loads `configuration.toml` like on startup, but returns the errors instead of panicking
+    pub fn try_load() -> Result<Self, Vec<ConfigError>> {
+        Self::try_load_from(&Self::get_path(), config_readonly())
+    }
+
+    /// Parses and validates the content of a `configuration.toml` without touching the disk, e.g. in tests.
+    /// Omitted values get their defaults and older layouts are migrated, environment overrides aren't applied.
+    /// An invalid value is returned as [`ConfigError::Validation`] without a path
+    pub fn from_toml_str(toml: &str) -> Result<Self, ConfigError> {
+        let (config, _) = parse_versioned::<Self>(toml)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Keys which are only read on startup, changing them requires a restart
+    const RESTART_REQUIRED: &'static [&'static str] = &[
+        "allow_end",
+        "allow_nether",
+        "online_mode",
+        "scrub_ips",
+        "seed",
+        "server_address",
+        "tps",
+        "world_type",
+    ];
+
+    /// Serializes the config so it can be shared, see `AdvancedConfiguration::to_sanitized_toml`
+    pub fn to_sanitized_toml(&self) -> String {
+        to_sanitized_toml(self, &[], REDACTED)
+    }
+
+    /// Checks every value and returns the first invalid one
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.validation_errors()
+            .into_iter()
+            .next()
+            .map_or(Ok(()), Err)
+    }
+
+    /// Like `validate`, but returns every invalid value
+    fn validation_errors(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        if self.view_distance < MIN_VIEW_DISTANCE {
+            errors.push(ConfigError::validation(
+                "view_distance",
+                format!("View distance must be at least {MIN_VIEW_DISTANCE}"),
+            ));
+        }
+        if self.view_distance > MAX_VIEW_DISTANCE {
+            errors.push(ConfigError::validation(
+                "view_distance",
+                format!("View distance must not be larger than {MAX_VIEW_DISTANCE}"),
+            ));
+        }
+        if self.simulation_distance < 2 {
+            errors.push(ConfigError::validation(
+                "simulation_distance",
+                "Simulation distance must be at least 2",
+            ));
+        }
+        if self.simulation_distance > self.view_distance {
+            errors.push(ConfigError::validation(
+                "simulation_distance",
+                "Simulation distance must not be larger than the view distance",
+            ));
+        }
+        if self.online_mode && !self.encryption {
+            errors.push(ConfigError::validation(
+                "encryption",
+                "When Online Mode is enabled, Encryption must be enabled",
+            ));
+        }
+        if self.spawn_protection_radius > MAX_SPAWN_RADIUS {
+            errors.push(ConfigError::validation(
+                "spawn_protection_radius",
+                format!("Spawn protection radius must not be larger than {MAX_SPAWN_RADIUS}"),
+            ));
+        }
+        if self.spawn_radius > MAX_SPAWN_RADIUS {
+            errors.push(ConfigError::validation(
+                "spawn_radius",
+                format!("Spawn radius must not be larger than {MAX_SPAWN_RADIUS}"),
+            ));
+        }
+        if !(MIN_TPS..=MAX_TPS).contains(&self.tps) {
+            errors.push(ConfigError::validation(
+                "tps",
+                format!("TPS must be between {MIN_TPS} and {MAX_TPS}"),
+            ));
+        }
+        errors
+    }
+
+    /// Valid values which are likely a mistake
+    fn validation_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.view_distance > HIGH_VIEW_DISTANCE {
+            warnings.push(format!(
+                "View distance of {} is above {HIGH_VIEW_DISTANCE}, this may hurt performance",
+                self.view_distance
+            ));
+        }
+        if self.tps > HIGH_TPS {
+            warnings.push(format!(
+                "TPS of {} is very high, the server likely can't keep up",
+                self.tps
+            ));
+        }
+        warnings
+    }
+
+    /// The time between two ticks at the configured `tps`
+    pub fn tick_duration(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / f64::from(self.tps))
+    }
+
+    /// Whether a player can't build at `x`, `z` because it is too close to the spawn point.
+    ///
+    /// Like vanilla the distance is the larger of the x and z distance, so the protected area is a square.
+    /// Operators are never protected against
+    pub fn is_spawn_protected(
+        &self,
+        x: i32,
+        z: i32,
+        spawn_x: i32,
+        spawn_z: i32,
+        player_is_op: bool,
+    ) -> bool {
+        if player_is_op || self.spawn_protection_radius == 0 {
+            return false;
+        }
+        let distance = x.abs_diff(spawn_x).max(z.abs_diff(spawn_z));
+        distance <= self.spawn_protection_radius
+    }
+
+    /// The MOTD as text component for the status response, text is cut off after the second line like in vanilla
+    pub fn motd_component(&self) -> serde_json::Value {
+        let trimmed = self.motd.trim_start();
+        if trimmed.starts_with('{') {
+            if let Ok(component @ serde_json::Value::Object(_)) = serde_json::from_str(trimmed) {
+                return component;
+            }
+        }
+        let lines: Vec<&str> = self.motd.splitn(3, '\n').take(2).collect();
+        legacy_to_json(&lines.join("\n"))
+    }
+
+    /// Returns the game mode a joining player gets, `saved` is the mode they had when they left (if known).
+    pub fn join_gamemode(&self, saved: Option<GameMode>) -> GameMode {
+        let default = match self.default_gamemode {
+            GameMode::Undefined => GameMode::Survival,
+            game_mode => game_mode,
+        };
+        if self.force_gamemode {
+            return default;
+        }
+        match saved {
+            Some(GameMode::Undefined) | None => default,
+            Some(game_mode) => game_mode,
+        }
+    }
+}
+
+/// Loads both config files without writing defaults and collects every error instead of stopping at the first,
+/// so configs can be checked before deploying them (`pumpkin --check-config`).
+/// Returns the warnings of valid configs, prefixed with the file they belong to, as the logger isn't set up yet
+pub fn validate_all() -> Result<Vec<String>, Vec<ConfigError>> {
+    validate_files(
+        &BasicConfiguration::get_path(),
+        &AdvancedConfiguration::get_path(),
+    )
+}
+
+fn validate_files(
+    basic_path: &Path,
+    advanced_path: &Path,
+) -> Result<Vec<String>, Vec<ConfigError>> {
+    let (basic, advanced) = read_files(basic_path, advanced_path)?;
+    let basic_warnings = basic
+        .validation_warnings()
+        .into_iter()
+        .map(|warning| format!("{:?}: {}", basic_path, warning));
+    let advanced_warnings = advanced
+        .validation_warnings(&basic)
+        .into_iter()
+        .map(|warning| format!("{:?}: {}", advanced_path, warning));
+    Ok(basic_warnings.chain(advanced_warnings).collect())
+}
+
+/// Both config files as they get loaded (including the environment overrides) with the secrets redacted,
+/// so they can be pasted into bug reports (`pumpkin --dump-config`)
+pub fn dump_all() -> Result<String, Vec<ConfigError>> {
+    dump_files(
+        &BasicConfiguration::get_path(),
+        &AdvancedConfiguration::get_path(),
+    )
+}
+
+fn dump_files(basic_path: &Path, advanced_path: &Path) -> Result<String, Vec<ConfigError>> {
+    let (basic, advanced) = read_files(basic_path, advanced_path)?;
+    Ok(format!(
+        "# {}\n{}\n# {}\n{}",
+        basic_path.display(),
+        basic.to_sanitized_toml(),
+        advanced_path.display(),
+        advanced.to_sanitized_toml()
+    ))
+}
+
+/// Reads and validates both config files, collecting every error
+fn read_files(
+    basic_path: &Path,
+    advanced_path: &Path,
+) -> Result<(BasicConfiguration, AdvancedConfiguration), Vec<ConfigError>> {
+    let mut errors = Vec::new();
+
+    let basic = match read_config::<BasicConfiguration>(basic_path) {
+        Ok(basic) => {
+            errors.extend(in_file(basic_path, basic.validation_errors()));
+            Some(basic)
+        }
+        Err(err) => {
+            errors.push(err);
+            None
+        }
+    };
+    let advanced = match read_config::<AdvancedConfiguration>(advanced_path) {
+        Ok(advanced) => {
+            // Values depending on the basic config are checked against the defaults if it is broken
+            let default = BasicConfiguration::default();
+            let basic = basic.as_ref().unwrap_or(&default);
+            errors.extend(in_file(advanced_path, advanced.validation_errors(basic)));
+            Some(advanced)
+        }
+        Err(err) => {
+            errors.push(err);
+            None
+        }
+    };
+
+    match (basic, advanced) {
+        (Some(basic), Some(advanced)) if errors.is_empty() => Ok((basic, advanced)),
+        _ => Err(errors),
+    }
+}
+
+/// Reads and parses a config file without creating it if it doesn't exist,
+/// then applies the environment variable overrides
+fn read_config<T>(path: &Path) -> Result<T, ConfigError>
+where
+    T: LoadConfiguration + Serialize + DeserializeOwned + ConfigDocs,
+{
+    read_versioned(path).map(|(config, _)| apply_env_overrides(config, env_vars()))
+}
+
+/// The environment variables which are valid unicode, others can't be config overrides anyway
+fn env_vars() -> impl Iterator<Item = (String, String)> {
+    env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+}
+
+/// Like `read_config` without the overrides, also returning the version the file had before it was migrated
+fn read_versioned<T: LoadConfiguration + DeserializeOwned>(
+    path: &Path,
+) -> Result<(T, u32), ConfigError> {
+    if !path.exists() {
+        return Err(ConfigError::Missing(path.to_path_buf()));
+    }
+    let content =
+        fs::read_to_string(path).map_err(|err| ConfigError::Read(Some(path.to_path_buf()), err))?;
+    let (config, version) = parse_versioned(&content)
+        .map_err(|err| ConfigError::Parse(Some(path.to_path_buf()), err))?;
+    if version > T::VERSION {
+        warn!(
+            "Config {:?} has version {}, which is newer than the supported version {}",
+            path,
+            version,
+            T::VERSION
+        );
+    }
+    Ok((config, version))
+}
+
+/// Parses a config and migrates it to the current layout, also returning the version it was written with
+fn parse_versioned<T: LoadConfiguration + DeserializeOwned>(
+    content: &str,
+) -> Result<(T, u32), toml::de::Error> {
+    let value: toml::Value = toml::from_str(content)?;
+
+    let version = config_version(&value);
+    let mut value = T::migrate(version, value);
+    if let Some(table) = value.as_table_mut() {
+        table.insert("version".to_string(), toml::Value::from(T::VERSION));
+    }
+    Ok((value.try_into()?, version))
+}
+
+/// The `version` of a parsed config, configs written before versioning are version 0
+fn config_version(value: &toml::Value) -> u32 {
+    value
+        .get("version")
+        .and_then(toml::Value::as_integer)
+        .and_then(|version| u32::try_from(version).ok())
+        .unwrap_or(0)
+}
+
+/// Keeps a custom session server of the old `auth_url` template, the query parameters are added by the server now
+fn migrate_auth_url(value: &mut toml::Value) {
+    let Some(authentication) = value
+        .get_mut("authentication")
+        .and_then(toml::Value::as_table_mut)
+    else {
+        return;
+    };
+    authentication.remove("prevent_proxy_connection_auth_url");
+    let Some(toml::Value::String(auth_url)) = authentication.remove("auth_url") else {
+        return;
+    };
+    if let Some((session_server_url, _)) = auth_url.split_once(auth::HAS_JOINED_PATH) {
+        authentication
+            .entry("session_server_url")
+            .or_insert_with(|| toml::Value::from(session_server_url));
+    } else {
+        warn!(
+            "The old authentication.auth_url {:?} doesn't end with {}, set authentication.session_server_url to keep using its server",
+            auth_url,
+            auth::HAS_JOINED_PATH
+        );
+    }
+}
+
+/// Moves the value at the `from` key path to the `to` key path, creating missing tables.
+/// Used by the migrations for renamed and moved fields, does nothing if `from` doesn't exist
+fn move_key(value: &mut toml::Value, from: &[&str], to: &[&str]) {
+    let Some((last, parents)) = from.split_last() else {
+        return;
+    };
+    let Some(moved) = parents
+        .iter()
+        .try_fold(&mut *value, |value, key| value.get_mut(*key))
+        .and_then(toml::Value::as_table_mut)
+        .and_then(|table| table.remove(*last))
+    else {
+        return;
+    };
+
+    let Some((last, parents)) = to.split_last() else {
+        return;
+    };
+    let mut target = value;
+    for key in parents {
+        let Some(table) = target.as_table_mut() else {
+            return;
+        };
+        target = table
+            .entry(key.to_string())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    }
+    if let Some(table) = target.as_table_mut() {
+        // A value already at the new place wins over the old one
+        table.entry(last.to_string()).or_insert(moved);
+    }
+}
+
+/// Attaches the config file to the validation errors of a config read from it
+fn in_file(path: &Path, errors: Vec<ConfigError>) -> Vec<ConfigError> {
+    errors.into_iter().map(|err| err.in_file(path)).collect()
+}
+
+const REDACTED: &str = "<redacted>";
+
+/// Serializes `config`, replacing every non empty string at the given key paths with `marker`
+fn to_sanitized_toml<T: Serialize>(config: &T, secrets: &[&[&str]], marker: &str) -> String {
+    let mut value = toml::Value::try_from(config).unwrap();
+    for path in secrets {
+        let mut current = Some(&mut value);
+        for key in *path {
+            current = current.and_then(|value| value.get_mut(key));
+        }
+        if let Some(toml::Value::String(secret)) = current {
+            if !secret.is_empty() {
+                *secret = marker.to_string();
+            }
         }
     }
+    toml::to_string(&value).unwrap()
 }
 
 trait LoadConfiguration {
     fn load() -> Self
     where
-        Self: Sized + Default + Serialize + DeserializeOwned,
+        Self: Sized + Default + Serialize + DeserializeOwned + ConfigDocs,
     {
-        let path = Self::get_path();
+        Self::load_from(&Self::get_path(), config_readonly())
+    }
 
-        let config = if path.exists() {
-            let file_content = fs::read_to_string(path)
-                .unwrap_or_else(|_| panic!("Couldn't read configuration file at {:?}", path));
+    /// Loads the config at `path`, panicking if it can't be read or is invalid, see `try_load_from`
+    fn load_from(path: &Path, readonly: bool) -> Self
+    where
+        Self: Sized + Default + Serialize + DeserializeOwned + ConfigDocs,
+    {
+        Self::try_load_from(path, readonly).unwrap_or_else(|errors| {
+            panic!(
+                "Invalid config. {}. If this is caused by a config update, just delete the old config and start Pumpkin again",
+                errors.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+            )
+        })
+    }
 
-            toml::from_str(&file_content).unwrap_or_else(|err| {
-                panic!(
-                    "Couldn't parse config at {:?}. Reason: {}. This is is proberbly caused by an Config update, Just delete the old Config and start Pumpkin again",
+    /// Loads the config at `path`. A missing config is created with the defaults, unless `readonly` is set.
+    /// Failing to write the defaults only logs a warning
+    fn try_load_from(path: &Path, readonly: bool) -> Result<Self, Vec<ConfigError>>
+    where
+        Self: Sized + Default + Serialize + DeserializeOwned + ConfigDocs,
+    {
+        let config = if path.exists() {
+            let (config, version) = read_versioned::<Self>(path).map_err(|err| vec![err])?;
+            if version < Self::VERSION {
+                info!(
+                    "Migrated config {:?} from version {} to {}",
                     path,
-                    err.message()
-                )
-            })
+                    version,
+                    Self::VERSION
+                );
+                if !readonly {
+                    if let Err(err) = fs::write(path, to_commented_toml(&config)) {
+                        warn!(
+                            "Couldn't write migrated config to {:?}. Reason: {}",
+                            path, err
+                        );
+                    }
+                }
+            }
+            config
+        } else if readonly {
+            info!(
+                "No config at {:?}, using the defaults (read only mode)",
+                path
+            );
+            Self::default()
         } else {
             let content = Self::default();
 
-            if let Err(err) = fs::write(path, toml::to_string(&content).unwrap()) {
+            if let Some(parent) = path.parent() {
+                if let Err(err) = fs::create_dir_all(parent) {
+                    warn!(
+                        "Couldn't create config directory {:?}. Reason: {}",
+                        parent, err
+                    );
+                }
+            }
+
+            if let Err(err) = fs::write(path, to_commented_toml(&content)) {
                 warn!(
                     "Couldn't write default config to {:?}. Reason: {}. This is is proberbly caused by an Config update, Just delete the old Config and start Pumpkin again",
                     path, err
@@ -144,42 +1241,807 @@ trait LoadConfiguration {
 
             content
         };
+        // Applied after writing the file, so values from the environment (e.g. secrets) never end up in it
+        let config = apply_env_overrides(config, env_vars());
 
-        config.validate();
-        config
+        let errors = config.errors();
+        if !errors.is_empty() {
+            return Err(in_file(path, errors));
+        }
+        for warning in config.warnings() {
+            warn!("Config {:?}: {}", path, warning);
+        }
+        Ok(config)
     }
 
-    fn get_path() -> &'static Path;
+    /// The current layout version, bump it together with a new step in `migrate`
+    const VERSION: u32;
+
+    fn get_path() -> PathBuf;
 
-    fn validate(&self);
+    /// Every invalid value
+    fn errors(&self) -> Vec<ConfigError>;
+
+    /// Problems which don't make the config invalid, they get logged after loading
+    fn warnings(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Upgrades a config written with `from_version` before it gets deserialized.
+    /// Missing fields are filled with the defaults anyway, only renamed and moved fields need a step here
+    fn migrate(_from_version: u32, value: toml::Value) -> toml::Value {
+        value
+    }
 }
 
 impl LoadConfiguration for AdvancedConfiguration {
-    fn get_path() -> &'static Path {
-        Path::new("features.toml")
+    const VERSION: u32 = 2;
+
+    fn get_path() -> PathBuf {
+        config_dir().join("features.toml")
     }
 
-    fn validate(&self) {
-        self.resource_pack.validate()
+    fn errors(&self) -> Vec<ConfigError> {
+        self.validation_errors(&BASIC_CONFIG.get())
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        self.validation_warnings(&BASIC_CONFIG.get())
+    }
+
+    fn migrate(from_version: u32, mut value: toml::Value) -> toml::Value {
+        // 1: `offline_uuid_strategy` moved into `[authentication]`
+        if from_version < 1 {
+            move_key(
+                &mut value,
+                &["offline_uuid_strategy"],
+                &["authentication", "offline_uuid_strategy"],
+            );
+        }
+        // 2: the `auth_url` templates were replaced by `session_server_url`
+        if from_version < 2 {
+            migrate_auth_url(&mut value);
+        }
+        value
     }
 }
 
 impl LoadConfiguration for BasicConfiguration {
-    fn get_path() -> &'static Path {
-        Path::new("configuration.toml")
+    const VERSION: u32 = 1;
+
+    fn get_path() -> PathBuf {
+        config_dir().join("configuration.toml")
+    }
+
+    fn errors(&self) -> Vec<ConfigError> {
+        self.validation_errors()
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        self.validation_warnings()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fs,
+        ops::Deref,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::{Duration, SystemTime},
+    };
+
+    use crate::{
+        changed_sections, config_version, dump_files, migrate_auth_url, move_key, read_versioned,
+        restart_required, validate_files, AdvancedConfiguration, BasicConfiguration, ConfigError,
+        ConfigWatcher, LoadConfiguration, OfflineUuidStrategy, ReloadableConfig, MAX_SPAWN_RADIUS,
+        MAX_TPS, MIN_TPS, REDACTED, VIEW_DISTANCE_RAMPUP_STEP,
+    };
+
+    fn messages(errors: Vec<ConfigError>) -> Vec<String> {
+        errors.iter().map(ToString::to_string).collect()
+    }
+
+    /// An empty directory only used by one test, it is removed again when dropped
+    pub(crate) struct TestDir(PathBuf);
+
+    impl TestDir {
+        pub(crate) fn new(name: &str) -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let dir = std::env::temp_dir().join(format!(
+                "pumpkin-config-test-{name}-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Deref for TestDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let config = ReloadableConfig::new(BasicConfiguration::default());
+        let mut receiver = config.subscribe();
+        // The current config is there right away, without a change
+        assert!(!receiver.has_changed().unwrap());
+        assert_eq!(receiver.borrow().motd, config.get().motd);
+
+        let old = config.get();
+        let mut reloaded = BasicConfiguration::default();
+        reloaded.motd = "Reloaded".to_string();
+        config.replace(reloaded);
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(receiver.borrow_and_update().motd, "Reloaded");
+        assert_eq!(config.get().motd, "Reloaded");
+        // Later subscribers start with the new config
+        assert_eq!(config.subscribe().borrow().motd, "Reloaded");
+        // The replaced config isn't kept alive by the config, only by the `Arc` of this test
+        assert_eq!(Arc::strong_count(&old), 1);
+    }
+
+    #[test]
+    fn test_sanitized_toml() {
+        let mut config = AdvancedConfiguration::default();
+        config.rcon.password = "rcon-password".to_string();
+        config.proxy.velocity.secret = "velocity-secret".to_string();
+
+        let dump = config.to_sanitized_toml();
+        assert!(!dump.contains("rcon-password"));
+        assert!(!dump.contains("velocity-secret"));
+        assert_eq!(dump.matches(REDACTED).count(), 2);
+
+        let parsed: AdvancedConfiguration = toml::from_str(&dump).unwrap();
+        assert_eq!(parsed.rcon.password, REDACTED);
+        assert_eq!(parsed.proxy.velocity.secret, REDACTED);
+
+        let dump = BasicConfiguration::default().to_sanitized_toml();
+        assert!(!dump.contains(REDACTED));
+    }
+
+    #[test]
+    fn test_redacted_dump() {
+        let mut config = AdvancedConfiguration::default();
+        config.rcon.password = "rcon-password".to_string();
+        config.proxy.velocity.secret = "velocity-secret".to_string();
+
+        let dump = config.redacted_dump();
+        assert!(!dump.contains("rcon-password"));
+        assert!(!dump.contains("velocity-secret"));
+
+        let parsed: AdvancedConfiguration = toml::from_str(&dump).unwrap();
+        assert_eq!(parsed.rcon.password, "***");
+        assert_eq!(parsed.proxy.velocity.secret, "***");
+    }
+
+    #[test]
+    fn test_dump_files() {
+        let dir = TestDir::new("dump");
+        let basic = dir.join("configuration.toml");
+        let advanced = dir.join("features.toml");
+        fs::write(&basic, "motd = \"Dumped\"\n").unwrap();
+        fs::write(
+            &advanced,
+            "[rcon]\npassword = \"rcon-password\"\n[proxy.velocity]\nsecret = \"velocity-secret\"\n",
+        )
+        .unwrap();
+
+        let dump = dump_files(&basic, &advanced).unwrap();
+        assert!(!dump.contains("rcon-password"));
+        assert!(!dump.contains("velocity-secret"));
+        assert!(dump.contains("motd = \"Dumped\""));
+        assert!(dump.contains("configuration.toml") && dump.contains("features.toml"));
+
+        // Invalid configs are reported instead
+        fs::write(&basic, "view_distance = 1").unwrap();
+        assert!(dump_files(&basic, &advanced).is_err());
+    }
+
+    #[test]
+    fn test_changed_sections() {
+        let old = AdvancedConfiguration::default();
+        assert!(changed_sections(&old, &AdvancedConfiguration::default()).is_empty());
+
+        let mut new = AdvancedConfiguration::default();
+        new.pvp.default.enabled = !old.pvp.default.enabled;
+        new.messages.banned = "Go away".to_string();
+        assert_eq!(changed_sections(&old, &new), ["messages", "pvp"]);
+
+        // Sections which only exist in one of the configs count as changed as well
+        new.gamerules.insert(
+            "keepInventory".to_string(),
+            crate::gamerules::GameRuleValue::Bool(true),
+        );
+        assert_eq!(
+            changed_sections(&old, &new),
+            ["gamerules", "messages", "pvp"]
+        );
+    }
+
+    #[test]
+    fn test_validate_files() {
+        let dir = TestDir::new("validate");
+        let basic = dir.join("configuration.toml");
+        let advanced = dir.join("features.toml");
+
+        fs::write(
+            &basic,
+            "view_distance = 1\nonline_mode = true\nencryption = false\n",
+        )
+        .unwrap();
+        fs::write(
+            &advanced,
+            "max_concurrent_chunk_gen = 0\n[access_control]\nips = [\"10.0.0.0/33\"]\n[world_gen]\nsea_level = 500\n",
+        )
+        .unwrap();
+
+        let errors = validate_files(&basic, &advanced).unwrap_err();
+        let count = |path: &PathBuf| {
+            errors
+                .iter()
+                .filter(|err| matches!(err, ConfigError::Validation { path: Some(file), .. } if file == path))
+                .count()
+        };
+        // The default simulation distance is larger than the view distance as well
+        assert_eq!(count(&basic), 3);
+        assert_eq!(count(&advanced), 3);
+        assert_eq!(errors.len(), 6);
+
+        // Broken and missing files are reported, not created
+        fs::write(&basic, "view_distance = \"far\"").unwrap();
+        fs::remove_file(&advanced).unwrap();
+        let errors = validate_files(&basic, &advanced).unwrap_err();
+        assert!(matches!(errors[0], ConfigError::Parse(..)));
+        assert!(matches!(errors[1], ConfigError::Missing(..)));
+        assert_eq!(errors.len(), 2);
+        assert!(!advanced.exists());
+
+        fs::write(&basic, "").unwrap();
+        fs::write(&advanced, "").unwrap();
+        assert!(validate_files(&basic, &advanced).unwrap().is_empty());
+
+        // Warnings are returned, the logger isn't set up when checking the config
+        fs::write(
+            &advanced,
+            "[proxy]\nenabled = true\n[proxy.bungeecord]\nenabled = true\n",
+        )
+        .unwrap();
+        let warnings = validate_files(&basic, &advanced).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with(&format!("{:?}: A proxy forwarding mode", advanced)));
+    }
+
+    #[test]
+    fn test_random_ticks_per_section() {
+        let mut config = AdvancedConfiguration::default();
+        assert_eq!(config.random_ticks_per_section(0, 10), 3);
+        assert_eq!(config.random_ticks_per_section(10, 10), 3);
+        assert_eq!(config.random_ticks_per_section(11, 10), 0);
+
+        config.random_tick_speed = 0;
+        assert_eq!(config.random_ticks_per_section(0, 10), 0);
+
+        config.gamerules.insert(
+            "randomTickSpeed".to_string(),
+            crate::gamerules::GameRuleValue::Int(5),
+        );
+        assert_eq!(
+            config.warnings(),
+            ["gamerules.randomTickSpeed is ignored, its initial value is random_tick_speed"]
+        );
+    }
+
+    #[test]
+    fn test_can_place_at() {
+        let mut config = AdvancedConfiguration::default();
+        assert!(config.can_place_at(-64, -64, 320));
+        assert!(config.can_place_at(319, -64, 320));
+        assert!(!config.can_place_at(320, -64, 320));
+        assert!(!config.can_place_at(-65, -64, 320));
+
+        config.max_build_height = Some(256);
+        assert!(config.can_place_at(255, -64, 320));
+        assert!(!config.can_place_at(256, -64, 320));
+        // The dimension limit still applies
+        assert!(!config.can_place_at(200, -64, 128));
     }
 
-    fn validate(&self) {
-        assert!(self.view_distance >= 2, "View distance must be at least 2");
+    #[test]
+    fn test_readonly_load() {
+        let dir = TestDir::new("readonly");
+        let path = dir.join("config").join("configuration.toml");
+
+        let config = BasicConfiguration::load_from(&path, true);
+        assert_eq!(
+            config.view_distance,
+            BasicConfiguration::default().view_distance
+        );
+        // Not even the directory gets created
+        assert!(!dir.join("config").exists());
+
+        BasicConfiguration::load_from(&path, false);
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_portal_radius() {
+        let config = AdvancedConfiguration::default();
+        assert_eq!(config.portal_search_radius, 128);
+        assert_eq!(config.portal_creation_radius, 16);
+
+        let invalid = AdvancedConfiguration {
+            portal_search_radius: 0,
+            portal_creation_radius: -1,
+            ..Default::default()
+        };
+        assert_eq!(
+            invalid
+                .validation_errors(&BasicConfiguration::default())
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_view_distance_rampup() {
+        let disabled = AdvancedConfiguration::default();
+        assert_eq!(disabled.view_distance_rampup(10), [(Duration::ZERO, 10)]);
+
+        let config = AdvancedConfiguration {
+            join_view_distance_rampup: true,
+            initial_view_distance: 4,
+            ..Default::default()
+        };
+        let rampup = config.view_distance_rampup(7);
+        assert_eq!(
+            rampup,
+            [
+                (Duration::ZERO, 4),
+                (VIEW_DISTANCE_RAMPUP_STEP, 5),
+                (VIEW_DISTANCE_RAMPUP_STEP * 2, 6),
+                (VIEW_DISTANCE_RAMPUP_STEP * 3, 7)
+            ]
+        );
+        // Lower view distances are reached right away
+        assert_eq!(config.view_distance_rampup(3), [(Duration::ZERO, 3)]);
+
+        let basic = BasicConfiguration::default();
+        for initial_view_distance in [1, basic.view_distance + 1] {
+            let invalid = AdvancedConfiguration {
+                initial_view_distance,
+                ..Default::default()
+            };
+            assert_eq!(invalid.validation_errors(&basic).len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_try_load() {
+        let dir = TestDir::new("try-load");
+        let path = dir.join("configuration.toml");
+
+        fs::write(&path, "view_distance = 33").unwrap();
+        let errors = BasicConfiguration::try_load_from(&path, true)
+            .err()
+            .unwrap();
+        assert!(matches!(errors[..], [ConfigError::Validation { .. }]));
+
+        fs::write(&path, "view_distance = ").unwrap();
+        let errors = BasicConfiguration::try_load_from(&path, true)
+            .err()
+            .unwrap();
+        assert!(matches!(errors[..], [ConfigError::Parse(..)]));
+
+        fs::write(&path, "view_distance = 12").unwrap();
+        let config = BasicConfiguration::try_load_from(&path, true).unwrap();
+        assert_eq!(config.view_distance, 12);
+        assert!(config.warnings().is_empty());
+
+        // Unwise but valid values load and only log a warning
+        fs::write(&path, "view_distance = 20").unwrap();
+        let config = BasicConfiguration::try_load_from(&path, true).unwrap();
+        assert_eq!(config.view_distance, 20);
+        assert_eq!(
+            config.warnings(),
+            ["View distance of 20 is above 16, this may hurt performance"]
+        );
+
+        fs::write(&path, "view_distance = 40").unwrap();
+        let errors = BasicConfiguration::try_load_from(&path, true)
+            .err()
+            .unwrap();
+        let [ConfigError::Validation {
+            path: Some(file),
+            field,
+            message,
+        }] = &errors[..]
+        else {
+            panic!("Unexpected errors {errors:?}");
+        };
+        assert_eq!(file, &path);
+        assert_eq!(field, "view_distance");
+        assert_eq!(message, "View distance must not be larger than 32");
+    }
+
+    #[test]
+    fn test_from_toml_str() {
+        let config = BasicConfiguration::from_toml_str("view_distance = 12").unwrap();
+        assert_eq!(config.view_distance, 12);
+        // Everything omitted keeps its default
+        let default = BasicConfiguration::default();
+        assert_eq!(config.simulation_distance, default.simulation_distance);
+        assert_eq!(config.motd, default.motd);
+        assert_eq!(config.online_mode, default.online_mode);
+
+        let config =
+            AdvancedConfiguration::from_toml_str("[packet_compression]\nlevel = 9").unwrap();
+        assert_eq!(config.packet_compression.compression_info.level, 9);
+        let default = AdvancedConfiguration::default();
+        assert_eq!(
+            config.packet_compression.enabled,
+            default.packet_compression.enabled
+        );
+        assert_eq!(
+            config.max_concurrent_chunk_gen,
+            default.max_concurrent_chunk_gen
+        );
+        assert_eq!(config.logging.max_size_mb, default.logging.max_size_mb);
+
+        // Also validated
+        let err = BasicConfiguration::from_toml_str("view_distance = 40")
+            .err()
+            .unwrap();
+        let ConfigError::Validation {
+            path: None,
+            field,
+            message,
+        } = &err
+        else {
+            panic!("Unexpected error {err:?}");
+        };
+        assert_eq!(field, "view_distance");
+        assert_eq!(message, "View distance must not be larger than 32");
+        let err = AdvancedConfiguration::from_toml_str("[packet_compression]\nlevel = 10")
+            .err()
+            .unwrap();
         assert!(
-            self.view_distance <= 32,
-            "View distance must be less than 32"
+            matches!(&err, ConfigError::Validation { path: None, field, .. } if field == "packet_compression.level")
         );
-        if self.online_mode {
-            assert!(
-                self.encryption,
-                "When Online Mode is enabled, Encryption must be enabled"
+        assert!(matches!(
+            BasicConfiguration::from_toml_str("view_distance = ").err(),
+            Some(ConfigError::Parse(None, _))
+        ));
+    }
+
+    #[test]
+    fn test_config_error() {
+        // Both convert with `?`, without a config path
+        fn load(path: &Path) -> Result<BasicConfiguration, ConfigError> {
+            Ok(toml::from_str(&fs::read_to_string(path)?)?)
+        }
+        let dir = TestDir::new("config-error");
+        let path = dir.join("configuration.toml");
+
+        let err = load(&path).err().unwrap();
+        assert!(matches!(err, ConfigError::Read(None, _)));
+        assert!(err.to_string().starts_with("Couldn't read the config: "));
+        assert!(std::error::Error::source(&err).is_some());
+
+        fs::write(&path, "view_distance = ").unwrap();
+        let err = load(&path).err().unwrap();
+        assert!(matches!(err, ConfigError::Parse(None, _)));
+        assert!(err.to_string().starts_with("Couldn't parse the config: "));
+
+        fs::write(&path, "view_distance = 12").unwrap();
+        assert_eq!(load(&path).unwrap().view_distance, 12);
+
+        let mut logging = AdvancedConfiguration::default().logging;
+        logging.file = Some(dir.join("pumpkin.log"));
+        logging.max_size_mb = 0;
+        let err = logging.validate().unwrap_err();
+        let ConfigError::Validation { field, .. } = &err else {
+            panic!("Unexpected error {err:?}");
+        };
+        assert_eq!(field, "logging.max_size_mb");
+        assert_eq!(err.to_string(), "logging.max_size_mb must be at least 1");
+    }
+
+    #[test]
+    fn test_migrate() {
+        let dir = TestDir::new("migrate");
+
+        // Written before versioning, with the strategy still at the top level
+        let path = dir.join("features.toml");
+        fs::write(
+            &path,
+            "offline_uuid_strategy = \"Random\"\nrandom_tick_speed = 5\n[authentication]\nenabled = false\n",
+        )
+        .unwrap();
+        let (config, version) = read_versioned::<AdvancedConfiguration>(&path).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(config.version, AdvancedConfiguration::VERSION);
+        assert!(matches!(
+            config.authentication.offline_uuid_strategy,
+            OfflineUuidStrategy::Random
+        ));
+        assert!(!config.authentication.enabled);
+        assert_eq!(config.random_tick_speed, 5);
+
+        // Loading writes the upgraded config back, unless it is read only
+        let path = dir.join("configuration.toml");
+        fs::write(&path, "view_distance = 12\n").unwrap();
+        BasicConfiguration::try_load_from(&path, true).unwrap();
+        let read = || toml::from_str::<toml::Value>(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(config_version(&read()), 0);
+
+        let config = BasicConfiguration::try_load_from(&path, false).unwrap();
+        assert_eq!(config.view_distance, 12);
+        assert_eq!(config_version(&read()), BasicConfiguration::VERSION);
+        let (config, version) = read_versioned::<BasicConfiguration>(&path).unwrap();
+        assert_eq!(version, BasicConfiguration::VERSION);
+        assert_eq!(config.view_distance, 12);
+    }
+
+    #[test]
+    fn test_migrate_auth_url() {
+        let mut value: toml::Value = toml::from_str(
+            r#"
+            [authentication]
+            auth_url = "https://auth.example.com/api/session/minecraft/hasJoined?username={username}&serverId={server_hash}"
+            prevent_proxy_connection_auth_url = "https://sessionserver.mojang.com/session/minecraft/hasJoined?username={username}&serverId={server_hash}&ip={ip}"
+            "#,
+        )
+        .unwrap();
+        migrate_auth_url(&mut value);
+        assert_eq!(
+            value,
+            toml::from_str(
+                "[authentication]\nsession_server_url = \"https://auth.example.com/api\"\n"
             )
+            .unwrap()
+        );
+
+        let config: AdvancedConfiguration =
+            AdvancedConfiguration::migrate(1, value).try_into().unwrap();
+        assert_eq!(
+            config.authentication.has_joined_url(),
+            "https://auth.example.com/api/session/minecraft/hasJoined"
+        );
+    }
+
+    #[test]
+    fn test_move_key() {
+        let mut value: toml::Value = toml::from_str("a = 1\n[b]\nc = 2\n").unwrap();
+        move_key(&mut value, &["a"], &["d", "e"]);
+        move_key(&mut value, &["b", "c"], &["f"]);
+        // Missing keys are ignored
+        move_key(&mut value, &["missing"], &["g"]);
+        assert_eq!(value, toml::from_str("f = 2\n[b]\n[d]\ne = 1\n").unwrap());
+
+        // Values already at the new place are kept
+        let mut value: toml::Value = toml::from_str("a = 1\nb = 2\n").unwrap();
+        move_key(&mut value, &["a"], &["b"]);
+        assert_eq!(value, toml::from_str("b = 2").unwrap());
+    }
+
+    #[test]
+    fn test_simulation_distance() {
+        let mut config = BasicConfiguration::default();
+        assert!(config.validation_errors().is_empty());
+
+        config.view_distance = 10;
+        config.simulation_distance = 12;
+        assert_eq!(
+            messages(config.validation_errors()),
+            ["Simulation distance must not be larger than the view distance"]
+        );
+
+        config.simulation_distance = 1;
+        assert_eq!(
+            messages(config.validation_errors()),
+            ["Simulation distance must be at least 2"]
+        );
+
+        config.simulation_distance = 10;
+        assert!(config.validation_errors().is_empty());
+    }
+
+    #[test]
+    fn test_motd_component() {
+        let mut config = BasicConfiguration::default();
+        assert_eq!(config.motd_component(), serde_json::json!(config.motd));
+
+        config.motd = "&6Pumpkin\nline two\nline three".to_string();
+        assert_eq!(
+            config.motd_component(),
+            serde_json::json!({"text": "", "extra": [{"text": "Pumpkin\nline two", "color": "gold"}]})
+        );
+
+        config.motd = r##"{"text": "Json", "color": "#FF8800"}"##.to_string();
+        assert_eq!(
+            config.motd_component(),
+            serde_json::json!({"text": "Json", "color": "#FF8800"})
+        );
+        // Invalid JSON is just text
+        config.motd = "{Pumpkin}".to_string();
+        assert_eq!(config.motd_component(), serde_json::json!("{Pumpkin}"));
+    }
+
+    #[test]
+    fn test_spawn_protection() {
+        let mut config = BasicConfiguration::default();
+        assert!(config.is_spawn_protected(16, -16, 0, 0, false));
+        assert!(config.is_spawn_protected(116, 84, 100, 100, false));
+        assert!(!config.is_spawn_protected(17, 0, 0, 0, false));
+        assert!(!config.is_spawn_protected(0, -17, 0, 0, false));
+        // Operators can build anywhere
+        assert!(!config.is_spawn_protected(0, 0, 0, 0, true));
+        // No overflow at the ends of the world
+        assert!(!config.is_spawn_protected(i32::MAX, i32::MIN, i32::MIN, i32::MAX, false));
+
+        config.spawn_protection_radius = 0;
+        assert!(!config.is_spawn_protected(0, 0, 0, 0, false));
+
+        config.spawn_protection_radius = MAX_SPAWN_RADIUS + 1;
+        config.spawn_radius = MAX_SPAWN_RADIUS;
+        assert_eq!(
+            messages(config.validation_errors()),
+            ["Spawn protection radius must not be larger than 1000"]
+        );
+    }
+
+    #[test]
+    fn test_tps() {
+        let mut config = BasicConfiguration::default();
+        assert_eq!(config.tick_duration(), Duration::from_millis(50));
+        assert!(config.validation_warnings().is_empty());
+
+        for tps in [MIN_TPS, MAX_TPS] {
+            config.tps = tps;
+            assert!(config.validation_errors().is_empty());
         }
+        assert_eq!(config.tick_duration(), Duration::from_millis(1));
+        assert_eq!(config.validation_warnings().len(), 1);
+        config.tps = MIN_TPS;
+        assert_eq!(config.tick_duration(), Duration::from_secs(1));
+
+        for tps in [0.0, -5.0, 0.99, 1000.5, 100000.0, f32::NAN, f32::INFINITY] {
+            config.tps = tps;
+            assert_eq!(
+                messages(config.validation_errors()),
+                ["TPS must be between 1 and 1000"]
+            );
+        }
+    }
+
+    #[test]
+    fn test_generation_threads() {
+        let basic = BasicConfiguration::default();
+        let mut config = AdvancedConfiguration::default();
+        assert!(config.generation_threads() >= 1);
+
+        config.generation_threads = Some(2);
+        assert_eq!(config.generation_threads(), 2);
+        assert!(config.validation_errors(&basic).is_empty());
+
+        config.generation_threads = Some(0);
+        assert_eq!(
+            messages(config.validation_errors(&basic)),
+            ["generation_threads must be at least 1"]
+        );
+    }
+
+    #[test]
+    fn test_biome_tuning() {
+        let basic = BasicConfiguration::default();
+        let mut config = AdvancedConfiguration::default();
+        assert!(config.warnings().is_empty());
+
+        config.biome_tuning.temperature_offset = 0.5;
+        config.biome_tuning.humidity_offset = -1.5;
+        config.biome_tuning.continentalness_offset = f64::NAN;
+        // Out of range offsets are clamped, so the config stays valid
+        assert!(config.validation_errors(&basic).is_empty());
+        assert_eq!(
+            config.warnings(),
+            [
+                "biome_tuning.humidity_offset is out of range (-1.5), clamping it to [-1.0, 1.0]",
+                "biome_tuning.continentalness_offset is NaN, using 0.0",
+                "biome_tuning has no effect yet, none of the generators select biomes by climate so far",
+            ]
+        );
+        assert_eq!(config.biome_tuning.temperature_offset(), 0.5);
+        assert_eq!(config.biome_tuning.humidity_offset(), -1.0);
+        assert_eq!(config.biome_tuning.continentalness_offset(), 0.0);
+    }
+
+    #[test]
+    fn test_restart_required() {
+        let old = BasicConfiguration::default();
+        let mut new = BasicConfiguration::default();
+        new.motd = "Reloaded".to_string();
+        new.server_address = "127.0.0.1:25566".parse().unwrap();
+        let changed = changed_sections(&old, &new);
+        assert_eq!(changed, ["motd", "server_address"]);
+        assert_eq!(
+            restart_required(&changed, BasicConfiguration::RESTART_REQUIRED),
+            ["server_address"]
+        );
+
+        let old = AdvancedConfiguration::default();
+        let mut new = AdvancedConfiguration::default();
+        new.pvp.default.enabled = !old.pvp.default.enabled;
+        let changed = changed_sections(&old, &new);
+        assert!(restart_required(&changed, AdvancedConfiguration::RESTART_REQUIRED).is_empty());
+    }
+
+    #[test]
+    fn test_restart_required_keys() {
+        let old = BasicConfiguration::default();
+        let changes: [(&str, fn(&mut BasicConfiguration)); 5] = [
+            ("online_mode", |config| config.online_mode = false),
+            ("allow_nether", |config| config.allow_nether = false),
+            ("allow_end", |config| config.allow_end = false),
+            ("scrub_ips", |config| config.scrub_ips = false),
+            ("tps", |config| config.tps = 30.0),
+        ];
+        for (key, change) in changes {
+            let mut new = BasicConfiguration::default();
+            change(&mut new);
+            let changed = changed_sections(&old, &new);
+            assert_eq!(
+                restart_required(&changed, BasicConfiguration::RESTART_REQUIRED),
+                [key]
+            );
+        }
+
+        let old = AdvancedConfiguration::default();
+        let new = AdvancedConfiguration {
+            hot_reload: !old.hot_reload,
+            ..Default::default()
+        };
+        let changed = changed_sections(&old, &new);
+        assert_eq!(
+            restart_required(&changed, AdvancedConfiguration::RESTART_REQUIRED),
+            ["hot_reload"]
+        );
+    }
+
+    #[test]
+    fn test_config_watcher() {
+        let dir = TestDir::new("watcher");
+        let path = dir.join("features.toml");
+
+        let mut watcher = ConfigWatcher::with_paths(vec![path.clone()]);
+        assert!(!watcher.poll());
+
+        // Creating the file counts as a change
+        fs::write(&path, "hot_reload = true").unwrap();
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+
+        // Set explicitly, as the timestamp resolution of the file system may be coarse
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(60))
+            .unwrap();
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
     }
 }
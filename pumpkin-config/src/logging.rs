@@ -1,14 +1,30 @@
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize)]
+use crate::{docs::ConfigDocs, ConfigError};
+
+#[derive(Deserialize, Serialize, ConfigDocs)]
 #[serde(default)]
 pub struct LoggingConfig {
+    /// Whether anything gets logged
     pub enabled: bool,
+    /// The most verbose level which gets logged
     pub level: LevelFilter,
+    /// Whether the `RUST_LOG` environment variable can override the level
     pub env: bool,
+    /// Whether the name of the logging thread is shown
     pub threads: bool,
+    /// Whether the output is colored
     pub color: bool,
+    /// Whether every line starts with a timestamp
     pub timestamp: bool,
+    /// Also writes the log into this file, next to the console output
+    pub file: Option<PathBuf>,
+    /// The size in MiB after which the log file is rotated, the old log gets the suffix `.1`
+    pub max_size_mb: u64,
+    /// How many rotated log files are kept, older ones are deleted
+    pub max_files: u32,
 }
 
 impl Default for LoggingConfig {
@@ -20,7 +36,28 @@ impl Default for LoggingConfig {
             threads: true,
             color: true,
             timestamp: true,
+            file: None,
+            max_size_mb: 10,
+            max_files: 5,
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// The size in bytes after which the log file is rotated
+    #[must_use]
+    pub fn max_file_size(&self) -> u64 {
+        self.max_size_mb.saturating_mul(1024 * 1024)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.file.is_some() && self.max_size_mb == 0 {
+            return Err(ConfigError::validation(
+                "logging.max_size_mb",
+                "logging.max_size_mb must be at least 1",
+            ));
         }
+        Ok(())
     }
 }
 
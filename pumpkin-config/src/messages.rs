@@ -0,0 +1,102 @@
+use pumpkin_core::text::TextComponent;
+use serde::{Deserialize, Serialize};
+
+use crate::docs::ConfigDocs;
+
+/// Messages shown to players when they get disconnected.
+///
+/// Every message can either be a JSON text component or plain text using `&` formatting codes (e.g. `&cRed`)
+#[derive(Deserialize, Serialize, ConfigDocs)]
+#[serde(default)]
+pub struct MessagesConfig {
+    /// Shown when `max_players` is reached
+    pub server_full: String,
+    /// Shown when a player is not on the whitelist
+    pub whitelist_rejected: String,
+    /// Shown when a banned player or IP tries to join. `{reason}` and `{expires}` (a date or `forever`) get replaced
+    pub banned: String,
+    /// Shown when a player declines a required resource pack
+    pub resource_pack_declined: String,
+    /// Shown when the client is older than the server.
+    /// `{client_protocol}`, `{server_version}` and `{server_protocol}` get replaced
+    pub outdated_client: String,
+    /// Shown when the client is newer than the server.
+    /// `{client_protocol}`, `{server_version}` and `{server_protocol}` get replaced
+    pub outdated_server: String,
+}
+
+impl Default for MessagesConfig {
+    fn default() -> Self {
+        Self {
+            server_full: "The server is currently full, please try again later".to_string(),
+            whitelist_rejected: "You are not whitelisted on this server!".to_string(),
+            banned: "You are banned from this server!\nReason: {reason}".to_string(),
+            resource_pack_declined: "This server requires a custom resource pack".to_string(),
+            outdated_client: "Client outdated ({client_protocol}), Server uses Minecraft {server_version}, Protocol {server_protocol}".to_string(),
+            outdated_server: "Server outdated, Server uses Minecraft {server_version}, Protocol {server_protocol}".to_string(),
+        }
+    }
+}
+
+impl MessagesConfig {
+    pub fn server_full(&self) -> TextComponent<'static> {
+        TextComponent::from_config_str(&self.server_full)
+    }
+
+    pub fn whitelist_rejected(&self) -> TextComponent<'static> {
+        TextComponent::from_config_str(&self.whitelist_rejected)
+    }
+
+    pub fn banned(&self, reason: &str, expires: &str) -> TextComponent<'static> {
+        TextComponent::from_config_str(
+            &self
+                .banned
+                .replace("{reason}", reason)
+                .replace("{expires}", expires),
+        )
+    }
+
+    pub fn resource_pack_declined(&self) -> TextComponent<'static> {
+        TextComponent::from_config_str(&self.resource_pack_declined)
+    }
+
+    pub fn outdated_client(
+        &self,
+        client_protocol: i32,
+        server_version: &str,
+        server_protocol: u32,
+    ) -> TextComponent<'static> {
+        TextComponent::from_config_str(&replace_version_placeholders(
+            &self.outdated_client,
+            client_protocol,
+            server_version,
+            server_protocol,
+        ))
+    }
+
+    pub fn outdated_server(
+        &self,
+        client_protocol: i32,
+        server_version: &str,
+        server_protocol: u32,
+    ) -> TextComponent<'static> {
+        TextComponent::from_config_str(&replace_version_placeholders(
+            &self.outdated_server,
+            client_protocol,
+            server_version,
+            server_protocol,
+        ))
+    }
+}
+
+fn replace_version_placeholders(
+    message: &str,
+    client_protocol: i32,
+    server_version: &str,
+    server_protocol: u32,
+) -> String {
+    message
+        .replace("{client_protocol}", &client_protocol.to_string())
+        .replace("{server_version}", server_version)
+        .replace("{server_protocol}", &server_protocol.to_string())
+}
@@ -0,0 +1,24 @@
+use std::{io, path::PathBuf};
+
+use crate::json_list;
+
+/// Files which still have to be written, so the callers can do the blocking writes
+/// off the async runtime
+#[must_use]
+pub struct PendingSave {
+    files: Vec<(PathBuf, String)>,
+}
+
+impl PendingSave {
+    /// Nothing to write if `files` is empty
+    pub(crate) fn new(files: Vec<(PathBuf, String)>) -> Option<Self> {
+        (!files.is_empty()).then_some(Self { files })
+    }
+
+    pub fn write(&self) -> io::Result<()> {
+        for (path, content) in &self.files {
+            json_list::write_file(path, content)?;
+        }
+        Ok(())
+    }
+}
@@ -1,21 +1,128 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Default)]
+use crate::{docs::ConfigDocs, ConfigError};
+
+#[derive(Deserialize, Serialize, Default, ConfigDocs)]
 #[serde(default)]
 pub struct ProxyConfig {
+    /// Whether players connect through a proxy
     pub enabled: bool,
+    /// Velocity modern forwarding
+    #[config_docs(nested)]
     pub velocity: VelocityConfig,
+    /// BungeeCord (legacy) forwarding
+    #[config_docs(nested)]
     pub bungeecord: BungeeCordConfig,
 }
-#[derive(Deserialize, Serialize, Default)]
+
+/// How the player info is forwarded by the proxy
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProxyMode<'a> {
+    /// Players connect directly, or through a proxy which doesn't forward anything
+    None,
+    Velocity {
+        secret: &'a str,
+    },
+    BungeeCord,
+}
+
+impl ProxyConfig {
+    /// The forwarding mode in use. `validate` makes sure that at most one is enabled
+    pub fn mode(&self) -> ProxyMode<'_> {
+        if !self.enabled {
+            ProxyMode::None
+        } else if self.velocity.enabled {
+            ProxyMode::Velocity {
+                secret: &self.velocity.secret,
+            }
+        } else if self.bungeecord.enabled {
+            ProxyMode::BungeeCord
+        } else {
+            ProxyMode::None
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.velocity.enabled && self.bungeecord.enabled {
+            return Err(ConfigError::validation("proxy", "Only one proxy forwarding mode can be enabled, but both `proxy.velocity.enabled` and `proxy.bungeecord.enabled` are set"));
+        }
+        if self.velocity.enabled && self.velocity.secret.is_empty() {
+            return Err(ConfigError::validation(
+                "proxy.velocity.secret",
+                "`proxy.velocity.secret` must be set to use Velocity forwarding",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Settings which are valid but most likely not intended
+    pub fn validation_warnings(&self, online_mode: bool) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !self.enabled {
+            return warnings;
+        }
+        if !self.velocity.enabled && !self.bungeecord.enabled {
+            warnings.push("The proxy is enabled without a forwarding mode, so players keep the address and UUID of the proxy connection".to_string());
+        }
+        if online_mode && (self.velocity.enabled || self.bungeecord.enabled) {
+            warnings.push("A proxy forwarding mode is enabled while `online_mode` is true. Proxies usually authenticate players themselves and expect the backend server to run in offline mode".to_string());
+        }
+        warnings
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, ConfigDocs)]
 #[serde(default)]
 pub struct BungeeCordConfig {
+    /// Whether the forwarded player info is trusted. It is not verified,
+    /// so the server must only be reachable through the proxy
     pub enabled: bool,
 }
 
-#[derive(Deserialize, Serialize, Default)]
+#[derive(Deserialize, Serialize, Default, ConfigDocs)]
 #[serde(default)]
 pub struct VelocityConfig {
+    /// Whether the forwarded player info is used
     pub enabled: bool,
+    /// Has to match the forwarding secret configured in Velocity
     pub secret: String,
 }
+
+#[cfg(test)]
+mod test {
+    use super::{ProxyConfig, ProxyMode};
+
+    #[test]
+    fn test_mode() {
+        let mut config = ProxyConfig::default();
+        config.velocity.enabled = true;
+        config.velocity.secret = "secret".to_string();
+        // Disabled as a whole
+        assert_eq!(config.mode(), ProxyMode::None);
+
+        config.enabled = true;
+        assert_eq!(config.mode(), ProxyMode::Velocity { secret: "secret" });
+        assert!(config.validate().is_ok());
+        assert!(config.validation_warnings(false).is_empty());
+        // Valid, but the proxy usually authenticates the players
+        assert_eq!(config.validation_warnings(true).len(), 1);
+
+        config.bungeecord.enabled = true;
+        assert!(config.validate().is_err());
+
+        config.velocity.enabled = false;
+        assert_eq!(config.mode(), ProxyMode::BungeeCord);
+        assert!(config.validate().is_ok());
+
+        config.bungeecord.enabled = false;
+        assert!(config.validate().is_ok());
+        assert_eq!(config.validation_warnings(false).len(), 1);
+
+        config.velocity.enabled = true;
+        config.velocity.secret.clear();
+        assert!(config.validate().is_err());
+    }
+}
@@ -1,6 +1,10 @@
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Deserialize, Serialize)]
+use serde::{de::Error, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::docs::ConfigDocs;
+
+#[derive(Deserialize, Serialize, ConfigDocs)]
 #[serde(default)]
 pub struct PVPConfig {
     /// Is PVP enabled ?
@@ -26,3 +30,138 @@ impl Default for PVPConfig {
         }
     }
 }
+
+/// The PVP settings of every world, written as `[pvp.default]` and one table per world, e.g. `[pvp.arena]`.
+///
+/// The old format with the fields directly in `[pvp]` is read as the default
+#[derive(Default)]
+pub struct PVPWorldsConfig {
+    /// Used in every world without its own table
+    pub default: PVPConfig,
+    /// Keyed by world name, names without a namespace are in the `minecraft` namespace
+    pub worlds: BTreeMap<String, PVPConfig>,
+}
+
+impl PVPWorldsConfig {
+    /// The settings of the world (e.g. `minecraft:overworld`), falls back to the default
+    pub fn pvp_for(&self, world_name: &str) -> &PVPConfig {
+        self.worlds
+            .get(world_name)
+            .or_else(|| {
+                world_name
+                    .strip_prefix("minecraft:")
+                    .and_then(|name| self.worlds.get(name))
+            })
+            .unwrap_or(&self.default)
+    }
+}
+
+impl Serialize for PVPWorldsConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.worlds.len() + 1))?;
+        map.serialize_entry("default", &self.default)?;
+        for (name, config) in &self.worlds {
+            map.serialize_entry(name, config)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for PVPWorldsConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut table = toml::Table::deserialize(deserializer)?;
+        let mut default = match table.remove("default") {
+            Some(toml::Value::Table(default)) => default,
+            Some(_) => return Err(D::Error::custom("pvp.default must be a table")),
+            None => toml::Table::new(),
+        };
+
+        let mut worlds = BTreeMap::new();
+        for (key, value) in table {
+            match value {
+                toml::Value::Table(world) => {
+                    let config = toml::Value::Table(world)
+                        .try_into()
+                        .map_err(|err| D::Error::custom(format!("pvp.{}: {}", key, err)))?;
+                    worlds.insert(key, config);
+                }
+                // A field of the old format
+                value => {
+                    default.insert(key, value);
+                }
+            }
+        }
+
+        Ok(Self {
+            default: toml::Value::Table(default)
+                .try_into()
+                .map_err(D::Error::custom)?,
+            worlds,
+        })
+    }
+}
+
+impl ConfigDocs for PVPWorldsConfig {
+    fn docs(path: &[&str]) -> Option<&'static str> {
+        match path {
+            ["default"] => Some("Used in every world without its own table"),
+            // Every world has the same fields
+            [_, rest @ ..] if !rest.is_empty() => PVPConfig::docs(rest),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PVPWorldsConfig;
+
+    #[test]
+    fn test_pvp_for() {
+        let config: PVPWorldsConfig = toml::from_str(
+            r#"
+            [default]
+            enabled = false
+
+            [arena]
+            enabled = true
+            knockback = false
+
+            ["minecraft:the_nether"]
+            swing = false
+            "#,
+        )
+        .unwrap();
+
+        assert!(!config.pvp_for("hub").enabled);
+        assert!(!config.pvp_for("minecraft:overworld").enabled);
+        assert!(config.pvp_for("arena").enabled);
+        assert!(!config.pvp_for("arena").knockback);
+        assert!(config.pvp_for("minecraft:arena").enabled);
+        assert!(!config.pvp_for("minecraft:the_nether").swing);
+    }
+
+    #[test]
+    fn test_old_format() {
+        let config: PVPWorldsConfig = toml::from_str(
+            r#"
+            enabled = false
+            hurt_animation = false
+            "#,
+        )
+        .unwrap();
+        assert!(config.worlds.is_empty());
+        assert!(!config.pvp_for("unknown").enabled);
+        assert!(!config.default.hurt_animation);
+        assert!(config.default.swing);
+
+        // Written in the new format
+        let written = toml::to_string(&config).unwrap();
+        assert!(written.starts_with("[default]\n"));
+        let read: PVPWorldsConfig = toml::from_str(&written).unwrap();
+        assert!(!read.default.enabled);
+
+        assert!(toml::from_str::<PVPWorldsConfig>("enabled = 3").is_err());
+        assert!(toml::from_str::<PVPWorldsConfig>("[arena]\nenabled = 3").is_err());
+    }
+}
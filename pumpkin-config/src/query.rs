@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Default)]
+use crate::docs::ConfigDocs;
+
+#[derive(Deserialize, Serialize, Default, ConfigDocs)]
 #[serde(default)]
 pub struct QueryConfig {
+    /// Whether the query protocol is enabled
     pub enabled: bool,
-    // Optional so if not specified the port server is running on will be used
+    /// The UDP port to listen on. Defaults to the port the server is running on
     pub port: Option<u16>,
 }
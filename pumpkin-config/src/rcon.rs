@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
+
+use crate::docs::ConfigDocs;
 use std::net::{Ipv4Addr, SocketAddr};
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, ConfigDocs)]
 #[serde(default)]
 pub struct RCONConfig {
     /// Is RCON Enabled?
@@ -13,7 +15,13 @@ pub struct RCONConfig {
     /// The maximum number of concurrent RCON connections allowed.
     /// If 0 there is no limit
     pub max_connections: u32,
+    /// How many wrong passwords an IP can send before its connections are refused.
+    /// If 0 there is no limit
+    pub max_auth_failures: u32,
+    /// How long in seconds the connections of an IP are refused after too many wrong passwords
+    pub auth_cooldown_secs: u64,
     /// RCON Logging
+    #[config_docs(nested)]
     pub logging: RCONLogging,
 }
 
@@ -24,12 +32,14 @@ impl Default for RCONConfig {
             address: SocketAddr::new(Ipv4Addr::new(0, 0, 0, 0).into(), 25575),
             password: "".to_string(),
             max_connections: 0,
+            max_auth_failures: 5,
+            auth_cooldown_secs: 60,
             logging: Default::default(),
         }
     }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, ConfigDocs)]
 #[serde(default)]
 pub struct RCONLogging {
     /// Whether successful RCON logins should be logged.
@@ -1,29 +1,312 @@
+use std::{fs::File, path::Path};
+
+use pumpkin_core::text::TextComponent;
 use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use uuid::Uuid;
 
-#[derive(Deserialize, Serialize, Default)]
-#[serde(default)]
+use crate::{docs::ConfigDocs, ConfigError};
+
+#[derive(Deserialize, Serialize, Default, ConfigDocs)]
+#[serde(from = "RawResourcePackConfig")]
 pub struct ResourcePackConfig {
+    /// Whether the resource packs are sent to joining players
     pub enabled: bool,
-    /// The path to the resource pack.
-    pub resource_pack_url: String,
-    /// The SHA1 hash (40) of the resource pack.
-    pub resource_pack_sha1: String,
-    /// Custom prompt Text component, Leave blank for none
-    pub prompt_message: String,
-    /// Will force the Player to accept the resource pack
-    pub force: bool,
+    /// The packs sent to joining players, later packs are applied on top of earlier ones
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[config_docs(nested)]
+    pub packs: Vec<ResourcePackEntry>,
+    /// Custom prompt, either a JSON Text component or text using `&` formatting codes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default, PartialEq, Eq, Debug, ConfigDocs)]
+#[serde(default)]
+pub struct ResourcePackEntry {
+    /// The URL the pack is downloaded from, must use http or https
+    pub url: String,
+    /// The SHA-1 hash of the pack as 40 lowercase hex characters, e.g. from `sha1sum`
+    pub sha1: String,
+    /// Will force the Player to accept the pack, Players declining it get kicked
+    #[serde(alias = "force")]
+    pub required: bool,
+    /// Identifies the pack on the client. Defaults to a UUID derived from the URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<Uuid>,
+}
+
+/// Also reads the single pack configs written before packs could be stacked
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawResourcePackConfig {
+    enabled: bool,
+    packs: Vec<ResourcePackEntry>,
+    #[serde(alias = "prompt_message")]
+    prompt: Option<String>,
+    resource_pack_url: String,
+    resource_pack_sha1: String,
+    #[serde(alias = "force")]
+    required: bool,
+}
+
+impl From<RawResourcePackConfig> for ResourcePackConfig {
+    fn from(raw: RawResourcePackConfig) -> Self {
+        let mut packs = raw.packs;
+        if !raw.resource_pack_url.is_empty() || !raw.resource_pack_sha1.is_empty() {
+            packs.insert(
+                0,
+                ResourcePackEntry {
+                    url: raw.resource_pack_url,
+                    sha1: raw.resource_pack_sha1,
+                    required: raw.required,
+                    uuid: None,
+                },
+            );
+        }
+        Self {
+            enabled: raw.enabled,
+            packs,
+            prompt: raw.prompt,
+        }
+    }
 }
 
 impl ResourcePackConfig {
-    pub fn validate(&self) {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for (i, pack) in self.packs.iter().enumerate() {
+            pack.validate().map_err(|err| {
+                ConfigError::validation(
+                    "resource_pack.packs",
+                    format!("Resource pack {}: {}", i + 1, err),
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Settings which are valid but most likely not intended
+    pub fn validation_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.enabled && self.packs.is_empty() {
+            warnings.push("Resource packs are enabled, but no packs are configured".to_string());
+        }
+        if let Some(prompt) = self.prompt.as_deref() {
+            let prompt = prompt.trim_start();
+            if prompt.starts_with('{') && serde_json::from_str::<TextComponent>(prompt).is_err() {
+                warnings.push("The resource pack prompt is no valid JSON Text component, it is shown as plain text".to_string());
+            }
+        }
+        warnings
+    }
+
+    /// Whether declining the pack with this UUID gets the player kicked, unknown packs are not required
+    pub fn is_required(&self, uuid: &Uuid) -> bool {
+        self.packs
+            .iter()
+            .any(|pack| pack.required && pack.uuid() == *uuid)
+    }
+
+    /// The prompt sent with the packs, if one is configured
+    pub fn prompt(&self) -> Option<TextComponent<'static>> {
+        let prompt = self.prompt.as_deref().filter(|prompt| !prompt.is_empty())?;
+        Some(TextComponent::from_config_str(prompt))
+    }
+}
+
+impl ResourcePackEntry {
+    /// The UUID sent to clients, either the configured one or one derived from the URL
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+            .unwrap_or_else(|| Uuid::new_v3(&Uuid::NAMESPACE_DNS, self.url.as_bytes()))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let host = self
+            .url
+            .strip_prefix("https://")
+            .or_else(|| self.url.strip_prefix("http://"));
+        if host.map_or(true, |host| host.is_empty() || host.starts_with('/'))
+            || self.url.contains(char::is_whitespace)
+        {
+            return Err(format!("{:?} is no valid http(s) URL", self.url));
+        }
+        if self.sha1.len() != 40
+            || !self
+                .sha1
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+        {
+            return Err(format!(
+                "The SHA-1 hash {:?} must be 40 lowercase hex characters",
+                self.sha1
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The SHA-1 hash of a local pack file, in the format expected by `sha1`
+pub fn file_sha1<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha1::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use pumpkin_core::text::TextComponent;
+    use uuid::Uuid;
+
+    use super::{file_sha1, ResourcePackConfig, ResourcePackEntry};
+    use crate::test::TestDir;
+
+    const HASH: &str = "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12";
+
+    fn pack(url: &str, sha1: &str) -> ResourcePackEntry {
+        ResourcePackEntry {
+            url: url.to_string(),
+            sha1: sha1.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_single_pack_compat() {
+        let config: ResourcePackConfig = toml::from_str(&format!(
+            "enabled = true\nresource_pack_url = \"https://example.com/pack.zip\"\nresource_pack_sha1 = \"{HASH}\"\nforce = true"
+        ))
+        .unwrap();
+        assert_eq!(config.packs.len(), 1);
+        assert_eq!(config.packs[0].url, "https://example.com/pack.zip");
+        assert!(config.packs[0].required);
+        assert!(config.validate().is_ok());
+
+        // Written in the new format
+        let written = toml::to_string(&config).unwrap();
+        assert!(written.contains("[[packs]]"));
+        assert!(!written.contains("resource_pack_url"));
+        let commented = crate::docs::to_commented_toml(&config);
+        assert!(commented
+            .contains("# The URL the pack is downloaded from, must use http or https\nurl = "));
+        let read: ResourcePackConfig = toml::from_str(&written).unwrap();
+        assert_eq!(read.packs, config.packs);
+    }
+
+    #[test]
+    fn test_pack_stack() {
+        let config: ResourcePackConfig = toml::from_str(&format!(
+            r#"
+            enabled = true
+
+            [[packs]]
+            url = "https://example.com/base.zip"
+            sha1 = "{HASH}"
+            required = true
+
+            [[packs]]
+            url = "http://example.com/extras.zip"
+            sha1 = "{HASH}"
+            uuid = "00000000-0000-0000-0000-00000000002a"
+            "#
+        ))
+        .unwrap();
+        assert_eq!(config.packs.len(), 2);
+        assert!(config.validate().is_ok());
+        assert!(config.validation_warnings().is_empty());
+
+        let base = config.packs[0].uuid();
+        assert_eq!(base, config.packs[0].uuid());
+        assert_ne!(base, Uuid::from_u128(42));
+        assert_eq!(config.packs[1].uuid(), Uuid::from_u128(42));
+        assert!(config.is_required(&base));
+        assert!(!config.is_required(&Uuid::from_u128(42)));
+        assert!(!config.is_required(&Uuid::nil()));
+    }
+
+    #[test]
+    fn test_no_packs_warning() {
+        let mut config = ResourcePackConfig::default();
+        assert!(config.validation_warnings().is_empty());
+        config.enabled = true;
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.validation_warnings(),
+            ["Resource packs are enabled, but no packs are configured"]
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let validate = |url: &str, sha1: &str| {
+            ResourcePackConfig {
+                packs: vec![pack("https://example.com/pack.zip", HASH), pack(url, sha1)],
+                ..Default::default()
+            }
+            .validate()
+        };
+        assert!(validate("https://example.com/other.zip", HASH).is_ok());
+        for url in [
+            "",
+            "example.com/pack.zip",
+            "https://",
+            "ftp://example.com/pack.zip",
+            "https://example.com/my pack.zip",
+        ] {
+            assert!(validate(url, HASH).is_err(), "{}", url);
+        }
+        for sha1 in [
+            "",
+            &HASH[1..],
+            &HASH.to_uppercase(),
+            &format!("{}0", HASH),
+            "g".repeat(40).as_str(),
+        ] {
+            assert!(
+                validate("https://example.com/pack.zip", sha1).is_err(),
+                "{}",
+                sha1
+            );
+        }
+        assert!(validate("https://example.com/pack.zip", "")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Resource pack 2: "));
+    }
+
+    #[test]
+    fn test_prompt() {
+        let mut config = ResourcePackConfig::default();
+        assert!(config.prompt().is_none());
+        config.prompt = Some(String::new());
+        assert!(config.prompt().is_none());
+        config.prompt = Some("&cPlease accept".to_string());
         assert_eq!(
-            !self.resource_pack_url.is_empty(),
-            !self.resource_pack_sha1.is_empty(),
-            "Resource Pack path or Sha1 hash is missing"
+            config.prompt(),
+            Some(TextComponent::from_config_str("&cPlease accept"))
         );
-        assert!(
-            self.resource_pack_sha1.len() <= 40,
-            "Resource pack sha1 hash is too long (max. 40)"
-        )
+        assert!(config.validation_warnings().is_empty());
+        // Broken JSON is only warned about
+        config.prompt = Some("{\"text\":".to_string());
+        assert!(config.validate().is_ok());
+        assert_eq!(config.validation_warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_file_sha1() {
+        let dir = TestDir::new("pack");
+        let path = dir.join("pack.zip");
+        fs::write(&path, "The quick brown fox jumps over the lazy dog").unwrap();
+        assert_eq!(file_sha1(&path).unwrap(), HASH);
+        fs::remove_file(&path).unwrap();
+
+        assert!(file_sha1(&path).is_err());
     }
 }
@@ -0,0 +1,175 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{docs::ConfigDocs, json_list, ConfigError};
+
+/// Only players on the whitelist can join
+#[derive(Deserialize, Serialize, ConfigDocs)]
+#[serde(default)]
+pub struct WhitelistConfig {
+    /// Whether players which aren't on the whitelist get rejected when they join
+    pub enabled: bool,
+    /// Whether online players which aren't on the whitelist get kicked when the whitelist is enabled or changed
+    pub enforce: bool,
+    /// The whitelist file, in the format of vanilla's `whitelist.json`
+    pub path: PathBuf,
+}
+
+impl Default for WhitelistConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            enforce: false,
+            path: "whitelist.json".into(),
+        }
+    }
+}
+
+impl WhitelistConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.path.as_os_str().is_empty() {
+            return Err(ConfigError::validation(
+                "whitelist.path",
+                "The whitelist path must not be empty",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A player on the whitelist, vanilla also identifies them by the UUID and only keeps the name for reference
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct WhitelistEntry {
+    pub uuid: Uuid,
+    pub name: String,
+}
+
+/// The players which may join while the whitelist is enabled, stored as JSON list like vanilla's `whitelist.json`.
+///
+/// Every change is written to the file right away
+#[derive(Debug)]
+pub struct Whitelist {
+    path: PathBuf,
+    entries: Vec<WhitelistEntry>,
+}
+
+impl Whitelist {
+    /// Reads the whitelist at `path`, a missing or empty file is an empty whitelist
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries: json_list::read(path)?,
+        })
+    }
+
+    /// An empty whitelist, which gets saved to `path` once it changes
+    pub fn empty(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn entries(&self) -> &[WhitelistEntry] {
+        &self.entries
+    }
+
+    pub fn is_whitelisted(&self, uuid: &Uuid) -> bool {
+        self.entries.iter().any(|entry| entry.uuid == *uuid)
+    }
+
+    /// Adds the player and saves the file. Returns false if they were already on the whitelist,
+    /// their name still gets updated
+    pub fn add(&mut self, uuid: Uuid, name: &str) -> io::Result<bool> {
+        let added = match self.entries.iter_mut().find(|entry| entry.uuid == uuid) {
+            Some(entry) if entry.name == name => return Ok(false),
+            Some(entry) => {
+                entry.name = name.to_string();
+                false
+            }
+            None => {
+                self.entries.push(WhitelistEntry {
+                    uuid,
+                    name: name.to_string(),
+                });
+                true
+            }
+        };
+        self.save()?;
+        Ok(added)
+    }
+
+    /// Removes the player and saves the file. Returns false if they weren't on the whitelist
+    pub fn remove(&mut self, uuid: &Uuid) -> io::Result<bool> {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.uuid != *uuid);
+        if self.entries.len() == len {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        json_list::write(&self.path, &self.entries)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use uuid::Uuid;
+
+    use super::Whitelist;
+    use crate::test::TestDir;
+
+    #[test]
+    fn test_vanilla_format() {
+        let dir = TestDir::new("whitelist");
+        let path = dir.join("whitelist.json");
+        // As written by vanilla
+        fs::write(
+            &path,
+            r#"[
+  {
+    "uuid": "069a79f4-44e9-4726-a5be-fca90e38aaf5",
+    "name": "Notch"
+  }
+]"#,
+        )
+        .unwrap();
+
+        let notch = Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+        let jeb = Uuid::parse_str("853c80ef-3c37-49fd-aa49-938b674adae6").unwrap();
+        let mut whitelist = Whitelist::load(&path).unwrap();
+        assert!(whitelist.is_whitelisted(&notch));
+        assert!(!whitelist.is_whitelisted(&jeb));
+
+        assert!(whitelist.add(jeb, "jeb_").unwrap());
+        assert!(!whitelist.add(jeb, "jeb_").unwrap());
+        assert!(whitelist.remove(&notch).unwrap());
+        assert!(!whitelist.remove(&notch).unwrap());
+
+        // The changes were saved
+        let whitelist = Whitelist::load(&path).unwrap();
+        assert!(whitelist.is_whitelisted(&jeb));
+        assert!(!whitelist.is_whitelisted(&notch));
+        assert_eq!(whitelist.entries()[0].name, "jeb_");
+
+        fs::write(&path, "").unwrap();
+        assert!(Whitelist::load(&path).unwrap().entries().is_empty());
+        fs::write(&path, "{").unwrap();
+        assert!(Whitelist::load(&path).is_err());
+        // Missing files are empty whitelists
+        assert!(Whitelist::load(&dir.join("missing.json"))
+            .unwrap()
+            .entries()
+            .is_empty());
+    }
+}
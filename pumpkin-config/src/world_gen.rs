@@ -0,0 +1,186 @@
+use std::{collections::HashSet, sync::LazyLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{docs::ConfigDocs, ConfigError, WORLD_BOTTOM_Y, WORLD_TOP_Y};
+
+#[derive(Deserialize, Serialize, ConfigDocs)]
+#[serde(default)]
+pub struct WorldGenConfig {
+    /// Empty blocks up to this height are filled with water
+    pub sea_level: i32,
+    /// Whether the bedrock layers above the bottom get randomized like vanilla, otherwise a single flat layer is placed
+    pub bedrock_roughness: bool,
+    /// Whether broken density functions returning NaN get logged and replaced with 0. Defaults to on in debug builds.
+    /// Only covers the End, the Overworld and Nether generators don't use density functions yet and are not guarded
+    pub nan_guard: bool,
+    /// Whether the time spent in each density function type gets measured and logged on shutdown
+    pub profile: bool,
+    /// The layers of flat worlds from the bottom up, e.g. `minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block`.
+    /// Layers above the top of the world are cut off
+    pub flat_layers: String,
+    /// Decorations placed after the terrain
+    #[config_docs(nested)]
+    pub features: FeaturesConfig,
+}
+
+/// Decorations placed after the terrain
+#[derive(Deserialize, Serialize, ConfigDocs)]
+#[serde(default)]
+pub struct FeaturesConfig {
+    /// Dirt veins in the stone
+    pub ore_dirt: bool,
+    /// Gravel veins in the stone
+    pub ore_gravel: bool,
+    /// Coal ore veins
+    pub ore_coal: bool,
+    /// Iron ore veins
+    pub ore_iron: bool,
+    /// Short grass on grass blocks
+    pub patch_grass: bool,
+    /// Flowers on grass blocks
+    pub flowers: bool,
+}
+
+impl Default for FeaturesConfig {
+    fn default() -> Self {
+        Self {
+            ore_dirt: true,
+            ore_gravel: true,
+            ore_coal: true,
+            ore_iron: true,
+            patch_grass: true,
+            flowers: true,
+        }
+    }
+}
+
+impl Default for WorldGenConfig {
+    fn default() -> Self {
+        Self {
+            sea_level: 63,
+            bedrock_roughness: true,
+            nan_guard: cfg!(debug_assertions),
+            profile: false,
+            flat_layers: "minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block".to_string(),
+            features: FeaturesConfig::default(),
+        }
+    }
+}
+
+impl WorldGenConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(WORLD_BOTTOM_Y..WORLD_TOP_Y).contains(&self.sea_level) {
+            return Err(ConfigError::validation(
+                "world_gen.sea_level",
+                format!(
+                    "world_gen.sea_level must be within the world height ({WORLD_BOTTOM_Y}..{WORLD_TOP_Y})"
+                ),
+            ));
+        }
+        check_flat_layers(&self.flat_layers)
+            .map_err(|err| ConfigError::validation("world_gen.flat_layers", err))
+    }
+}
+
+/// The names of every block, e.g. `minecraft:stone`. Read from the registry the world generator uses
+static BLOCK_NAMES: LazyLock<HashSet<String>> = LazyLock::new(|| {
+    #[derive(Deserialize)]
+    struct Registry {
+        blocks: Vec<Block>,
+    }
+    #[derive(Deserialize)]
+    struct Block {
+        name: String,
+    }
+
+    serde_json::from_str::<Registry>(include_str!("../../assets/blocks.json"))
+        .expect("Could not parse blocks.json registry.")
+        .blocks
+        .into_iter()
+        .map(|block| block.name)
+        .collect()
+});
+
+/// Checks a layer spec like the world generator parses it, so unknown blocks are rejected when loading the config
+fn check_flat_layers(spec: &str) -> Result<(), String> {
+    for layer in spec.split(',').map(str::trim) {
+        if layer.is_empty() {
+            continue;
+        }
+        let block = match layer.split_once('*') {
+            Some((height, block)) => {
+                if height
+                    .trim()
+                    .parse::<u32>()
+                    .map_or(true, |height| height == 0)
+                {
+                    return Err(format!("Invalid layer height in {:?}", layer));
+                }
+                block.trim()
+            }
+            None => layer,
+        };
+        // Like vanilla's resource locations, with an optional namespace
+        let (namespace, path) = block.split_once(':').unwrap_or(("minecraft", block));
+        let valid = |part: &str, extra: &[char]| {
+            part.chars().all(|c| {
+                c.is_ascii_lowercase()
+                    || c.is_ascii_digit()
+                    || "_-.".contains(c)
+                    || extra.contains(&c)
+            })
+        };
+        if namespace.is_empty() || path.is_empty() || !valid(namespace, &[]) || !valid(path, &['/'])
+        {
+            return Err(format!("{:?} is no valid block name", block));
+        }
+        if !BLOCK_NAMES.contains(&format!("{}:{}", namespace, path)) {
+            return Err(format!("Unknown block {:?}", block));
+        }
+    }
+    Ok(())
+}
+
+/// The kind of world to generate
+#[derive(Deserialize, Serialize, Clone, Default, PartialEq, Eq, Debug)]
+pub enum WorldGenType {
+    #[default]
+    Default,
+    Flat,
+    Amplified,
+    /// The default terrain with a single biome, e.g. `{ SingleBiome = "minecraft:plains" }`
+    SingleBiome(String),
+}
+
+#[cfg(test)]
+mod test {
+    use super::WorldGenConfig;
+
+    #[test]
+    fn test_flat_layers() {
+        let validate = |flat_layers: &str| {
+            WorldGenConfig {
+                flat_layers: flat_layers.to_string(),
+                ..Default::default()
+            }
+            .validate()
+        };
+        assert!(validate("minecraft:bedrock, 2*dirt,grass_block,").is_ok());
+        assert!(validate("bedrock,4294967295*stone").is_ok());
+        assert!(validate("").is_ok());
+        for invalid in [
+            "bedrock,0*dirt",
+            "bedrock,x*dirt",
+            "bedrock,4294967296*dirt",
+            "bedrock,2*",
+            "Minecraft:Stone",
+            ":stone",
+            "minecraft:",
+            "minecraft:not_a_block",
+            "stone,other:stone",
+        ] {
+            assert!(validate(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
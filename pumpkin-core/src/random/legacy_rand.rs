@@ -96,7 +96,7 @@ pub struct LegacySplitter {
 }
 
 impl LegacySplitter {
-    fn new(seed: u64) -> Self {
+    pub(crate) fn new(seed: u64) -> Self {
         LegacySplitter { seed }
     }
 }
@@ -3,6 +3,7 @@ use xoroshiro128::{Xoroshiro, XoroshiroSplitter};
 
 mod gaussian;
 pub mod legacy_rand;
+pub mod worldgen;
 pub mod xoroshiro128;
 
 pub enum RandomGenerator {
@@ -215,7 +216,8 @@ fn hash_block_pos(x: i32, y: i32, z: i32) -> i64 {
     l >> 16
 }
 
-fn java_string_hash(string: &str) -> u32 {
+/// Java's `String.hashCode`
+pub fn java_string_hash(string: &str) -> u32 {
     // All byte values of latin1 align with
     // the values of U+0000 - U+00FF making this code
     // equivalent to both java hash implementations
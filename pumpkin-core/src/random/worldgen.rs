@@ -0,0 +1,269 @@
+use super::{
+    gaussian::GaussianGenerator,
+    legacy_rand::{LegacyRand, LegacySplitter},
+    RandomImpl,
+};
+
+/// Vanilla's `WorldgenRandom`, which wraps another random and gets reseeded for every chunk and feature.
+///
+/// Like vanilla it draws its values bit by bit like `java.util.Random` (`next(bits)`), even if the wrapped
+/// random is a [`super::xoroshiro128::Xoroshiro`], so the results match vanilla for both sources
+pub struct WorldgenRandom<R: RandomImpl> {
+    source: R,
+    count: u64,
+    internal_next_gaussian: Option<f64>,
+}
+
+impl<R: RandomImpl> WorldgenRandom<R> {
+    pub fn new(source: R) -> Self {
+        Self {
+            source,
+            count: 0,
+            internal_next_gaussian: None,
+        }
+    }
+
+    /// The number of times bits were drawn from the wrapped random
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Reseeds the wrapped random, like `setSeed`
+    pub fn set_seed(&mut self, seed: i64) {
+        self.source = R::from_seed(seed as u64);
+    }
+
+    /// Seeds the random for decorating the chunk at the block position `x`, `z` and returns the seed,
+    /// which is passed to [`Self::set_feature_seed`] for each feature
+    pub fn set_decoration_seed(&mut self, world_seed: i64, x: i32, z: i32) -> i64 {
+        self.set_seed(world_seed);
+        let a = self.next_i64() | 1;
+        let b = self.next_i64() | 1;
+        let seed =
+            (i64::from(x).wrapping_mul(a)).wrapping_add(i64::from(z).wrapping_mul(b)) ^ world_seed;
+        self.set_seed(seed);
+        seed
+    }
+
+    /// Seeds the random for the feature with the index `index` in the generation step `step`
+    pub fn set_feature_seed(&mut self, decoration_seed: i64, index: i32, step: i32) {
+        let seed = decoration_seed
+            .wrapping_add(i64::from(index))
+            .wrapping_add(i64::from(step) * 10000);
+        self.set_seed(seed);
+    }
+
+    /// Seeds the random for carvers and structures starting in the chunk at `chunk_x`, `chunk_z`
+    pub fn set_large_feature_seed(&mut self, world_seed: i64, chunk_x: i32, chunk_z: i32) {
+        self.set_seed(world_seed);
+        let a = self.next_i64();
+        let b = self.next_i64();
+        let seed =
+            i64::from(chunk_x).wrapping_mul(a) ^ i64::from(chunk_z).wrapping_mul(b) ^ world_seed;
+        self.set_seed(seed);
+    }
+
+    /// Seeds the random for the structure region at `region_x`, `region_z`, the salt differs per structure set
+    pub fn set_large_feature_with_salt(
+        &mut self,
+        world_seed: i64,
+        region_x: i32,
+        region_z: i32,
+        salt: i32,
+    ) {
+        let seed = i64::from(region_x)
+            .wrapping_mul(341873128712)
+            .wrapping_add(i64::from(region_z).wrapping_mul(132897987541))
+            .wrapping_add(world_seed)
+            .wrapping_add(i64::from(salt));
+        self.set_seed(seed);
+    }
+}
+
+/// The random deciding whether the chunk at `chunk_x`, `chunk_z` is a slime chunk,
+/// vanilla uses the salt `987234911` and checks `next_bounded_i32(10) == 0`
+pub fn seed_slime_chunk(chunk_x: i32, chunk_z: i32, world_seed: i64, salt: i64) -> LegacyRand {
+    let seed = world_seed
+        .wrapping_add(i64::from(
+            chunk_x.wrapping_mul(chunk_x).wrapping_mul(4987142),
+        ))
+        .wrapping_add(i64::from(chunk_x.wrapping_mul(5947611)))
+        .wrapping_add(i64::from(chunk_z.wrapping_mul(chunk_z)).wrapping_mul(4392871))
+        .wrapping_add(i64::from(chunk_z.wrapping_mul(389711)))
+        ^ salt;
+    LegacyRand::from_seed(seed as u64)
+}
+
+impl<R: RandomImpl> GaussianGenerator for WorldgenRandom<R> {
+    fn stored_next_gaussian(&self) -> Option<f64> {
+        self.internal_next_gaussian
+    }
+
+    fn set_stored_next_gaussian(&mut self, value: Option<f64>) {
+        self.internal_next_gaussian = value;
+    }
+}
+
+impl<R: RandomImpl> RandomImpl for WorldgenRandom<R> {
+    fn from_seed(seed: u64) -> Self {
+        Self::new(R::from_seed(seed))
+    }
+
+    /// Vanilla forks into a plain legacy random, this keeps the wrapped type and seeds it the same way
+    fn split(&mut self) -> Self {
+        let seed = self.next_i64() as u64;
+        Self::from_seed(seed)
+    }
+
+    #[allow(refining_impl_trait)]
+    fn next_splitter(&mut self) -> LegacySplitter {
+        LegacySplitter::new(self.next_i64() as u64)
+    }
+
+    fn next(&mut self, bits: u64) -> u64 {
+        self.count += 1;
+        self.source.next(bits)
+    }
+
+    fn next_i32(&mut self) -> i32 {
+        self.next(32) as i32
+    }
+
+    fn next_bounded_i32(&mut self, bound: i32) -> i32 {
+        if (bound & bound.wrapping_sub(1)) == 0 {
+            ((bound as u64).wrapping_mul(self.next(31)) >> 31) as i32
+        } else {
+            loop {
+                let i = self.next(31) as i32;
+                let j = i % bound;
+                if (i.wrapping_sub(j).wrapping_add(bound.wrapping_sub(1))) >= 0 {
+                    return j;
+                }
+            }
+        }
+    }
+
+    fn next_i64(&mut self) -> i64 {
+        let i = self.next_i32();
+        let j = self.next_i32();
+        ((i as i64) << 32).wrapping_add(j as i64)
+    }
+
+    fn next_bool(&mut self) -> bool {
+        self.next(1) != 0
+    }
+
+    fn next_f32(&mut self) -> f32 {
+        self.next(24) as f32 * 5.9604645E-8f32
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        let i = self.next(26);
+        let j = self.next(27);
+        let l = (i << 27).wrapping_add(j);
+        l as f64 * 1.110223E-16f32 as f64
+    }
+
+    fn next_gaussian(&mut self) -> f64 {
+        self.calculate_gaussian()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::{legacy_rand::LegacyRand, xoroshiro128::Xoroshiro, RandomImpl};
+
+    use super::{seed_slime_chunk, WorldgenRandom};
+
+    // Values checked against results from the equivalent Java source
+
+    const WORLD_SEED: i64 = -4172144997902289642;
+
+    #[test]
+    fn test_legacy_seeds() {
+        let mut rand = WorldgenRandom::new(LegacyRand::from_seed(0));
+
+        let decoration_seed = rand.set_decoration_seed(WORLD_SEED, -96, 208);
+        assert_eq!(decoration_seed, -2130623769025964634);
+        for value in [7, 0, 0, 0, 6] {
+            assert_eq!(rand.next_bounded_i32(16), value);
+        }
+
+        rand.set_feature_seed(decoration_seed, 3, 6);
+        for value in [57497844, -787634931, 2071833002] {
+            assert_eq!(rand.next_i32(), value);
+        }
+        assert_eq!(rand.next_f64(), 0.03397433561042862);
+
+        rand.set_large_feature_seed(WORLD_SEED, -7, 13);
+        for value in [84, 59, 53, 42] {
+            assert_eq!(rand.next_bounded_i32(100), value);
+        }
+        assert_eq!(rand.next_i64(), 2550471570404653339);
+
+        rand.set_large_feature_with_salt(WORLD_SEED, 2, -5, 10387312);
+        for value in [22, 23, 15, 17] {
+            assert_eq!(rand.next_bounded_i32(24), value);
+        }
+    }
+
+    #[test]
+    fn test_xoroshiro_seeds() {
+        let mut rand = WorldgenRandom::new(Xoroshiro::from_seed(0));
+
+        let decoration_seed = rand.set_decoration_seed(WORLD_SEED, -96, 208);
+        assert_eq!(decoration_seed, 328518313406292198);
+        for value in [8, 3, 8, 11, 0] {
+            assert_eq!(rand.next_bounded_i32(16), value);
+        }
+
+        rand.set_feature_seed(decoration_seed, 3, 6);
+        for value in [524226972, 138134393, 1388039809] {
+            assert_eq!(rand.next_i32(), value);
+        }
+        assert_eq!(rand.next_f64(), 0.7154968400475304);
+
+        rand.set_large_feature_seed(WORLD_SEED, -7, 13);
+        for value in [69, 7, 65, 37] {
+            assert_eq!(rand.next_bounded_i32(100), value);
+        }
+        assert_eq!(rand.next_i64(), -7375721117240890013);
+
+        rand.set_large_feature_with_salt(WORLD_SEED, 2, -5, 10387312);
+        for value in [1, 12, 13, 8] {
+            assert_eq!(rand.next_bounded_i32(24), value);
+        }
+        // Longs and doubles draw twice, nothing else was rejected and drawn again
+        assert_eq!(rand.count(), 28);
+    }
+
+    #[test]
+    fn test_slime_chunks() {
+        let slime_chunks: Vec<(i32, i32)> = (-5..=5)
+            .flat_map(|x| (-5..=5).map(move |z| (x, z)))
+            .filter(|(x, z)| {
+                seed_slime_chunk(*x, *z, WORLD_SEED, 987234911).next_bounded_i32(10) == 0
+            })
+            .collect();
+        assert_eq!(
+            slime_chunks,
+            [
+                (-5, 2),
+                (-5, 3),
+                (-4, -4),
+                (-3, -4),
+                (-2, -5),
+                (-2, 3),
+                (-1, -5),
+                (1, 0),
+                (1, 1),
+                (2, -5),
+                (3, 0),
+                (4, -4),
+                (4, -2),
+                (4, 3),
+                (5, -5)
+            ]
+        );
+    }
+}
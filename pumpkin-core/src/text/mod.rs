@@ -160,23 +160,148 @@ impl<'a> TextComponent<'a> {
     }
 
     pub fn encode(&self) -> Vec<u8> {
-        // TODO: Somehow fix this ugly mess
-        #[derive(serde::Serialize)]
-        #[serde(rename_all = "camelCase")]
-        struct TempStruct<'a> {
-            #[serde(flatten)]
-            text: &'a TextContent<'a>,
-            #[serde(flatten)]
-            style: &'a Style<'a>,
-        }
-        let astruct = TempStruct {
+        fastnbt::to_bytes_with_opts(&self.as_raw(), SerOpts::network_nbt()).unwrap()
+    }
+
+    /// Encodes the component as JSON, which is still used by some packets (e.g. the Login disconnect)
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.as_raw()).unwrap()
+    }
+
+    fn as_raw(&self) -> RawTextComponent<'_> {
+        RawTextComponent {
             text: &self.content,
             style: &self.style,
+        }
+    }
+}
+
+impl TextComponent<'static> {
+    /// Parses a user supplied string, e.g. from the config.
+    ///
+    /// The string is either a JSON text component or plain text, which may use `&` or `§` legacy formatting codes.
+    /// Invalid JSON is treated as plain text.
+    pub fn from_config_str(input: &str) -> Self {
+        let trimmed = input.trim_start();
+        if trimmed.starts_with('{') {
+            if let Ok(component) = serde_json::from_str(trimmed) {
+                return component;
+            }
+        }
+        Self::text_string(translate_legacy_codes(input))
+    }
+}
+
+// TODO: Somehow fix this ugly mess
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTextComponent<'a> {
+    #[serde(flatten)]
+    text: &'a TextContent<'a>,
+    #[serde(flatten)]
+    style: &'a Style<'a>,
+}
+
+const LEGACY_CODES: &str = "0123456789abcdefklmnor";
+
+/// Replaces `&` formatting codes (e.g. `&c`) with the `§` codes the client understands
+pub fn translate_legacy_codes(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(char) = chars.next() {
+        if char == '&' {
+            if let Some(code) = chars.peek() {
+                if LEGACY_CODES.contains(code.to_ascii_lowercase()) {
+                    result.push('§');
+                    continue;
+                }
+            }
+        }
+        result.push(char);
+    }
+    result
+}
+
+/// The colors of the legacy codes `0` to `f`
+const LEGACY_COLORS: [&str; 16] = [
+    "black",
+    "dark_blue",
+    "dark_green",
+    "dark_aqua",
+    "dark_red",
+    "dark_purple",
+    "gold",
+    "gray",
+    "dark_gray",
+    "blue",
+    "green",
+    "aqua",
+    "red",
+    "light_purple",
+    "yellow",
+    "white",
+];
+
+/// Converts text with `&` or `§` legacy formatting codes into a JSON text component,
+/// with one `extra` entry for every differently formatted part.
+///
+/// Like in vanilla a color code resets the formatting codes before it. Text without codes stays a plain JSON string
+pub fn legacy_to_json(input: &str) -> serde_json::Value {
+    let translated = translate_legacy_codes(input);
+    if !translated.contains('§') {
+        return serde_json::Value::String(input.to_string());
+    }
+
+    let mut extra = Vec::new();
+    let mut part = serde_json::Map::new();
+    let mut text = String::new();
+    let mut chars = translated.chars().peekable();
+    while let Some(char) = chars.next() {
+        let code = chars
+            .peek()
+            .map(char::to_ascii_lowercase)
+            .filter(|code| char == '§' && LEGACY_CODES.contains(*code));
+        let Some(code) = code else {
+            text.push(char);
+            continue;
+        };
+        chars.next();
+
+        if !text.is_empty() {
+            let mut finished = part.clone();
+            finished.insert("text".to_string(), std::mem::take(&mut text).into());
+            extra.push(serde_json::Value::Object(finished));
+        }
+        let format = match code {
+            'k' => "obfuscated",
+            'l' => "bold",
+            'm' => "strikethrough",
+            'n' => "underlined",
+            'o' => "italic",
+            'r' => {
+                part.clear();
+                continue;
+            }
+            color => {
+                part.clear();
+                let index = color.to_digit(16).unwrap() as usize;
+                part.insert("color".to_string(), LEGACY_COLORS[index].into());
+                continue;
+            }
         };
-        // dbg!(&serde_json::to_string(&astruct));
+        part.insert(format.to_string(), true.into());
+    }
+    if !text.is_empty() {
+        part.insert("text".to_string(), text.into());
+        extra.push(serde_json::Value::Object(part));
+    }
 
-        fastnbt::to_bytes_with_opts(&astruct, SerOpts::network_nbt()).unwrap()
+    let mut component = serde_json::Map::new();
+    component.insert("text".to_string(), "".into());
+    if !extra.is_empty() {
+        component.insert("extra".to_string(), extra.into());
     }
+    serde_json::Value::Object(component)
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -200,3 +325,79 @@ pub enum TextContent<'a> {
     /// https://minecraft.wiki/w/Controls#Configurable_controls
     Keybind { keybind: Cow<'a, str> },
 }
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{
+        color::NamedColor, legacy_to_json, translate_legacy_codes, Color, TextComponent,
+        TextContent,
+    };
+
+    #[test]
+    fn test_legacy_codes() {
+        assert_eq!(translate_legacy_codes("&cRed &lBold"), "§cRed §lBold");
+        assert_eq!(translate_legacy_codes("Fish & Chips &"), "Fish & Chips &");
+        assert_eq!(translate_legacy_codes("§aAlready"), "§aAlready");
+    }
+
+    #[test]
+    fn test_legacy_to_json() {
+        assert_eq!(legacy_to_json("Plain & simple"), json!("Plain & simple"));
+        assert_eq!(
+            legacy_to_json("&c&lHot §rPumpkins &6&oand &npies"),
+            json!({
+                "text": "",
+                "extra": [
+                    {"text": "Hot ", "color": "red", "bold": true},
+                    {"text": "Pumpkins "},
+                    {"text": "and ", "color": "gold", "italic": true},
+                    {"text": "pies", "color": "gold", "italic": true, "underlined": true},
+                ]
+            })
+        );
+        // The color resets the bold code before it
+        assert_eq!(
+            legacy_to_json("&lBold&9Blue"),
+            json!({
+                "text": "",
+                "extra": [
+                    {"text": "Bold", "bold": true},
+                    {"text": "Blue", "color": "blue"},
+                ]
+            })
+        );
+        assert_eq!(legacy_to_json("&a"), json!({"text": ""}));
+    }
+
+    #[test]
+    fn test_from_config_str() {
+        let component = TextComponent::from_config_str("&4Server is full");
+        assert_eq!(
+            component.content,
+            TextContent::Text {
+                text: "§4Server is full".into()
+            }
+        );
+
+        let component =
+            TextComponent::from_config_str(r#"{"text": "Banned", "color": "red", "bold": true}"#);
+        assert_eq!(
+            component.content,
+            TextContent::Text {
+                text: "Banned".into()
+            }
+        );
+        assert_eq!(component.style.color, Some(Color::Named(NamedColor::Red)));
+        assert_eq!(component.style.bold, Some(1));
+
+        let component = TextComponent::from_config_str("{not json");
+        assert_eq!(
+            component.content,
+            TextContent::Text {
+                text: "{not json".into()
+            }
+        );
+    }
+}
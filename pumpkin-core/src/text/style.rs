@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use super::{
     click::ClickEvent,
@@ -12,23 +12,43 @@ pub struct Style<'a> {
     /// Changes the color to render the content
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub color: Option<Color>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_bool_byte",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub bold: Option<u8>,
     /// Whether to render the content in italic.
     /// Keep in mind that booleans are represented as bytes in nbt
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_bool_byte",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub italic: Option<u8>,
     /// Whether to render the content in underlined.
     /// Keep in mind that booleans are represented as bytes in nbt
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_bool_byte",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub underlined: Option<u8>,
     /// Whether to render the content in strikethrough.
     /// Keep in mind that booleans are represented as bytes in nbt
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_bool_byte",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub strikethrough: Option<u8>,
     /// Whether to render the content in obfuscated.
     /// Keep in mind that booleans are represented as bytes in nbt
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_bool_byte",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub obfuscated: Option<u8>,
     /// When the text is shift-clicked by a player, this string is inserted in their chat input. It does not overwrite any existing text the player was writing. This only works in chat messages
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -41,6 +61,26 @@ pub struct Style<'a> {
     pub hover_event: Option<HoverEvent<'a>>,
 }
 
+/// Booleans are bytes in NBT but `true`/`false` in JSON, so accept both
+fn deserialize_bool_byte<'de, D>(deserializer: D) -> Result<Option<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrByte {
+        Bool(bool),
+        Byte(u8),
+    }
+
+    Ok(
+        Option::<BoolOrByte>::deserialize(deserializer)?.map(|value| match value {
+            BoolOrByte::Bool(value) => u8::from(value),
+            BoolOrByte::Byte(value) => value,
+        }),
+    )
+}
+
 impl<'a> Style<'a> {
     pub fn color(mut self, color: Color) -> Self {
         self.color = Some(color);
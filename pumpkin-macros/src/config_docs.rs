@@ -0,0 +1,86 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Expr, ExprLit, Lit, Meta};
+
+pub(crate) fn config_docs_impl(item: TokenStream) -> TokenStream {
+    let ast: DeriveInput = syn::parse(item).unwrap();
+    let name = &ast.ident;
+    let Data::Struct(data) = &ast.data else {
+        panic!("ConfigDocs can only be derived for structs");
+    };
+
+    let mut arms = Vec::new();
+    let mut flattened = Vec::new();
+    for field in &data.fields {
+        let ident = field.ident.as_ref().expect("ConfigDocs needs named fields");
+        let key = ident.to_string();
+        let ty = &field.ty;
+
+        if has_marker(&field.attrs, "serde", "flatten") {
+            flattened.push(quote! {
+                if let Some(doc) = <#ty as crate::docs::ConfigDocs>::docs(path) {
+                    return Some(doc);
+                }
+            });
+            continue;
+        }
+
+        if let Some(doc) = doc_comment(&field.attrs) {
+            arms.push(quote! { [#key] => return Some(#doc), });
+        }
+        if has_marker(&field.attrs, "config_docs", "nested") {
+            arms.push(quote! {
+                [#key, rest @ ..] => return <#ty as crate::docs::ConfigDocs>::docs(rest),
+            });
+        }
+    }
+
+    quote! {
+        impl crate::docs::ConfigDocs for #name {
+            fn docs(path: &[&str]) -> Option<&'static str> {
+                #[allow(clippy::match_single_binding)]
+                match path {
+                    #(#arms)*
+                    _ => {}
+                }
+                #(#flattened)*
+                None
+            }
+        }
+    }
+    .into()
+}
+
+/// Joins the `///` lines of the attributes, without the leading space
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(doc), ..
+                }) => Some(doc.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).to_string())
+        .collect();
+
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Whether one of the attributes is `#[attribute(..., marker, ...)]`
+fn has_marker(attrs: &[Attribute], attribute: &str, marker: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident(attribute))
+        .filter_map(|attr| attr.meta.require_list().ok())
+        .any(|list| {
+            list.tokens
+                .clone()
+                .into_iter()
+                .any(|token| token.to_string() == marker)
+        })
+}
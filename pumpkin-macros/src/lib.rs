@@ -58,3 +58,11 @@ mod particle;
 pub fn particle(item: TokenStream) -> TokenStream {
     particle::particle_impl(item)
 }
+
+mod config_docs;
+/// Implements `ConfigDocs` from the doc comments of the fields.
+/// Fields holding another documented config need `#[config_docs(nested)]`
+#[proc_macro_derive(ConfigDocs, attributes(config_docs))]
+pub fn config_docs(item: TokenStream) -> TokenStream {
+    config_docs::config_docs_impl(item)
+}
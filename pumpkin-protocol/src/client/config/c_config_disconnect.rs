@@ -1,13 +1,14 @@
+use pumpkin_core::text::TextComponent;
 use pumpkin_macros::client_packet;
 
 #[derive(serde::Serialize)]
 #[client_packet("config:disconnect")]
 pub struct CConfigDisconnect<'a> {
-    reason: &'a str,
+    reason: &'a TextComponent<'a>,
 }
 
 impl<'a> CConfigDisconnect<'a> {
-    pub fn new(reason: &'a str) -> Self {
+    pub fn new(reason: &'a TextComponent<'a>) -> Self {
         Self { reason }
     }
 }
@@ -0,0 +1,105 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The stats of every connection together
+pub static GLOBAL_COMPRESSION_STATS: CompressionStats = CompressionStats::new();
+
+/// Counts the packet bytes before and after compression, to see how much bandwidth the
+/// compression threshold and level save.
+///
+/// Only packets sent or received while compression is enabled are counted, including the ones
+/// below the threshold which are sent as is
+#[derive(Default, Debug)]
+pub struct CompressionStats {
+    sent_uncompressed: AtomicU64,
+    sent_compressed: AtomicU64,
+    received_uncompressed: AtomicU64,
+    received_compressed: AtomicU64,
+}
+
+impl CompressionStats {
+    pub const fn new() -> Self {
+        Self {
+            sent_uncompressed: AtomicU64::new(0),
+            sent_compressed: AtomicU64::new(0),
+            received_uncompressed: AtomicU64::new(0),
+            received_compressed: AtomicU64::new(0),
+        }
+    }
+
+    /// Counts a packet of `uncompressed` bytes which took `compressed` bytes to send
+    pub fn record_sent(&self, uncompressed: usize, compressed: usize) {
+        self.sent_uncompressed
+            .fetch_add(uncompressed as u64, Ordering::Relaxed);
+        self.sent_compressed
+            .fetch_add(compressed as u64, Ordering::Relaxed);
+    }
+
+    /// Counts a packet received with `compressed` bytes which were `uncompressed` bytes of data
+    pub fn record_received(&self, uncompressed: usize, compressed: usize) {
+        self.received_uncompressed
+            .fetch_add(uncompressed as u64, Ordering::Relaxed);
+        self.received_compressed
+            .fetch_add(compressed as u64, Ordering::Relaxed);
+    }
+
+    pub fn sent_uncompressed(&self) -> u64 {
+        self.sent_uncompressed.load(Ordering::Relaxed)
+    }
+
+    pub fn sent_compressed(&self) -> u64 {
+        self.sent_compressed.load(Ordering::Relaxed)
+    }
+
+    pub fn received_uncompressed(&self) -> u64 {
+        self.received_uncompressed.load(Ordering::Relaxed)
+    }
+
+    pub fn received_compressed(&self) -> u64 {
+        self.received_compressed.load(Ordering::Relaxed)
+    }
+
+    /// The bytes sent and received for every byte before compression, e.g. 0.25 if compression
+    /// saved three quarters of the bandwidth. `None` if nothing was counted yet
+    pub fn compression_ratio(&self) -> Option<f64> {
+        let uncompressed = self.sent_uncompressed() + self.received_uncompressed();
+        let compressed = self.sent_compressed() + self.received_compressed();
+        if uncompressed == 0 {
+            return None;
+        }
+        Some(compressed as f64 / uncompressed as f64)
+    }
+
+    /// The bytes compression saved, negative if compressing made the packets larger
+    pub fn saved_bytes(&self) -> i64 {
+        let uncompressed = self.sent_uncompressed() + self.received_uncompressed();
+        let compressed = self.sent_compressed() + self.received_compressed();
+        uncompressed as i64 - compressed as i64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CompressionStats;
+
+    #[test]
+    fn test_compression_ratio() {
+        let stats = CompressionStats::new();
+        assert_eq!(stats.compression_ratio(), None);
+
+        stats.record_sent(1000, 200);
+        stats.record_sent(100, 100);
+        stats.record_received(400, 100);
+        assert_eq!(stats.sent_uncompressed(), 1100);
+        assert_eq!(stats.sent_compressed(), 300);
+        assert_eq!(stats.received_uncompressed(), 400);
+        assert_eq!(stats.received_compressed(), 100);
+        assert_eq!(stats.compression_ratio(), Some(400f64 / 1500f64));
+        assert_eq!(stats.saved_bytes(), 1100);
+
+        // Tiny packets can grow
+        let stats = CompressionStats::new();
+        stats.record_sent(10, 15);
+        assert_eq!(stats.compression_ratio(), Some(1.5f64));
+        assert_eq!(stats.saved_bytes(), -5);
+    }
+}
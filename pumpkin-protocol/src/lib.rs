@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 pub mod bytebuf;
 pub mod client;
+pub mod compression_stats;
 pub mod packet_decoder;
 pub mod packet_encoder;
 pub mod query;
@@ -88,7 +89,7 @@ pub struct StatusResponse {
     /// Information about currently connected Players. Optional
     pub players: Option<Players>,
     /// The description displayed also called MOTD (Message of the day). Optional
-    pub description: String,
+    pub description: serde_json::Value,
     /// The icon displayed, Optional
     pub favicon: Option<String>,
     /// Players are forced to use Secure chat
@@ -7,7 +7,11 @@ use std::io::Write;
 use bytes::BufMut;
 use flate2::write::ZlibDecoder;
 
-use crate::{bytebuf::ByteBuffer, RawPacket, VarInt, VarIntDecodeError, MAX_PACKET_SIZE};
+use crate::{
+    bytebuf::ByteBuffer,
+    compression_stats::{CompressionStats, GLOBAL_COMPRESSION_STATS},
+    RawPacket, VarInt, VarIntDecodeError, MAX_PACKET_SIZE,
+};
 
 type Cipher = cfb8::Decryptor<aes::Aes128>;
 
@@ -19,6 +23,7 @@ pub struct PacketDecoder {
     buf: BytesMut,
     decompress_buf: BytesMut,
     compression: bool,
+    compression_stats: CompressionStats,
     cipher: Option<Cipher>,
 }
 
@@ -67,6 +72,7 @@ impl PacketDecoder {
                 z.write_all(r)
                     .map_err(|e| PacketDecodeError::FailedWrite(e.to_string()))?;
                 z.finish().map_err(|_| PacketDecodeError::FailedFinish)?;
+                self.record_received(data_len as usize, r.len());
 
                 let total_packet_len = VarInt(packet_len).written_size() + packet_len as usize;
 
@@ -77,6 +83,7 @@ impl PacketDecoder {
                 debug_assert_eq!(data_len, 0);
 
                 let remaining_len = r.len();
+                self.record_received(remaining_len, remaining_len);
 
                 self.buf.advance(packet_len_len + 1);
 
@@ -121,6 +128,17 @@ impl PacketDecoder {
         self.compression = compression;
     }
 
+    /// The bytes this connection received before and after decompression
+    pub fn compression_stats(&self) -> &CompressionStats {
+        &self.compression_stats
+    }
+
+    fn record_received(&self, uncompressed: usize, compressed: usize) {
+        self.compression_stats
+            .record_received(uncompressed, compressed);
+        GLOBAL_COMPRESSION_STATS.record_received(uncompressed, compressed);
+    }
+
     fn decrypt_bytes(cipher: &mut Cipher, bytes: &mut [u8]) {
         for chunk in bytes.chunks_mut(Cipher::block_size()) {
             let gen_arr = GenericArray::from_mut_slice(chunk);
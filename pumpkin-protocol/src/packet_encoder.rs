@@ -2,7 +2,7 @@ use std::io::Write;
 
 use aes::cipher::{generic_array::GenericArray, BlockEncryptMut, BlockSizeUser, KeyIvInit};
 use bytes::{BufMut, BytesMut};
-use pumpkin_config::compression::CompressionInfo;
+use pumpkin_config::compression::{CompressionAlgorithm, CompressionInfo};
 use thiserror::Error;
 
 use std::io::Read;
@@ -10,7 +10,11 @@ use std::io::Read;
 use flate2::bufread::ZlibEncoder;
 use flate2::Compression;
 
-use crate::{bytebuf::ByteBuffer, ClientPacket, VarInt, MAX_PACKET_SIZE};
+use crate::{
+    bytebuf::ByteBuffer,
+    compression_stats::{CompressionStats, GLOBAL_COMPRESSION_STATS},
+    ClientPacket, VarInt, MAX_PACKET_SIZE,
+};
 
 type Cipher = cfb8::Encryptor<aes::Aes128>;
 
@@ -22,6 +26,7 @@ pub struct PacketEncoder {
     buf: BytesMut,
     compress_buf: Vec<u8>,
     compression: Option<CompressionInfo>,
+    compression_stats: CompressionStats,
     cipher: Option<Cipher>,
 }
 
@@ -43,22 +48,18 @@ impl PacketEncoder {
         let data_len = self.buf.len() - start_len;
 
         if let Some(compression) = &self.compression {
-            if data_len > compression.threshold as usize {
-                let mut z =
-                    ZlibEncoder::new(&self.buf[start_len..], Compression::new(compression.level));
-
-                self.compress_buf.clear();
-
+            if compression.threshold.compresses(data_len)
+                && compress(compression, &self.buf[start_len..], &mut self.compress_buf)
+            {
                 let data_len_size = VarInt(data_len as i32).written_size();
 
-                let packet_len = data_len_size + z.read_to_end(&mut self.compress_buf).unwrap();
+                let packet_len = data_len_size + self.compress_buf.len();
 
                 if packet_len >= MAX_PACKET_SIZE as usize {
                     Err(PacketEncodeError::TooLong)?
                 }
 
-                drop(z);
-
+                self.record_sent(data_len, self.compress_buf.len());
                 self.buf.truncate(start_len);
 
                 let mut writer = (&mut self.buf).writer();
@@ -78,6 +79,7 @@ impl PacketEncoder {
                     Err(PacketEncodeError::TooLong)?
                 }
 
+                self.record_sent(data_len, data_len);
                 let packet_len_size = VarInt(packet_len as i32).written_size();
 
                 let data_prefix_len = packet_len_size + data_len_size;
@@ -131,11 +133,21 @@ impl PacketEncoder {
         }
     }
 
-    /// Enables ZLib Compression
+    /// Enables compression with the algorithm of the `CompressionInfo`
     pub fn set_compression(&mut self, compression: Option<CompressionInfo>) {
         self.compression = compression;
     }
 
+    /// The bytes this connection sent before and after compression
+    pub fn compression_stats(&self) -> &CompressionStats {
+        &self.compression_stats
+    }
+
+    fn record_sent(&self, uncompressed: usize, compressed: usize) {
+        self.compression_stats.record_sent(uncompressed, compressed);
+        GLOBAL_COMPRESSION_STATS.record_sent(uncompressed, compressed);
+    }
+
     pub fn take(&mut self) -> BytesMut {
         if let Some(cipher) = &mut self.cipher {
             for chunk in self.buf.chunks_mut(Cipher::block_size()) {
@@ -148,6 +160,21 @@ impl PacketEncoder {
     }
 }
 
+/// Compresses `data` into `out`, `false` if the algorithm sends the packet uncompressed with a data
+/// length of 0 instead
+fn compress(compression: &CompressionInfo, data: &[u8], out: &mut Vec<u8>) -> bool {
+    out.clear();
+    match compression.algorithm {
+        CompressionAlgorithm::Zlib => {
+            ZlibEncoder::new(data, Compression::new(compression.level))
+                .read_to_end(out)
+                .unwrap();
+            true
+        }
+        CompressionAlgorithm::None => false,
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum PacketEncodeError {
     #[error("failed to encode packet ID")]
@@ -168,3 +195,63 @@ impl PacketEncodeError {
         !matches!(self, Self::EncodeData | Self::EncodeFailedWrite)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use pumpkin_config::compression::{
+        CompressionAlgorithm, CompressionInfo, CompressionThreshold,
+    };
+
+    use crate::{
+        bytebuf::{packet_id::Packet, ByteBuffer},
+        packet_decoder::PacketDecoder,
+        ClientPacket,
+    };
+
+    use super::PacketEncoder;
+
+    struct TestPacket(Vec<u8>);
+
+    impl Packet for TestPacket {
+        const PACKET_ID: i32 = 0x42;
+    }
+
+    impl ClientPacket for TestPacket {
+        fn write(&self, bytebuf: &mut ByteBuffer) {
+            bytebuf.put_slice(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for algorithm in [CompressionAlgorithm::Zlib, CompressionAlgorithm::None] {
+            let mut encoder = PacketEncoder::default();
+            encoder.set_compression(Some(CompressionInfo {
+                threshold: CompressionThreshold::Threshold(256),
+                level: 4,
+                algorithm,
+            }));
+            let mut decoder = PacketDecoder::default();
+            decoder.set_compression(true);
+
+            // Below and above the threshold
+            for len in [10, 5000] {
+                let data = vec![7u8; len];
+                encoder.append_packet(&TestPacket(data.clone())).unwrap();
+                let bytes = encoder.take();
+                // The data length is 0 for packets sent uncompressed
+                let compressed = bytes.len() < len;
+                assert_eq!(
+                    compressed,
+                    algorithm == CompressionAlgorithm::Zlib && len >= 256
+                );
+
+                decoder.queue_bytes(bytes);
+                let mut packet = decoder.decode().unwrap().unwrap();
+                assert_eq!(packet.id.0, 0x42);
+                assert_eq!(packet.bytebuf.get_slice()[..], data[..]);
+                assert!(decoder.decode().unwrap().is_none());
+            }
+        }
+    }
+}
@@ -2,8 +2,10 @@ mod s_acknowledge_finish_config;
 mod s_client_information;
 mod s_known_packs;
 mod s_plugin_message;
+mod s_resource_pack;
 
 pub use s_acknowledge_finish_config::*;
 pub use s_client_information::*;
 pub use s_known_packs::*;
 pub use s_plugin_message::*;
+pub use s_resource_pack::*;
@@ -0,0 +1,44 @@
+use num_derive::FromPrimitive;
+use pumpkin_macros::server_packet;
+
+use crate::{
+    bytebuf::{ByteBuffer, DeserializerError},
+    ServerPacket, VarInt,
+};
+
+#[server_packet("config:resource_pack")]
+pub struct SConfigResourcePack {
+    pub uuid: uuid::Uuid,
+    pub result: VarInt,
+}
+
+#[derive(FromPrimitive, PartialEq, Eq)]
+pub enum ResourcePackResponseResult {
+    SuccessfullyLoaded = 0,
+    Declined,
+    FailedDownload,
+    Accepted,
+    Downloaded,
+    InvalidUrl,
+    FailedReload,
+    Discarded,
+}
+
+impl ResourcePackResponseResult {
+    /// Whether the client is done handling the pack without it being loaded
+    pub fn is_failure(&self) -> bool {
+        !matches!(
+            self,
+            Self::SuccessfullyLoaded | Self::Accepted | Self::Downloaded
+        )
+    }
+}
+
+impl ServerPacket for SConfigResourcePack {
+    fn read(bytebuf: &mut ByteBuffer) -> Result<Self, DeserializerError> {
+        Ok(Self {
+            uuid: bytebuf.get_uuid()?,
+            result: bytebuf.get_var_int()?,
+        })
+    }
+}
@@ -8,7 +8,8 @@ use crate::{
 #[server_packet("handshake:intention")]
 pub struct SHandShake {
     pub protocol_version: VarInt,
-    pub server_address: String, // 255
+    /// At most 255 characters, but BungeeCord appends the forwarded player info
+    pub server_address: String,
     pub server_port: u16,
     pub next_state: ConnectionState,
 }
@@ -17,7 +18,7 @@ impl ServerPacket for SHandShake {
     fn read(bytebuf: &mut ByteBuffer) -> Result<Self, DeserializerError> {
         Ok(Self {
             protocol_version: bytebuf.get_var_int()?,
-            server_address: bytebuf.get_string_len(255)?,
+            server_address: bytebuf.get_string_len(i16::MAX as i32)?,
             server_port: bytebuf.get_u16()?,
             next_state: bytebuf.get_var_int()?.into(),
         })
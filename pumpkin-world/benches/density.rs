@@ -0,0 +1,44 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pumpkin_world::density_bench;
+
+fn cache_2d(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cache_2d_chunk");
+    let mut direct = density_bench::continents(false);
+    group.bench_function("direct", |b| b.iter(|| black_box(direct.run())));
+    let mut cached = density_bench::continents(true);
+    group.bench_function("cached", |b| b.iter(|| black_box(cached.run())));
+    group.finish();
+}
+
+fn cache_cell(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cache_cell_chunk");
+    let mut direct = density_bench::base_3d_noise_reused(false);
+    group.bench_function("direct", |b| b.iter(|| black_box(direct.run())));
+    let mut cached = density_bench::base_3d_noise_reused(true);
+    group.bench_function("cached", |b| b.iter(|| black_box(cached.run())));
+    group.finish();
+}
+
+fn interpolated(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interpolated_chunk");
+    let mut direct = density_bench::base_3d_noise(false);
+    group.bench_function("direct", |b| b.iter(|| black_box(direct.run())));
+    let mut interpolated = density_bench::base_3d_noise(true);
+    group.bench_function("interpolated", |b| b.iter(|| black_box(interpolated.run())));
+    group.finish();
+}
+
+fn add(c: &mut Criterion) {
+    let mut fill = density_bench::base_3d_noise_plus_y();
+    c.bench_function("add_chunk", |b| b.iter(|| black_box(fill.run())));
+
+    let mut group = c.benchmark_group("add_arrays");
+    let mut scalar = density_bench::add_arrays(false);
+    group.bench_function("scalar", |b| b.iter(|| black_box(scalar.run())));
+    let mut vector = density_bench::add_arrays(true);
+    group.bench_function("vector", |b| b.iter(|| black_box(vector.run())));
+    group.finish();
+}
+
+criterion_group!(benches, cache_2d, cache_cell, interpolated, add);
+criterion_main!(benches);
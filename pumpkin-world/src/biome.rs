@@ -1,24 +1,59 @@
+use std::sync::Arc;
+
 use enum_dispatch::enum_dispatch;
+use pumpkin_config::biome_tuning::BiomeTuningConfig;
 use serde::{Deserialize, Serialize};
 
+use crate::world_gen::{
+    biome_coords,
+    noise::density::{DensityFunction, DensityFunctionImpl, NoisePos},
+};
+
 // TODO make this work with the protocol
 // Send by the registry
-#[derive(Serialize, Deserialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[non_exhaustive]
 pub enum Biome {
     Plains,
     SnowyTiga,
+    Ocean,
+    Beach,
+    Taiga,
+    Forest,
+    Desert,
+    Savanna,
+    Jungle,
     // TODO list all Biomes
 }
 
+impl Biome {
+    /// Parses a biome name like `plains` or `minecraft:snowy_taiga`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.strip_prefix("minecraft:").unwrap_or(name) {
+            "plains" => Some(Self::Plains),
+            "snowy_taiga" => Some(Self::SnowyTiga),
+            "ocean" => Some(Self::Ocean),
+            "beach" => Some(Self::Beach),
+            "taiga" => Some(Self::Taiga),
+            "forest" => Some(Self::Forest),
+            "desert" => Some(Self::Desert),
+            "savanna" => Some(Self::Savanna),
+            "jungle" => Some(Self::Jungle),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone)]
 #[enum_dispatch(BiomeSupplierImpl)]
 pub enum BiomeSupplier {
     Debug(DebugBiomeSupplier),
+    MultiNoise(MultiNoiseBiomeSupplier),
 }
 
 #[enum_dispatch]
 pub trait BiomeSupplierImpl {
+    /// The biome at the biome coordinates (a quarter of the block coordinates), like vanilla
     fn biome(&self, x: i32, y: i32, z: i32, noise: &MultiNoiseSampler) -> Biome;
 }
 
@@ -31,5 +66,368 @@ impl BiomeSupplierImpl for DebugBiomeSupplier {
     }
 }
 
-// TODO: Implement
-pub struct MultiNoiseSampler {}
+/// Global offsets added to the climate parameters before a biome gets selected
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct ClimateOffsets {
+    pub temperature: f64,
+    pub humidity: f64,
+    pub continentalness: f64,
+}
+
+impl From<&BiomeTuningConfig> for ClimateOffsets {
+    /// Uses the clamped offsets
+    fn from(config: &BiomeTuningConfig) -> Self {
+        Self {
+            temperature: config.temperature_offset(),
+            humidity: config.humidity_offset(),
+            continentalness: config.continentalness_offset(),
+        }
+    }
+}
+
+/// Climate parameters are compared as fixed point numbers with 4 decimals, see `quantize`
+const QUANTIZATION_FACTOR: f64 = 10000.0;
+
+/// Vanilla's `Climate.quantizeCoord`
+pub fn quantize(value: f64) -> i64 {
+    (value * QUANTIZATION_FACTOR) as i64
+}
+
+/// The climate at a position, quantized
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NoiseValuePoint {
+    pub temperature: i64,
+    pub humidity: i64,
+    pub continentalness: i64,
+    pub erosion: i64,
+    pub depth: i64,
+    pub weirdness: i64,
+}
+
+impl NoiseValuePoint {
+    /// Quantizes the climate values
+    pub fn new(
+        temperature: f64,
+        humidity: f64,
+        continentalness: f64,
+        erosion: f64,
+        depth: f64,
+        weirdness: f64,
+    ) -> Self {
+        Self {
+            temperature: quantize(temperature),
+            humidity: quantize(humidity),
+            continentalness: quantize(continentalness),
+            erosion: quantize(erosion),
+            depth: quantize(depth),
+            weirdness: quantize(weirdness),
+        }
+    }
+}
+
+/// A range of one climate parameter, quantized
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParameterRange {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl ParameterRange {
+    pub fn new(min: f64, max: f64) -> Self {
+        assert!(
+            min <= max,
+            "The minimum must not be larger than the maximum"
+        );
+        Self {
+            min: quantize(min),
+            max: quantize(max),
+        }
+    }
+
+    /// A range containing a single value
+    pub fn point(value: f64) -> Self {
+        Self::new(value, value)
+    }
+
+    /// Every value between -1 and 1, which the climate noises are mostly in
+    pub fn full() -> Self {
+        Self::new(-1.0, 1.0)
+    }
+
+    /// How far the value lies outside of the range, 0 inside of it
+    pub fn distance(&self, value: i64) -> i64 {
+        if value > self.max {
+            value - self.max
+        } else if value < self.min {
+            self.min - value
+        } else {
+            0
+        }
+    }
+}
+
+/// The climate a biome is placed in, vanilla's `Climate.ParameterPoint`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NoiseHypercube {
+    pub temperature: ParameterRange,
+    pub humidity: ParameterRange,
+    pub continentalness: ParameterRange,
+    pub erosion: ParameterRange,
+    pub depth: ParameterRange,
+    pub weirdness: ParameterRange,
+    /// Added to the distance, makes the biome less likely, quantized
+    pub offset: i64,
+}
+
+impl NoiseHypercube {
+    /// The squared distance of the point to the hypercube, 0 inside of it
+    pub fn distance_squared(&self, point: &NoiseValuePoint) -> i64 {
+        [
+            self.temperature.distance(point.temperature),
+            self.humidity.distance(point.humidity),
+            self.continentalness.distance(point.continentalness),
+            self.erosion.distance(point.erosion),
+            self.depth.distance(point.depth),
+            self.weirdness.distance(point.weirdness),
+            self.offset,
+        ]
+        .iter()
+        .map(|distance| distance * distance)
+        .sum()
+    }
+}
+
+/// Picks the biome whose climate is closest to the sampled one, like vanilla's `MultiNoiseBiomeSource`.
+///
+/// Searches the whole parameter list instead of vanilla's R-tree, ties are won by the earlier entry
+#[derive(Clone)]
+pub struct MultiNoiseBiomeSupplier {
+    entries: Arc<[(NoiseHypercube, Biome)]>,
+}
+
+impl MultiNoiseBiomeSupplier {
+    pub fn new(entries: Vec<(NoiseHypercube, Biome)>) -> Self {
+        assert!(!entries.is_empty(), "At least one biome is required");
+        Self {
+            entries: entries.into(),
+        }
+    }
+
+    /// A simplified overworld with a handful of biomes, placed by temperature, humidity and continentalness only
+    pub fn overworld() -> Self {
+        let full = ParameterRange::full();
+        let entry = |temperature, humidity, continentalness, biome| {
+            (
+                NoiseHypercube {
+                    temperature,
+                    humidity,
+                    continentalness,
+                    erosion: full,
+                    depth: full,
+                    weirdness: full,
+                    offset: 0,
+                },
+                biome,
+            )
+        };
+        let range = ParameterRange::new;
+        // Vanilla's continentalness boundaries between the ocean, the coast and inland
+        let ocean = range(-1.05, -0.19);
+        let coast = range(-0.19, -0.11);
+        let inland = range(-0.11, 1.0);
+        Self::new(vec![
+            entry(full, full, ocean, Biome::Ocean),
+            entry(full, full, coast, Biome::Beach),
+            entry(range(-1.0, -0.45), full, inland, Biome::SnowyTiga),
+            entry(range(-0.45, -0.15), full, inland, Biome::Taiga),
+            entry(range(-0.15, 0.55), range(-1.0, -0.1), inland, Biome::Plains),
+            entry(range(-0.15, 0.55), range(-0.1, 1.0), inland, Biome::Forest),
+            entry(range(0.55, 1.0), range(-1.0, -0.1), inland, Biome::Desert),
+            entry(range(0.55, 1.0), range(-0.1, 0.3), inland, Biome::Savanna),
+            entry(range(0.55, 1.0), range(0.3, 1.0), inland, Biome::Jungle),
+        ])
+    }
+
+    /// The biome closest to the climate point
+    pub fn find(&self, point: &NoiseValuePoint) -> Biome {
+        self.entries
+            .iter()
+            .min_by_key(|(hypercube, _)| hypercube.distance_squared(point))
+            .map(|(_, biome)| *biome)
+            .unwrap()
+    }
+}
+
+impl BiomeSupplierImpl for MultiNoiseBiomeSupplier {
+    fn biome(&self, x: i32, y: i32, z: i32, noise: &MultiNoiseSampler) -> Biome {
+        self.find(&noise.sample(x, y, z))
+    }
+}
+
+/// Samples the climate from the density functions of the noise router
+pub struct MultiNoiseSampler<'a> {
+    temperature: Arc<DensityFunction<'a>>,
+    humidity: Arc<DensityFunction<'a>>,
+    continentalness: Arc<DensityFunction<'a>>,
+    erosion: Arc<DensityFunction<'a>>,
+    depth: Arc<DensityFunction<'a>>,
+    weirdness: Arc<DensityFunction<'a>>,
+    offsets: ClimateOffsets,
+}
+
+impl<'a> MultiNoiseSampler<'a> {
+    pub(crate) fn new(
+        temperature: Arc<DensityFunction<'a>>,
+        humidity: Arc<DensityFunction<'a>>,
+        continentalness: Arc<DensityFunction<'a>>,
+        erosion: Arc<DensityFunction<'a>>,
+        depth: Arc<DensityFunction<'a>>,
+        weirdness: Arc<DensityFunction<'a>>,
+        offsets: ClimateOffsets,
+    ) -> Self {
+        Self {
+            temperature,
+            humidity,
+            continentalness,
+            erosion,
+            depth,
+            weirdness,
+            offsets,
+        }
+    }
+
+    /// The climate at the biome coordinates, sampled at the lowest block of the biome cell
+    pub fn sample(&self, x: i32, y: i32, z: i32) -> NoiseValuePoint {
+        let pos = NoisePos::new(
+            biome_coords::to_block(x),
+            biome_coords::to_block(y),
+            biome_coords::to_block(z),
+        );
+        let (temperature, humidity, continentalness) = self.apply_offsets(
+            self.temperature.sample(&pos),
+            self.humidity.sample(&pos),
+            self.continentalness.sample(&pos),
+        );
+        NoiseValuePoint::new(
+            temperature,
+            humidity,
+            continentalness,
+            self.erosion.sample(&pos),
+            self.depth.sample(&pos),
+            self.weirdness.sample(&pos),
+        )
+    }
+
+    pub fn offsets(&self) -> ClimateOffsets {
+        self.offsets
+    }
+
+    /// Applies the offsets to sampled `(temperature, humidity, continentalness)` values
+    pub fn apply_offsets(
+        &self,
+        temperature: f64,
+        humidity: f64,
+        continentalness: f64,
+    ) -> (f64, f64, f64) {
+        (
+            temperature + self.offsets.temperature,
+            humidity + self.offsets.humidity,
+            continentalness + self.offsets.continentalness,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use pumpkin_config::biome_tuning::BiomeTuningConfig;
+
+    use crate::world_gen::noise::density::DensityFunction;
+
+    use super::{
+        Biome, BiomeSupplierImpl, ClimateOffsets, MultiNoiseBiomeSupplier, MultiNoiseSampler,
+        NoiseValuePoint, ParameterRange,
+    };
+
+    fn point(temperature: f64, humidity: f64, continentalness: f64) -> NoiseValuePoint {
+        NoiseValuePoint::new(temperature, humidity, continentalness, 0.0, 0.0, 0.0)
+    }
+
+    #[test]
+    fn test_parameter_range() {
+        let range = ParameterRange::new(-0.5, 0.25);
+        assert_eq!(range.distance(0), 0);
+        assert_eq!(range.distance(-5000), 0);
+        assert_eq!(range.distance(2500), 0);
+        assert_eq!(range.distance(3000), 500);
+        assert_eq!(range.distance(-10000), 5000);
+        assert_eq!(ParameterRange::point(0.1).distance(1000), 0);
+    }
+
+    #[test]
+    fn test_multi_noise_biomes() {
+        let biomes = MultiNoiseBiomeSupplier::overworld();
+        for (point, biome) in [
+            (point(0.0, 0.0, -0.5), Biome::Ocean),
+            (point(0.9, 0.9, -1.1), Biome::Ocean),
+            (point(0.0, 0.0, -0.15), Biome::Beach),
+            (point(-0.7, 0.0, 0.5), Biome::SnowyTiga),
+            (point(-0.3, 0.5, 0.5), Biome::Taiga),
+            (point(0.0, -0.5, 0.2), Biome::Plains),
+            (point(0.3, 0.5, 0.2), Biome::Forest),
+            (point(0.8, -0.5, 0.3), Biome::Desert),
+            (point(0.8, 0.0, 0.3), Biome::Savanna),
+            (point(0.8, 0.6, 0.3), Biome::Jungle),
+            // Outside of every range the closest biome wins
+            (point(2.0, 2.0, 2.0), Biome::Jungle),
+            (point(-2.0, -2.0, 2.0), Biome::SnowyTiga),
+        ] {
+            assert_eq!(biomes.find(&point), biome, "{point:?}");
+        }
+
+        let constant = |value| Arc::new(DensityFunction::constant(value));
+        let sampler = |offsets| {
+            MultiNoiseSampler::new(
+                constant(0.8),
+                constant(-0.5),
+                constant(0.3),
+                constant(0.0),
+                constant(0.0),
+                constant(0.0),
+                offsets,
+            )
+        };
+        let noise = sampler(ClimateOffsets::default());
+        assert_eq!(noise.sample(1, 2, 3), point(0.8, -0.5, 0.3));
+        assert_eq!(biomes.biome(-10, 16, 7, &noise), Biome::Desert);
+        // The offsets are applied before the biome gets selected
+        let noise = sampler(ClimateOffsets {
+            temperature: -1.0,
+            humidity: 0.0,
+            continentalness: -1.0,
+        });
+        assert_eq!(biomes.biome(-10, 16, 7, &noise), Biome::Ocean);
+    }
+
+    #[test]
+    fn test_offsets_from_config() {
+        let config = BiomeTuningConfig {
+            temperature_offset: 0.25,
+            humidity_offset: 3.0,
+            continentalness_offset: f64::NAN,
+        };
+        assert_eq!(
+            ClimateOffsets::from(&config),
+            ClimateOffsets {
+                temperature: 0.25,
+                humidity: 1.0,
+                continentalness: 0.0,
+            }
+        );
+        assert_eq!(
+            ClimateOffsets::from(&BiomeTuningConfig::default()),
+            ClimateOffsets::default()
+        );
+    }
+}
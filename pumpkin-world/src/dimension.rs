@@ -1,6 +1,12 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
-use crate::level::Level;
+use crate::{
+    level::Level,
+    world_gen::{GenerationSettings, Seed, WorldGenType},
+};
+
+/// One block in the Nether equals this many blocks in the Overworld
+pub const NETHER_COORDINATE_SCALE: f64 = 8.0;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Dimension {
@@ -10,12 +16,66 @@ pub enum Dimension {
 }
 
 impl Dimension {
-    pub fn into_level(&self, mut base_directory: PathBuf) -> Level {
+    /// The vanilla name of the dimension, like `minecraft:overworld`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dimension::OverWorld => "minecraft:overworld",
+            Dimension::Nether => "minecraft:the_nether",
+            Dimension::End => "minecraft:the_end",
+        }
+    }
+
+    /// Loads the level of the dimension, missing chunks get generated with `world_type` on `generation_pool`
+    pub fn into_level(
+        &self,
+        mut base_directory: PathBuf,
+        seed: Seed,
+        world_type: WorldGenType,
+        settings: GenerationSettings,
+        generation_pool: Arc<rayon::ThreadPool>,
+    ) -> Level {
         match self {
             Dimension::OverWorld => {}
             Dimension::Nether => base_directory.push("DIM-1"),
             Dimension::End => base_directory.push("DIM1"),
         }
-        Level::from_root_folder(base_directory)
+        Level::from_root_folder(base_directory, seed, world_type, settings, generation_pool)
+    }
+}
+
+/// Converts horizontal block coordinates to the coordinates a portal leads to in the other dimension
+pub fn portal_destination(x: i32, z: i32, to_nether: bool) -> (i32, i32) {
+    let scale = if to_nether {
+        1.0 / NETHER_COORDINATE_SCALE
+    } else {
+        NETHER_COORDINATE_SCALE
+    };
+    (
+        (f64::from(x) * scale).floor() as i32,
+        (f64::from(z) * scale).floor() as i32,
+    )
+}
+
+/// The configured `portal_search_radius` (in Overworld blocks) in the coordinates of the destination dimension
+pub fn destination_search_radius(radius: i32, to_nether: bool) -> i32 {
+    if to_nether {
+        (f64::from(radius) / NETHER_COORDINATE_SCALE).ceil() as i32
+    } else {
+        radius
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{destination_search_radius, portal_destination};
+
+    #[test]
+    fn test_portal_destination() {
+        assert_eq!(portal_destination(100, -100, true), (12, -13));
+        assert_eq!(portal_destination(12, -13, false), (96, -104));
+
+        assert_eq!(destination_search_radius(128, false), 128);
+        assert_eq!(destination_search_radius(128, true), 16);
+        assert_eq!(destination_search_radius(1, true), 1);
     }
 }
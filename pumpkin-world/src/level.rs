@@ -5,7 +5,7 @@ use num_traits::Zero;
 use pumpkin_core::math::vector2::Vector2;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use tokio::{
-    sync::{mpsc, RwLock},
+    sync::{mpsc, RwLock, Semaphore},
     task::JoinHandle,
 };
 
@@ -13,7 +13,9 @@ use crate::{
     chunk::{
         anvil::AnvilChunkReader, ChunkData, ChunkParsingError, ChunkReader, ChunkReadingError,
     },
-    world_gen::{get_world_gen, Seed, WorldGenerator},
+    world_gen::{
+        generate_chunk_async, get_world_gen, GenerationSettings, Seed, WorldGenType, WorldGenerator,
+    },
 };
 
 pub type ConcurrentChunkResult = Vec<(Vector2<i32>, JoinHandle<()>)>;
@@ -33,6 +35,10 @@ pub struct Level {
     chunk_watchers: Arc<DashMap<Vector2<i32>, usize>>,
     chunk_reader: Arc<dyn ChunkReader>,
     world_gen: Arc<dyn WorldGenerator>,
+    /// Limits how many chunks of this level wait for the generation pool at the same time
+    chunk_gen_permits: Arc<Semaphore>,
+    /// The threads generating chunks, shared by every level of the server
+    generation_pool: Arc<rayon::ThreadPool>,
 }
 
 #[derive(Clone)]
@@ -42,8 +48,15 @@ pub struct SaveFile {
 }
 
 impl Level {
-    pub fn from_root_folder(root_folder: PathBuf) -> Self {
-        let world_gen = get_world_gen(Seed(0)).into(); // TODO Read Seed from config.
+    /// Loads the level, missing chunks get generated with `world_type` on `generation_pool`
+    pub fn from_root_folder(
+        root_folder: PathBuf,
+        seed: Seed,
+        world_type: WorldGenType,
+        settings: GenerationSettings,
+        generation_pool: Arc<rayon::ThreadPool>,
+    ) -> Self {
+        let world_gen = get_world_gen(seed, world_type, settings).into();
         if root_folder.exists() {
             let region_folder = root_folder.join("region");
             assert!(
@@ -60,6 +73,8 @@ impl Level {
                 chunk_reader: Arc::new(AnvilChunkReader::new()),
                 loaded_chunks: Arc::new(DashMap::new()),
                 chunk_watchers: Arc::new(DashMap::new()),
+                chunk_gen_permits: Arc::new(Semaphore::new(Semaphore::MAX_PERMITS)),
+                generation_pool,
             }
         } else {
             Self {
@@ -68,10 +83,23 @@ impl Level {
                 chunk_reader: Arc::new(AnvilChunkReader::new()),
                 loaded_chunks: Arc::new(DashMap::new()),
                 chunk_watchers: Arc::new(DashMap::new()),
+                chunk_gen_permits: Arc::new(Semaphore::new(Semaphore::MAX_PERMITS)),
+                generation_pool,
             }
         }
     }
 
+    /// Limits the amount of chunks of this level queued for generation at the same time.
+    /// The generation pool decides how many of them actually run in parallel
+    pub fn with_max_concurrent_chunk_gen(mut self, limit: usize) -> Self {
+        self.chunk_gen_permits = Arc::new(Semaphore::new(limit.clamp(1, Semaphore::MAX_PERMITS)));
+        self
+    }
+
+    pub fn generation_pool(&self) -> &rayon::ThreadPool {
+        &self.generation_pool
+    }
+
     pub fn get_block() {}
 
     pub fn loaded_chunk_count(&self) -> usize {
@@ -221,34 +249,42 @@ impl Level {
                 let chunk_reader = self.chunk_reader.clone();
                 let save_file = self.save_file.clone();
                 let world_gen = self.world_gen.clone();
+                let chunk_gen_permits = self.chunk_gen_permits.clone();
+                let generation_pool = self.generation_pool.clone();
                 let chunk_pos = *at;
 
                 let join_handle = tokio::spawn(async move {
-                    let chunk = loaded_chunks
+                    let cached_chunk = loaded_chunks
                         .get(&chunk_pos)
-                        .map(|entry| entry.value().clone())
-                        .unwrap_or_else(|| {
-                            let loaded_chunk = save_file
-                                .and_then(|save_file| {
-                                    match Self::load_chunk_from_save(
-                                        chunk_reader,
-                                        save_file,
-                                        chunk_pos,
-                                    ) {
-                                        Ok(chunk) => chunk,
-                                        Err(err) => {
-                                            log::error!(
-                                                "Failed to read chunk (regenerating) {:?}: {:?}",
-                                                chunk_pos,
-                                                err
-                                            );
-                                            None
-                                        }
+                        .map(|entry| entry.value().clone());
+                    let chunk = match cached_chunk {
+                        Some(chunk) => chunk,
+                        None => {
+                            let saved_chunk = save_file.and_then(|save_file| {
+                                match Self::load_chunk_from_save(chunk_reader, save_file, chunk_pos)
+                                {
+                                    Ok(chunk) => chunk,
+                                    Err(err) => {
+                                        log::error!(
+                                            "Failed to read chunk (regenerating) {:?}: {:?}",
+                                            chunk_pos,
+                                            err
+                                        );
+                                        None
                                     }
-                                })
-                                .unwrap_or_else(|| {
-                                    Arc::new(RwLock::new(world_gen.generate_chunk(chunk_pos)))
-                                });
+                                }
+                            });
+                            let loaded_chunk = match saved_chunk {
+                                Some(chunk) => chunk,
+                                None => {
+                                    // The semaphore is never closed
+                                    let _permit = chunk_gen_permits.acquire().await;
+                                    Arc::new(RwLock::new(
+                                        generate_chunk_async(world_gen, generation_pool, chunk_pos)
+                                            .await,
+                                    ))
+                                }
+                            };
 
                             if let Some(data) = loaded_chunks.get(&chunk_pos) {
                                 // Another thread populated in between the previous check and now
@@ -259,7 +295,8 @@ impl Level {
                                 loaded_chunks.insert(chunk_pos, loaded_chunk.clone());
                                 loaded_chunk
                             }
-                        });
+                        }
+                    };
 
                     let _ = channel
                         .send(chunk)
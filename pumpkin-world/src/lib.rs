@@ -8,6 +8,14 @@ pub mod item;
 pub mod level;
 mod world_gen;
 
+#[doc(hidden)]
+pub use world_gen::density_bench;
+
+pub use world_gen::{
+    density_profile_report, generation_pool, FeatureSettings, FlatLayers, FlatLayersError,
+    GenerationSettings, Seed, WorldGenType, CLASSIC_FLAT_LAYERS,
+};
+
 pub const WORLD_HEIGHT: usize = 384;
 pub const WORLD_LOWEST_Y: i16 = -64;
 pub const WORLD_MAX_Y: i16 = WORLD_HEIGHT as i16 - WORLD_LOWEST_Y.abs();
@@ -0,0 +1,140 @@
+use pumpkin_core::random::{xoroshiro128::XoroshiroSplitter, RandomDeriverImpl, RandomImpl};
+
+use crate::{chunk::ChunkBlocks, coordinates::ChunkRelativeBlockCoordinates, WORLD_LOWEST_Y};
+
+use super::{state_id, Seed};
+
+/// The number of layers above the bottom which may contain bedrock, like vanilla's `bedrock_floor` rule
+const ROUGH_LAYERS: i16 = 5;
+
+/// Places the bedrock floor at the bottom of the world
+pub(crate) struct BedrockFloor {
+    random_deriver: XoroshiroSplitter,
+    bedrock: u16,
+}
+
+impl BedrockFloor {
+    pub fn new(seed: Seed) -> Self {
+        Self {
+            random_deriver: seed
+                .positional_factory()
+                .split_string("minecraft:bedrock_floor")
+                .next_splitter(),
+            bedrock: state_id("minecraft:bedrock"),
+        }
+    }
+
+    /// Places a solid bedrock layer at the bottom of the column.
+    /// When `rough` is set, the layers above get bedrock with a decreasing chance, otherwise they are left untouched
+    pub fn generate_column(
+        &self,
+        blocks: &mut ChunkBlocks,
+        x: u8,
+        z: u8,
+        block_x: i32,
+        block_z: i32,
+        rough: bool,
+    ) {
+        let layers = if rough { ROUGH_LAYERS } else { 1 };
+
+        for layer in 0..layers {
+            let y = WORLD_LOWEST_Y + layer;
+            // The same vertical gradient as vanilla: always at the bottom, never `ROUGH_LAYERS` above it
+            let chance = 1.0 - f32::from(layer) / f32::from(ROUGH_LAYERS);
+            if layer > 0
+                && self
+                    .random_deriver
+                    .split_pos(block_x, y.into(), block_z)
+                    .next_f32()
+                    >= chance
+            {
+                continue;
+            }
+
+            blocks.set_block(
+                ChunkRelativeBlockCoordinates {
+                    x: x.into(),
+                    y: y.into(),
+                    z: z.into(),
+                },
+                self.bedrock,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pumpkin_core::math::vector2::Vector2;
+
+    use crate::{
+        chunk::ChunkData,
+        world_gen::{
+            state_id,
+            test::{block_at, generate_plains},
+            GenerationSettings, Seed,
+        },
+        WORLD_LOWEST_Y,
+    };
+
+    use super::ROUGH_LAYERS;
+
+    fn generate(seed: Seed, bedrock_roughness: bool, at: Vector2<i32>) -> ChunkData {
+        generate_plains(
+            seed,
+            GenerationSettings {
+                bedrock_roughness,
+                ..Default::default()
+            },
+            at,
+        )
+    }
+
+    /// The bedrock blocks in the layers above the bottom of the chunk
+    fn rough_layers(chunk: &ChunkData) -> Vec<bool> {
+        let bedrock = state_id("minecraft:bedrock");
+        let mut result = Vec::new();
+        for y in WORLD_LOWEST_Y + 1..WORLD_LOWEST_Y + ROUGH_LAYERS {
+            for x in 0..16u8 {
+                for z in 0..16u8 {
+                    result.push(block_at(&chunk.blocks, x, y, z) == bedrock);
+                }
+            }
+        }
+        result
+    }
+
+    fn bottom_is_bedrock(chunk: &ChunkData) -> bool {
+        let bedrock = state_id("minecraft:bedrock");
+        (0..16u8)
+            .all(|x| (0..16u8).all(|z| block_at(&chunk.blocks, x, WORLD_LOWEST_Y, z) == bedrock))
+    }
+
+    #[test]
+    fn test_rough_bedrock() {
+        let seed = Seed(-4_172_144_997_902_289_642);
+        for at in [Vector2::new(0, 0), Vector2::new(-5, 12)] {
+            let chunk = generate(seed, true, at);
+            assert!(bottom_is_bedrock(&chunk));
+
+            let layers = rough_layers(&chunk);
+            assert!(layers.iter().any(|bedrock| *bedrock));
+            assert!(layers.iter().any(|bedrock| !*bedrock));
+            assert_eq!(layers, rough_layers(&generate(seed, true, at)));
+        }
+
+        // Another seed results in another pattern
+        let at = Vector2::new(0, 0);
+        assert_ne!(
+            rough_layers(&generate(seed, true, at)),
+            rough_layers(&generate(Seed(1), true, at))
+        );
+    }
+
+    #[test]
+    fn test_flat_bedrock() {
+        let chunk = generate(Seed(0), false, Vector2::new(3, -7));
+        assert!(bottom_is_bedrock(&chunk));
+        assert!(rough_layers(&chunk).iter().all(|bedrock| !*bedrock));
+    }
+}
@@ -0,0 +1,410 @@
+use pumpkin_config::world_gen::FeaturesConfig;
+use pumpkin_core::{
+    math::vector2::Vector2,
+    random::{
+        xoroshiro128::{Xoroshiro, XoroshiroSplitter},
+        RandomDeriverImpl, RandomImpl,
+    },
+};
+
+use crate::{
+    biome::Biome, block::BlockState, chunk::ChunkBlocks,
+    coordinates::ChunkRelativeBlockCoordinates, WORLD_LOWEST_Y, WORLD_MAX_Y,
+};
+
+use super::{state_id, Seed};
+
+/// Which features get placed after the terrain, all are enabled by default
+#[derive(Clone, Copy)]
+pub struct FeatureSettings {
+    pub ore_dirt: bool,
+    pub ore_gravel: bool,
+    pub ore_coal: bool,
+    pub ore_iron: bool,
+    pub patch_grass: bool,
+    pub flowers: bool,
+}
+
+impl Default for FeatureSettings {
+    fn default() -> Self {
+        Self {
+            ore_dirt: true,
+            ore_gravel: true,
+            ore_coal: true,
+            ore_iron: true,
+            patch_grass: true,
+            flowers: true,
+        }
+    }
+}
+
+impl From<&FeaturesConfig> for FeatureSettings {
+    fn from(config: &FeaturesConfig) -> Self {
+        Self {
+            ore_dirt: config.ore_dirt,
+            ore_gravel: config.ore_gravel,
+            ore_coal: config.ore_coal,
+            ore_iron: config.ore_iron,
+            patch_grass: config.patch_grass,
+            flowers: config.flowers,
+        }
+    }
+}
+
+impl FeatureSettings {
+    /// Whether the placed feature with this name is enabled, unknown features are always enabled
+    pub fn is_enabled(&self, name: &str) -> bool {
+        match name {
+            "ore_dirt" => self.ore_dirt,
+            "ore_gravel" => self.ore_gravel,
+            "ore_coal" => self.ore_coal,
+            "ore_iron" => self.ore_iron,
+            "patch_grass" => self.patch_grass,
+            "flowers" => self.flowers,
+            _ => true,
+        }
+    }
+}
+
+/// The block states are looked up once, when the feature is created
+pub(crate) enum Feature {
+    /// A vein of up to `size` blocks, only replacing stone
+    Ore { block: u16, stone: u16, size: u32 },
+    /// Plants placed on grass blocks with air above
+    Patch { plants: Vec<u16>, grass_block: u16 },
+}
+
+impl Feature {
+    fn ore(block: &str, size: u32) -> Self {
+        Self::Ore {
+            block: state_id(block),
+            stone: state_id("minecraft:stone"),
+            size,
+        }
+    }
+
+    fn patch(plants: &[&str]) -> Self {
+        Self::Patch {
+            plants: plants.iter().map(|plant| state_id(plant)).collect(),
+            grass_block: state_id("minecraft:grass_block"),
+        }
+    }
+
+    fn place(&self, blocks: &mut ChunkBlocks, random: &mut Xoroshiro, x: i32, y: i32, z: i32) {
+        match self {
+            Self::Ore { block, stone, size } => {
+                let (mut x, mut y, mut z) = (x, y, z);
+                for _ in 0..*size {
+                    // Veins which leave the chunk are cut off
+                    let Some(coordinates) = chunk_relative(x, y, z) else {
+                        break;
+                    };
+                    if blocks.get_block(coordinates) == *stone {
+                        blocks.set_block(coordinates, *block);
+                    }
+                    x += random.next_bounded_i32(3) - 1;
+                    y += random.next_bounded_i32(3) - 1;
+                    z += random.next_bounded_i32(3) - 1;
+                }
+            }
+            Self::Patch {
+                plants,
+                grass_block,
+            } => {
+                let plant = plants[random.next_bounded_i32(plants.len() as i32) as usize];
+                let Some(top) = top_block(blocks, x, z) else {
+                    return;
+                };
+                let (Some(ground), Some(above)) =
+                    (chunk_relative(x, top, z), chunk_relative(x, top + 1, z))
+                else {
+                    return;
+                };
+                if blocks.get_block(ground) != *grass_block
+                    || blocks.get_block(above) != BlockState::AIR.state_id
+                {
+                    return;
+                }
+                blocks.set_block(above, plant);
+            }
+        }
+    }
+}
+
+/// A feature together with where and how often it gets placed, like vanilla's placed features
+pub(crate) struct PlacedFeature {
+    name: &'static str,
+    feature: Feature,
+    /// Placement attempts per chunk
+    count: u32,
+    /// The range of the starting height, ignored by patches which are placed on the surface
+    min_y: i32,
+    max_y: i32,
+    /// Like vanilla's biome filter, attempts starting outside of these biomes are skipped.
+    /// `None` places the feature in every biome
+    biomes: Option<&'static [Biome]>,
+    random_deriver: XoroshiroSplitter,
+}
+
+impl PlacedFeature {
+    fn new(
+        seed: Seed,
+        name: &'static str,
+        feature: Feature,
+        count: u32,
+        min_y: i32,
+        max_y: i32,
+        biomes: Option<&'static [Biome]>,
+    ) -> Self {
+        Self {
+            name,
+            feature,
+            count,
+            min_y,
+            max_y,
+            biomes,
+            random_deriver: seed
+                .positional_factory()
+                .split_string(&format!("minecraft:{}", name))
+                .next_splitter(),
+        }
+    }
+
+    /// The features of the overworld, in the order they are placed. The ores are placed in every biome,
+    /// the grass and flowers only in the plains so far
+    // TODO: Trees, the vegetation of the other biomes
+    pub fn overworld(seed: Seed) -> Vec<Self> {
+        let ore = Feature::ore;
+        let plains: Option<&'static [Biome]> = Some(&[Biome::Plains]);
+        vec![
+            Self::new(seed, "ore_dirt", ore("minecraft:dirt", 33), 7, 0, 160, None),
+            Self::new(
+                seed,
+                "ore_gravel",
+                ore("minecraft:gravel", 33),
+                14,
+                -64,
+                320,
+                None,
+            ),
+            Self::new(
+                seed,
+                "ore_coal",
+                ore("minecraft:coal_ore", 17),
+                20,
+                0,
+                192,
+                None,
+            ),
+            Self::new(
+                seed,
+                "ore_iron",
+                ore("minecraft:iron_ore", 9),
+                10,
+                -64,
+                72,
+                None,
+            ),
+            Self::new(
+                seed,
+                "patch_grass",
+                Feature::patch(&["minecraft:short_grass"]),
+                32,
+                0,
+                0,
+                plains,
+            ),
+            Self::new(
+                seed,
+                "flowers",
+                Feature::patch(&[
+                    "minecraft:dandelion",
+                    "minecraft:poppy",
+                    "minecraft:azure_bluet",
+                    "minecraft:oxeye_daisy",
+                    "minecraft:cornflower",
+                ]),
+                2,
+                0,
+                0,
+                plains,
+            ),
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Places the feature in the chunk, the result only depends on the seed, the chunk position and the biomes.
+    /// `biome_at` gives the biome of a column by its chunk relative x and z
+    pub fn place(
+        &self,
+        blocks: &mut ChunkBlocks,
+        at: Vector2<i32>,
+        biome_at: impl Fn(i32, i32) -> Biome,
+    ) {
+        let mut random = self.random_deriver.split_pos(at.x * 16, 0, at.z * 16);
+        for _ in 0..self.count {
+            let x = random.next_bounded_i32(16);
+            let z = random.next_bounded_i32(16);
+            let y = random.next_inbetween_i32(self.min_y, self.max_y);
+            if self
+                .biomes
+                .is_some_and(|biomes| !biomes.contains(&biome_at(x, z)))
+            {
+                continue;
+            }
+            self.feature.place(blocks, &mut random, x, y, z);
+        }
+    }
+}
+
+fn chunk_relative(x: i32, y: i32, z: i32) -> Option<ChunkRelativeBlockCoordinates> {
+    if !(0..16).contains(&x)
+        || !(0..16).contains(&z)
+        || !(i32::from(WORLD_LOWEST_Y)..i32::from(WORLD_MAX_Y)).contains(&y)
+    {
+        return None;
+    }
+    Some(ChunkRelativeBlockCoordinates {
+        x: (x as u8).into(),
+        y: (y as i16).into(),
+        z: (z as u8).into(),
+    })
+}
+
+/// The height of the highest non air block in the column
+fn top_block(blocks: &ChunkBlocks, x: i32, z: i32) -> Option<i32> {
+    (i32::from(WORLD_LOWEST_Y)..i32::from(WORLD_MAX_Y))
+        .rev()
+        .find(|y| {
+            chunk_relative(x, *y, z).is_some_and(|coordinates| {
+                blocks.get_block(coordinates) != BlockState::AIR.state_id
+            })
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use pumpkin_config::world_gen::FeaturesConfig;
+    use pumpkin_core::math::vector2::Vector2;
+
+    use crate::{
+        biome::Biome,
+        chunk::{ChunkBlocks, ChunkData},
+        coordinates::ChunkRelativeBlockCoordinates,
+        world_gen::{
+            state_id,
+            test::{count_blocks, generate_plains},
+            GenerationSettings, Seed,
+        },
+    };
+
+    use super::{FeatureSettings, PlacedFeature};
+
+    fn generate(seed: Seed, features: FeatureSettings) -> ChunkData {
+        generate_plains(
+            seed,
+            GenerationSettings {
+                // Keep the surface dry, so plants can be placed
+                sea_level: 0,
+                features,
+                ..Default::default()
+            },
+            Vector2::new(3, -2),
+        )
+    }
+
+    fn count(chunk: &ChunkData, block: &str) -> usize {
+        count_blocks(&chunk.blocks, block)
+    }
+
+    #[test]
+    fn test_disable_feature() {
+        let seed = Seed(1_234_567);
+        let enabled = generate(seed, FeatureSettings::default());
+        assert!(count(&enabled, "minecraft:coal_ore") > 0);
+        assert!(count(&enabled, "minecraft:short_grass") > 0);
+
+        let disabled = generate(
+            seed,
+            FeatureSettings {
+                ore_coal: false,
+                patch_grass: false,
+                ..Default::default()
+            },
+        );
+        assert_eq!(count(&disabled, "minecraft:coal_ore"), 0);
+        assert_eq!(count(&disabled, "minecraft:short_grass"), 0);
+        // Features placed before are left untouched
+        assert_eq!(
+            count(&disabled, "minecraft:gravel"),
+            count(&enabled, "minecraft:gravel")
+        );
+    }
+
+    #[test]
+    fn test_features_deterministic() {
+        let first = generate(Seed(42), FeatureSettings::default());
+        let second = generate(Seed(42), FeatureSettings::default());
+        assert!(first
+            .blocks
+            .iter_subchunks()
+            .eq(second.blocks.iter_subchunks()));
+
+        let other = generate(Seed(43), FeatureSettings::default());
+        assert!(!first
+            .blocks
+            .iter_subchunks()
+            .eq(other.blocks.iter_subchunks()));
+    }
+
+    /// A chunk with one layer of grass blocks at y 0
+    fn grass_ground() -> ChunkBlocks {
+        let grass_block = state_id("minecraft:grass_block");
+        let mut ground = ChunkBlocks::default();
+        for x in 0..16u8 {
+            for z in 0..16u8 {
+                ground.set_block(
+                    ChunkRelativeBlockCoordinates {
+                        x: x.into(),
+                        y: 0i16.into(),
+                        z: z.into(),
+                    },
+                    grass_block,
+                );
+            }
+        }
+        ground
+    }
+
+    #[test]
+    fn test_biome_filter() {
+        let features = PlacedFeature::overworld(Seed(42));
+        let patch_grass = features
+            .iter()
+            .find(|feature| feature.name() == "patch_grass")
+            .unwrap();
+
+        let mut plains = grass_ground();
+        patch_grass.place(&mut plains, Vector2::new(0, 0), |_, _| Biome::Plains);
+        assert!(count_blocks(&plains, "minecraft:short_grass") > 0);
+
+        let mut desert = grass_ground();
+        patch_grass.place(&mut desert, Vector2::new(0, 0), |_, _| Biome::Desert);
+        assert_eq!(count_blocks(&desert, "minecraft:short_grass"), 0);
+    }
+
+    #[test]
+    fn test_from_config() {
+        let settings = FeatureSettings::from(&FeaturesConfig {
+            ore_iron: false,
+            flowers: false,
+            ..Default::default()
+        });
+        assert!(settings.is_enabled("ore_coal"));
+        assert!(!settings.is_enabled("ore_iron"));
+        assert!(!settings.is_enabled("flowers"));
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use noise::Perlin;
 use pumpkin_core::math::vector2::Vector2;
 
@@ -15,6 +17,31 @@ pub trait WorldGenerator: Sync + Send {
     fn generate_chunk(&self, at: Vector2<i32>) -> ChunkData;
 }
 
+/// A thread pool with `threads` threads (at least one) for [`generate_chunk_async`]
+pub fn generation_pool(threads: usize) -> rayon::ThreadPool {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads.max(1))
+        .thread_name(|index| format!("chunk-gen-{index}"))
+        // The panic hook already logged it, the task awaiting the chunk fails instead of aborting
+        .panic_handler(|_| {})
+        .build()
+        .expect("Failed to build the chunk generation thread pool")
+}
+
+/// Runs the CPU heavy generation on the generation thread pool,
+/// so async tasks can await a chunk without stalling the other tasks (e.g. networking)
+pub async fn generate_chunk_async(
+    generator: Arc<dyn WorldGenerator>,
+    pool: Arc<rayon::ThreadPool>,
+    at: Vector2<i32>,
+) -> ChunkData {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    pool.spawn(move || {
+        let _ = sender.send(generator.generate_chunk(at));
+    });
+    receiver.await.expect("Chunk generation panicked")
+}
+
 pub(crate) trait BiomeGenerator: Sync + Send {
     fn generate_biome(&self, at: XZBlockCoordinates) -> Biome;
 }
@@ -44,3 +71,59 @@ pub(crate) trait PerlinTerrainGenerator: Sync + Send {
         biome: Biome,
     );
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::{mpsc, Arc};
+
+    use parking_lot::Mutex;
+    use pumpkin_core::math::vector2::Vector2;
+
+    use crate::chunk::{ChunkBlocks, ChunkData};
+
+    use super::{generate_chunk_async, generation_pool, WorldGenerator};
+
+    /// Only finishes a chunk once the test allows it
+    struct WaitingGenerator {
+        receiver: Mutex<mpsc::Receiver<()>>,
+    }
+
+    impl WorldGenerator for WaitingGenerator {
+        fn generate_chunk(&self, at: Vector2<i32>) -> ChunkData {
+            self.receiver.lock().recv().unwrap();
+            ChunkData {
+                blocks: ChunkBlocks::default(),
+                position: at,
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_generate_chunk_async() {
+        let (sender, receiver) = mpsc::channel();
+        let generator = Arc::new(WaitingGenerator {
+            receiver: Mutex::new(receiver),
+        });
+
+        let at = Vector2::new(4, -7);
+        let pool = Arc::new(generation_pool(1));
+        let generation = tokio::spawn(generate_chunk_async(generator, pool, at));
+        // Lets the generation start. With a single runtime thread this would never return
+        // if the generation blocked the runtime
+        tokio::task::yield_now().await;
+        sender.send(()).unwrap();
+
+        let chunk = generation.await.unwrap();
+        assert_eq!(chunk.position, at);
+    }
+
+    #[test]
+    fn test_generation_pool() {
+        let pool = generation_pool(3);
+        assert_eq!(pool.current_num_threads(), 3);
+        let name = pool.install(|| std::thread::current().name().map(str::to_string));
+        assert!(name.unwrap().starts_with("chunk-gen-"));
+
+        assert_eq!(generation_pool(0).current_num_threads(), 1);
+    }
+}
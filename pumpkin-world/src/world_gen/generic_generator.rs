@@ -2,14 +2,19 @@ use noise::{NoiseFn, Perlin};
 use pumpkin_core::math::vector2::Vector2;
 
 use crate::{
+    biome::Biome,
+    block::BlockState,
     chunk::{ChunkBlocks, ChunkData},
     coordinates::{ChunkRelativeBlockCoordinates, ChunkRelativeXZBlockCoordinates},
-    WORLD_LOWEST_Y,
+    WORLD_LOWEST_Y, WORLD_MAX_Y,
 };
 
 use super::{
+    bedrock::BedrockFloor,
+    feature::PlacedFeature,
     generator::{BiomeGenerator, GeneratorInit, PerlinTerrainGenerator, WorldGenerator},
-    Seed,
+    surface::SurfaceSystem,
+    GenerationSettings, Seed,
 };
 
 pub struct GenericGenerator<B: BiomeGenerator, T: PerlinTerrainGenerator> {
@@ -18,6 +23,17 @@ pub struct GenericGenerator<B: BiomeGenerator, T: PerlinTerrainGenerator> {
     // TODO: May make this optional?. But would be pain to use in most biomes then. Maybe make a new trait like
     // PerlinTerrainGenerator
     perlin: Perlin,
+    bedrock_floor: BedrockFloor,
+    surface: SurfaceSystem,
+    features: Vec<PlacedFeature>,
+    settings: GenerationSettings,
+}
+
+impl<B: BiomeGenerator, T: PerlinTerrainGenerator> GenericGenerator<B, T> {
+    pub fn with_settings(mut self, settings: GenerationSettings) -> Self {
+        self.settings = settings;
+        self
+    }
 }
 
 impl<B: BiomeGenerator + GeneratorInit, T: PerlinTerrainGenerator + GeneratorInit> GeneratorInit
@@ -28,6 +44,10 @@ impl<B: BiomeGenerator + GeneratorInit, T: PerlinTerrainGenerator + GeneratorIni
             biome_generator: B::new(seed),
             terrain_generator: T::new(seed),
             perlin: Perlin::new(seed.0 as u32),
+            bedrock_floor: BedrockFloor::new(seed),
+            surface: SurfaceSystem::overworld(),
+            features: PlacedFeature::overworld(seed),
+            settings: GenerationSettings::default(),
         }
     }
 }
@@ -41,6 +61,13 @@ impl<B: BiomeGenerator, T: PerlinTerrainGenerator> WorldGenerator for GenericGen
         let base_height = 64.0;
         let chunk_height =
             noise_value.mul_add(self.terrain_generator.height_variation(), base_height) as i16;
+        let water = BlockState::new("minecraft:water").unwrap().state_id;
+        let sea_level = self
+            .settings
+            .sea_level
+            .clamp(WORLD_LOWEST_Y.into(), (WORLD_MAX_Y - 1).into()) as i16;
+        // Kept for the biome filters of the features
+        let mut biomes = [[Biome::Plains; 16]; 16];
 
         for x in 0..16u8 {
             for z in 0..16u8 {
@@ -51,6 +78,7 @@ impl<B: BiomeGenerator, T: PerlinTerrainGenerator> WorldGenerator for GenericGen
                     }
                     .with_chunk_coordinates(at),
                 );
+                biomes[usize::from(x)][usize::from(z)] = biome;
 
                 // Iterate from the highest block to the lowest, in order to minimize the heightmap updates
                 for y in (WORLD_LOWEST_Y..chunk_height).rev() {
@@ -69,6 +97,36 @@ impl<B: BiomeGenerator, T: PerlinTerrainGenerator> WorldGenerator for GenericGen
                         biome,
                     );
                 }
+                self.surface
+                    .apply_column(&mut blocks, x, z, biome, chunk_height);
+
+                // Fill everything left empty below the sea level
+                for y in WORLD_LOWEST_Y..=sea_level {
+                    let coordinates = ChunkRelativeBlockCoordinates {
+                        x: x.into(),
+                        y: y.into(),
+                        z: z.into(),
+                    };
+                    if blocks.get_block(coordinates) == BlockState::AIR.state_id {
+                        blocks.set_block(coordinates, water);
+                    }
+                }
+
+                self.bedrock_floor.generate_column(
+                    &mut blocks,
+                    x,
+                    z,
+                    at.x * 16 + i32::from(x),
+                    at.z * 16 + i32::from(z),
+                    self.settings.bedrock_roughness,
+                );
+            }
+        }
+
+        // Decorations go on top of the finished terrain
+        for feature in &self.features {
+            if self.settings.features.is_enabled(feature.name()) {
+                feature.place(&mut blocks, at, |x, z| biomes[x as usize][z as usize]);
             }
         }
 
@@ -117,3 +175,38 @@ fn generate_chunk(&mut self, at: Vector2<i32>) -> ChunkData {
     }
 }
 */
+
+#[cfg(test)]
+mod test {
+    use pumpkin_core::math::vector2::Vector2;
+
+    use crate::{
+        block::BlockState,
+        world_gen::{
+            state_id,
+            test::{block_at, generate_plains},
+            GenerationSettings, Seed,
+        },
+    };
+
+    #[test]
+    fn test_sea_level() {
+        // The plains terrain is never higher than 68
+        let chunk = generate_plains(
+            Seed(0),
+            GenerationSettings {
+                sea_level: 100,
+                ..Default::default()
+            },
+            Vector2::new(0, 0),
+        );
+        let water = state_id("minecraft:water");
+        let column = |y: i16| block_at(&chunk.blocks, 0, y, 0);
+
+        assert_eq!(column(100), water);
+        assert_eq!(column(80), water);
+        assert_eq!(column(101), BlockState::AIR.state_id);
+        assert_ne!(column(-64), water);
+        assert_ne!(column(0), water);
+    }
+}
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use pumpkin_core::math::vector2::Vector2;
+
+use crate::{
+    block::BlockState,
+    chunk::{ChunkBlocks, ChunkData},
+    coordinates::ChunkRelativeBlockCoordinates,
+    world_gen::{
+        generator::{GeneratorInit, WorldGenerator},
+        noise::density::{
+            end::EndIslandFunction,
+            profiler::{ProfileVisitor, DENSITY_PROFILER},
+            DensityFunction, DensityFunctionImpl, NoisePos, Visitor,
+        },
+        Seed,
+    },
+};
+
+/// The lowest block of the end, like vanilla's `minecraft:the_end` dimension type
+pub const END_BOTTOM_Y: i16 = 0;
+/// Nothing generates at or above this height
+pub const END_TOP_Y: i16 = 256;
+
+/// The islands are thickest at this height
+const ISLAND_CENTER_Y: i16 = 56;
+/// The blocks above the center over which the density falls off by 1, the tops are flat
+const FALLOFF_ABOVE: f64 = 16.0;
+/// The blocks below the center over which the density falls off by 1, the bottoms are deep
+const FALLOFF_BELOW: f64 = 64.0;
+
+/// A simplified end: the main island at the origin, void around it and the outer islands beyond 1024 blocks.
+///
+/// The outlines come from vanilla's `minecraft:end_islands` density (a radial falloff combined
+/// with simplex noise), the height is shaped with a fixed falloff instead of vanilla's 3D noise
+pub struct EndGenerator {
+    islands: Arc<DensityFunction<'static>>,
+    nan_guard: bool,
+}
+
+impl GeneratorInit for EndGenerator {
+    fn new(seed: Seed) -> Self {
+        Self {
+            islands: Arc::new(DensityFunction::EndIsland(EndIslandFunction::new(
+                seed.0 as u64,
+            ))),
+            nan_guard: false,
+        }
+    }
+}
+
+impl EndGenerator {
+    /// See `GenerationSettings::nan_guard`
+    pub fn with_nan_guard(mut self, nan_guard: bool) -> Self {
+        self.nan_guard = nan_guard;
+        self
+    }
+
+    /// See `GenerationSettings::profile`
+    pub fn with_profile(mut self, profile: bool) -> Self {
+        if profile {
+            self.islands = self.islands.apply(&Visitor::Profile(ProfileVisitor::new(
+                DENSITY_PROFILER.clone(),
+            )));
+        }
+        self
+    }
+
+    /// The island density of the column, positive within an island's outline
+    fn island_density(&self, x: i32, z: i32) -> f64 {
+        self.islands
+            .sample_root(&NoisePos::new(x, 0, z), self.nan_guard)
+    }
+
+    /// Positive values are solid, `island_density` is the density of the column
+    fn density(island_density: f64, y: i16) -> f64 {
+        let above = f64::from((y - ISLAND_CENTER_Y).max(0));
+        let below = f64::from((ISLAND_CENTER_Y - y).max(0));
+        island_density - above / FALLOFF_ABOVE - below / FALLOFF_BELOW
+    }
+}
+
+impl WorldGenerator for EndGenerator {
+    fn generate_chunk(&self, at: Vector2<i32>) -> ChunkData {
+        let end_stone = BlockState::new("minecraft:end_stone").unwrap().state_id;
+
+        let mut blocks = ChunkBlocks::default();
+        for x in 0..16u8 {
+            for z in 0..16u8 {
+                let island_density =
+                    self.island_density(at.x * 16 + i32::from(x), at.z * 16 + i32::from(z));
+                if island_density <= 0.0 {
+                    continue;
+                }
+                for y in END_BOTTOM_Y..END_TOP_Y {
+                    if Self::density(island_density, y) <= 0.0 {
+                        continue;
+                    }
+                    blocks.set_block(
+                        ChunkRelativeBlockCoordinates {
+                            x: x.into(),
+                            y: y.into(),
+                            z: z.into(),
+                        },
+                        end_stone,
+                    );
+                }
+            }
+        }
+
+        ChunkData {
+            blocks,
+            position: at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pumpkin_core::math::vector2::Vector2;
+
+    use crate::{
+        block::BlockState,
+        coordinates::ChunkRelativeBlockCoordinates,
+        world_gen::{
+            generator::{GeneratorInit, WorldGenerator},
+            Seed,
+        },
+    };
+
+    use super::{EndGenerator, END_BOTTOM_Y, END_TOP_Y, ISLAND_CENTER_Y};
+
+    fn column(generator: &EndGenerator, x: i32, z: i32) -> Vec<u16> {
+        let chunk = generator.generate_chunk(Vector2::new(x.div_euclid(16), z.div_euclid(16)));
+        (END_BOTTOM_Y..END_TOP_Y)
+            .map(|y| {
+                chunk.blocks.get_block(ChunkRelativeBlockCoordinates {
+                    x: (x.rem_euclid(16) as u8).into(),
+                    y: y.into(),
+                    z: (z.rem_euclid(16) as u8).into(),
+                })
+            })
+            .collect()
+    }
+
+    /// The sampled columns within the outer islands
+    fn outer_islands(generator: &EndGenerator) -> Vec<(i32, i32)> {
+        (1200..2400)
+            .step_by(32)
+            .flat_map(|x| (0..1200).step_by(32).map(move |z| (x, z)))
+            .filter(|(x, z)| generator.island_density(*x, *z) > 0.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_end_generator() {
+        let generator = EndGenerator::new(Seed(0));
+        let end_stone = BlockState::new("minecraft:end_stone").unwrap().state_id;
+        let air = BlockState::AIR.state_id;
+
+        // The main island, with void above and below
+        let center = column(&generator, 0, 0);
+        assert_eq!(center[ISLAND_CENTER_Y as usize], end_stone);
+        assert_eq!(center[END_BOTTOM_Y as usize], air);
+        assert_eq!(center[(END_TOP_Y - 1) as usize], air);
+        let top = center
+            .iter()
+            .rposition(|block| *block == end_stone)
+            .unwrap() as i16;
+        assert!((60..70).contains(&top), "{top}");
+
+        // Only void between the main and the outer islands
+        for (x, z) in [(500, 0), (0, -700), (-400, 400)] {
+            assert!(column(&generator, x, z).iter().all(|block| *block == air));
+        }
+
+        let outer = outer_islands(&generator);
+        assert!(!outer.is_empty());
+        let (x, z) = outer[0];
+        assert!(column(&generator, x, z).contains(&end_stone));
+
+        // The seed only moves the outer islands
+        let other = EndGenerator::new(Seed(1));
+        assert_eq!(column(&other, 0, 0), center);
+        assert_ne!(outer_islands(&other), outer);
+
+        // The island density is always finite, so the guard changes nothing
+        let guarded = EndGenerator::new(Seed(0)).with_nan_guard(true);
+        assert_eq!(column(&guarded, 0, 0), center);
+        assert_eq!(outer_islands(&guarded), outer);
+    }
+}
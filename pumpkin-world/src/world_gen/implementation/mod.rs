@@ -1,2 +1,4 @@
+pub mod end;
+pub mod nether;
 pub mod overworld;
 pub mod superflat;
@@ -0,0 +1,202 @@
+use pumpkin_core::{
+    math::vector2::Vector2,
+    random::{xoroshiro128::XoroshiroSplitter, RandomDeriverImpl, RandomGenerator, RandomImpl},
+};
+
+use crate::{
+    block::BlockState,
+    chunk::{ChunkBlocks, ChunkData},
+    coordinates::ChunkRelativeBlockCoordinates,
+    world_gen::{
+        generator::{GeneratorInit, WorldGenerator},
+        noise::perlin::OctavePerlinNoiseSampler,
+        Seed,
+    },
+};
+
+/// The lowest block of the nether, like vanilla's `minecraft:the_nether` dimension type
+pub const NETHER_BOTTOM_Y: i16 = 0;
+/// The nether has a roof, nothing generates at or above this height
+pub const NETHER_TOP_Y: i16 = 128;
+/// Empty blocks up to (and including) this height are filled with lava
+pub const NETHER_LAVA_LEVEL: i16 = 31;
+
+/// The number of layers at the floor and below the roof which may contain bedrock
+const BEDROCK_LAYERS: i16 = 5;
+/// How far from the floor and the roof the terrain gets pushed towards solid blocks
+const FALLOFF: f64 = 24.0;
+/// The vertical coordinate is scaled up, so the caverns are flatter than they are wide
+const VERTICAL_SCALE: f64 = 2.0;
+/// Densities above this are netherrack, which leaves room for the caverns
+const SOLID_THRESHOLD: f64 = 0.15;
+
+/// A simplified nether: netherrack caverns between a bedrock floor and roof, with lava seas at the bottom.
+/// Uses a single 3D noise instead of vanilla's density functions, so the shapes differ from vanilla
+pub struct NetherGenerator {
+    terrain: OctavePerlinNoiseSampler,
+    bedrock_floor: XoroshiroSplitter,
+    bedrock_roof: XoroshiroSplitter,
+}
+
+impl GeneratorInit for NetherGenerator {
+    fn new(seed: Seed) -> Self {
+        let factory = seed.positional_factory();
+        let mut random = RandomGenerator::Xoroshiro(factory.split_string("pumpkin:nether_terrain"));
+        Self {
+            // Features from 64 down to 8 blocks
+            terrain: OctavePerlinNoiseSampler::new(&mut random, -6, &[1.0, 1.0, 1.0, 1.0]),
+            bedrock_floor: factory
+                .split_string("minecraft:bedrock_floor")
+                .next_splitter(),
+            bedrock_roof: factory
+                .split_string("minecraft:bedrock_roof")
+                .next_splitter(),
+        }
+    }
+}
+
+impl NetherGenerator {
+    /// Positive values are solid, the floor and the roof are always solid
+    fn density(&self, x: i32, y: i16, z: i32) -> f64 {
+        let noise = self
+            .terrain
+            .sample(f64::from(x), f64::from(y) * VERTICAL_SCALE, f64::from(z));
+        let above_floor = f64::from(y - NETHER_BOTTOM_Y);
+        let below_roof = f64::from(NETHER_TOP_Y - 1 - y);
+        let falloff =
+            (1.0 - above_floor / FALLOFF).max(0.0) + (1.0 - below_roof / FALLOFF).max(0.0);
+        noise + falloff * 2.0
+    }
+
+    /// Whether bedrock is placed, always at the floor and roof and with a decreasing chance towards the inside
+    fn is_bedrock(&self, x: i32, y: i16, z: i32) -> bool {
+        let (random, layer) = if y < NETHER_BOTTOM_Y + BEDROCK_LAYERS {
+            (&self.bedrock_floor, y - NETHER_BOTTOM_Y)
+        } else if y >= NETHER_TOP_Y - BEDROCK_LAYERS {
+            (&self.bedrock_roof, NETHER_TOP_Y - 1 - y)
+        } else {
+            return false;
+        };
+        let chance = 1.0 - f32::from(layer) / f32::from(BEDROCK_LAYERS);
+        layer == 0 || random.split_pos(x, y.into(), z).next_f32() < chance
+    }
+}
+
+impl WorldGenerator for NetherGenerator {
+    fn generate_chunk(&self, at: Vector2<i32>) -> ChunkData {
+        let bedrock = BlockState::new("minecraft:bedrock").unwrap().state_id;
+        let netherrack = BlockState::new("minecraft:netherrack").unwrap().state_id;
+        let lava = BlockState::new("minecraft:lava").unwrap().state_id;
+
+        let mut blocks = ChunkBlocks::default();
+        for x in 0..16u8 {
+            for z in 0..16u8 {
+                let block_x = at.x * 16 + i32::from(x);
+                let block_z = at.z * 16 + i32::from(z);
+                for y in NETHER_BOTTOM_Y..NETHER_TOP_Y {
+                    let block = if self.is_bedrock(block_x, y, block_z) {
+                        bedrock
+                    } else if self.density(block_x, y, block_z) > SOLID_THRESHOLD {
+                        netherrack
+                    } else if y <= NETHER_LAVA_LEVEL {
+                        lava
+                    } else {
+                        continue;
+                    };
+                    blocks.set_block(
+                        ChunkRelativeBlockCoordinates {
+                            x: x.into(),
+                            y: y.into(),
+                            z: z.into(),
+                        },
+                        block,
+                    );
+                }
+            }
+        }
+
+        ChunkData {
+            blocks,
+            position: at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pumpkin_core::math::vector2::Vector2;
+
+    use crate::{
+        block::BlockState,
+        world_gen::{
+            generator::{GeneratorInit, WorldGenerator},
+            state_id,
+            test::block_at,
+            Seed,
+        },
+    };
+
+    use super::{NetherGenerator, NETHER_BOTTOM_Y, NETHER_LAVA_LEVEL, NETHER_TOP_Y};
+
+    #[test]
+    fn test_nether_generator() {
+        let generator = NetherGenerator::new(Seed(0));
+        let mut counts = [0usize; 3];
+        for at in [
+            Vector2::new(0, 0),
+            Vector2::new(-7, 12),
+            Vector2::new(30, -3),
+        ] {
+            let chunk = generator.generate_chunk(at);
+            for x in 0..16 {
+                for z in 0..16 {
+                    assert_eq!(
+                        block_at(&chunk.blocks, x, NETHER_BOTTOM_Y, z),
+                        state_id("minecraft:bedrock")
+                    );
+                    assert_eq!(
+                        block_at(&chunk.blocks, x, NETHER_TOP_Y - 1, z),
+                        state_id("minecraft:bedrock")
+                    );
+                    assert_eq!(
+                        block_at(&chunk.blocks, x, NETHER_TOP_Y, z),
+                        BlockState::AIR.state_id
+                    );
+                    assert_eq!(
+                        block_at(&chunk.blocks, x, NETHER_BOTTOM_Y - 1, z),
+                        BlockState::AIR.state_id
+                    );
+
+                    for y in NETHER_BOTTOM_Y..NETHER_TOP_Y {
+                        let block = block_at(&chunk.blocks, x, y, z);
+                        if block == state_id("minecraft:netherrack") {
+                            counts[0] += 1;
+                        } else if block == state_id("minecraft:lava") {
+                            assert!(y <= NETHER_LAVA_LEVEL);
+                            counts[1] += 1;
+                        } else if block == BlockState::AIR.state_id {
+                            assert!(y > NETHER_LAVA_LEVEL);
+                            counts[2] += 1;
+                        }
+                    }
+                }
+            }
+        }
+        // Caverns with both terrain and lava seas
+        assert!(counts.iter().all(|count| *count > 0), "{counts:?}");
+
+        // The seed changes the terrain, but the same seed always gives the same chunks
+        let at = Vector2::new(3, 4);
+        let blocks = |seed| generator_blocks(&NetherGenerator::new(Seed(seed)), at);
+        assert_eq!(blocks(1), blocks(1));
+        assert_ne!(blocks(1), blocks(2));
+    }
+
+    fn generator_blocks(generator: &NetherGenerator, at: Vector2<i32>) -> Vec<u16> {
+        let chunk = generator.generate_chunk(at);
+        (0..16)
+            .flat_map(|x| (NETHER_BOTTOM_Y..NETHER_TOP_Y).map(move |y| (x, y)))
+            .map(|(x, y)| block_at(&chunk.blocks, x, y, 8))
+            .collect()
+    }
+}
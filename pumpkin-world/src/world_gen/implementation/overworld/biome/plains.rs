@@ -1,6 +1,5 @@
 use noise::Perlin;
 use pumpkin_core::math::vector2::Vector2;
-use rand::Rng;
 
 use crate::{
     biome::Biome,
@@ -41,7 +40,6 @@ impl GeneratorInit for PlainsTerrainGenerator {
 
 impl PerlinTerrainGenerator for PlainsTerrainGenerator {
     fn prepare_chunk(&self, _at: &Vector2<i32>, _perlin: &Perlin) {}
-    // TODO allow specifying which blocks should be at which height in the config.
     fn generate_block(
         &self,
         coordinates: ChunkRelativeBlockCoordinates,
@@ -50,77 +48,50 @@ impl PerlinTerrainGenerator for PlainsTerrainGenerator {
         chunk_height: i16,
         _: Biome,
     ) {
-        let begin_stone_height = chunk_height - 5;
-        let begin_dirt_height = chunk_height - 2;
-
-        let y = *at.y;
-        if y == -64 {
-            blocks.set_block(
-                coordinates,
-                BlockState::new("minecraft:bedrock").unwrap().state_id,
-            );
-        } else if y >= -63 && y <= begin_stone_height {
+        // Only the stone, the surface rules replace the top with grass and dirt afterwards.
+        // The bedrock floor gets placed by the generator afterwards too
+        if *at.y <= chunk_height - 2 {
             blocks.set_block(
                 coordinates,
                 BlockState::new("minecraft:stone").unwrap().state_id,
             );
-        } else if y >= begin_stone_height && y < begin_dirt_height {
-            blocks.set_block(
-                coordinates,
-                BlockState::new("minecraft:dirt").unwrap().state_id,
-            );
-        } else if y == chunk_height - 2 {
-            blocks.set_block(
-                coordinates,
-                BlockState::new("minecraft:grass_block").unwrap().state_id,
-            );
-        } else if y == chunk_height - 1 {
-            // TODO: generate flowers and grass
-            let grass: u8 = rand::thread_rng().gen_range(0..7);
-            if grass == 3 {
-                let flower: u8 = rand::thread_rng().gen_range(0..20);
-                if flower == 6 {
-                    match rand::thread_rng().gen_range(0..4) {
-                        0 => {
-                            blocks.set_block(
-                                coordinates,
-                                BlockState::new("minecraft:dandelion").unwrap().state_id,
-                            );
-                        }
-                        1 => {
-                            blocks.set_block(
-                                coordinates,
-                                BlockState::new("minecraft:oxeye_daisy").unwrap().state_id,
-                            );
-                        }
-                        2 => {
-                            blocks.set_block(
-                                coordinates,
-                                BlockState::new("minecraft:cornflower").unwrap().state_id,
-                            );
-                        }
-                        3 => {
-                            blocks.set_block(
-                                coordinates,
-                                BlockState::new("minecraft:poppy").unwrap().state_id,
-                            );
-                        }
-                        _ => {
-                            blocks.set_block(
-                                coordinates,
-                                BlockState::new("minecraft:azure_bluet").unwrap().state_id,
-                            );
-                        }
-                    }
-                } else {
-                    // TODO: Tall grass, Tall grass data called `half`, There is `upper` and `lower`
-                    blocks.set_block(
-                        coordinates,
-                        BlockState::new("minecraft:short_grass").unwrap().state_id,
-                    );
-                }
-            }
         }
+        // Grass and flowers get placed by the features afterwards
         //  BlockState::AIR
     }
 }
+
+#[cfg(test)]
+mod test {
+    use pumpkin_core::math::vector2::Vector2;
+
+    use crate::world_gen::{generator::GeneratorInit, Seed, WorldGenerator};
+
+    use super::PlainsGenerator;
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let seed = Seed(7_388_910_120_457_418_657);
+        let generator = PlainsGenerator::new(seed);
+
+        for at in [
+            Vector2::new(0, 0),
+            Vector2::new(-3, 17),
+            Vector2::new(42, -9),
+        ] {
+            let first = generator.generate_chunk(at);
+            let second = generator.generate_chunk(at);
+            assert!(first
+                .blocks
+                .iter_subchunks()
+                .eq(second.blocks.iter_subchunks()));
+
+            // A freshly created generator behaves like a restarted server
+            let restarted = PlainsGenerator::new(seed).generate_chunk(at);
+            assert!(first
+                .blocks
+                .iter_subchunks()
+                .eq(restarted.blocks.iter_subchunks()));
+        }
+    }
+}
@@ -1,51 +1,253 @@
+use std::{fmt, str::FromStr};
+
 use pumpkin_core::math::vector2::Vector2;
 
 use crate::{
-    biome::Biome,
     block::block_state::BlockState,
-    coordinates::{BlockCoordinates, XZBlockCoordinates},
+    chunk::{ChunkBlocks, ChunkData},
+    coordinates::ChunkRelativeBlockCoordinates,
     world_gen::{
-        generator::{BiomeGenerator, GeneratorInit, TerrainGenerator},
-        generic_generator::GenericGenerator,
+        generator::{GeneratorInit, WorldGenerator},
         Seed,
     },
+    WORLD_HEIGHT, WORLD_LOWEST_Y, WORLD_MAX_Y,
 };
 
-#[expect(dead_code)]
-pub type SuperflatGenerator = GenericGenerator<SuperflatBiomeGenerator, SuperflatTerrainGenerator>;
+/// The layers of vanilla's classic superflat preset
+pub const CLASSIC_FLAT_LAYERS: &str = "minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block";
 
-pub(crate) struct SuperflatBiomeGenerator {}
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct FlatLayer {
+    block: u16,
+    height: u32,
+}
 
-impl GeneratorInit for SuperflatBiomeGenerator {
-    fn new(_: Seed) -> Self {
-        Self {}
+/// The layers of a flat world from the bottom up, parsed from a layer spec like
+/// `minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block`.
+/// Block names without a namespace are in the `minecraft` namespace, layers above the top of the world are cut off
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FlatLayers {
+    layers: Vec<FlatLayer>,
+}
+
+impl Default for FlatLayers {
+    fn default() -> Self {
+        CLASSIC_FLAT_LAYERS.parse().unwrap()
     }
 }
 
-impl BiomeGenerator for SuperflatBiomeGenerator {
-    // TODO make generic over Biome and allow changing the Biome in the config.
-    fn generate_biome(&self, _: XZBlockCoordinates) -> Biome {
-        Biome::Plains
+#[derive(Debug, PartialEq, Eq)]
+pub enum FlatLayersError {
+    /// The count before the `*` is no positive number
+    InvalidHeight(String),
+    UnknownBlock(String),
+}
+
+impl fmt::Display for FlatLayersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeight(layer) => write!(f, "Invalid layer height in {:?}", layer),
+            Self::UnknownBlock(block) => write!(f, "Unknown block {:?}", block),
+        }
     }
 }
 
-pub(crate) struct SuperflatTerrainGenerator {}
+impl FromStr for FlatLayers {
+    type Err = FlatLayersError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut layers: Vec<FlatLayer> = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|layer| !layer.is_empty())
+            .map(|layer| {
+                let (height, block) = match layer.split_once('*') {
+                    Some((height, block)) => (
+                        height
+                            .trim()
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|height| *height > 0)
+                            .ok_or_else(|| FlatLayersError::InvalidHeight(layer.to_string()))?,
+                        block.trim(),
+                    ),
+                    None => (1, layer),
+                };
+                let name = if block.contains(':') {
+                    block.to_string()
+                } else {
+                    format!("minecraft:{}", block)
+                };
+                let block = BlockState::new(&name)
+                    .ok_or_else(|| FlatLayersError::UnknownBlock(block.to_string()))?;
+                Ok(FlatLayer {
+                    block: block.state_id,
+                    height,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        // Keeps the sum of the heights in `block_at` from overflowing
+        let mut total = 0;
+        for layer in &mut layers {
+            layer.height = layer.height.min(WORLD_HEIGHT as u32 - total);
+            total += layer.height;
+        }
+        layers.retain(|layer| layer.height > 0);
+        Ok(Self { layers })
+    }
+}
+
+impl FlatLayers {
+    /// The block at the given height above the bottom of the world, air above the top layer
+    fn block_at(&self, height: u32) -> u16 {
+        let mut bottom = 0;
+        for layer in &self.layers {
+            bottom += layer.height;
+            if height < bottom {
+                return layer.block;
+            }
+        }
+        BlockState::AIR.state_id
+    }
+}
+
+/// Generates the same layers in every chunk, without any noise, biomes or features
+pub struct FlatGenerator {
+    layers: FlatLayers,
+}
 
-impl GeneratorInit for SuperflatTerrainGenerator {
+impl FlatGenerator {
+    pub fn with_layers(mut self, layers: FlatLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+}
+
+impl GeneratorInit for FlatGenerator {
     fn new(_: Seed) -> Self {
-        Self {}
+        Self {
+            layers: FlatLayers::default(),
+        }
+    }
+}
+
+impl WorldGenerator for FlatGenerator {
+    fn generate_chunk(&self, at: Vector2<i32>) -> ChunkData {
+        let mut blocks = ChunkBlocks::default();
+        // Layers above the top of the world are cut off
+        for (y, height) in (WORLD_LOWEST_Y..WORLD_MAX_Y).zip(0..) {
+            let block = self.layers.block_at(height);
+            if block == BlockState::AIR.state_id {
+                continue;
+            }
+            for x in 0..16u8 {
+                for z in 0..16u8 {
+                    blocks.set_block(
+                        ChunkRelativeBlockCoordinates {
+                            x: x.into(),
+                            y: y.into(),
+                            z: z.into(),
+                        },
+                        block,
+                    );
+                }
+            }
+        }
+
+        ChunkData {
+            blocks,
+            position: at,
+        }
     }
 }
 
-impl TerrainGenerator for SuperflatTerrainGenerator {
-    fn prepare_chunk(&self, _at: &Vector2<i32>) {}
-    // TODO allow specifying which blocks should be at which height in the config.
-    fn generate_block(&self, at: BlockCoordinates, _: Biome) -> BlockState {
-        match *at.y {
-            -64 => BlockState::new("minecraft:bedrock").unwrap(),
-            -63..=-62 => BlockState::new("minecraft:dirt").unwrap(),
-            -61 => BlockState::new("minecraft:grass_block").unwrap(),
-            _ => BlockState::AIR,
+#[cfg(test)]
+mod test {
+    use pumpkin_core::math::vector2::Vector2;
+
+    use crate::{
+        block::BlockState,
+        world_gen::{
+            generator::{GeneratorInit, WorldGenerator},
+            state_id,
+            test::block_at,
+            Seed,
+        },
+    };
+
+    use super::{FlatGenerator, FlatLayers, FlatLayersError};
+
+    /// The block at the world coordinates, generating its chunk
+    fn generated_block(generator: &FlatGenerator, x: i32, y: i16, z: i32) -> u16 {
+        let chunk = generator.generate_chunk(Vector2::new(x.div_euclid(16), z.div_euclid(16)));
+        block_at(
+            &chunk.blocks,
+            x.rem_euclid(16) as u8,
+            y,
+            z.rem_euclid(16) as u8,
+        )
+    }
+
+    #[test]
+    fn test_parse_layers() {
+        assert_eq!(
+            "bedrock, 2*dirt,minecraft:grass_block".parse::<FlatLayers>(),
+            Ok(FlatLayers::default())
+        );
+        assert_eq!(
+            "bedrock,0*dirt".parse::<FlatLayers>(),
+            Err(FlatLayersError::InvalidHeight("0*dirt".to_string()))
+        );
+        assert_eq!(
+            "bedrock,x*dirt".parse::<FlatLayers>(),
+            Err(FlatLayersError::InvalidHeight("x*dirt".to_string()))
+        );
+        assert_eq!(
+            "bedrock,pumpkin_pie".parse::<FlatLayers>(),
+            Err(FlatLayersError::UnknownBlock("pumpkin_pie".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_flat_generator() {
+        let generator = FlatGenerator::new(Seed(0));
+        for (x, z) in [(0, 0), (-17, 40), (1000, -3)] {
+            assert_eq!(
+                generated_block(&generator, x, -64, z),
+                state_id("minecraft:bedrock")
+            );
+            assert_eq!(
+                generated_block(&generator, x, -63, z),
+                state_id("minecraft:dirt")
+            );
+            assert_eq!(
+                generated_block(&generator, x, -62, z),
+                state_id("minecraft:dirt")
+            );
+            assert_eq!(
+                generated_block(&generator, x, -61, z),
+                state_id("minecraft:grass_block")
+            );
+            assert_eq!(
+                generated_block(&generator, x, -60, z),
+                BlockState::AIR.state_id
+            );
         }
+
+        // Layers higher than the world are cut off
+        let generator =
+            FlatGenerator::new(Seed(0)).with_layers("bedrock,1000*stone".parse().unwrap());
+        assert_eq!(
+            generated_block(&generator, 3, 319, 3),
+            state_id("minecraft:stone")
+        );
+        let layers: FlatLayers = "4294967295*stone,4294967295*dirt".parse().unwrap();
+        assert_eq!(layers, "384*stone".parse().unwrap());
+        let generator = FlatGenerator::new(Seed(0)).with_layers(layers);
+        assert_eq!(
+            generated_block(&generator, 3, 319, 3),
+            state_id("minecraft:stone")
+        );
     }
 }
@@ -1,30 +1,140 @@
 #![allow(dead_code)]
 
+mod bedrock;
 mod blender;
+mod feature;
 mod generator;
 mod generic_generator;
 pub mod height_limit;
 mod implementation;
-mod noise;
+pub(crate) mod noise;
 mod positions;
 mod proto_chunk;
 mod sampler;
 mod seed;
+pub mod surface;
 
-pub use generator::WorldGenerator;
+pub use feature::FeatureSettings;
+pub use generator::{generate_chunk_async, generation_pool, WorldGenerator};
+use implementation::end::EndGenerator;
+use implementation::nether::NetherGenerator;
 use implementation::overworld::biome::plains::PlainsGenerator;
+use implementation::superflat::FlatGenerator;
+pub use implementation::superflat::{FlatLayers, FlatLayersError, CLASSIC_FLAT_LAYERS};
+#[doc(hidden)]
+pub use noise::density::bench as density_bench;
 pub use seed::Seed;
 
+use crate::{
+    biome::{Biome, ClimateOffsets},
+    block::BlockState,
+};
+
 use generator::GeneratorInit;
 
-pub fn get_world_gen(seed: Seed) -> Box<dyn WorldGenerator> {
-    // TODO decide which WorldGenerator to pick based on config.
-    Box::new(PlainsGenerator::new(seed))
+/// The vanilla sea level of the overworld
+pub const DEFAULT_SEA_LEVEL: i32 = 63;
+
+/// Options which change how chunks are generated
+#[derive(Clone)]
+pub struct GenerationSettings {
+    /// Empty blocks up to (and including) this height get filled with water
+    pub sea_level: i32,
+    /// Whether the bedrock layers above the bottom of the world get randomized like vanilla,
+    /// otherwise a single flat layer is placed
+    pub bedrock_roughness: bool,
+    /// Whether non finite densities get replaced with 0 and logged, instead of silently corrupting the terrain.
+    /// Only applies to `EndGenerator`, the other generators don't sample density functions yet, so they are not guarded
+    pub nan_guard: bool,
+    /// Whether the density functions get measured with `DENSITY_PROFILER`, see `density_profile_report`
+    pub profile: bool,
+    /// Which decorations get placed after the terrain
+    pub features: FeatureSettings,
+    /// The layers of `WorldGenType::Flat` worlds
+    pub flat_layers: FlatLayers,
+    /// Added to the climate before biomes get picked, passed to `NoiseRouter::multi_noise_sampler`.
+    /// Unused so far, the overworld still generates plains and the router's noises aren't seeded yet
+    pub climate_offsets: ClimateOffsets,
+}
+
+impl Default for GenerationSettings {
+    fn default() -> Self {
+        Self {
+            sea_level: DEFAULT_SEA_LEVEL,
+            bedrock_roughness: true,
+            nan_guard: cfg!(debug_assertions),
+            profile: false,
+            features: FeatureSettings::default(),
+            flat_layers: FlatLayers::default(),
+            climate_offsets: ClimateOffsets::default(),
+        }
+    }
+}
+
+/// The state id of a block which is known to exist, e.g. `minecraft:stone`.
+/// The lookup searches the whole registry, so generators do it once and keep the id
+pub(crate) fn state_id(name: &str) -> u16 {
+    BlockState::new(name).unwrap().state_id
+}
+
+/// The time spent in density functions which were built with the profiler, see `GenerationSettings::profile`
+pub fn density_profile_report() -> String {
+    noise::density::profiler::DENSITY_PROFILER.report()
+}
+
+/// The kind of world to generate
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum WorldGenType {
+    #[default]
+    Default,
+    Flat,
+    Amplified,
+    /// The default terrain, but with only a single biome
+    SingleBiome(Biome),
+    /// Netherrack caverns and lava seas between a bedrock floor and roof
+    Nether,
+    /// The main end island surrounded by void, and the outer islands beyond 1024 blocks
+    End,
+}
+
+/// Creates the generator for the world type, unimplemented world types fall back to plains
+pub fn get_world_gen(
+    seed: Seed,
+    world_type: WorldGenType,
+    settings: GenerationSettings,
+) -> Box<dyn WorldGenerator> {
+    match world_type {
+        WorldGenType::Default | WorldGenType::SingleBiome(Biome::Plains) => {}
+        WorldGenType::Flat => {
+            return Box::new(FlatGenerator::new(seed).with_layers(settings.flat_layers));
+        }
+        WorldGenType::Nether => return Box::new(NetherGenerator::new(seed)),
+        WorldGenType::End => {
+            return Box::new(
+                EndGenerator::new(seed)
+                    .with_nan_guard(settings.nan_guard)
+                    .with_profile(settings.profile),
+            );
+        }
+        WorldGenType::Amplified | WorldGenType::SingleBiome(_) => {
+            log::warn!(
+                "World type {:?} is not implemented yet, generating plains instead",
+                world_type
+            );
+        }
+    }
+    Box::new(PlainsGenerator::new(seed).with_settings(settings))
 }
 
+/// Conversions between block, biome, chunk and section coordinates.
+///
+/// Biomes are stored in cells of 4x4x4 blocks, so a chunk is 16 blocks or 4 biome cells wide and a section
+/// is 16 blocks or 4 biome cells high. Converting to a coarser grid rounds towards negative infinity,
+/// converting to a finer grid gives the first (lowest) coordinate of that cell
 pub mod biome_coords {
     use num_traits::PrimInt;
 
+    /// The biome cell containing the block
     #[inline]
     pub fn from_block<T>(coord: T) -> T
     where
@@ -33,6 +143,7 @@ pub mod biome_coords {
         coord >> 2
     }
 
+    /// The lowest block of the biome cell
     #[inline]
     pub fn to_block<T>(coord: T) -> T
     where
@@ -41,6 +152,7 @@ pub mod biome_coords {
         coord << 2
     }
 
+    /// The lowest biome cell of the chunk, chunks have more biome cells than the other way round
     #[inline]
     pub fn from_chunk<T>(coord: T) -> T
     where
@@ -49,6 +161,7 @@ pub mod biome_coords {
         coord << 2
     }
 
+    /// The chunk containing the biome cell
     #[inline]
     pub fn to_chunk<T>(coord: T) -> T
     where
@@ -56,9 +169,28 @@ pub mod biome_coords {
     {
         coord >> 2
     }
+
+    /// The lowest biome cell of the section, which has the same height as a chunk's width
+    #[inline]
+    pub fn from_section<T>(coord: T) -> T
+    where
+        T: PrimInt,
+    {
+        from_chunk(coord)
+    }
+
+    /// The section containing the biome cell
+    #[inline]
+    pub fn to_section<T>(coord: T) -> T
+    where
+        T: PrimInt,
+    {
+        to_chunk(coord)
+    }
 }
 
-#[derive(PartialEq)]
+/// A horizontal direction, north is towards negative z and east towards positive x
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Direction {
     North,
     NorthEast,
@@ -69,3 +201,206 @@ pub enum Direction {
     West,
     NorthWest,
 }
+
+impl Direction {
+    /// Every direction, clockwise starting at north
+    pub const ALL: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    pub fn opposite(&self) -> Self {
+        self.rotate(4)
+    }
+
+    /// The x and z delta of one step into this direction
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Self::North => (0, -1),
+            Self::NorthEast => (1, -1),
+            Self::East => (1, 0),
+            Self::SouthEast => (1, 1),
+            Self::South => (0, 1),
+            Self::SouthWest => (-1, 1),
+            Self::West => (-1, 0),
+            Self::NorthWest => (-1, -1),
+        }
+    }
+
+    /// The next direction clockwise, 45 degrees apart
+    pub fn rotate_cw(&self) -> Self {
+        self.rotate(1)
+    }
+
+    /// The next direction counterclockwise, 45 degrees apart
+    pub fn rotate_ccw(&self) -> Self {
+        self.rotate(7)
+    }
+
+    /// Rotates clockwise by `steps` times 45 degrees
+    fn rotate(&self, steps: usize) -> Self {
+        Self::ALL[(*self as usize + steps) % Self::ALL.len()]
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use pumpkin_core::math::vector2::Vector2;
+
+    use crate::{
+        biome::Biome,
+        chunk::{ChunkBlocks, ChunkData},
+        coordinates::ChunkRelativeBlockCoordinates,
+    };
+
+    use super::{
+        biome_coords, generator::GeneratorInit, get_world_gen,
+        noise::density::profiler::DENSITY_PROFILER, state_id, Direction, GenerationSettings,
+        PlainsGenerator, Seed, WorldGenType, WorldGenerator,
+    };
+
+    /// The chunk at `at` of a plains world
+    pub(crate) fn generate_plains(
+        seed: Seed,
+        settings: GenerationSettings,
+        at: Vector2<i32>,
+    ) -> ChunkData {
+        PlainsGenerator::new(seed)
+            .with_settings(settings)
+            .generate_chunk(at)
+    }
+
+    /// The block at the chunk relative coordinates
+    pub(crate) fn block_at(blocks: &ChunkBlocks, x: u8, y: i16, z: u8) -> u16 {
+        blocks.get_block(ChunkRelativeBlockCoordinates {
+            x: x.into(),
+            y: y.into(),
+            z: z.into(),
+        })
+    }
+
+    /// How often the block appears in the chunk
+    pub(crate) fn count_blocks(blocks: &ChunkBlocks, block: &str) -> usize {
+        let block = state_id(block);
+        blocks
+            .iter_subchunks()
+            .flat_map(|subchunk| subchunk.iter())
+            .filter(|id| **id == block)
+            .count()
+    }
+
+    #[test]
+    fn test_biome_coords() {
+        for block in -1000i32..1000 {
+            let biome = biome_coords::from_block(block);
+            // The cell starts at or below the block and contains the 4 blocks from there
+            assert!(biome_coords::to_block(biome) <= block);
+            assert!(block - biome_coords::to_block(biome) < 4);
+            assert_eq!(biome, block.div_euclid(4));
+
+            // The biome cell is in the same chunk and section as the block
+            let chunk = block.div_euclid(16);
+            assert_eq!(biome_coords::to_chunk(biome), chunk);
+            assert_eq!(biome_coords::to_section(biome), chunk);
+            assert!(biome_coords::from_chunk(chunk) <= biome);
+            assert!(biome - biome_coords::from_chunk(chunk) < 4);
+        }
+
+        for coord in -100i32..100 {
+            assert_eq!(
+                biome_coords::from_block(biome_coords::to_block(coord)),
+                coord
+            );
+            assert_eq!(
+                biome_coords::to_chunk(biome_coords::from_chunk(coord)),
+                coord
+            );
+            assert_eq!(
+                biome_coords::to_section(biome_coords::from_section(coord)),
+                coord
+            );
+            // A chunk is 16 blocks and 4 biome cells wide
+            assert_eq!(
+                biome_coords::to_block(biome_coords::from_chunk(coord)),
+                coord * 16
+            );
+        }
+
+        assert_eq!(biome_coords::from_block(-1i64), -1);
+        assert_eq!(biome_coords::from_block(-64i16), -16);
+        assert_eq!(biome_coords::from_section(-4i8), -16);
+    }
+
+    #[test]
+    fn test_direction() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.opposite().opposite(), direction);
+            assert_ne!(direction.opposite(), direction);
+            assert_eq!(direction.rotate_cw().rotate_ccw(), direction);
+            assert_eq!(direction.rotate_ccw().rotate_cw(), direction);
+
+            let (x, z) = direction.offset();
+            let (opposite_x, opposite_z) = direction.opposite().offset();
+            assert_eq!((x + opposite_x, z + opposite_z), (0, 0));
+            // Rotating clockwise by 90 degrees maps (x, z) to (-z, x)
+            assert_eq!(direction.rotate_cw().rotate_cw().offset(), (-z, x));
+        }
+        assert_eq!(Direction::North.offset(), (0, -1));
+        assert_eq!(Direction::East.offset(), (1, 0));
+        assert_eq!(Direction::North.rotate_cw(), Direction::NorthEast);
+        assert_eq!(Direction::North.rotate_ccw(), Direction::NorthWest);
+        assert_eq!(Direction::NorthWest.rotate_cw(), Direction::North);
+        assert_eq!(Direction::SouthWest.opposite(), Direction::NorthEast);
+    }
+
+    #[test]
+    fn test_world_gen_fallback() {
+        let seed = Seed(5);
+        let at = Vector2::new(1, 2);
+        let plains = get_world_gen(seed, WorldGenType::Default, GenerationSettings::default())
+            .generate_chunk(at);
+
+        // Not implemented yet, so these must not panic but generate plains
+        for world_type in [
+            WorldGenType::Amplified,
+            WorldGenType::SingleBiome(Biome::SnowyTiga),
+            WorldGenType::SingleBiome(Biome::Plains),
+        ] {
+            let chunk =
+                get_world_gen(seed, world_type, GenerationSettings::default()).generate_chunk(at);
+            assert!(chunk
+                .blocks
+                .iter_subchunks()
+                .eq(plains.blocks.iter_subchunks()));
+        }
+    }
+
+    #[test]
+    fn test_world_gen_profile() {
+        let at = Vector2::new(0, 0);
+        // The only test using the global profiler
+        DENSITY_PROFILER.reset();
+        let unprofiled = get_world_gen(Seed(0), WorldGenType::End, GenerationSettings::default())
+            .generate_chunk(at);
+        assert!(DENSITY_PROFILER.stats().is_empty());
+
+        let settings = GenerationSettings {
+            profile: true,
+            ..Default::default()
+        };
+        let profiled = get_world_gen(Seed(0), WorldGenType::End, settings).generate_chunk(at);
+        // Measuring doesn't change the terrain
+        assert!(profiled
+            .blocks
+            .iter_subchunks()
+            .eq(unprofiled.blocks.iter_subchunks()));
+        assert_eq!(DENSITY_PROFILER.stats()["EndIsland"].calls, 16 * 16);
+        assert!(super::density_profile_report().contains("EndIsland: 256 calls"));
+    }
+}
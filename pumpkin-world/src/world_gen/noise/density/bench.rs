@@ -0,0 +1,158 @@
+//! Density functions for the benchmarks in `benches/density.rs`, not meant to be used otherwise
+
+use std::sync::Arc;
+
+use crate::world_gen::noise::BuiltInNoiseParams;
+
+use super::{
+    math::{add_assign, add_assign_scalar, BinaryFunction, BinaryType, ScratchBuffers},
+    Applier, BuiltInNoiseFunctions, CacheVisitor, CellApplier, DensityFunction,
+    DensityFunctionImpl, InterpolationVisitor, MarkerFunction, MarkerType, Visitor,
+};
+
+/// The built in functions, leaked so the benchmarks can keep them until the process exits
+fn noise_functions() -> &'static BuiltInNoiseFunctions<'static> {
+    let noise_params: &'static BuiltInNoiseParams<'static> =
+        Box::leak(Box::new(BuiltInNoiseParams::new()));
+    Box::leak(Box::new(BuiltInNoiseFunctions::new(noise_params)))
+}
+
+/// The 4x8 block cells of a chunk, in the order the chunk is filled
+pub(crate) fn chunk_cells() -> Vec<CellApplier> {
+    let scratch = Arc::new(ScratchBuffers::default());
+    (0..4)
+        .flat_map(|x| (0..4).flat_map(move |z| (0..48).map(move |y| (x, y, z))))
+        .map(|(x, y, z)| {
+            CellApplier::new(x * 4, y * 8 - 64, z * 4, 4, 8).with_scratch(scratch.clone())
+        })
+        .collect()
+}
+
+fn with_marker(
+    function: Arc<DensityFunction<'static>>,
+    kind: MarkerType,
+    visitor: &Visitor<'static>,
+) -> Arc<DensityFunction<'static>> {
+    Arc::new(DensityFunction::Marker(MarkerFunction::new(function, kind))).apply(visitor)
+}
+
+/// Fills every cell of a chunk with a density function
+pub struct ChunkFill {
+    function: Arc<DensityFunction<'static>>,
+    cells: Vec<CellApplier>,
+    densities: Vec<f64>,
+}
+
+impl ChunkFill {
+    fn new(function: Arc<DensityFunction<'static>>) -> Self {
+        let cells = chunk_cells();
+        Self {
+            function,
+            densities: vec![0f64; cells[0].size()],
+            cells,
+        }
+    }
+
+    /// Fills the chunk once, the sum of the densities keeps the work from being optimized away
+    pub fn run(&mut self) -> f64 {
+        let mut sum = 0f64;
+        for cell in &self.cells {
+            self.function
+                .fill(&mut self.densities, &Applier::Cell(cell.clone()));
+            sum += self.densities.iter().sum::<f64>();
+        }
+        sum
+    }
+}
+
+/// The overworld continents, which only vary horizontally. With `cached` they get a `cache_2d` marker
+pub fn continents(cached: bool) -> ChunkFill {
+    let function = noise_functions().continents_overworld().clone();
+    ChunkFill::new(if cached {
+        with_marker(
+            function,
+            MarkerType::Cache2D,
+            &Visitor::Cache(CacheVisitor {}),
+        )
+    } else {
+        function
+    })
+}
+
+/// Reads every value of the base 3D noise three times, like interpolation does.
+/// With `cached` the reads share a `cache_all_in_cell` marker
+pub fn base_3d_noise_reused(cached: bool) -> ChunkFill {
+    let mut function = noise_functions().base_3d_noise_overworld().clone();
+    if cached {
+        // Clones don't share the cache, the arguments must use the same `Arc`
+        function = with_marker(
+            function,
+            MarkerType::CacheCell,
+            &Visitor::Cache(CacheVisitor {}),
+        );
+    }
+    ChunkFill::new(Arc::new(BinaryFunction::create(
+        BinaryType::Mul,
+        Arc::new(BinaryFunction::create(
+            BinaryType::Add,
+            function.clone(),
+            function.clone(),
+        )),
+        function,
+    )))
+}
+
+/// The base 3D noise of the overworld, with `interpolated` it's only sampled at the corners of the cells
+pub fn base_3d_noise(interpolated: bool) -> ChunkFill {
+    let function = noise_functions().base_3d_noise_overworld().clone();
+    ChunkFill::new(if interpolated {
+        with_marker(
+            function,
+            MarkerType::Interpolated,
+            &Visitor::Interpolate(InterpolationVisitor::new(4, 8)),
+        )
+    } else {
+        function
+    })
+}
+
+/// The base 3D noise of the overworld plus `y`, which fills the second argument into a scratch buffer
+pub fn base_3d_noise_plus_y() -> ChunkFill {
+    let noise_functions = noise_functions();
+    ChunkFill::new(Arc::new(BinaryFunction::create(
+        BinaryType::Add,
+        noise_functions.base_3d_noise_overworld().clone(),
+        noise_functions.y().clone(),
+    )))
+}
+
+/// The number of densities in a chunk, 16x16x384
+pub const CHUNK_VALUES: usize = 16 * 16 * 384;
+
+/// Adds two chunk sized arrays, like `Add` fills do with their scratch buffer
+pub struct AddArrays {
+    densities: Vec<f64>,
+    values: Vec<f64>,
+    vectorized: bool,
+}
+
+impl AddArrays {
+    /// Adds the arrays once, returns a density so the work isn't optimized away
+    pub fn run(&mut self) -> f64 {
+        if self.vectorized {
+            add_assign(&mut self.densities, &self.values);
+        } else {
+            add_assign_scalar(&mut self.densities, &self.values);
+        }
+        self.densities[CHUNK_VALUES - 1]
+    }
+}
+
+/// With `vectorized` the arrays are added with SIMD instructions, otherwise one value at a time
+pub fn add_arrays(vectorized: bool) -> AddArrays {
+    AddArrays {
+        densities: (0..CHUNK_VALUES).map(|i| i as f64).collect(),
+        values: (0..CHUNK_VALUES).map(|i| (i % 384) as f64 * 0.5).collect(),
+        vectorized,
+    }
+}
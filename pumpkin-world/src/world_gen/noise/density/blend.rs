@@ -28,6 +28,10 @@ impl<'a> DensityFunctionImpl<'a> for BlendOffsetFunction {
     fn apply(&self, visitor: &Visitor<'a>) -> Arc<DensityFunction<'a>> {
         visitor.apply(Arc::new(DensityFunction::BlendOffset(self.clone())))
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "type": "minecraft:blend_offset" })
+    }
 }
 
 #[derive(Clone)]
@@ -53,6 +57,10 @@ impl<'a> DensityFunctionImpl<'a> for BlendAlphaFunction {
     fn apply(&self, visitor: &Visitor<'a>) -> Arc<DensityFunction<'a>> {
         visitor.apply(Arc::new(DensityFunction::BlendAlpha(self.clone())))
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "type": "minecraft:blend_alpha" })
+    }
 }
 
 #[derive(Clone)]
@@ -98,4 +106,15 @@ impl<'a> DensityFunctionImpl<'a> for BlendDensityFunction<'a> {
     fn max(&self) -> f64 {
         f64::INFINITY
     }
+
+    fn estimated_cost(&self) -> u32 {
+        self.function.estimated_cost() + 1
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "minecraft:blend_density",
+            "argument": self.function.to_json(),
+        })
+    }
 }
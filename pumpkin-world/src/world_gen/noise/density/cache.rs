@@ -0,0 +1,640 @@
+use std::{ops::Deref, sync::Arc};
+
+use parking_lot::Mutex;
+
+use super::{
+    super::lerp3, math::ScratchBuffers, Applier, ApplierImpl, DensityFunction, DensityFunctionImpl,
+    MarkerFunction, MarkerType, NoisePos, NoisePosImpl, VisitorImpl,
+};
+
+/// The cache of a `MarkerFunction`, chosen by `CacheVisitor` and `InterpolationVisitor`.
+///
+/// Each cache only keeps the last column, position or cell, so a function shared between threads
+/// would have them evict each other. Every chunk is sampled with its own copy, see `NoiseRouter::for_chunk`
+pub enum MarkerCache {
+    /// Passes straight through to the input, e.g. `flat_cache` or a marker no visitor replaced yet
+    None,
+    /// The last sampled column, for functions which only vary horizontally (`cache_2d`)
+    Column(Mutex<Option<(i32, i32, f64)>>),
+    /// The last sampled position, for functions which are used multiple times per position (`cache_once`)
+    Position(Mutex<Option<(i32, i32, i32, f64)>>),
+    /// Every value of the last filled interpolation cell (`cache_all_in_cell`).
+    ///
+    /// Filling with a `CellApplier` samples the input once per position of the cell,
+    /// afterwards samples inside of that cell are served from the cache
+    Cell(Mutex<Option<(CellApplier, Vec<f64>)>>),
+    /// The corners of the last interpolation cell (`interpolated`)
+    Interpolated(Interpolator),
+}
+
+impl MarkerCache {
+    /// The same kind of cache without any cached values
+    pub fn empty_copy(&self) -> Self {
+        match self {
+            Self::None => Self::None,
+            Self::Column(_) => Self::Column(Mutex::new(None)),
+            Self::Position(_) => Self::Position(Mutex::new(None)),
+            Self::Cell(_) => Self::Cell(Mutex::new(None)),
+            Self::Interpolated(interpolator) => Self::Interpolated(Interpolator::new(
+                interpolator.horizontal_size,
+                interpolator.vertical_size,
+            )),
+        }
+    }
+
+    pub fn sample(&self, input: &DensityFunction, pos: &NoisePos) -> f64 {
+        match self {
+            Self::None => input.sample(pos),
+            Self::Column(last_column) => {
+                let x = pos.x();
+                let z = pos.z();
+
+                let mut last_column = last_column.lock();
+                if let Some((last_x, last_z, value)) = *last_column {
+                    if last_x == x && last_z == z {
+                        return value;
+                    }
+                }
+
+                let value = input.sample(pos);
+                *last_column = Some((x, z, value));
+                value
+            }
+            Self::Position(last_pos) => {
+                let x = pos.x();
+                let y = pos.y();
+                let z = pos.z();
+
+                let mut last_pos = last_pos.lock();
+                if let Some((last_x, last_y, last_z, value)) = *last_pos {
+                    if last_x == x && last_y == y && last_z == z {
+                        return value;
+                    }
+                }
+
+                let value = input.sample(pos);
+                *last_pos = Some((x, y, z, value));
+                value
+            }
+            Self::Cell(cached) => {
+                if let Some((cell, values)) = cached.lock().as_ref() {
+                    if let Some(index) = cell.index_of(pos) {
+                        return values[index];
+                    }
+                }
+                input.sample(pos)
+            }
+            Self::Interpolated(interpolator) => interpolator.sample(input, pos),
+        }
+    }
+
+    pub fn fill<'a>(
+        &self,
+        input: &DensityFunction<'a>,
+        densities: &mut [f64],
+        applier: &Applier<'a>,
+    ) {
+        match self {
+            // Filling visits every position once, so there is nothing to reuse
+            Self::None | Self::Position(_) => input.fill(densities, applier),
+            Self::Column(_) => self.sample_each(input, densities, applier),
+            Self::Cell(cached) => match applier {
+                Applier::Cell(cell) if densities.len() == cell.size() => {
+                    let mut cached = cached.lock();
+                    match cached.as_ref() {
+                        Some((cached_cell, values)) if cached_cell == cell => {
+                            densities.copy_from_slice(values);
+                        }
+                        _ => {
+                            input.fill(densities, applier);
+                            *cached = Some((cell.clone(), densities.to_vec()));
+                        }
+                    }
+                }
+                _ => self.sample_each(input, densities, applier),
+            },
+            Self::Interpolated(interpolator) => match applier {
+                // A whole cell only needs its corners once
+                Applier::Cell(cell) if interpolator.is_cell(cell) => {
+                    let start = [cell.start_x, cell.start_y, cell.start_z];
+                    let corners = interpolator.corners(input, start);
+                    densities.iter_mut().enumerate().for_each(|(i, val)| {
+                        *val = interpolator.interpolate(start, &corners, &cell.at(i));
+                    });
+                }
+                _ => self.sample_each(input, densities, applier),
+            },
+        }
+    }
+
+    fn sample_each<'a>(
+        &self,
+        input: &DensityFunction<'a>,
+        densities: &mut [f64],
+        applier: &Applier<'a>,
+    ) {
+        densities
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, val)| *val = self.sample(input, &applier.at(i)));
+    }
+}
+
+/// The positions of a single interpolation cell, starting at its lowest corner.
+///
+/// Index `i` maps to `y` first, then `z`, then `x`, so a cell is filled column by column.
+/// The sizes must match the ones used for interpolation (4x8 blocks in the vanilla overworld)
+#[derive(Clone, Debug)]
+pub struct CellApplier {
+    start_x: i32,
+    start_y: i32,
+    start_z: i32,
+    horizontal_size: usize,
+    vertical_size: usize,
+    scratch: Arc<ScratchBuffers>,
+}
+
+/// Cells are equal if they cover the same positions, the scratch buffers don't matter
+impl PartialEq for CellApplier {
+    fn eq(&self, other: &Self) -> bool {
+        self.start_x == other.start_x
+            && self.start_y == other.start_y
+            && self.start_z == other.start_z
+            && self.horizontal_size == other.horizontal_size
+            && self.vertical_size == other.vertical_size
+    }
+}
+
+impl Eq for CellApplier {}
+
+impl CellApplier {
+    pub fn new(
+        start_x: i32,
+        start_y: i32,
+        start_z: i32,
+        horizontal_size: usize,
+        vertical_size: usize,
+    ) -> Self {
+        assert!(horizontal_size > 0 && vertical_size > 0);
+        Self {
+            start_x,
+            start_y,
+            start_z,
+            horizontal_size,
+            vertical_size,
+            scratch: Arc::default(),
+        }
+    }
+
+    /// Uses the scratch buffers of another cell, so the cells of a chunk only allocate them once
+    pub fn with_scratch(mut self, scratch: Arc<ScratchBuffers>) -> Self {
+        self.scratch = scratch;
+        self
+    }
+
+    /// The number of positions in the cell
+    pub fn size(&self) -> usize {
+        self.horizontal_size * self.horizontal_size * self.vertical_size
+    }
+
+    /// The index of `pos` in the cell's densities, `None` if it lies outside of the cell
+    pub fn index_of(&self, pos: &NoisePos) -> Option<usize> {
+        let offset = |value: i32, start: i32, size: usize| {
+            usize::try_from(value - start)
+                .ok()
+                .filter(|offset| *offset < size)
+        };
+        let x = offset(pos.x(), self.start_x, self.horizontal_size)?;
+        let y = offset(pos.y(), self.start_y, self.vertical_size)?;
+        let z = offset(pos.z(), self.start_z, self.horizontal_size)?;
+        Some((x * self.horizontal_size + z) * self.vertical_size + y)
+    }
+}
+
+impl<'a> ApplierImpl<'a> for CellApplier {
+    fn at(&self, index: usize) -> NoisePos<'a> {
+        let y = index % self.vertical_size;
+        let z = (index / self.vertical_size) % self.horizontal_size;
+        let x = index / (self.vertical_size * self.horizontal_size);
+        NoisePos::new(
+            self.start_x + x as i32,
+            self.start_y + y as i32,
+            self.start_z + z as i32,
+        )
+    }
+
+    fn fill(&self, densities: &mut [f64], function: &DensityFunction<'a>) {
+        densities
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, val)| *val = function.sample(&self.at(i)));
+    }
+
+    fn scratch(&self) -> &ScratchBuffers {
+        &self.scratch
+    }
+}
+
+/// Samples the input only at the corners of the interpolation cells and trilinearly interpolates in between,
+/// like vanilla's `interpolated` marker.
+///
+/// Cells are aligned to multiples of the cell sizes. The corners of the last cell are cached,
+/// so filling a whole cell samples the input 8 times instead of once per position
+pub struct Interpolator {
+    horizontal_size: usize,
+    vertical_size: usize,
+    /// The lowest corner of the last cell and the values at its corners, see `corner_pos`
+    corners: Mutex<Option<([i32; 3], [f64; 8])>>,
+}
+
+impl Interpolator {
+    pub fn new(horizontal_size: usize, vertical_size: usize) -> Self {
+        assert!(horizontal_size > 0 && vertical_size > 0);
+        Self {
+            horizontal_size,
+            vertical_size,
+            corners: Mutex::new(None),
+        }
+    }
+
+    fn sizes(&self) -> (i32, i32) {
+        (self.horizontal_size as i32, self.vertical_size as i32)
+    }
+
+    /// The lowest corner of the cell containing the position
+    fn cell_start(&self, x: i32, y: i32, z: i32) -> [i32; 3] {
+        let (horizontal, vertical) = self.sizes();
+        [
+            x.div_euclid(horizontal) * horizontal,
+            y.div_euclid(vertical) * vertical,
+            z.div_euclid(horizontal) * horizontal,
+        ]
+    }
+
+    /// Whether the applier covers exactly one of the interpolated cells
+    fn is_cell(&self, cell: &CellApplier) -> bool {
+        cell.horizontal_size == self.horizontal_size
+            && cell.vertical_size == self.vertical_size
+            && self.cell_start(cell.start_x, cell.start_y, cell.start_z)
+                == [cell.start_x, cell.start_y, cell.start_z]
+    }
+
+    /// Corner `i` is offset by a cell in x if bit 0 is set, in y for bit 1 and in z for bit 2,
+    /// which is the argument order of `lerp3`
+    fn corner_pos(&self, start: [i32; 3], i: usize) -> NoisePos<'static> {
+        let (horizontal, vertical) = self.sizes();
+        let offset = |bit: usize, size: i32| if i & bit == 0 { 0 } else { size };
+        NoisePos::new(
+            start[0] + offset(1, horizontal),
+            start[1] + offset(2, vertical),
+            start[2] + offset(4, horizontal),
+        )
+    }
+
+    fn corners(&self, input: &DensityFunction, start: [i32; 3]) -> [f64; 8] {
+        let mut cached = self.corners.lock();
+        if let Some((cached_start, corners)) = *cached {
+            if cached_start == start {
+                return corners;
+            }
+        }
+        let corners: [f64; 8] = std::array::from_fn(|i| input.sample(&self.corner_pos(start, i)));
+        *cached = Some((start, corners));
+        corners
+    }
+
+    fn interpolate(&self, start: [i32; 3], corners: &[f64; 8], pos: &NoisePos) -> f64 {
+        let (horizontal, vertical) = self.sizes();
+        let delta = |value: i32, start: i32, size: i32| f64::from(value - start) / f64::from(size);
+        lerp3(
+            delta(pos.x(), start[0], horizontal),
+            delta(pos.y(), start[1], vertical),
+            delta(pos.z(), start[2], horizontal),
+            corners[0],
+            corners[1],
+            corners[2],
+            corners[3],
+            corners[4],
+            corners[5],
+            corners[6],
+            corners[7],
+        )
+    }
+
+    fn sample(&self, input: &DensityFunction, pos: &NoisePos) -> f64 {
+        let start = self.cell_start(pos.x(), pos.y(), pos.z());
+        let corners = self.corners(input, start);
+        self.interpolate(start, &corners, pos)
+    }
+}
+
+/// Gives `interpolated` markers an `Interpolator` using the given cell sizes
+pub struct InterpolationVisitor {
+    horizontal_size: usize,
+    vertical_size: usize,
+}
+
+impl InterpolationVisitor {
+    pub fn new(horizontal_size: usize, vertical_size: usize) -> Self {
+        assert!(horizontal_size > 0 && vertical_size > 0);
+        Self {
+            horizontal_size,
+            vertical_size,
+        }
+    }
+}
+
+impl<'a> VisitorImpl<'a> for InterpolationVisitor {
+    fn apply(&self, function: Arc<DensityFunction<'a>>) -> Arc<DensityFunction<'a>> {
+        match function.deref() {
+            DensityFunction::Marker(marker) if marker.kind == MarkerType::Interpolated => {
+                Arc::new(DensityFunction::Marker(MarkerFunction::with_cache(
+                    marker.input.clone(),
+                    marker.kind,
+                    MarkerCache::Interpolated(Interpolator::new(
+                        self.horizontal_size,
+                        self.vertical_size,
+                    )),
+                )))
+            }
+            _ => function.clone(),
+        }
+    }
+}
+
+/// Gives the cache markers their caches, `flat_cache` keeps passing through
+pub struct CacheVisitor {}
+
+impl<'a> VisitorImpl<'a> for CacheVisitor {
+    fn apply(&self, function: Arc<DensityFunction<'a>>) -> Arc<DensityFunction<'a>> {
+        let DensityFunction::Marker(marker) = function.deref() else {
+            return function;
+        };
+        let cache = match marker.kind {
+            MarkerType::Cache2D => MarkerCache::Column(Mutex::new(None)),
+            MarkerType::CacheOnce => MarkerCache::Position(Mutex::new(None)),
+            MarkerType::CacheCell => MarkerCache::Cell(Mutex::new(None)),
+            MarkerType::CacheFlat | MarkerType::Interpolated => return function,
+        };
+        Arc::new(DensityFunction::Marker(MarkerFunction::with_cache(
+            marker.input.clone(),
+            marker.kind,
+            cache,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::world_gen::noise::{
+        density::{
+            bench::chunk_cells,
+            profiler::{DensityProfiler, ProfileVisitor},
+            Applier, ApplierImpl, DensityFunction, DensityFunctionImpl, MarkerFunction, MarkerType,
+            NoisePos, UnblendedNoisePos, Visitor,
+        },
+        BuiltInNoiseFunctions, BuiltInNoiseParams,
+    };
+
+    use super::{CacheVisitor, CellApplier, InterpolationVisitor, MarkerCache};
+
+    #[test]
+    fn test_cache_2d() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+
+        // `y` changes with every sample in a column, so every reused value is visible
+        let marker = DensityFunction::Marker(MarkerFunction::new(
+            noise_functions.y().clone(),
+            MarkerType::Cache2D,
+        ));
+        let function = marker.apply(&Visitor::Cache(CacheVisitor {}));
+        assert!(matches!(
+            function.as_ref(),
+            DensityFunction::Marker(MarkerFunction {
+                cache: MarkerCache::Column(_),
+                ..
+            })
+        ));
+
+        let sample =
+            |x, y, z| function.sample(&NoisePos::Unblended(UnblendedNoisePos::new(x, y, z)));
+
+        // A full column only evaluates the input once
+        let values: Vec<f64> = (-64..320).map(|y| sample(0, y, 0)).collect();
+        assert!(values.iter().all(|value| *value == -64f64));
+
+        // Moving to another column invalidates the cache
+        assert_eq!(sample(1, 10, 0), 10f64);
+        assert_eq!(sample(1, 20, 0), 10f64);
+        assert_eq!(sample(1, 20, 1), 20f64);
+
+        // Clones don't share the cache
+        let cloned = Arc::new(function.as_ref().clone());
+        assert_eq!(
+            cloned.sample(&NoisePos::Unblended(UnblendedNoisePos::new(1, 30, 1))),
+            30f64
+        );
+    }
+
+    #[test]
+    fn test_cache_2d_chunk() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+
+        // The continents only vary horizontally
+        let profiler = Arc::new(DensityProfiler::default());
+        let input = noise_functions
+            .continents_overworld()
+            .apply(&Visitor::Profile(ProfileVisitor::new(profiler.clone())));
+        let cached =
+            DensityFunction::Marker(MarkerFunction::new(input.clone(), MarkerType::Cache2D))
+                .apply(&Visitor::Cache(CacheVisitor {}));
+
+        // Profiled fills only count once, so every position is sampled on its own
+        let count_samples = |function: &DensityFunction| {
+            profiler.reset();
+            for cell in chunk_cells() {
+                for i in 0..cell.size() {
+                    function.sample(&ApplierImpl::at(&cell, i));
+                }
+            }
+            profiler.stats()["ShiftedNoise"].calls
+        };
+
+        assert_eq!(count_samples(&input), 16 * 16 * 384);
+        // Once per column of a cell
+        assert_eq!(count_samples(&cached), 16 * 16 * 48);
+    }
+
+    #[test]
+    fn test_cache_once() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+
+        let input = noise_functions.base_3d_noise_overworld().clone();
+        let marker =
+            DensityFunction::Marker(MarkerFunction::new(input.clone(), MarkerType::CacheOnce));
+        let function = marker.apply(&Visitor::Cache(CacheVisitor {}));
+        let DensityFunction::Marker(MarkerFunction {
+            cache: MarkerCache::Position(last_pos),
+            ..
+        }) = function.as_ref()
+        else {
+            panic!("The marker has no cache");
+        };
+
+        let pos = NoisePos::new(3, 40, -7);
+        let value = input.sample(&pos);
+        assert_eq!(function.sample(&pos), value);
+        // The same position is served from the cache
+        assert_eq!(*last_pos.lock(), Some((3, 40, -7, value)));
+        assert_eq!(function.sample(&pos), value);
+
+        // Any other position samples the input again
+        let other = NoisePos::new(3, 41, -7);
+        assert_eq!(function.sample(&other), input.sample(&other));
+        assert_eq!(*last_pos.lock(), Some((3, 41, -7, input.sample(&other))));
+
+        let cell = CellApplier::new(0, 0, 0, 4, 8);
+        let mut densities = vec![0f64; cell.size()];
+        function.fill(&mut densities, &Applier::Cell(cell.clone()));
+        for (i, density) in densities.iter().enumerate() {
+            assert_eq!(*density, input.sample(&ApplierImpl::at(&cell, i)));
+        }
+
+        // The copy for another chunk starts out empty
+        let copy = function.apply(&Visitor::Cache(CacheVisitor {}));
+        let DensityFunction::Marker(MarkerFunction {
+            cache: MarkerCache::Position(copy_last_pos),
+            ..
+        }) = copy.as_ref()
+        else {
+            panic!("The cache was not copied");
+        };
+        assert_eq!(*copy_last_pos.lock(), None);
+        assert!(last_pos.lock().is_some());
+    }
+
+    #[test]
+    fn test_cell_applier() {
+        let cell = CellApplier::new(16, -64, -32, 4, 8);
+        assert_eq!(cell.size(), 128);
+        for i in 0..cell.size() {
+            let pos = ApplierImpl::at(&cell, i);
+            assert_eq!(cell.index_of(&pos), Some(i));
+        }
+        assert_eq!(cell.index_of(&NoisePos::new(16, -56, -32)), None);
+        assert_eq!(cell.index_of(&NoisePos::new(15, -64, -32)), None);
+    }
+
+    #[test]
+    fn test_cache_cell() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+
+        let input = noise_functions.base_3d_noise_overworld().clone();
+        let marker =
+            DensityFunction::Marker(MarkerFunction::new(input.clone(), MarkerType::CacheCell));
+        let function = marker.apply(&Visitor::Cache(CacheVisitor {}));
+        assert!(matches!(
+            function.as_ref(),
+            DensityFunction::Marker(MarkerFunction {
+                cache: MarkerCache::Cell(_),
+                ..
+            })
+        ));
+
+        let cell = CellApplier::new(4, 0, 8, 4, 8);
+        let mut densities = vec![0f64; cell.size()];
+        function.fill(&mut densities, &Applier::Cell(cell.clone()));
+        for (i, density) in densities.iter().enumerate() {
+            let pos = ApplierImpl::at(&cell, i);
+            assert_eq!(*density, input.sample(&pos));
+            assert_eq!(function.sample(&pos), *density);
+        }
+
+        // Outside of the cell the input is sampled directly
+        let outside = NoisePos::new(100, 0, 100);
+        assert_eq!(function.sample(&outside), input.sample(&outside));
+    }
+
+    #[test]
+    fn test_interpolated() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+        let visitor = Visitor::Interpolate(InterpolationVisitor::new(4, 8));
+
+        // Linear functions are interpolated exactly
+        let y = noise_functions.y().clone();
+        let function = DensityFunction::Marker(MarkerFunction::new(y, MarkerType::Interpolated))
+            .apply(&visitor);
+        assert!(matches!(
+            function.as_ref(),
+            DensityFunction::Marker(MarkerFunction {
+                cache: MarkerCache::Interpolated(_),
+                ..
+            })
+        ));
+        for (x, y, z) in [(0, 0, 0), (3, -61, 2), (-5, 13, -9), (17, 319, 100)] {
+            assert_eq!(function.sample(&NoisePos::new(x, y, z)), f64::from(y));
+        }
+
+        let profiler = Arc::new(DensityProfiler::default());
+        let input = noise_functions
+            .base_3d_noise_overworld()
+            .apply(&Visitor::Profile(ProfileVisitor::new(profiler.clone())));
+        let function =
+            DensityFunction::Marker(MarkerFunction::new(input.clone(), MarkerType::Interpolated))
+                .apply(&visitor);
+
+        let cell = CellApplier::new(-8, 16, 4, 4, 8);
+        let mut densities = vec![0f64; cell.size()];
+        function.fill(&mut densities, &Applier::Cell(cell.clone()));
+        // Only the corners of the cell are sampled
+        assert_eq!(profiler.stats()["InterpolatedNoise"].calls, 8);
+
+        // The corners keep their values, in between they get interpolated
+        assert_eq!(densities[0], input.sample(&NoisePos::new(-8, 16, 4)));
+        let top = input.sample(&NoisePos::new(-8, 24, 4));
+        assert_eq!(function.sample(&NoisePos::new(-8, 24, 4)), top);
+        let middle = function.sample(&NoisePos::new(-8, 20, 4));
+        assert!((middle - (densities[0] + top) / 2f64).abs() < 1e-12);
+        for (i, density) in densities.iter().enumerate() {
+            assert_eq!(*density, function.sample(&ApplierImpl::at(&cell, i)));
+        }
+    }
+
+    #[test]
+    fn test_interpolated_chunk() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+
+        let profiler = Arc::new(DensityProfiler::default());
+        let input = noise_functions
+            .base_3d_noise_overworld()
+            .apply(&Visitor::Profile(ProfileVisitor::new(profiler.clone())));
+        let interpolated =
+            DensityFunction::Marker(MarkerFunction::new(input.clone(), MarkerType::Interpolated))
+                .apply(&Visitor::Interpolate(InterpolationVisitor::new(4, 8)));
+
+        // Profiled fills only count once, so the input samples every position on its own
+        profiler.reset();
+        for cell in chunk_cells() {
+            for i in 0..cell.size() {
+                input.sample(&ApplierImpl::at(&cell, i));
+            }
+        }
+        assert_eq!(profiler.stats()["InterpolatedNoise"].calls, 16 * 16 * 384);
+
+        profiler.reset();
+        let cells = chunk_cells();
+        let mut densities = vec![0f64; cells[0].size()];
+        for cell in cells {
+            interpolated.fill(&mut densities, &Applier::Cell(cell));
+        }
+        assert_eq!(profiler.stats()["InterpolatedNoise"].calls, 4 * 4 * 48 * 8);
+    }
+}
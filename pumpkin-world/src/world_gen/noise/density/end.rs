@@ -6,7 +6,7 @@ use crate::world_gen::noise::simplex::SimplexNoiseSampler;
 
 use super::{
     Applier, ApplierImpl, DensityFunction, DensityFunctionImpl, NoisePos, NoisePosImpl, Visitor,
-    VisitorImpl,
+    VisitorImpl, NOISE_COST,
 };
 
 #[derive(Clone)]
@@ -73,7 +73,15 @@ impl<'a> DensityFunctionImpl<'a> for EndIslandFunction {
         0.5625f64
     }
 
+    fn estimated_cost(&self) -> u32 {
+        NOISE_COST
+    }
+
     fn apply(&self, visitor: &Visitor<'a>) -> Arc<DensityFunction<'a>> {
         visitor.apply(Arc::new(DensityFunction::EndIsland(self.clone())))
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "type": "minecraft:end_islands" })
+    }
 }
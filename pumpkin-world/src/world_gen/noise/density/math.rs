@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
 use log::warn;
+use parking_lot::Mutex;
+use wide::f64x4;
 
 use super::{
     Applier, ApplierImpl, DensityFunction, DensityFunctionImpl, NoisePos, UnaryDensityFunction,
@@ -13,6 +15,16 @@ pub enum LinearType {
     Add,
 }
 
+impl LinearType {
+    /// Vanilla has no linear functions, they are the `Binary` function of this id with a constant argument
+    fn registry_name(&self) -> &'static str {
+        match self {
+            Self::Mul => "minecraft:mul",
+            Self::Add => "minecraft:add",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct LinearFunction<'a> {
     action: LinearType,
@@ -25,6 +37,11 @@ pub struct LinearFunction<'a> {
 impl<'a> DensityFunctionImpl<'a> for LinearFunction<'a> {
     fn apply(&self, visitor: &Visitor<'a>) -> Arc<DensityFunction<'a>> {
         let new_function = self.input.apply(visitor);
+        // The visitor can turn the input into a constant, then there is nothing left to sample
+        if let DensityFunction::Constant(func) = new_function.as_ref() {
+            return Arc::new(DensityFunction::constant(self.apply_density(func.value)));
+        }
+
         let d = new_function.min();
         let e = new_function.max();
 
@@ -53,6 +70,11 @@ impl<'a> DensityFunctionImpl<'a> for LinearFunction<'a> {
     }
 
     fn fill(&self, densities: &mut [f64], applier: &Applier<'a>) {
+        if let Some(value) = self.input.as_constant() {
+            densities.fill(self.apply_density(value));
+            return;
+        }
+
         self.input.fill(densities, applier);
         densities
             .iter_mut()
@@ -66,6 +88,18 @@ impl<'a> DensityFunctionImpl<'a> for LinearFunction<'a> {
     fn max(&self) -> f64 {
         self.max
     }
+
+    fn estimated_cost(&self) -> u32 {
+        self.input.estimated_cost() + 1
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": self.action.registry_name(),
+            "argument1": self.input.to_json(),
+            "argument2": self.arg,
+        })
+    }
 }
 
 impl<'a> UnaryDensityFunction<'a> for LinearFunction<'a> {
@@ -77,6 +111,48 @@ impl<'a> UnaryDensityFunction<'a> for LinearFunction<'a> {
     }
 }
 
+/// The number of values added at once by `add_assign`, the width of `f64x4`
+const LANES: usize = 4;
+
+/// Buffers for the second argument of `Add` fills, handed down with the `Applier`.
+/// Nested fills each take their own one, so the buffers are only allocated by the first fills
+#[derive(Default, Debug)]
+pub struct ScratchBuffers {
+    buffers: Mutex<Vec<Vec<f64>>>,
+}
+
+impl ScratchBuffers {
+    /// Runs `f` with a zeroed buffer of `len` values, reusing the buffers of earlier fills
+    pub fn with<R>(&self, len: usize, f: impl FnOnce(&mut [f64]) -> R) -> R {
+        let mut buffer = self.buffers.lock().pop().unwrap_or_default();
+        buffer.clear();
+        buffer.resize(len, 0f64);
+        let result = f(&mut buffer);
+        self.buffers.lock().push(buffer);
+        result
+    }
+}
+
+/// Adds `values` to `densities`, `LANES` values at a time with SIMD instructions and the rest one by one
+pub(crate) fn add_assign(densities: &mut [f64], values: &[f64]) {
+    let mut chunks = densities.chunks_exact_mut(LANES);
+    let mut value_chunks = values.chunks_exact(LANES);
+    for (chunk, value_chunk) in chunks.by_ref().zip(value_chunks.by_ref()) {
+        let sum = f64x4::from(<[f64; LANES]>::try_from(&*chunk).unwrap())
+            + f64x4::from(<[f64; LANES]>::try_from(value_chunk).unwrap());
+        chunk.copy_from_slice(&sum.to_array());
+    }
+    add_assign_scalar(chunks.into_remainder(), value_chunks.remainder());
+}
+
+/// Adds `values` to `densities` one by one, for the values which don't fill a whole lane
+pub(crate) fn add_assign_scalar(densities: &mut [f64], values: &[f64]) {
+    densities
+        .iter_mut()
+        .zip(values)
+        .for_each(|(density, value)| *density += value);
+}
+
 #[derive(Clone)]
 pub enum BinaryType {
     Mul,
@@ -85,6 +161,26 @@ pub enum BinaryType {
     Max,
 }
 
+impl BinaryType {
+    fn registry_name(&self) -> &'static str {
+        match self {
+            Self::Add => "minecraft:add",
+            Self::Mul => "minecraft:mul",
+            Self::Min => "minecraft:min",
+            Self::Max => "minecraft:max",
+        }
+    }
+
+    fn apply(&self, d: f64, e: f64) -> f64 {
+        match self {
+            Self::Add => d + e,
+            Self::Mul => d * e,
+            Self::Min => d.min(e),
+            Self::Max => d.max(e),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct BinaryFunction<'a> {
     action: BinaryType,
@@ -100,6 +196,14 @@ impl<'a> BinaryFunction<'a> {
         arg1: Arc<DensityFunction<'a>>,
         arg2: Arc<DensityFunction<'a>>,
     ) -> DensityFunction<'a> {
+        // Folds constant chains like `add(constant(3), mul(constant(2), constant(5)))` into a
+        // single constant, so sampling doesn't walk the tree for a fixed value
+        if let (DensityFunction::Constant(func1), DensityFunction::Constant(func2)) =
+            (arg1.as_ref(), arg2.as_ref())
+        {
+            return DensityFunction::constant(action.apply(func1.value, func2.value));
+        }
+
         let d = arg1.min();
         let e = arg2.min();
         let f = arg1.max();
@@ -211,17 +315,32 @@ impl<'a> DensityFunctionImpl<'a> for BinaryFunction<'a> {
     }
 
     fn fill(&self, densities: &mut [f64], applier: &Applier<'a>) {
-        self.arg1.fill(densities, applier);
+        let constant1 = self.arg1.as_constant();
+        let constant2 = self.arg2.as_constant();
+
+        if let (Some(d), Some(e)) = (constant1, constant2) {
+            densities.fill(self.action.apply(d, e));
+            return;
+        }
+
+        match constant1 {
+            Some(d) => densities.fill(d),
+            None => self.arg1.fill(densities, applier),
+        }
+
+        if let Some(e) = constant2 {
+            densities
+                .iter_mut()
+                .for_each(|val| *val = self.action.apply(*val, e));
+            return;
+        }
+
         match self.action {
-            BinaryType::Add => {
-                let mut ds = Vec::with_capacity(densities.len());
-                densities.iter().for_each(|_| ds.push(0f64));
-                self.arg2.fill(&mut ds, applier);
-                densities
-                    .iter_mut()
-                    .zip(ds)
-                    .for_each(|(real, temp)| *real += temp);
-            }
+            BinaryType::Add => applier.scratch().with(densities.len(), |ds| {
+                self.arg2.fill(ds, applier);
+                add_assign(densities, ds);
+            }),
+            // Like vanilla, the second argument is only sampled where it can change the result
             BinaryType::Mul => {
                 densities.iter_mut().enumerate().for_each(|(i, val)| {
                     if *val != 0f64 {
@@ -265,4 +384,190 @@ impl<'a> DensityFunctionImpl<'a> for BinaryFunction<'a> {
     fn min(&self) -> f64 {
         self.min
     }
+
+    fn estimated_cost(&self) -> u32 {
+        self.arg1.estimated_cost() + self.arg2.estimated_cost() + 1
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": self.action.registry_name(),
+            "argument1": self.arg1.to_json(),
+            "argument2": self.arg2.to_json(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::world_gen::noise::{
+        density::{
+            cache::CellApplier, Applier, ApplierImpl, DensityFunction, DensityFunctionImpl,
+            MarkerFunction, MarkerType, UnwrapVisitor, Visitor,
+        },
+        BuiltInNoiseFunctions, BuiltInNoiseParams,
+    };
+
+    use super::{add_assign, add_assign_scalar, BinaryFunction, BinaryType, LinearType};
+
+    #[test]
+    fn test_constant_folding() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+        let noise = noise_functions.base_3d_noise_overworld().clone();
+
+        let constant = DensityFunction::constant(2.0);
+        assert_eq!(constant.min(), 2.0);
+        assert_eq!(constant.max(), 2.0);
+        assert_eq!(constant.as_constant(), Some(2.0));
+
+        // The constant can be on either side
+        for function in [
+            BinaryFunction::create(BinaryType::Mul, Arc::new(constant.clone()), noise.clone()),
+            BinaryFunction::create(BinaryType::Mul, noise.clone(), Arc::new(constant.clone())),
+        ] {
+            let DensityFunction::Linear(linear) = &function else {
+                panic!("multiplying with a constant must fold into a linear function");
+            };
+            assert!(matches!(linear.action, LinearType::Mul));
+            assert_eq!(linear.arg, 2.0);
+            assert_eq!(function.min(), 2.0 * noise.min());
+            assert_eq!(function.max(), 2.0 * noise.max());
+        }
+
+        let constant = |value: f64| Arc::new(DensityFunction::constant(value));
+        let function = BinaryFunction::create(
+            BinaryType::Add,
+            constant(3.0),
+            Arc::new(BinaryFunction::create(
+                BinaryType::Mul,
+                constant(2.0),
+                constant(5.0),
+            )),
+        );
+        let DensityFunction::Constant(folded) = &function else {
+            panic!("a tree of constants must fold into a constant");
+        };
+        assert_eq!(folded.value, 13.0);
+        assert_eq!(function.min(), 13.0);
+        assert_eq!(function.max(), 13.0);
+        let function = BinaryFunction::create(BinaryType::Min, constant(-1.0), constant(4.0));
+        assert!(matches!(function, DensityFunction::Constant(folded) if folded.value == -1.0));
+    }
+
+    #[test]
+    fn test_linear_apply_folding() {
+        // Unwrapping leaves a constant input, which folds with the linear operation
+        let cached = MarkerFunction::new(
+            Arc::new(DensityFunction::constant(1.5)),
+            MarkerType::Cache2D,
+        );
+        let DensityFunction::Linear(linear) = BinaryFunction::create(
+            BinaryType::Mul,
+            Arc::new(DensityFunction::Marker(cached)),
+            Arc::new(DensityFunction::constant(-2.0)),
+        ) else {
+            panic!("multiplying with a constant must fold into a linear function");
+        };
+        let function = linear.apply(&Visitor::Unwrap(UnwrapVisitor {}));
+        assert!(
+            matches!(function.as_ref(), DensityFunction::Constant(folded) if folded.value == -3.0)
+        );
+    }
+
+    fn add<'a>(
+        arg1: Arc<DensityFunction<'a>>,
+        arg2: Arc<DensityFunction<'a>>,
+    ) -> Arc<DensityFunction<'a>> {
+        Arc::new(BinaryFunction::create(BinaryType::Add, arg1, arg2))
+    }
+
+    #[test]
+    fn test_add_assign() {
+        for len in [0u8, 3, 4, 9] {
+            let mut densities: Vec<f64> = (0..len).map(f64::from).collect();
+            let values: Vec<f64> = (0..len).map(|i| f64::from(i) * 0.5).collect();
+            let mut scalar = densities.clone();
+            add_assign(&mut densities, &values);
+            add_assign_scalar(&mut scalar, &values);
+            let expected: Vec<f64> = (0..len).map(|i| f64::from(i) * 1.5).collect();
+            assert_eq!(densities, expected);
+            assert_eq!(scalar, expected);
+        }
+    }
+
+    #[test]
+    fn test_add_fill() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+        let sum = add(
+            noise_functions.base_3d_noise_overworld().clone(),
+            noise_functions.y().clone(),
+        );
+        // Nested adds each need their own buffer
+        let function = add(sum.clone(), add(sum.clone(), sum));
+
+        // Neither is a multiple of the lanes
+        for cell in [
+            CellApplier::new(0, -64, 0, 4, 8),
+            CellApplier::new(3, 10, -5, 1, 7),
+        ] {
+            let mut densities = vec![0f64; cell.size()];
+            function.fill(&mut densities, &Applier::Cell(cell.clone()));
+            for (i, density) in densities.iter().enumerate() {
+                assert_eq!(*density, function.sample(&ApplierImpl::at(&cell, i)));
+            }
+        }
+    }
+
+    /// The short-circuits of `fill` must give the same values as applying the operation everywhere
+    #[test]
+    fn test_fill_matches_reference() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+        let noise = noise_functions.base_3d_noise_overworld();
+        // Exactly 0 at y = 0
+        let crossing = Arc::new(noise_functions.y().mul_const(0.01));
+        // Often exactly at its bounds
+        let clamped = Arc::new(noise.clamp(-0.05, 0.05));
+        let negated_clamped = Arc::new(noise.mul_const(-1.0).clamp(-0.05, 0.05));
+        let crossing_clamped = Arc::new(crossing.clamp(-0.05, 0.05));
+
+        let pairs = [
+            (crossing.clone(), clamped.clone()),
+            (clamped.clone(), crossing.clone()),
+            (clamped.clone(), negated_clamped.clone()),
+            (crossing_clamped.clone(), clamped.clone()),
+            (negated_clamped, noise.clone()),
+            (crossing_clamped, crossing),
+        ];
+        let cell = CellApplier::new(0, -8, 0, 4, 16);
+        for action in [
+            BinaryType::Add,
+            BinaryType::Mul,
+            BinaryType::Min,
+            BinaryType::Max,
+        ] {
+            for (arg1, arg2) in &pairs {
+                let function = BinaryFunction::create(action.clone(), arg1.clone(), arg2.clone());
+                let mut densities = vec![0f64; cell.size()];
+                function.fill(&mut densities, &Applier::Cell(cell.clone()));
+
+                for (i, density) in densities.iter().enumerate() {
+                    let pos = ApplierImpl::at(&cell, i);
+                    let (d, e) = (arg1.sample(&pos), arg2.sample(&pos));
+                    let expected = match action {
+                        BinaryType::Add => d + e,
+                        BinaryType::Mul => d * e,
+                        BinaryType::Min => d.min(e),
+                        BinaryType::Max => d.max(e),
+                    };
+                    assert_eq!(*density, expected);
+                    assert_eq!(function.sample(&pos), expected);
+                }
+            }
+        }
+    }
 }
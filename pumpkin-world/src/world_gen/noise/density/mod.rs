@@ -1,31 +1,52 @@
-use std::{ops::Deref, sync::Arc};
+use std::{
+    ops::Deref,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use blend::{BlendAlphaFunction, BlendDensityFunction, BlendOffsetFunction};
+use cache::{CacheVisitor, CellApplier, InterpolationVisitor, MarkerCache};
 use derive_getters::Getters;
 use end::EndIslandFunction;
 use enum_dispatch::enum_dispatch;
-use math::{BinaryFunction, BinaryType, LinearFunction};
+use log::warn;
+use math::{BinaryFunction, BinaryType, LinearFunction, ScratchBuffers};
 use noise::{InternalNoise, InterpolatedNoiseSampler, NoiseFunction, ShiftedNoiseFunction};
 use offset::{ShiftAFunction, ShiftBFunction};
+use parking_lot::Mutex;
+use profiler::{ProfileVisitor, ProfiledFunction};
+use pumpkin_core::math::{position::WorldPosition, vector3::Vector3};
 use spline::SplineFunction;
 use terrain_helpers::{create_factor_spline, create_jaggedness_spline, create_offset_spline};
 use unary::{ClampFunction, UnaryFunction, UnaryType};
 use weird::{RarityMapper, WierdScaledFunction};
 
-use crate::world_gen::blender::Blender;
+use crate::world_gen::{blender::Blender, height_limit::HeightLimitViewImpl};
 
 use super::{clamped_map, perlin::DoublePerlinNoiseParameters, BuiltInNoiseParams};
 
+#[doc(hidden)]
+pub mod bench;
 pub mod blend;
-mod end;
+pub mod cache;
+pub mod end;
 mod math;
 pub mod noise;
 mod offset;
+pub mod parser;
+pub mod profiler;
 pub mod spline;
 mod terrain_helpers;
 mod unary;
 mod weird;
 
+/// Relative cost of sampling a single noise, used by `DensityFunctionImpl::estimated_cost`
+const NOISE_COST: u32 = 10;
+
+/// The minimum time between two warnings about non finite densities
+const NAN_WARNING_INTERVAL: Duration = Duration::from_secs(10);
+static LAST_NAN_WARNING: Mutex<Option<Instant>> = Mutex::new(None);
+
 struct SlopedCheeseResult<'a> {
     offset: Arc<DensityFunction<'a>>,
     factor: Arc<DensityFunction<'a>>,
@@ -86,8 +107,8 @@ impl<'a> BuiltInNoiseFunctions<'a> {
     pub fn new(built_in_noise_params: &BuiltInNoiseParams<'a>) -> Self {
         let blend_alpha = Arc::new(DensityFunction::BlendAlpha(BlendAlphaFunction {}));
         let blend_offset = Arc::new(DensityFunction::BlendOffset(BlendOffsetFunction {}));
-        let zero = Arc::new(DensityFunction::Constant(ConstantFunction::new(0f64)));
-        let ten = Arc::new(DensityFunction::Constant(ConstantFunction::new(10f64)));
+        let zero = Arc::new(DensityFunction::constant(0f64));
+        let ten = Arc::new(DensityFunction::constant(10f64));
 
         let y = Arc::new({
             DensityFunction::ClampedY(YClampedFunction {
@@ -99,26 +120,26 @@ impl<'a> BuiltInNoiseFunctions<'a> {
         });
 
         let shift_x = Arc::new({
-            DensityFunction::Wrapper(WrapperFunction::new(
-                Arc::new(DensityFunction::Wrapper(WrapperFunction::new(
+            DensityFunction::Marker(MarkerFunction::new(
+                Arc::new(DensityFunction::Marker(MarkerFunction::new(
                     Arc::new(DensityFunction::ShiftA(ShiftAFunction::new(Arc::new(
                         InternalNoise::new(built_in_noise_params.offset().clone(), None),
                     )))),
-                    WrapperType::Cache2D,
+                    MarkerType::Cache2D,
                 ))),
-                WrapperType::CacheFlat,
+                MarkerType::CacheFlat,
             ))
         });
 
         let shift_z = Arc::new({
-            DensityFunction::Wrapper(WrapperFunction::new(
-                Arc::new(DensityFunction::Wrapper(WrapperFunction::new(
+            DensityFunction::Marker(MarkerFunction::new(
+                Arc::new(DensityFunction::Marker(MarkerFunction::new(
                     Arc::new(DensityFunction::ShiftB(ShiftBFunction::new(Arc::new(
                         InternalNoise::new(built_in_noise_params.offset().clone(), None),
                     )))),
-                    WrapperType::Cache2D,
+                    MarkerType::Cache2D,
                 ))),
-                WrapperType::CacheFlat,
+                MarkerType::CacheFlat,
             ))
         });
 
@@ -147,7 +168,7 @@ impl<'a> BuiltInNoiseFunctions<'a> {
         });
 
         let continents_overworld = Arc::new({
-            DensityFunction::Wrapper(WrapperFunction::new(
+            DensityFunction::Marker(MarkerFunction::new(
                 Arc::new(DensityFunction::ShiftedNoise(ShiftedNoiseFunction::new(
                     shift_x.clone(),
                     zero.clone(),
@@ -159,12 +180,12 @@ impl<'a> BuiltInNoiseFunctions<'a> {
                         None,
                     )),
                 ))),
-                WrapperType::CacheFlat,
+                MarkerType::CacheFlat,
             ))
         });
 
         let erosion_overworld = Arc::new({
-            DensityFunction::Wrapper(WrapperFunction::new(
+            DensityFunction::Marker(MarkerFunction::new(
                 Arc::new(DensityFunction::ShiftedNoise(ShiftedNoiseFunction::new(
                     shift_x.clone(),
                     zero.clone(),
@@ -176,12 +197,12 @@ impl<'a> BuiltInNoiseFunctions<'a> {
                         None,
                     )),
                 ))),
-                WrapperType::CacheFlat,
+                MarkerType::CacheFlat,
             ))
         });
 
         let ridges_overworld = Arc::new({
-            DensityFunction::Wrapper(WrapperFunction::new(
+            DensityFunction::Marker(MarkerFunction::new(
                 Arc::new(DensityFunction::ShiftedNoise(ShiftedNoiseFunction::new(
                     shift_x.clone(),
                     zero.clone(),
@@ -193,7 +214,7 @@ impl<'a> BuiltInNoiseFunctions<'a> {
                         None,
                     )),
                 ))),
-                WrapperType::CacheFlat,
+                MarkerType::CacheFlat,
             ))
         });
 
@@ -227,7 +248,7 @@ impl<'a> BuiltInNoiseFunctions<'a> {
         );
 
         let continents_overworld_large_biome = Arc::new({
-            DensityFunction::Wrapper(WrapperFunction::new(
+            DensityFunction::Marker(MarkerFunction::new(
                 Arc::new(DensityFunction::ShiftedNoise(ShiftedNoiseFunction::new(
                     shift_x.clone(),
                     zero.clone(),
@@ -239,12 +260,12 @@ impl<'a> BuiltInNoiseFunctions<'a> {
                         None,
                     )),
                 ))),
-                WrapperType::CacheFlat,
+                MarkerType::CacheFlat,
             ))
         });
 
         let erosion_overworld_large_biome = Arc::new({
-            DensityFunction::Wrapper(WrapperFunction::new(
+            DensityFunction::Marker(MarkerFunction::new(
                 Arc::new(DensityFunction::ShiftedNoise(ShiftedNoiseFunction::new(
                     shift_x.clone(),
                     zero.clone(),
@@ -256,7 +277,7 @@ impl<'a> BuiltInNoiseFunctions<'a> {
                         None,
                     )),
                 ))),
-                WrapperType::CacheFlat,
+                MarkerType::CacheFlat,
             ))
         });
 
@@ -305,7 +326,7 @@ impl<'a> BuiltInNoiseFunctions<'a> {
         });
 
         let caves_spaghetti_roughness_function_overworld = Arc::new({
-            DensityFunction::Wrapper(WrapperFunction::new(
+            DensityFunction::Marker(MarkerFunction::new(
                 Arc::new(
                     noise_in_range(
                         built_in_noise_params
@@ -329,12 +350,12 @@ impl<'a> BuiltInNoiseFunctions<'a> {
                         .add_const(-0.4f64),
                     )),
                 ),
-                WrapperType::CacheOnce,
+                MarkerType::CacheOnce,
             ))
         });
 
         let caves_spaghetti_2d_thickness_modular_overworld = Arc::new({
-            DensityFunction::Wrapper(WrapperFunction::new(
+            DensityFunction::Marker(MarkerFunction::new(
                 Arc::new(noise_in_range(
                     built_in_noise_params.spaghetti_2d_thickness().clone(),
                     2f64,
@@ -342,7 +363,7 @@ impl<'a> BuiltInNoiseFunctions<'a> {
                     -0.6f64,
                     -1.3f64,
                 )),
-                WrapperType::CacheOnce,
+                MarkerType::CacheOnce,
             ))
         });
 
@@ -455,9 +476,9 @@ impl<'a> BuiltInNoiseFunctions<'a> {
                     to_val: 0f64,
                 })));
 
-            DensityFunction::Wrapper(WrapperFunction::new(
+            DensityFunction::Marker(MarkerFunction::new(
                 Arc::new(function8.binary_min(Arc::new(function6.add(function5)))),
-                WrapperType::CacheOnce,
+                MarkerType::CacheOnce,
             ))
         });
 
@@ -530,13 +551,13 @@ impl<'a> BuiltInNoiseFunctions<'a> {
                     .mul_const(1.5f64),
             );
 
-            DensityFunction::Range(RangeFunction {
-                input: Arc::new(function2),
-                min: -1000000f64,
-                max: 0f64,
-                in_range: Arc::new(DensityFunction::Constant(ConstantFunction::new(64f64))),
-                out_range: Arc::new(function3.add(function6)),
-            })
+            DensityFunction::Range(RangeFunction::new(
+                Arc::new(function2),
+                -1000000f64,
+                0f64,
+                Arc::new(DensityFunction::constant(64f64)),
+                Arc::new(function3.add(function6)),
+            ))
         });
 
         let caves_pillars_overworld = Arc::new({
@@ -567,9 +588,9 @@ impl<'a> BuiltInNoiseFunctions<'a> {
 
             let function4 = function.mul_const(2f64).add(function2);
 
-            DensityFunction::Wrapper(WrapperFunction::new(
+            DensityFunction::Marker(MarkerFunction::new(
                 Arc::new(function4.mul(Arc::new(function3.cube()))),
-                WrapperType::CacheOnce,
+                MarkerType::CacheOnce,
             ))
         });
 
@@ -616,6 +637,58 @@ impl<'a> BuiltInNoiseFunctions<'a> {
             caves_pillars_overworld,
         }
     }
+
+    /// Looks up a function by its id in vanilla's density function registry,
+    /// to resolve the references in [`parser::parse_density_function`]
+    pub fn get(&self, id: &str) -> Option<Arc<DensityFunction<'a>>> {
+        let function = match id {
+            "minecraft:zero" => &self.zero,
+            "minecraft:y" => &self.y,
+            "minecraft:shift_x" => &self.shift_x,
+            "minecraft:shift_z" => &self.shift_z,
+            "minecraft:overworld/base_3d_noise" => &self.base_3d_noise_overworld,
+            "minecraft:nether/base_3d_noise" => &self.base_3d_noise_nether,
+            "minecraft:end/base_3d_noise" => &self.base_3d_noise_end,
+            "minecraft:overworld/continents" => &self.continents_overworld,
+            "minecraft:overworld/erosion" => &self.erosion_overworld,
+            "minecraft:overworld/ridges" => &self.ridges_overworld,
+            "minecraft:overworld/ridges_folded" => &self.ridges_folded_overworld,
+            "minecraft:overworld/offset" => &self.offset_overworld,
+            "minecraft:overworld/factor" => &self.factor_overworld,
+            "minecraft:overworld/jaggedness" => &self.jaggedness_overworld,
+            "minecraft:overworld/depth" => &self.depth_overworld,
+            "minecraft:overworld/sloped_cheese" => &self.sloped_cheese_overworld,
+            "minecraft:overworld_large_biomes/continents" => &self.continents_overworld_large_biome,
+            "minecraft:overworld_large_biomes/erosion" => &self.erosion_overworld_large_biome,
+            "minecraft:overworld_large_biomes/offset" => &self.offset_overworld_large_biome,
+            "minecraft:overworld_large_biomes/factor" => &self.factor_overworld_large_biome,
+            "minecraft:overworld_large_biomes/jaggedness" => &self.jaggedness_overworld_large_biome,
+            "minecraft:overworld_large_biomes/depth" => &self.depth_overworld_large_biome,
+            "minecraft:overworld_large_biomes/sloped_cheese" => {
+                &self.sloped_cheese_overworld_large_biome
+            }
+            "minecraft:overworld_amplified/offset" => &self.offset_overworld_amplified,
+            "minecraft:overworld_amplified/factor" => &self.factor_overworld_amplified,
+            "minecraft:overworld_amplified/jaggedness" => &self.jaggedness_overworld_amplified,
+            "minecraft:overworld_amplified/depth" => &self.depth_overworld_amplified,
+            "minecraft:overworld_amplified/sloped_cheese" => {
+                &self.sloped_cheese_overworld_amplified
+            }
+            "minecraft:end/sloped_cheese" => &self.sloped_cheese_end,
+            "minecraft:overworld/caves/spaghetti_roughness_function" => {
+                &self.caves_spaghetti_roughness_function_overworld
+            }
+            "minecraft:overworld/caves/spaghetti_2d_thickness_modulator" => {
+                &self.caves_spaghetti_2d_thickness_modular_overworld
+            }
+            "minecraft:overworld/caves/spaghetti_2d" => &self.caves_spaghetti_2d_overworld,
+            "minecraft:overworld/caves/entrances" => &self.caves_entrances_overworld,
+            "minecraft:overworld/caves/noodle" => &self.caves_noodle_overworld,
+            "minecraft:overworld/caves/pillars" => &self.caves_pillars_overworld,
+            _ => return None,
+        };
+        Some(function.clone())
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -632,16 +705,14 @@ fn sloped_cheese_function<'a>(
     amplified: bool,
 ) -> SlopedCheeseResult<'a> {
     let offset = Arc::new(apply_blending(
-        Arc::new(
-            DensityFunction::Constant(ConstantFunction::new(-0.50375f32 as f64)).add(Arc::new(
-                DensityFunction::Spline(SplineFunction::new(Arc::new(create_offset_spline(
-                    continents.clone(),
-                    erosion.clone(),
-                    ridges_folded.clone(),
-                    amplified,
-                )))),
-            )),
-        ),
+        Arc::new(DensityFunction::constant(-0.50375f32 as f64).add(Arc::new(
+            DensityFunction::Spline(SplineFunction::new(Arc::new(create_offset_spline(
+                continents.clone(),
+                erosion.clone(),
+                ridges_folded.clone(),
+                amplified,
+            )))),
+        ))),
         blend_offset,
     ));
 
@@ -676,7 +747,7 @@ fn sloped_cheese_function<'a>(
     ));
 
     let density1 = Arc::new(jaggedness.mul(Arc::new(jagged_noise.half_negative())));
-    let density2 = DensityFunction::Constant(ConstantFunction::new(4f64)).mul(Arc::new(
+    let density2 = DensityFunction::constant(4f64).mul(Arc::new(
         depth.add(density1).mul(factor.clone()).quarter_negative(),
     ));
 
@@ -702,23 +773,23 @@ pub fn veritcal_range_choice<'a>(
     max: i32,
     out: i32,
 ) -> DensityFunction<'a> {
-    DensityFunction::Wrapper(WrapperFunction::new(
-        Arc::new(DensityFunction::Range(RangeFunction {
+    DensityFunction::Marker(MarkerFunction::new(
+        Arc::new(DensityFunction::Range(RangeFunction::new(
             input,
-            min: min as f64,
-            max: (max + 1) as f64,
+            min as f64,
+            (max + 1) as f64,
             in_range,
-            out_range: Arc::new(DensityFunction::Constant(ConstantFunction::new(out as f64))),
-        })),
-        WrapperType::Interpolated,
+            Arc::new(DensityFunction::constant(out as f64)),
+        ))),
+        MarkerType::Interpolated,
     ))
 }
 
 pub fn apply_blend_density(density: DensityFunction) -> DensityFunction {
     let function = DensityFunction::BlendDensity(BlendDensityFunction::new(Arc::new(density)));
-    DensityFunction::Wrapper(WrapperFunction::new(
+    DensityFunction::Marker(MarkerFunction::new(
         Arc::new(function),
-        WrapperType::Interpolated,
+        MarkerType::Interpolated,
     ))
     .mul_const(0.64f64)
     .squeeze()
@@ -734,12 +805,12 @@ fn apply_blending<'a>(
         function,
     );
 
-    DensityFunction::Wrapper(WrapperFunction::new(
-        Arc::new(DensityFunction::Wrapper(WrapperFunction::new(
+    DensityFunction::Marker(MarkerFunction::new(
+        Arc::new(DensityFunction::Marker(MarkerFunction::new(
             Arc::new(function),
-            WrapperType::Cache2D,
+            MarkerType::Cache2D,
         ))),
-        WrapperType::CacheFlat,
+        MarkerType::CacheFlat,
     ))
 }
 
@@ -765,9 +836,7 @@ fn map_range(function: Arc<DensityFunction>, min: f64, max: f64) -> DensityFunct
     let d = (min + max) * 0.5f64;
     let e = (max - min) * 0.5f64;
 
-    DensityFunction::Constant(ConstantFunction::new(d)).add(Arc::new(
-        DensityFunction::Constant(ConstantFunction::new(e)).mul(function),
-    ))
+    DensityFunction::constant(d).add(Arc::new(DensityFunction::constant(e).mul(function)))
 }
 
 #[derive(Clone)]
@@ -791,7 +860,8 @@ pub enum DensityFunction<'a> {
     EndIsland(EndIslandFunction),
     Wierd(WierdScaledFunction<'a>),
     Range(RangeFunction<'a>),
-    Wrapper(WrapperFunction<'a>),
+    Marker(MarkerFunction<'a>),
+    Profiled(ProfiledFunction<'a>),
 }
 
 impl<'a> DensityFunction<'a> {
@@ -846,8 +916,13 @@ impl<'a> DensityFunction<'a> {
         ))
     }
 
+    /// A function which is `value` everywhere, combining it with other functions folds it into a `Linear`
+    pub fn constant(value: f64) -> Self {
+        Self::Constant(ConstantFunction::new(value))
+    }
+
     pub fn add_const(&self, val: f64) -> Self {
-        self.add(Arc::new(Self::Constant(ConstantFunction::new(val))))
+        self.add(Arc::new(Self::constant(val)))
     }
 
     pub fn add(&self, other: Arc<DensityFunction<'a>>) -> Self {
@@ -855,7 +930,7 @@ impl<'a> DensityFunction<'a> {
     }
 
     pub fn mul_const(&self, val: f64) -> Self {
-        self.mul(Arc::new(Self::Constant(ConstantFunction::new(val))))
+        self.mul(Arc::new(Self::constant(val)))
     }
 
     pub fn mul(&self, other: Arc<DensityFunction<'a>>) -> Self {
@@ -869,6 +944,127 @@ impl<'a> DensityFunction<'a> {
     pub fn binary_max(&self, other: Arc<DensityFunction<'a>>) -> Self {
         BinaryFunction::create(BinaryType::Max, Arc::new(self.clone()), other)
     }
+
+    /// Returns the value of this function if it evaluates to the same value everywhere
+    pub fn as_constant(&self) -> Option<f64> {
+        let min = self.min();
+        (min == self.max()).then_some(min)
+    }
+
+    /// The name of the variant, used to group the profiler stats. Markers are named after their kind
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Clamp(_) => "Clamp",
+            Self::Unary(_) => "Unary",
+            Self::Noise(_) => "Noise",
+            Self::ShiftA(_) => "ShiftA",
+            Self::ShiftB(_) => "ShiftB",
+            Self::ShiftedNoise(_) => "ShiftedNoise",
+            Self::Spline(_) => "Spline",
+            Self::Constant(_) => "Constant",
+            Self::Linear(_) => "Linear",
+            Self::Binary(_) => "Binary",
+            Self::BlendOffset(_) => "BlendOffset",
+            Self::BlendAlpha(_) => "BlendAlpha",
+            Self::BlendDensity(_) => "BlendDensity",
+            Self::ClampedY(_) => "ClampedY",
+            Self::InterpolatedNoise(_) => "InterpolatedNoise",
+            Self::EndIsland(_) => "EndIsland",
+            Self::Wierd(_) => "Wierd",
+            Self::Range(_) => "Range",
+            Self::Marker(marker) => marker.kind.type_name(),
+            Self::Profiled(_) => "Profiled",
+        }
+    }
+
+    /// The ids in vanilla's density function registry which are implemented, sorted
+    pub fn registered_types() -> &'static [&'static str] {
+        &[
+            "minecraft:abs",                  // Unary
+            "minecraft:add",                  // Binary, Linear
+            "minecraft:blend_alpha",          // BlendAlpha
+            "minecraft:blend_density",        // BlendDensity
+            "minecraft:blend_offset",         // BlendOffset
+            "minecraft:cache_2d",             // Marker
+            "minecraft:cache_all_in_cell",    // Marker
+            "minecraft:cache_once",           // Marker
+            "minecraft:clamp",                // Clamp
+            "minecraft:constant",             // Constant
+            "minecraft:cube",                 // Unary
+            "minecraft:end_islands",          // EndIsland
+            "minecraft:flat_cache",           // Marker
+            "minecraft:half_negative",        // Unary
+            "minecraft:interpolated",         // Marker
+            "minecraft:max",                  // Binary
+            "minecraft:min",                  // Binary
+            "minecraft:mul",                  // Binary, Linear
+            "minecraft:noise",                // Noise
+            "minecraft:old_blended_noise",    // InterpolatedNoise
+            "minecraft:quarter_negative",     // Unary
+            "minecraft:range_choice",         // Range
+            "minecraft:shift_a",              // ShiftA
+            "minecraft:shift_b",              // ShiftB
+            "minecraft:shifted_noise",        // ShiftedNoise
+            "minecraft:spline",               // Spline
+            "minecraft:square",               // Unary
+            "minecraft:squeeze",              // Unary
+            "minecraft:weird_scaled_sampler", // Wierd
+            "minecraft:y_clamped_gradient",   // ClampedY
+        ]
+    }
+
+    /// Decodes a function without references from vanilla's JSON format, see `DensityFunctionImpl::to_json`.
+    /// Logs why if it can't be decoded, use [`parser::parse_density_function`] to get the error
+    pub fn from_json(json: &serde_json::Value) -> Option<Self> {
+        match parser::parse_density_function(json, &|_| None) {
+            Ok(function) => Some(Arc::unwrap_or_clone(function)),
+            Err(err) => {
+                warn!("Failed to decode density function: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Fills the densities, skipping the whole tree if it reduces to a constant.
+    /// Takes precedence over `DensityFunctionImpl::fill`, so the root and every input filled by it are checked
+    pub fn fill(&self, densities: &mut [f64], applier: &Applier<'a>) {
+        if let Some(value) = self.as_constant() {
+            densities.fill(value);
+        } else {
+            DensityFunctionImpl::fill(self, densities, applier);
+        }
+    }
+
+    /// Samples the root of a tree. If `nan_guard` is set, non finite results are replaced with 0
+    /// and a (rate limited) warning is logged, see `GenerationSettings::nan_guard`
+    pub fn sample_root(&self, pos: &NoisePos, nan_guard: bool) -> f64 {
+        let value = self.sample(pos);
+        if !nan_guard || value.is_finite() {
+            return value;
+        }
+
+        let mut last_warning = LAST_NAN_WARNING.lock();
+        let should_warn = match *last_warning {
+            Some(last) => last.elapsed() >= NAN_WARNING_INTERVAL,
+            None => true,
+        };
+        if should_warn {
+            *last_warning = Some(Instant::now());
+            warn!(
+                "Density function returned {} at ({}, {}, {}), using 0 instead",
+                value,
+                pos.x(),
+                pos.y(),
+                pos.z()
+            );
+        }
+        0f64
+    }
+
+    /// Samples the function at every position, mostly useful to compare against values dumped from vanilla
+    pub fn sample_many(&self, positions: &[NoisePos]) -> Vec<f64> {
+        positions.iter().map(|pos| self.sample(pos)).collect()
+    }
 }
 
 pub struct Unused<'a> {
@@ -897,6 +1093,10 @@ impl<'a> ApplierImpl<'a> for Unused<'a> {
     fn fill(&self, _densities: &mut [f64], _function: &DensityFunction<'a>) {
         todo!()
     }
+
+    fn scratch(&self) -> &ScratchBuffers {
+        unreachable!("Unused is never sampled")
+    }
 }
 
 impl<'a> VisitorImpl<'a> for Unused<'a> {
@@ -911,6 +1111,36 @@ pub enum NoisePos<'a> {
     Todo(Unused<'a>),
 }
 
+impl<'a> NoisePos<'a> {
+    /// Creates an unblended position, the coordinates can be read back through `NoisePosImpl`
+    ///
+    /// ```ignore
+    /// // The density module is private to the crate, see `test_noise_pos`
+    /// let pos = NoisePos::new(1, -64, 3);
+    /// assert_eq!((pos.x(), pos.y(), pos.z()), (1, -64, 3));
+    /// assert_eq!(DensityFunction::constant(2.5).sample(&pos), 2.5);
+    /// ```
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self::Unblended(UnblendedNoisePos::new(x, y, z))
+    }
+
+    /// The position of the block at `x`, `y`, `z`, density functions are sampled in block coordinates
+    pub fn block(x: i32, y: i32, z: i32) -> Self {
+        Self::new(x, y, z)
+    }
+
+    /// The block coordinates back, the inverse of [`Self::block`]
+    pub fn block_pos(&self) -> WorldPosition {
+        WorldPosition(Vector3::new(self.x(), self.y(), self.z()))
+    }
+}
+
+impl From<WorldPosition> for NoisePos<'_> {
+    fn from(pos: WorldPosition) -> Self {
+        Self::block(pos.0.x, pos.0.y, pos.0.z)
+    }
+}
+
 pub struct UnblendedNoisePos {
     x: i32,
     y: i32,
@@ -950,6 +1180,7 @@ pub trait NoisePosImpl {
 
 #[enum_dispatch(ApplierImpl)]
 pub enum Applier<'a> {
+    Cell(CellApplier),
     Todo(Unused<'a>),
 }
 
@@ -958,11 +1189,17 @@ pub trait ApplierImpl<'a> {
     fn at(&self, index: usize) -> NoisePos<'a>;
 
     fn fill(&self, densities: &mut [f64], function: &DensityFunction<'a>);
+
+    /// The buffers for fills which need a second array of densities
+    fn scratch(&self) -> &ScratchBuffers;
 }
 
 #[enum_dispatch(VisitorImpl)]
 pub enum Visitor<'a> {
     Unwrap(UnwrapVisitor),
+    Cache(CacheVisitor),
+    Interpolate(InterpolationVisitor),
+    Profile(ProfileVisitor),
     Todo(Unused<'a>),
 }
 
@@ -971,7 +1208,7 @@ pub struct UnwrapVisitor {}
 impl<'a> VisitorImpl<'a> for UnwrapVisitor {
     fn apply(&self, function: Arc<DensityFunction<'a>>) -> Arc<DensityFunction<'a>> {
         match function.deref() {
-            DensityFunction::Wrapper(wrapper) => wrapper.wrapped(),
+            DensityFunction::Marker(marker) => marker.input(),
             _ => function.clone(),
         }
     }
@@ -997,6 +1234,15 @@ pub trait DensityFunctionImpl<'a> {
     fn min(&self) -> f64;
 
     fn max(&self) -> f64;
+
+    /// Rough relative cost of sampling this function, including its inputs
+    fn estimated_cost(&self) -> u32 {
+        1
+    }
+
+    /// Encodes the function in vanilla's JSON format, which [`DensityFunction::from_json`] decodes again.
+    /// Noises are written inline, as the tree doesn't know their ids
+    fn to_json(&self) -> serde_json::Value;
 }
 
 #[derive(Clone)]
@@ -1030,10 +1276,15 @@ impl<'a> DensityFunctionImpl<'a> for ConstantFunction {
     fn max(&self) -> f64 {
         self.value
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self.value)
+    }
 }
 
-#[derive(Clone)]
-pub enum WrapperType {
+/// The kinds of vanilla's marker functions, which keep the value of their input but tell the filler how to cache
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkerType {
     Cache2D,
     CacheFlat,
     CacheOnce,
@@ -1041,51 +1292,131 @@ pub enum WrapperType {
     CacheCell,
 }
 
-#[derive(Clone)]
-pub struct WrapperFunction<'a> {
+impl MarkerType {
+    /// The name of the marker in vanilla's density function registry
+    pub fn registry_name(&self) -> &'static str {
+        match self {
+            Self::Cache2D => "minecraft:cache_2d",
+            Self::CacheFlat => "minecraft:flat_cache",
+            Self::CacheOnce => "minecraft:cache_once",
+            Self::Interpolated => "minecraft:interpolated",
+            Self::CacheCell => "minecraft:cache_all_in_cell",
+        }
+    }
+
+    pub fn from_registry_name(name: &str) -> Option<Self> {
+        match name {
+            "minecraft:cache_2d" => Some(Self::Cache2D),
+            "minecraft:flat_cache" => Some(Self::CacheFlat),
+            "minecraft:cache_once" => Some(Self::CacheOnce),
+            "minecraft:interpolated" => Some(Self::Interpolated),
+            "minecraft:cache_all_in_cell" => Some(Self::CacheCell),
+            _ => None,
+        }
+    }
+
+    /// The name in the profiler stats, see `DensityFunction::type_name`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Cache2D => "Cache2D",
+            Self::CacheFlat => "CacheFlat",
+            Self::CacheOnce => "CacheOnce",
+            Self::Interpolated => "Interpolated",
+            Self::CacheCell => "CacheCell",
+        }
+    }
+}
+
+/// One of vanilla's marker functions, e.g. `minecraft:interpolated`.
+///
+/// Sampling passes straight through to the input until `CacheVisitor` or `InterpolationVisitor`
+/// give the marker a cache, see `MarkerCache`. The marker stays in the tree either way,
+/// so it can still be inspected and encoded afterwards
+pub struct MarkerFunction<'a> {
+    kind: MarkerType,
     input: Arc<DensityFunction<'a>>,
-    wrapper: WrapperType,
+    cache: MarkerCache,
+    min: f64,
+    max: f64,
 }
 
-impl<'a> WrapperFunction<'a> {
-    pub fn new(input: Arc<DensityFunction<'a>>, wrapper: WrapperType) -> Self {
-        Self { input, wrapper }
+impl<'a> MarkerFunction<'a> {
+    pub fn new(input: Arc<DensityFunction<'a>>, kind: MarkerType) -> Self {
+        Self::with_cache(input, kind, MarkerCache::None)
+    }
+
+    fn with_cache(input: Arc<DensityFunction<'a>>, kind: MarkerType, cache: MarkerCache) -> Self {
+        Self {
+            min: input.min(),
+            max: input.max(),
+            kind,
+            input,
+            cache,
+        }
     }
 
-    pub fn wrapped(&self) -> Arc<DensityFunction<'a>> {
+    pub fn input(&self) -> Arc<DensityFunction<'a>> {
         self.input.clone()
     }
 
-    pub fn wrapper(&self) -> WrapperType {
-        self.wrapper.clone()
+    pub fn kind(&self) -> MarkerType {
+        self.kind
+    }
+}
+
+impl<'a> Clone for MarkerFunction<'a> {
+    /// The cache is not shared with the clone
+    fn clone(&self) -> Self {
+        Self::with_cache(self.input.clone(), self.kind, self.cache.empty_copy())
     }
 }
 
-impl<'a> DensityFunctionImpl<'a> for WrapperFunction<'a> {
+impl<'a> DensityFunctionImpl<'a> for MarkerFunction<'a> {
     fn max(&self) -> f64 {
-        self.input.max()
+        self.max
     }
 
     fn min(&self) -> f64 {
-        self.input.min()
+        self.min
     }
 
     fn sample(&self, pos: &NoisePos) -> f64 {
-        self.input.sample(pos)
+        self.cache.sample(&self.input, pos)
     }
 
     fn apply(&self, visitor: &Visitor<'a>) -> Arc<DensityFunction<'a>> {
-        visitor.apply(Arc::new(DensityFunction::Wrapper(WrapperFunction {
-            input: self.input.apply(visitor),
-            wrapper: self.wrapper.clone(),
-        })))
+        // Every copy starts with an empty cache, see `NoiseRouter::for_chunk`
+        visitor.apply(Arc::new(DensityFunction::Marker(
+            MarkerFunction::with_cache(
+                self.input.apply(visitor),
+                self.kind,
+                self.cache.empty_copy(),
+            ),
+        )))
     }
 
     fn fill(&self, densities: &mut [f64], applier: &Applier<'a>) {
-        self.input.fill(densities, applier)
+        self.cache.fill(&self.input, densities, applier)
+    }
+
+    /// Markers without a cache are free
+    fn estimated_cost(&self) -> u32 {
+        match self.cache {
+            MarkerCache::None => self.input.estimated_cost(),
+            _ => self.input.estimated_cost() + 1,
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": self.kind.registry_name(),
+            "argument": self.input.to_json(),
+        })
     }
 }
 
+/// Vanilla's `minecraft:range_choice`, samples `in_range` where the input is within `min`
+/// (inclusive) to `max` (exclusive) and `out_range` everywhere else
 #[derive(Clone)]
 pub struct RangeFunction<'a> {
     input: Arc<DensityFunction<'a>>,
@@ -1093,6 +1424,8 @@ pub struct RangeFunction<'a> {
     max: f64,
     in_range: Arc<DensityFunction<'a>>,
     out_range: Arc<DensityFunction<'a>>,
+    min_value: f64,
+    max_value: f64,
 }
 
 impl<'a> RangeFunction<'a> {
@@ -1107,6 +1440,8 @@ impl<'a> RangeFunction<'a> {
             input,
             min,
             max,
+            min_value: in_range.min().min(out_range.min()),
+            max_value: in_range.max().max(out_range.max()),
             in_range,
             out_range,
         }
@@ -1135,24 +1470,44 @@ impl<'a> DensityFunctionImpl<'a> for RangeFunction<'a> {
     }
 
     fn apply(&self, visitor: &Visitor<'a>) -> Arc<DensityFunction<'a>> {
-        visitor.apply(Arc::new(DensityFunction::Range(RangeFunction {
-            input: self.input.apply(visitor),
-            min: self.min,
-            max: self.max,
-            in_range: self.in_range.apply(visitor),
-            out_range: self.out_range.apply(visitor),
-        })))
+        visitor.apply(Arc::new(DensityFunction::Range(RangeFunction::new(
+            self.input.apply(visitor),
+            self.min,
+            self.max,
+            self.in_range.apply(visitor),
+            self.out_range.apply(visitor),
+        ))))
     }
 
     fn min(&self) -> f64 {
-        self.in_range.min().min(self.out_range.min())
+        self.min_value
     }
 
     fn max(&self) -> f64 {
-        self.in_range.max().max(self.out_range.max())
+        self.max_value
+    }
+
+    fn estimated_cost(&self) -> u32 {
+        self.input.estimated_cost()
+            + self.in_range.estimated_cost()
+            + self.out_range.estimated_cost()
+            + 1
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "minecraft:range_choice",
+            "input": self.input.to_json(),
+            "min_inclusive": self.min,
+            "max_exclusive": self.max,
+            "when_in_range": self.in_range.to_json(),
+            "when_out_of_range": self.out_range.to_json(),
+        })
     }
 }
 
+/// Vanilla's `minecraft:y_clamped_gradient`, maps `from`..`to` linearly to `from_val`..`to_val`.
+/// Below and above the range the value at the closer end is used
 #[derive(Clone)]
 pub struct YClampedFunction {
     from: i32,
@@ -1170,18 +1525,35 @@ impl YClampedFunction {
             to_val,
         }
     }
-}
 
-impl<'a> DensityFunctionImpl<'a> for YClampedFunction {
-    fn sample(&self, pos: &NoisePos) -> f64 {
+    /// The same gradient with the range cut down to the blocks of the world,
+    /// so `min` and `max` only cover values which can actually be sampled
+    pub fn within(&self, height_limit: &impl HeightLimitViewImpl) -> Self {
+        let clamp = |y: i32| y.clamp(height_limit.bottom_y(), height_limit.top_y() - 1);
+        let (from, to) = (clamp(self.from), clamp(self.to));
+        if from == to {
+            // The world only sees one end of the gradient, which is constant
+            let value = self.sample_y(from);
+            return Self::new(from, from + 1, value, value);
+        }
+        Self::new(from, to, self.sample_y(from), self.sample_y(to))
+    }
+
+    fn sample_y(&self, y: i32) -> f64 {
         clamped_map(
-            pos.y() as f64,
+            y as f64,
             self.from as f64,
             self.to as f64,
             self.from_val,
             self.to_val,
         )
     }
+}
+
+impl<'a> DensityFunctionImpl<'a> for YClampedFunction {
+    fn sample(&self, pos: &NoisePos) -> f64 {
+        self.sample_y(pos.y())
+    }
 
     fn min(&self) -> f64 {
         self.from_val.min(self.to_val)
@@ -1198,6 +1570,16 @@ impl<'a> DensityFunctionImpl<'a> for YClampedFunction {
     fn apply(&self, visitor: &Visitor<'a>) -> Arc<DensityFunction<'a>> {
         visitor.apply(Arc::new(DensityFunction::ClampedY(self.clone())))
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "minecraft:y_clamped_gradient",
+            "from_y": self.from,
+            "to_y": self.to,
+            "from_value": self.from_val,
+            "to_value": self.to_val,
+        })
+    }
 }
 
 pub trait UnaryDensityFunction<'a>: DensityFunctionImpl<'a> {
@@ -1222,9 +1604,9 @@ pub fn lerp_density<'a>(
     if let DensityFunction::Constant(function) = start.as_ref() {
         lerp_density_static_start(delta, function.value, end)
     } else {
-        let function = Arc::new(DensityFunction::Wrapper(WrapperFunction::new(
+        let function = Arc::new(DensityFunction::Marker(MarkerFunction::new(
             delta,
-            WrapperType::CacheOnce,
+            MarkerType::CacheOnce,
         )));
         let function2 = Arc::new(function.mul_const(-1f64).add_const(1f64));
         start.mul(function2).add(Arc::new(end.mul(function)))
@@ -1241,9 +1623,55 @@ pub fn lerp_density_static_start<'a>(
 
 #[cfg(test)]
 mod test {
-    use crate::world_gen::noise::{density::DensityFunctionImpl, BuiltInNoiseParams};
+    use std::sync::Arc;
+
+    use pumpkin_core::math::vector3::Vector3;
+
+    use crate::world_gen::{
+        height_limit::{HeightLimitViewImpl, StandardHeightLimitView},
+        noise::{density::DensityFunctionImpl, BuiltInNoiseParams},
+    };
+
+    use super::{
+        Applier, BuiltInNoiseFunctions, CacheVisitor, CellApplier, ConstantFunction,
+        DensityFunction, InterpolationVisitor, MarkerFunction, MarkerType, NoisePos, NoisePosImpl,
+        RangeFunction, UnblendedNoisePos, Unused, Visitor, YClampedFunction,
+    };
+
+    #[test]
+    fn test_y_clamped() {
+        // Solid below y 0, air above y 64
+        let gradient = YClampedFunction::new(0, 64, 1.0, -1.0);
+        let sample = |y| gradient.sample(&NoisePos::new(7, y, -3));
+        assert_eq!(sample(0), 1.0);
+        assert_eq!(sample(32), 0.0);
+        assert_eq!(sample(48), -0.5);
+        assert_eq!(sample(64), -1.0);
+        // Clamped outside of the range
+        assert_eq!(sample(-1), 1.0);
+        assert_eq!(sample(i32::MIN), 1.0);
+        assert_eq!(sample(65), -1.0);
+        assert_eq!(sample(i32::MAX), -1.0);
+        assert_eq!(gradient.min(), -1.0);
+        assert_eq!(gradient.max(), 1.0);
+
+        // Only the part inside the world is reachable
+        let height_limit = StandardHeightLimitView::new(48, 16);
+        let within = gradient.within(&height_limit);
+        assert_eq!(within.min(), -0.96875);
+        assert_eq!(within.max(), 0.5);
+        for y in [-100, 0, 16, 40, 63, 64, 100] {
+            let y = y.clamp(height_limit.bottom_y(), height_limit.top_y() - 1);
+            assert!((within.sample(&NoisePos::new(0, y, 0)) - sample(y)).abs() < 1e-12);
+        }
 
-    use super::{BuiltInNoiseFunctions, NoisePos, UnblendedNoisePos};
+        // Worlds entirely above the gradient see a constant
+        let within = gradient.within(&StandardHeightLimitView::new(16, 128));
+        assert_eq!(within.min(), -1.0);
+        assert_eq!(within.max(), -1.0);
+        assert_eq!(within.sample(&NoisePos::new(0, 128, 0)), -1.0);
+        assert_eq!(within.sample(&NoisePos::new(0, 143, 0)), -1.0);
+    }
 
     #[test]
     fn test_density_function_correctness() {
@@ -1616,4 +2044,241 @@ mod test {
         assert_eq!(noise_functions.caves_spaghetti_2d_overworld.min(), -1f64);
         assert_eq!(noise_functions.caves_spaghetti_2d_overworld.max(), 1f64);
     }
+
+    #[test]
+    fn test_constant_fill() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+
+        // Not `Constant` variants, but their range collapses to a single value
+        let function = noise_functions
+            .blend_alpha
+            .add(noise_functions.blend_offset.clone())
+            .mul(noise_functions.blend_alpha.clone());
+        assert_eq!(function.as_constant(), Some(1f64));
+        assert_eq!(noise_functions.y.as_constant(), None);
+
+        // The applier panics if it is ever used, so this only passes if the tree is skipped
+        let applier = Applier::Todo(Unused { _x: "" });
+        let mut densities = [0f64; 16];
+        function.fill(&mut densities, &applier);
+
+        for (i, density) in densities.iter().enumerate() {
+            let pos = NoisePos::Unblended(UnblendedNoisePos::new(i as i32, 0, 0));
+            assert_eq!(*density, function.sample(&pos));
+        }
+    }
+
+    #[test]
+    fn test_sample_many() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+
+        let function = noise_functions.y.mul_const(2f64).add_const(-3f64);
+        assert!(matches!(function, DensityFunction::Linear(_)));
+
+        let positions = [(0, 0, 0), (5, -64, 3), (-1, 319, 7)]
+            .map(|(x, y, z)| NoisePos::Unblended(UnblendedNoisePos::new(x, y, z)));
+        assert_eq!(function.sample_many(&positions), [-3f64, -131f64, 635f64]);
+        assert!(function.sample_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_registered_types() {
+        let types = DensityFunction::registered_types();
+        assert!(types.windows(2).all(|pair| pair[0] < pair[1]));
+        for kind in [
+            MarkerType::Cache2D,
+            MarkerType::CacheFlat,
+            MarkerType::CacheOnce,
+            MarkerType::Interpolated,
+            MarkerType::CacheCell,
+        ] {
+            assert!(types.contains(&kind.registry_name()));
+        }
+        for function_type in ["minecraft:add", "minecraft:constant", "minecraft:spline"] {
+            assert!(types.contains(&function_type));
+        }
+        // Not implemented yet
+        assert!(!types.contains(&"minecraft:beardifier"));
+
+        let unsupported = serde_json::json!({ "type": "minecraft:beardifier" });
+        assert!(DensityFunction::from_json(&unsupported).is_none());
+    }
+
+    #[test]
+    fn test_nan_guard() {
+        let pos = NoisePos::Unblended(UnblendedNoisePos::new(1, 2, 3));
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let function = DensityFunction::constant(value);
+            assert_eq!(function.sample_root(&pos, true), 0f64);
+            // Repeated failures are still replaced while the warning is rate limited
+            assert_eq!(function.sample_root(&pos, true), 0f64);
+            assert!(!function.sample_root(&pos, false).is_finite());
+        }
+
+        let function = DensityFunction::constant(-0.5f64);
+        assert_eq!(function.sample_root(&pos, true), -0.5f64);
+    }
+
+    #[test]
+    fn test_estimated_cost() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+
+        assert_eq!(noise_functions.ten.estimated_cost(), 1);
+        assert_eq!(noise_functions.y.estimated_cost(), 1);
+        // Cache markers are free
+        assert_eq!(noise_functions.shift_x.estimated_cost(), 10);
+
+        // Unary(Linear(Binary(shift_x, shift_z)))
+        let function = noise_functions
+            .shift_x
+            .add(noise_functions.shift_z.clone())
+            .mul_const(2f64)
+            .abs();
+        assert_eq!(function.estimated_cost(), 10 + 10 + 1 + 1 + 1);
+    }
+
+    #[test]
+    fn test_noise_pos() {
+        let pos = NoisePos::new(1, -64, 3);
+        assert_eq!((pos.x(), pos.y(), pos.z()), (1, -64, 3));
+
+        let function = DensityFunction::constant(2.5f64);
+        assert_eq!(function.sample(&pos), 2.5f64);
+    }
+
+    #[test]
+    fn test_block_pos() {
+        let pos = NoisePos::block(7, -20, -3);
+        assert_eq!((pos.x(), pos.y(), pos.z()), (7, -20, -3));
+        let block_pos = pos.block_pos();
+        assert_eq!(block_pos.0, Vector3::new(7, -20, -3));
+        let pos = NoisePos::from(block_pos);
+        assert_eq!((pos.x(), pos.y(), pos.z()), (7, -20, -3));
+
+        // 1 at y -64, falling by 1/16 per block to -1 at y -32
+        let gradient = DensityFunction::ClampedY(YClampedFunction::new(-64, -32, 1f64, -1f64));
+        assert_eq!(gradient.sample(&NoisePos::block(100, -64, -100)), 1f64);
+        assert_eq!(gradient.sample(&NoisePos::block(7, -48, -3)), 0f64);
+        assert_eq!(gradient.sample(&NoisePos::block(0, -40, 0)), -0.5f64);
+        assert_eq!(gradient.sample(&NoisePos::block(0, 64, 0)), -1f64);
+    }
+
+    #[test]
+    fn test_marker_json() {
+        let json = serde_json::json!({
+            "type": "minecraft:interpolated",
+            "argument": {
+                "type": "minecraft:cache_all_in_cell",
+                "argument": {
+                    "type": "minecraft:flat_cache",
+                    "argument": {
+                        "type": "minecraft:cache_2d",
+                        "argument": {
+                            "type": "minecraft:cache_once",
+                            "argument": 0.5
+                        }
+                    }
+                }
+            }
+        });
+
+        let function = DensityFunction::from_json(&json).unwrap();
+        let DensityFunction::Marker(marker) = &function else {
+            panic!("Expected a marker");
+        };
+        assert_eq!(marker.kind(), MarkerType::Interpolated);
+        assert_eq!(function.sample(&NoisePos::new(0, 0, 0)), 0.5f64);
+        assert_eq!(function.to_json(), json);
+
+        // Caches don't change the encoding
+        let cached = function
+            .apply(&Visitor::Cache(CacheVisitor {}))
+            .apply(&Visitor::Interpolate(InterpolationVisitor::new(4, 8)));
+        assert_eq!(cached.to_json(), json);
+
+        let unknown = serde_json::json!({"type": "minecraft:unknown", "argument": 1});
+        assert!(DensityFunction::from_json(&unknown).is_none());
+    }
+
+    #[test]
+    fn test_router_json() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+
+        // Splines, shifted noises and markers with caches
+        for function in [
+            &noise_functions.depth_overworld,
+            &noise_functions.factor_overworld_amplified,
+        ] {
+            let function = function.apply(&Visitor::Cache(CacheVisitor {}));
+            let json = function.to_json();
+            let decoded = DensityFunction::from_json(&json).unwrap();
+            assert_eq!(decoded.to_json(), json);
+            for (x, y, z) in [(0, 0, 0), (1000, 64, -500), (-2500, -40, 123)] {
+                let pos = NoisePos::new(x, y, z);
+                assert_eq!(decoded.sample(&pos), function.sample(&pos));
+            }
+        }
+    }
+
+    #[test]
+    fn test_range_choice() {
+        let y = Arc::new(DensityFunction::ClampedY(YClampedFunction::new(
+            -64, 320, -64f64, 320f64,
+        )));
+        let function = DensityFunction::Range(RangeFunction::new(
+            y.clone(),
+            0f64,
+            10f64,
+            Arc::new(DensityFunction::constant(100f64)),
+            y.clone(),
+        ));
+        assert_eq!(function.min(), -64f64);
+        assert_eq!(function.max(), 320f64);
+
+        let expected = |y: i32| {
+            if (0..10).contains(&y) {
+                100f64
+            } else {
+                f64::from(y)
+            }
+        };
+        for y in [-1, 0, 9, 10] {
+            assert_eq!(function.sample(&NoisePos::new(0, y, 0)), expected(y));
+        }
+
+        // The cell spans y -4 to 11, so both boundaries are filled
+        let cell = CellApplier::new(0, -4, 0, 1, 16);
+        let mut densities = [0f64; 16];
+        function.fill(&mut densities, &Applier::Cell(cell));
+        for (i, density) in densities.iter().enumerate() {
+            assert_eq!(*density, expected(i as i32 - 4));
+        }
+    }
+
+    #[test]
+    fn test_deep_tree_bounds() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+
+        // Reading the bounds of a level only reads the cached bounds of its direct children,
+        // so building and querying a deep tree stays linear
+        let mut function = noise_functions.y.as_ref().clone();
+        for _ in 0..1000 {
+            let range = Arc::new(DensityFunction::Range(RangeFunction::new(
+                Arc::new(function.clone()),
+                0f64,
+                1f64,
+                Arc::new(function.clone()),
+                Arc::new(function.add_const(1f64)),
+            )));
+            function = DensityFunction::Marker(MarkerFunction::new(range, MarkerType::CacheOnce));
+        }
+
+        assert_eq!(function.min(), -4064f64);
+        assert_eq!(function.max(), 4062f64 + 1000f64);
+    }
 }
@@ -9,7 +9,7 @@ use crate::world_gen::noise::{
 
 use super::{
     Applier, ApplierImpl, DensityFunction, DensityFunctionImpl, NoisePos, NoisePosImpl, Visitor,
-    VisitorImpl,
+    VisitorImpl, NOISE_COST,
 };
 
 pub(crate) struct InternalNoise<'a> {
@@ -41,6 +41,11 @@ impl<'a> InternalNoise<'a> {
             None => 2f64,
         }
     }
+
+    /// The parameters the noise was created from, written inline
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        self.data.to_json()
+    }
 }
 
 #[derive(Clone)]
@@ -84,6 +89,19 @@ impl<'a> DensityFunctionImpl<'a> for NoiseFunction<'a> {
     fn min(&self) -> f64 {
         -self.max()
     }
+
+    fn estimated_cost(&self) -> u32 {
+        NOISE_COST
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "minecraft:noise",
+            "noise": self.noise.to_json(),
+            "xz_scale": self.xz_scale,
+            "y_scale": self.y_scale,
+        })
+    }
 }
 
 #[derive(Clone)]
@@ -152,8 +170,33 @@ impl<'a> DensityFunctionImpl<'a> for ShiftedNoiseFunction<'a> {
     fn min(&self) -> f64 {
         -self.max()
     }
+
+    fn estimated_cost(&self) -> u32 {
+        self.shift_x.estimated_cost()
+            + self.shift_y.estimated_cost()
+            + self.shift_z.estimated_cost()
+            + NOISE_COST
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "minecraft:shifted_noise",
+            "noise": self.noise.to_json(),
+            "shift_x": self.shift_x.to_json(),
+            "shift_y": self.shift_y.to_json(),
+            "shift_z": self.shift_z.to_json(),
+            "xz_scale": self.xz_scale,
+            "y_scale": self.y_scale,
+        })
+    }
 }
 
+/// Vanilla's `BlendedNoise`, the main terrain noise of `minecraft:old_blended_noise`.
+///
+/// A low resolution "main" octave noise blends between two detailed limit noises, the xz and y
+/// factors stretch the main noise and the smear scale multiplier smooths it vertically
+pub type BlendedNoiseFunction = InterpolatedNoiseSampler;
+
 #[derive(Clone)]
 pub struct InterpolatedNoiseSampler {
     lower: Arc<OctavePerlinNoiseSampler>,
@@ -170,7 +213,11 @@ pub struct InterpolatedNoiseSampler {
 }
 
 impl InterpolatedNoiseSampler {
-    fn create_from_random(
+    /// Seeds the three octave noises with `rand`, vanilla derives it from the world seed
+    /// (`minecraft:terrain` for the xoroshiro random).
+    /// `xz_scale`, `y_scale`, `xz_factor`, `y_factor` and `smear_scale` are the fields of the JSON,
+    /// e.g. 0.25, 0.125, 80, 160 and 8 in the overworld
+    pub fn create_from_random(
         rand: &mut RandomGenerator,
         xz_scale: f64,
         y_scale: f64,
@@ -207,6 +254,7 @@ impl InterpolatedNoiseSampler {
         )
     }
 
+    /// Unseeded like vanilla's `createUnseeded`, the world seed is applied with [`Self::copy_with_random`]
     pub fn create_base_3d_noise_function(
         xz_scale: f64,
         y_scale: f64,
@@ -330,6 +378,11 @@ impl<'a> DensityFunctionImpl<'a> for InterpolatedNoiseSampler {
         -self.max()
     }
 
+    fn estimated_cost(&self) -> u32 {
+        // Samples three octave noises
+        3 * NOISE_COST
+    }
+
     fn fill(&self, densities: &mut [f64], applier: &Applier) {
         applier.fill(densities, &DensityFunction::InterpolatedNoise(self.clone()))
     }
@@ -337,4 +390,110 @@ impl<'a> DensityFunctionImpl<'a> for InterpolatedNoiseSampler {
     fn apply(&self, visitor: &Visitor<'a>) -> Arc<DensityFunction<'a>> {
         visitor.apply(Arc::new(DensityFunction::InterpolatedNoise(self.clone())))
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "minecraft:old_blended_noise",
+            "xz_scale": self.xz_scale,
+            "y_scale": self.y_scale,
+            "xz_factor": self.xz_factor,
+            "y_factor": self.y_factor,
+            "smear_scale_multiplier": self.smear_scale,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pumpkin_core::random::{
+        legacy_rand::LegacyRand, xoroshiro128::Xoroshiro, RandomGenerator, RandomImpl,
+    };
+
+    use crate::world_gen::noise::density::{DensityFunctionImpl, NoisePos};
+
+    use super::BlendedNoiseFunction;
+
+    /// The overworld parameters
+    fn overworld(rand: &mut RandomGenerator) -> BlendedNoiseFunction {
+        BlendedNoiseFunction::create_from_random(rand, 0.25, 0.125, 80.0, 160.0, 8.0)
+    }
+
+    fn assert_samples(function: &BlendedNoiseFunction, expected: &[((i32, i32, i32), f64)]) {
+        for ((x, y, z), value) in expected {
+            let sample = function.sample(&NoisePos::block(*x, *y, *z));
+            assert_eq!(sample, *value, "at {x} {y} {z}");
+            assert!((function.min()..=function.max()).contains(&sample));
+        }
+    }
+
+    /// Checks what holds for vanilla's `BlendedNoise` no matter the exact values
+    #[test]
+    fn test_blended_noise_properties() {
+        let positions = [(0, 0, 0), (100, 64, -50), (-1234, -40, 987), (31, -7, -13)];
+        let sample = |function: &BlendedNoiseFunction| {
+            positions
+                .iter()
+                .map(|(x, y, z)| function.sample(&NoisePos::block(*x, *y, *z)))
+                .collect::<Vec<_>>()
+        };
+
+        let first = overworld(&mut RandomGenerator::Xoroshiro(Xoroshiro::from_seed(42)));
+        let second = overworld(&mut RandomGenerator::Xoroshiro(Xoroshiro::from_seed(42)));
+        let other = overworld(&mut RandomGenerator::Xoroshiro(Xoroshiro::from_seed(43)));
+        assert_eq!(sample(&first), sample(&second));
+        assert_ne!(sample(&first), sample(&other));
+
+        // The range only depends on the parameters, not on the seed
+        assert_eq!(first.max(), other.max());
+        assert_eq!(first.min(), -first.max());
+        for value in sample(&first).into_iter().chain(sample(&other)) {
+            assert!((first.min()..=first.max()).contains(&value));
+        }
+
+        // The unseeded noise is the copy source, reseeding it gives the seeded noise
+        let unseeded =
+            BlendedNoiseFunction::create_base_3d_noise_function(0.25, 0.125, 80.0, 160.0, 8.0);
+        let copy =
+            unseeded.copy_with_random(&mut RandomGenerator::Xoroshiro(Xoroshiro::from_seed(42)));
+        assert_eq!(sample(&copy), sample(&first));
+    }
+
+    /// Regression values from this implementation, not from vanilla: no vanilla server is available to the tests.
+    /// Replace them with values sampled from vanilla's `BlendedNoise` once they have been taken
+    #[test]
+    fn test_blended_noise_regression() {
+        let unseeded =
+            BlendedNoiseFunction::create_base_3d_noise_function(0.25, 0.125, 80.0, 160.0, 8.0);
+        assert_eq!(unseeded.max(), 87.55150000000002);
+        assert_eq!(unseeded.min(), -unseeded.max());
+        assert_samples(
+            &unseeded,
+            &[
+                ((0, 0, 0), 0.05283727086562935),
+                ((100, 64, -50), -0.2813823669592253),
+                ((-1234, -40, 987), 0.04031705442131164),
+            ],
+        );
+
+        let mut rand = RandomGenerator::Xoroshiro(Xoroshiro::from_seed(42));
+        assert_samples(
+            &overworld(&mut rand),
+            &[
+                ((0, 0, 0), 0.0044527116389995736),
+                ((100, 64, -50), 0.2632834767964558),
+                ((-1234, -40, 987), 0.2815213340265571),
+                ((31, -7, -13), 0.41834532768399396),
+            ],
+        );
+
+        let mut rand = RandomGenerator::Legacy(LegacyRand::from_seed(42));
+        assert_samples(
+            &overworld(&mut rand),
+            &[
+                ((0, 0, 0), 0.17498762833457276),
+                ((100, 64, -50), 0.1289511576095448),
+                ((-1234, -40, 987), -0.5204621249511476),
+            ],
+        );
+    }
 }
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use super::{
     noise::InternalNoise, Applier, ApplierImpl, DensityFunction, DensityFunctionImpl, NoisePos,
-    NoisePosImpl, OffsetDensityFunction, Visitor, VisitorImpl,
+    NoisePosImpl, OffsetDensityFunction, Visitor, VisitorImpl, NOISE_COST,
 };
 
 #[derive(Clone)]
@@ -44,6 +44,17 @@ impl<'a> DensityFunctionImpl<'a> for ShiftAFunction<'a> {
     fn min(&self) -> f64 {
         -self.max()
     }
+
+    fn estimated_cost(&self) -> u32 {
+        NOISE_COST
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "minecraft:shift_a",
+            "argument": self.offset.to_json(),
+        })
+    }
 }
 
 #[derive(Clone)]
@@ -85,4 +96,15 @@ impl<'a> DensityFunctionImpl<'a> for ShiftBFunction<'a> {
     fn min(&self) -> f64 {
         -self.max()
     }
+
+    fn estimated_cost(&self) -> u32 {
+        NOISE_COST
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "minecraft:shift_b",
+            "argument": self.offset.to_json(),
+        })
+    }
 }
@@ -0,0 +1,397 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::world_gen::noise::perlin::DoublePerlinNoiseParameters;
+
+use super::{
+    blend::{BlendAlphaFunction, BlendDensityFunction, BlendOffsetFunction},
+    math::{BinaryFunction, BinaryType},
+    noise::{InternalNoise, NoiseFunction, ShiftedNoiseFunction},
+    offset::{ShiftAFunction, ShiftBFunction},
+    spline::{SplineFunction, SplineParseError, SplineValue},
+    unary::{ClampFunction, UnaryFunction, UnaryType},
+    weird::{RarityMapper, WierdScaledFunction},
+    DensityFunction, MarkerFunction, MarkerType, RangeFunction, YClampedFunction,
+};
+
+#[derive(Error, Debug)]
+pub enum DensityParseError {
+    #[error("Unknown density function type {0}")]
+    UnknownType(String),
+    #[error("Density function type {0} can't be parsed yet")]
+    Unsupported(String),
+    #[error("Unable to resolve density function {0}")]
+    UnknownReference(String),
+    #[error("Density function {function_type} has no valid field {field}")]
+    InvalidField {
+        function_type: String,
+        field: &'static str,
+    },
+    #[error("Invalid density function {0}")]
+    Invalid(Value),
+    #[error(transparent)]
+    Spline(#[from] SplineParseError),
+}
+
+/// Like vanilla, ids without a namespace are in the `minecraft` namespace
+fn namespaced(id: &str) -> String {
+    if id.contains(':') {
+        id.to_string()
+    } else {
+        format!("minecraft:{}", id)
+    }
+}
+
+/// Builds a density function tree from vanilla's JSON format, as found in the `density_function` and
+/// `noise_settings` folders of a datapack.
+///
+/// A number is a constant, a string references another density function which is looked up
+/// with `resolve`, e.g. [`super::BuiltInNoiseFunctions::get`]. Everything else is an object with a `type`.
+/// Noises must be inline parameters, they are unseeded like the ones of `BuiltInNoiseFunctions`
+pub fn parse_density_function<'a, F>(
+    json: &Value,
+    resolve: &F,
+) -> Result<Arc<DensityFunction<'a>>, DensityParseError>
+where
+    F: Fn(&str) -> Option<Arc<DensityFunction<'a>>>,
+{
+    if let Some(value) = json.as_f64() {
+        return Ok(Arc::new(DensityFunction::constant(value)));
+    }
+    if let Some(id) = json.as_str() {
+        let id = namespaced(id);
+        return resolve(&id).ok_or(DensityParseError::UnknownReference(id));
+    }
+
+    let Some(function_type) = json.get("type").and_then(Value::as_str) else {
+        return Err(DensityParseError::Invalid(json.clone()));
+    };
+    let function_type = namespaced(function_type);
+    let parser = FieldParser {
+        json,
+        function_type: &function_type,
+        resolve,
+    };
+
+    if let Some(kind) = MarkerType::from_registry_name(&function_type) {
+        return Ok(Arc::new(DensityFunction::Marker(MarkerFunction::new(
+            parser.function("argument")?,
+            kind,
+        ))));
+    }
+
+    let function = match function_type.as_str() {
+        "minecraft:constant" => DensityFunction::constant(parser.number("argument")?),
+        "minecraft:add" => parser.binary(BinaryType::Add)?,
+        "minecraft:mul" => parser.binary(BinaryType::Mul)?,
+        "minecraft:min" => parser.binary(BinaryType::Min)?,
+        "minecraft:max" => parser.binary(BinaryType::Max)?,
+        "minecraft:abs" => parser.unary(UnaryType::Abs)?,
+        "minecraft:square" => parser.unary(UnaryType::Square)?,
+        "minecraft:cube" => parser.unary(UnaryType::Cube)?,
+        "minecraft:half_negative" => parser.unary(UnaryType::HalfNeg)?,
+        "minecraft:quarter_negative" => parser.unary(UnaryType::QuartNeg)?,
+        "minecraft:squeeze" => parser.unary(UnaryType::Squeeze)?,
+        "minecraft:clamp" => {
+            let min = parser.number("min")?;
+            let max = parser.number("max")?;
+            if min > max {
+                return Err(parser.invalid("max"));
+            }
+            DensityFunction::Clamp(ClampFunction {
+                input: parser.function("input")?,
+                min,
+                max,
+            })
+        }
+        "minecraft:y_clamped_gradient" => DensityFunction::ClampedY(YClampedFunction::new(
+            parser.int("from_y")?,
+            parser.int("to_y")?,
+            parser.number("from_value")?,
+            parser.number("to_value")?,
+        )),
+        "minecraft:range_choice" => DensityFunction::Range(RangeFunction::new(
+            parser.function("input")?,
+            parser.number("min_inclusive")?,
+            parser.number("max_exclusive")?,
+            parser.function("when_in_range")?,
+            parser.function("when_out_of_range")?,
+        )),
+        "minecraft:blend_alpha" => DensityFunction::BlendAlpha(BlendAlphaFunction {}),
+        "minecraft:blend_offset" => DensityFunction::BlendOffset(BlendOffsetFunction {}),
+        "minecraft:blend_density" => {
+            DensityFunction::BlendDensity(BlendDensityFunction::new(parser.function("argument")?))
+        }
+        "minecraft:noise" => DensityFunction::Noise(NoiseFunction::new(
+            parser.noise("noise")?,
+            parser.number("xz_scale")?,
+            parser.number("y_scale")?,
+        )),
+        "minecraft:shifted_noise" => DensityFunction::ShiftedNoise(ShiftedNoiseFunction::new(
+            parser.function("shift_x")?,
+            parser.function("shift_y")?,
+            parser.function("shift_z")?,
+            parser.number("xz_scale")?,
+            parser.number("y_scale")?,
+            parser.noise("noise")?,
+        )),
+        "minecraft:shift_a" => {
+            DensityFunction::ShiftA(ShiftAFunction::new(parser.noise("argument")?))
+        }
+        "minecraft:shift_b" => {
+            DensityFunction::ShiftB(ShiftBFunction::new(parser.noise("argument")?))
+        }
+        "minecraft:weird_scaled_sampler" => {
+            let rarity = match json.get("rarity_value_mapper").and_then(Value::as_str) {
+                Some("type_1") => RarityMapper::Tunnels,
+                Some("type_2") => RarityMapper::Caves,
+                _ => return Err(parser.invalid("rarity_value_mapper")),
+            };
+            DensityFunction::Wierd(WierdScaledFunction::new(
+                parser.function("input")?,
+                parser.noise("noise")?,
+                rarity,
+            ))
+        }
+        "minecraft:spline" => {
+            let spline = json.get("spline").ok_or_else(|| parser.invalid("spline"))?;
+            let spline = SplineValue::from_value(spline, &|coordinate| {
+                parse_density_function(coordinate, resolve).ok()
+            })?;
+            match spline {
+                SplineValue::Fixed(value) => DensityFunction::constant(f64::from(value)),
+                SplineValue::Spline(spline) => {
+                    DensityFunction::Spline(SplineFunction::new(Arc::new(spline)))
+                }
+            }
+        }
+        // `old_blended_noise` and `end_islands` are seeded from the world seed, which the JSON doesn't contain
+        _ if DensityFunction::registered_types().contains(&function_type.as_str()) => {
+            return Err(DensityParseError::Unsupported(function_type));
+        }
+        _ => return Err(DensityParseError::UnknownType(function_type)),
+    };
+    Ok(Arc::new(function))
+}
+
+struct FieldParser<'j, 'r, F> {
+    json: &'j Value,
+    function_type: &'j str,
+    resolve: &'r F,
+}
+
+impl<'a, F> FieldParser<'_, '_, F>
+where
+    F: Fn(&str) -> Option<Arc<DensityFunction<'a>>>,
+{
+    fn invalid(&self, field: &'static str) -> DensityParseError {
+        DensityParseError::InvalidField {
+            function_type: self.function_type.to_string(),
+            field,
+        }
+    }
+
+    fn function(&self, field: &'static str) -> Result<Arc<DensityFunction<'a>>, DensityParseError> {
+        let json = self.json.get(field).ok_or_else(|| self.invalid(field))?;
+        parse_density_function(json, self.resolve)
+    }
+
+    fn number(&self, field: &'static str) -> Result<f64, DensityParseError> {
+        self.json
+            .get(field)
+            .and_then(Value::as_f64)
+            .ok_or_else(|| self.invalid(field))
+    }
+
+    fn int(&self, field: &'static str) -> Result<i32, DensityParseError> {
+        self.json
+            .get(field)
+            .and_then(Value::as_i64)
+            .and_then(|value| i32::try_from(value).ok())
+            .ok_or_else(|| self.invalid(field))
+    }
+
+    /// Noise ids can't be resolved yet, only inline parameters
+    fn noise(&self, field: &'static str) -> Result<Arc<InternalNoise<'a>>, DensityParseError> {
+        let json = self.json.get(field).ok_or_else(|| self.invalid(field))?;
+        if json.is_string() {
+            return Err(DensityParseError::Unsupported(
+                self.function_type.to_string(),
+            ));
+        }
+        let parameters =
+            DoublePerlinNoiseParameters::from_json(json).ok_or_else(|| self.invalid(field))?;
+        Ok(Arc::new(InternalNoise::new(parameters, None)))
+    }
+
+    fn binary(&self, action: BinaryType) -> Result<DensityFunction<'a>, DensityParseError> {
+        Ok(BinaryFunction::create(
+            action,
+            self.function("argument1")?,
+            self.function("argument2")?,
+        ))
+    }
+
+    fn unary(&self, action: UnaryType) -> Result<DensityFunction<'a>, DensityParseError> {
+        Ok(DensityFunction::Unary(UnaryFunction::create(
+            action,
+            self.function("argument")?,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::world_gen::noise::{
+        density::{
+            BuiltInNoiseFunctions, DensityFunction, DensityFunctionImpl, NoisePos, YClampedFunction,
+        },
+        BuiltInNoiseParams,
+    };
+
+    use super::{parse_density_function, DensityParseError};
+
+    fn no_references<'a>(_: &str) -> Option<Arc<DensityFunction<'a>>> {
+        None
+    }
+
+    #[test]
+    fn test_parse_tree() {
+        let json = serde_json::json!({
+            "type": "minecraft:clamp",
+            "min": -1.0,
+            "max": 1.0,
+            "input": {
+                "type": "add",
+                "argument1": {
+                    "type": "minecraft:mul",
+                    "argument1": {
+                        "type": "minecraft:y_clamped_gradient",
+                        "from_y": -64,
+                        "to_y": 320,
+                        "from_value": 1.5,
+                        "to_value": -1.5
+                    },
+                    "argument2": {
+                        "type": "minecraft:constant",
+                        "argument": 2.0
+                    }
+                },
+                "argument2": {
+                    "type": "minecraft:quarter_negative",
+                    "argument": "minecraft:shifted_y"
+                }
+            }
+        });
+        let shifted_y = Arc::new(DensityFunction::ClampedY(YClampedFunction::new(
+            0, 100, -1f64, 1f64,
+        )));
+        let resolve = |id: &str| (id == "minecraft:shifted_y").then(|| shifted_y.clone());
+        let function = parse_density_function(&json, &resolve).unwrap();
+
+        let expected = |y: i32| {
+            let gradient = 1.5f64 - 3f64 * f64::from(y + 64) / 384f64;
+            let shifted = (f64::from(y) / 50f64 - 1f64).clamp(-1f64, 1f64);
+            let quarter_negative = if shifted > 0f64 {
+                shifted
+            } else {
+                shifted * 0.25f64
+            };
+            (gradient * 2f64 + quarter_negative).clamp(-1f64, 1f64)
+        };
+        for y in [-64, -10, 0, 25, 64, 100, 200, 320] {
+            let value = function.sample(&NoisePos::new(0, y, 0));
+            assert!((value - expected(y)).abs() < 1e-9, "{} at y {}", value, y);
+        }
+        assert_eq!(function.min(), -1f64);
+        assert_eq!(function.max(), 1f64);
+    }
+
+    #[test]
+    fn test_parse_references() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+        let resolve = |id: &str| noise_functions.get(id);
+
+        let json = serde_json::json!({
+            "type": "minecraft:cache_2d",
+            "argument": {
+                "type": "minecraft:spline",
+                "spline": {
+                    "coordinate": "minecraft:overworld/continents",
+                    "points": [
+                        { "location": -1.0, "value": -0.5, "derivative": 0.0 },
+                        { "location": 1.0, "value": 0.5, "derivative": 0.0 }
+                    ]
+                }
+            }
+        });
+        let function = parse_density_function(&json, &resolve).unwrap();
+        let continents = noise_functions
+            .get("minecraft:overworld/continents")
+            .unwrap();
+        for x in [0, 1000, -2500] {
+            let pos = NoisePos::new(x, 0, x / 2);
+            let continents = continents.sample(&pos);
+            let value = function.sample(&pos);
+            assert!((-0.5f64..=0.5f64).contains(&value));
+            assert_eq!(value > 0f64, continents > 0f64);
+        }
+
+        let missing = serde_json::json!("minecraft:overworld/missing");
+        assert!(matches!(
+            parse_density_function(&missing, &resolve),
+            Err(DensityParseError::UnknownReference(id)) if id == "minecraft:overworld/missing"
+        ));
+        assert!(parse_density_function(&serde_json::json!("y"), &resolve).is_ok());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let unknown = serde_json::json!({ "type": "minecraft:beardifier" });
+        let err = parse_density_function(&unknown, &no_references)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Unknown density function type minecraft:beardifier"
+        );
+
+        let unsupported = serde_json::json!({
+            "type": "minecraft:noise",
+            "noise": "minecraft:erosion",
+            "xz_scale": 1.0,
+            "y_scale": 1.0
+        });
+        assert!(matches!(
+            parse_density_function(&unsupported, &no_references),
+            Err(DensityParseError::Unsupported(_))
+        ));
+
+        let missing_field = serde_json::json!({ "type": "minecraft:add", "argument1": 1.0 });
+        let err = parse_density_function(&missing_field, &no_references)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Density function minecraft:add has no valid field argument2"
+        );
+
+        assert!(matches!(
+            parse_density_function(&serde_json::json!([1, 2]), &no_references),
+            Err(DensityParseError::Invalid(_))
+        ));
+        let empty_spline = serde_json::json!({
+            "type": "minecraft:spline",
+            "spline": { "coordinate": 0.0, "points": [] }
+        });
+        assert!(matches!(
+            parse_density_function(&empty_spline, &no_references),
+            Err(DensityParseError::Spline(_))
+        ));
+    }
+}
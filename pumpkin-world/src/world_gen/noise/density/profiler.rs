@@ -0,0 +1,323 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, LazyLock, Weak,
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use super::{Applier, DensityFunction, DensityFunctionImpl, NoisePos, Visitor, VisitorImpl};
+
+type Stats = BTreeMap<&'static str, ProfileStats>;
+
+/// The profiler used by the world generation, see `GenerationSettings::profile`
+pub static DENSITY_PROFILER: LazyLock<Arc<DensityProfiler>> =
+    LazyLock::new(|| Arc::new(DensityProfiler::default()));
+
+static NEXT_PROFILER_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// The stats this thread recorded, per profiler id. The profiler owns them, so the entries of
+    /// dropped profilers can't be upgraded anymore and get pruned
+    static THREAD_STATS: RefCell<Vec<(usize, Weak<Mutex<Stats>>)>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ProfileStats {
+    pub calls: u64,
+    /// Includes the time spent in the inputs
+    pub total: Duration,
+}
+
+/// Collects the time spent sampling density functions, per function type.
+///
+/// Every thread records into its own stats, they are only merged when they are read
+pub struct DensityProfiler {
+    id: usize,
+    threads: Mutex<Vec<Arc<Mutex<Stats>>>>,
+}
+
+impl Default for DensityProfiler {
+    fn default() -> Self {
+        Self {
+            id: NEXT_PROFILER_ID.fetch_add(1, Ordering::Relaxed),
+            threads: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl DensityProfiler {
+    /// The stats of the current thread, the first call of each thread registers them
+    fn thread_stats(&self) -> Arc<Mutex<Stats>> {
+        THREAD_STATS.with_borrow_mut(|threads| {
+            if let Some(stats) = threads
+                .iter()
+                .find(|(id, _)| *id == self.id)
+                .and_then(|(_, stats)| stats.upgrade())
+            {
+                return stats;
+            }
+            // Profilers dropped while this thread wasn't running can only be cleaned up here
+            threads.retain(|(_, stats)| stats.strong_count() > 0);
+            let stats = Arc::new(Mutex::new(Stats::new()));
+            self.threads.lock().push(stats.clone());
+            threads.push((self.id, Arc::downgrade(&stats)));
+            stats
+        })
+    }
+
+    fn record(&self, name: &'static str, elapsed: Duration) {
+        let stats = self.thread_stats();
+        // Only contended while the stats are read
+        let mut stats = stats.lock();
+        let entry = stats.entry(name).or_default();
+        entry.calls += 1;
+        entry.total += elapsed;
+    }
+
+    /// The stats of every function type which was sampled so far, summed over all threads
+    pub fn stats(&self) -> BTreeMap<&'static str, ProfileStats> {
+        let mut merged = Stats::new();
+        for stats in self.threads.lock().iter() {
+            for (name, stats) in stats.lock().iter() {
+                let entry = merged.entry(*name).or_default();
+                entry.calls += stats.calls;
+                entry.total += stats.total;
+            }
+        }
+        merged
+    }
+
+    /// A table of the stats, with the most expensive function type first
+    pub fn report(&self) -> String {
+        let mut stats: Vec<_> = self.stats().into_iter().collect();
+        stats.sort_by(|(_, a), (_, b)| b.total.cmp(&a.total));
+
+        let mut report = String::from("Density function profile (type, calls, total time):");
+        for (name, stats) in stats {
+            let _ = write!(
+                report,
+                "\n{}: {} calls, {:?}",
+                name, stats.calls, stats.total
+            );
+        }
+        report
+    }
+
+    pub fn reset(&self) {
+        for stats in self.threads.lock().iter() {
+            stats.lock().clear();
+        }
+    }
+}
+
+impl Drop for DensityProfiler {
+    fn drop(&mut self) {
+        // The entries of other threads get pruned by their next `thread_stats`. The thread local
+        // is gone already if this runs while the thread exits
+        let _ = THREAD_STATS.try_with(|threads| {
+            threads.borrow_mut().retain(|(id, _)| *id != self.id);
+        });
+    }
+}
+
+/// Measures the calls to its input, inserted by `ProfileVisitor`
+#[derive(Clone)]
+pub struct ProfiledFunction<'a> {
+    input: Arc<DensityFunction<'a>>,
+    name: &'static str,
+    profiler: Arc<DensityProfiler>,
+}
+
+impl<'a> DensityFunctionImpl<'a> for ProfiledFunction<'a> {
+    fn sample(&self, pos: &NoisePos) -> f64 {
+        let start = Instant::now();
+        let value = self.input.sample(pos);
+        self.profiler.record(self.name, start.elapsed());
+        value
+    }
+
+    fn fill(&self, densities: &mut [f64], applier: &Applier<'a>) {
+        let start = Instant::now();
+        self.input.fill(densities, applier);
+        self.profiler.record(self.name, start.elapsed());
+    }
+
+    fn apply(&self, visitor: &Visitor<'a>) -> Arc<DensityFunction<'a>> {
+        let input = self.input.apply(visitor);
+        // Applying the profiler again must not measure everything twice
+        if matches!(input.as_ref(), DensityFunction::Profiled(_)) {
+            return input;
+        }
+        visitor.apply(Arc::new(DensityFunction::Profiled(ProfiledFunction {
+            input,
+            name: self.name,
+            profiler: self.profiler.clone(),
+        })))
+    }
+
+    fn min(&self) -> f64 {
+        self.input.min()
+    }
+
+    fn max(&self) -> f64 {
+        self.input.max()
+    }
+
+    fn estimated_cost(&self) -> u32 {
+        self.input.estimated_cost()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        // Vanilla has no profiler, it is transparent
+        self.input.to_json()
+    }
+}
+
+/// Wraps every function of a tree with a `ProfiledFunction`, the sampled values don't change
+pub struct ProfileVisitor {
+    profiler: Arc<DensityProfiler>,
+}
+
+impl ProfileVisitor {
+    pub fn new(profiler: Arc<DensityProfiler>) -> Self {
+        Self { profiler }
+    }
+}
+
+impl<'a> VisitorImpl<'a> for ProfileVisitor {
+    fn apply(&self, function: Arc<DensityFunction<'a>>) -> Arc<DensityFunction<'a>> {
+        if matches!(function.as_ref(), DensityFunction::Profiled(_)) {
+            return function;
+        }
+        Arc::new(DensityFunction::Profiled(ProfiledFunction {
+            name: function.type_name(),
+            input: function,
+            profiler: self.profiler.clone(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{sync::Arc, time::Duration};
+
+    use crate::world_gen::noise::{
+        density::{DensityFunctionImpl, NoisePos, Visitor},
+        BuiltInNoiseFunctions, BuiltInNoiseParams,
+    };
+
+    use super::{DensityProfiler, ProfileVisitor, THREAD_STATS};
+
+    #[test]
+    fn test_profiler() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+        let function = noise_functions
+            .base_3d_noise_overworld()
+            .add(noise_functions.y().clone());
+
+        let profiler = Arc::new(DensityProfiler::default());
+        let profiled = function.apply(&Visitor::Profile(ProfileVisitor::new(profiler.clone())));
+        // Applying it again doesn't wrap twice
+        let profiled = profiled.apply(&Visitor::Profile(ProfileVisitor::new(profiler.clone())));
+
+        let positions: Vec<NoisePos> = (0..100).map(|i| NoisePos::new(i, i - 64, -i)).collect();
+        assert_eq!(
+            function.sample_many(&positions),
+            profiled.sample_many(&positions)
+        );
+
+        let stats = profiler.stats();
+        for name in ["Binary", "InterpolatedNoise", "ClampedY"] {
+            assert_eq!(stats[name].calls, 100);
+        }
+        assert!(stats["Binary"].total > Duration::ZERO);
+        assert!(stats["Binary"].total >= stats["InterpolatedNoise"].total);
+        assert!(profiler.report().contains("InterpolatedNoise: 100 calls"));
+
+        profiler.reset();
+        // Functions built without the visitor are not measured
+        function.sample_many(&positions);
+        assert!(profiler.stats().is_empty());
+    }
+
+    #[test]
+    fn test_profiler_threads() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+
+        let profiler = Arc::new(DensityProfiler::default());
+        let function = noise_functions
+            .y()
+            .apply(&Visitor::Profile(ProfileVisitor::new(profiler.clone())));
+
+        // Every thread records on its own, reading the stats sums them up
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for y in 0..25 {
+                        function.sample(&NoisePos::new(0, y, 0));
+                    }
+                });
+            }
+        });
+        function.sample(&NoisePos::new(0, 0, 0));
+        assert_eq!(profiler.stats()["ClampedY"].calls, 101);
+
+        // Another profiler doesn't see these stats
+        assert!(DensityProfiler::default().stats().is_empty());
+        profiler.reset();
+        assert!(profiler.stats().is_empty());
+    }
+
+    #[test]
+    fn test_profiler_drop() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+        let thread_entries = || THREAD_STATS.with_borrow(Vec::len);
+
+        let profiler = Arc::new(DensityProfiler::default());
+        let function = noise_functions
+            .y()
+            .apply(&Visitor::Profile(ProfileVisitor::new(profiler.clone())));
+        let (sampled, dropped) = std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    function.sample(&NoisePos::new(0, 0, 0));
+                    let sampled = thread_entries();
+                    drop(function);
+                    drop(profiler);
+                    (sampled, thread_entries())
+                })
+                .join()
+                .unwrap()
+        });
+        // Dropping the profiler on the thread which recorded removes its entry
+        assert_eq!(sampled, 1);
+        assert_eq!(dropped, 0);
+
+        // Entries of profilers dropped on other threads get pruned by the next new entry
+        let other = Arc::new(DensityProfiler::default());
+        let function = noise_functions
+            .y()
+            .apply(&Visitor::Profile(ProfileVisitor::new(other.clone())));
+        function.sample(&NoisePos::new(0, 0, 0));
+        std::thread::scope(|scope| {
+            scope.spawn(move || drop((function, other)));
+        });
+        assert_eq!(thread_entries(), 1);
+        let new = DensityProfiler::default();
+        new.record("Test", Duration::ZERO);
+        assert_eq!(
+            THREAD_STATS
+                .with_borrow(|threads| threads.iter().map(|(id, _)| *id).collect::<Vec<_>>()),
+            [new.id]
+        );
+    }
+}
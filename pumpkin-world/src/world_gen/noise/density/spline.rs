@@ -1,5 +1,8 @@
 use std::sync::Arc;
 
+use serde::Deserialize;
+use thiserror::Error;
+
 use crate::world_gen::noise::lerp;
 
 use super::{
@@ -39,6 +42,14 @@ impl<'a> SplineValue<'a> {
             Self::Spline(spline) => Self::Spline(spline.visit(visitor)),
         }
     }
+
+    /// The inverse of [`Self::from_json`], the coordinates are written inline
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::Fixed(value) => serde_json::json!(value),
+            Self::Spline(spline) => spline.to_json(),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -109,7 +120,7 @@ impl<'a> Spline<'a> {
             let l = Self::sample_outside_range(h, points[0].value.max(), points, 0);
 
             f = f.min(k.min(l));
-            g = f.max(k.max(l));
+            g = g.max(k.max(l));
         }
 
         if j > points[i].location {
@@ -203,6 +214,38 @@ impl<'a> Spline<'a> {
         }
     }
 
+    /// The location function plus every nested spline, see `DensityFunctionImpl::estimated_cost`
+    pub fn estimated_cost(&self) -> u32 {
+        self.function.estimated_cost()
+            + self
+                .points
+                .iter()
+                .map(|point| match point.value.as_ref() {
+                    SplineValue::Spline(spline) => spline.estimated_cost(),
+                    SplineValue::Fixed(_) => 0,
+                })
+                .sum::<u32>()
+            + 1
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let points = self
+            .points
+            .iter()
+            .map(|point| {
+                serde_json::json!({
+                    "location": point.location,
+                    "value": point.value.to_json(),
+                    "derivative": point.derivative,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({
+            "coordinate": self.function.to_json(),
+            "points": points,
+        })
+    }
+
     pub fn visit(&self, visitor: &Visitor<'a>) -> Spline<'a> {
         let new_function = visitor.apply(self.function.clone());
         let new_points = self
@@ -249,9 +292,20 @@ impl<'a> DensityFunctionImpl<'a> for SplineFunction<'a> {
         self.spline.max as f64
     }
 
+    fn estimated_cost(&self) -> u32 {
+        self.spline.estimated_cost()
+    }
+
     fn min(&self) -> f64 {
         self.spline.min as f64
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "minecraft:spline",
+            "spline": self.spline.to_json(),
+        })
+    }
 }
 
 #[derive(Clone)]
@@ -330,15 +384,177 @@ impl<'a> SplineBuilder<'a> {
     }
 }
 
+#[derive(Error, Debug)]
+pub enum SplineParseError {
+    #[error("Failed to deserialize spline: {0}")]
+    Json(serde_json::Error),
+    #[error("Unable to resolve spline coordinate {0}")]
+    UnknownCoordinate(serde_json::Value),
+    #[error("Spline has no points")]
+    NoPoints,
+    #[error("Spline points must be in ascending order")]
+    UnorderedPoints,
+}
+
+/// The raw vanilla JSON representation of a spline value: either a bare number or a nested
+/// spline object.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SplineValueJson {
+    Fixed(f32),
+    Spline(SplineJson),
+}
+
+#[derive(Deserialize)]
+struct SplineJson {
+    coordinate: serde_json::Value,
+    points: Vec<SplinePointJson>,
+}
+
+#[derive(Deserialize)]
+struct SplinePointJson {
+    location: f32,
+    value: SplineValueJson,
+    derivative: f32,
+}
+
+impl<'a> SplineValue<'a> {
+    /// Parses a spline from its vanilla JSON structure.
+    ///
+    /// `resolve` maps the `coordinate` of every (nested) spline to the density function that drives it.
+    pub fn from_json<F>(json: &str, resolve: &F) -> Result<Self, SplineParseError>
+    where
+        F: Fn(&serde_json::Value) -> Option<Arc<DensityFunction<'a>>>,
+    {
+        let raw: SplineValueJson = serde_json::from_str(json).map_err(SplineParseError::Json)?;
+        Self::from_raw(raw, resolve)
+    }
+
+    /// Like [`Self::from_json`], for a spline which is already part of a parsed JSON document
+    pub fn from_value<F>(json: &serde_json::Value, resolve: &F) -> Result<Self, SplineParseError>
+    where
+        F: Fn(&serde_json::Value) -> Option<Arc<DensityFunction<'a>>>,
+    {
+        let raw = SplineValueJson::deserialize(json).map_err(SplineParseError::Json)?;
+        Self::from_raw(raw, resolve)
+    }
+
+    fn from_raw<F>(raw: SplineValueJson, resolve: &F) -> Result<Self, SplineParseError>
+    where
+        F: Fn(&serde_json::Value) -> Option<Arc<DensityFunction<'a>>>,
+    {
+        match raw {
+            SplineValueJson::Fixed(value) => Ok(Self::Fixed(value)),
+            SplineValueJson::Spline(spline) => {
+                if spline.points.is_empty() {
+                    return Err(SplineParseError::NoPoints);
+                }
+
+                let function = resolve(&spline.coordinate)
+                    .ok_or(SplineParseError::UnknownCoordinate(spline.coordinate))?;
+
+                let mut points: Vec<SplinePoint<'a>> = Vec::with_capacity(spline.points.len());
+                for point in spline.points {
+                    if let Some(last) = points.last() {
+                        if point.location <= last.location {
+                            return Err(SplineParseError::UnorderedPoints);
+                        }
+                    }
+
+                    points.push(SplinePoint {
+                        location: point.location,
+                        value: Arc::new(Self::from_raw(point.value, resolve)?),
+                        derivative: point.derivative,
+                    });
+                }
+
+                Ok(Self::Spline(Spline::new(function, &points)))
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use std::sync::Arc;
 
     use crate::world_gen::noise::{
-        density::{BuiltInNoiseFunctions, NoisePos, UnblendedNoisePos},
+        density::{
+            BuiltInNoiseFunctions, DensityFunction, NoisePos, UnblendedNoisePos, YClampedFunction,
+        },
         BuiltInNoiseParams,
     };
 
-    use super::{FloatAmplifier, SplineBuilder};
+    use super::{FloatAmplifier, Spline, SplineBuilder, SplineParseError, SplineValue};
+
+    /// The block y from -64 to 320, so the tests can pick the location of the spline
+    fn y<'a>() -> Arc<DensityFunction<'a>> {
+        Arc::new(DensityFunction::ClampedY(YClampedFunction::new(
+            -64, 320, -64f64, 320f64,
+        )))
+    }
+
+    fn at(spline: &Spline, y: i32) -> f32 {
+        spline.apply(&NoisePos::new(0, y, 0))
+    }
+
+    fn assert_bounds(spline: &Spline) {
+        for y in -64..=320 {
+            let value = at(spline, y);
+            assert!(
+                spline.min <= value && value <= spline.max,
+                "{} at y {} is outside of {}..{}",
+                value,
+                y,
+                spline.min,
+                spline.max
+            );
+        }
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let spline = SplineBuilder::new(y(), FloatAmplifier::Identity)
+            .add_value(0f32, 0f32, 1f32)
+            .add_value(10f32, 10f32, 0f32)
+            .build();
+
+        assert_eq!(at(&spline, 0), 0f32);
+        assert_eq!(at(&spline, 10), 10f32);
+        // Cubic between the points, lerp(0.5, 0, 10) + 0.25 * lerp(0.5, 0, 10)
+        assert_eq!(at(&spline, 5), 6.25f32);
+        // Extended linearly with the derivative of the outer points
+        assert_eq!(at(&spline, -4), -4f32);
+        assert_eq!(at(&spline, 20), 10f32);
+
+        assert_eq!(spline.min, -64f32);
+        // Vanilla's bounds between the points are an estimate
+        assert_eq!(spline.max, 12.5f32);
+        assert_bounds(&spline);
+    }
+
+    #[test]
+    fn test_nested() {
+        let inner = SplineBuilder::new(y(), FloatAmplifier::Identity)
+            .add_value(0f32, 1f32, 0f32)
+            .add_value(100f32, 3f32, 0f32)
+            .build();
+        let spline = SplineBuilder::new(y(), FloatAmplifier::Identity)
+            .add_value(0f32, 0f32, 1f32)
+            .add_spline(10f32, SplineValue::Spline(inner), 0f32)
+            .build();
+
+        // The nested spline is sampled at the same position
+        let inner = 1.1f32 + 0.05 * 0.95 * -1.8;
+        let expected = 0.5 * inner + 0.25 * 5f32;
+        assert!((at(&spline, 5) - expected).abs() < 1e-5);
+        assert_eq!(at(&spline, 50), 2f32);
+        assert_eq!(at(&spline, 300), 3f32);
+
+        assert_eq!(spline.min, -64f32);
+        assert_eq!(spline.max, 5.25f32);
+        assert_bounds(&spline);
+    }
 
     #[test]
     fn test_correctness() {
@@ -359,4 +575,65 @@ mod test {
 
         assert_eq!(spline.apply(&pos), -0.12f32);
     }
+
+    #[test]
+    fn test_from_json() {
+        let noise_params = BuiltInNoiseParams::new();
+        let noise_functions = BuiltInNoiseFunctions::new(&noise_params);
+        let pos = NoisePos::Unblended(UnblendedNoisePos { x: 0, y: 0, z: 0 });
+
+        let json = r#"{
+            "coordinate": "minecraft:overworld/continents",
+            "points": [
+                {"location": -1.1, "value": 0.044, "derivative": 0.0},
+                {"location": -1.02, "value": -0.2222, "derivative": 0.0},
+                {"location": -0.51, "value": -0.2222, "derivative": 0.0},
+                {"location": -0.44, "value": -0.12, "derivative": 0.0},
+                {
+                    "location": -0.18,
+                    "value": {
+                        "coordinate": "minecraft:overworld/continents",
+                        "points": [{"location": 0.0, "value": -0.12, "derivative": 0.0}]
+                    },
+                    "derivative": 0.0
+                }
+            ]
+        }"#;
+
+        let resolve = |coordinate: &serde_json::Value| {
+            (coordinate == "minecraft:overworld/continents")
+                .then(|| noise_functions.continents_overworld.clone())
+        };
+
+        let SplineValue::Spline(spline) = SplineValue::from_json(json, &resolve).unwrap() else {
+            panic!("Expected a spline");
+        };
+        let built = SplineBuilder::new(
+            noise_functions.continents_overworld.clone(),
+            FloatAmplifier::Identity,
+        )
+        .add_value(-1.1f32, 0.044f32, 0f32)
+        .add_value(-1.02f32, -0.2222f32, 0f32)
+        .add_value(-0.51f32, -0.2222f32, 0f32)
+        .add_value(-0.44f32, -0.12f32, 0f32)
+        .add_value(-0.18f32, -0.12f32, 0f32)
+        .build();
+
+        assert_eq!(spline.apply(&pos), -0.12f32);
+        assert_eq!(spline.apply(&pos), built.apply(&pos));
+        assert_eq!(spline.min, built.min);
+        assert_eq!(spline.max, built.max);
+
+        assert!(matches!(
+            SplineValue::from_json(r#"{"coordinate": "unknown", "points": []}"#, &resolve),
+            Err(SplineParseError::NoPoints)
+        ));
+        assert!(matches!(
+            SplineValue::from_json(
+                r#"{"coordinate": "unknown", "points": [{"location": 0.0, "value": 1.0, "derivative": 0.0}]}"#,
+                &resolve
+            ),
+            Err(SplineParseError::UnknownCoordinate(_))
+        ));
+    }
 }
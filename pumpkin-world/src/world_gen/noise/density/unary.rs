@@ -37,6 +37,7 @@ impl<'a> DensityFunctionImpl<'a> for ClampFunction<'a> {
         });
     }
 
+    /// The clamp bounds, like vanilla even if the input never reaches them
     fn min(&self) -> f64 {
         self.min
     }
@@ -44,6 +45,19 @@ impl<'a> DensityFunctionImpl<'a> for ClampFunction<'a> {
     fn max(&self) -> f64 {
         self.max
     }
+
+    fn estimated_cost(&self) -> u32 {
+        self.input.estimated_cost() + 1
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "minecraft:clamp",
+            "input": self.input.to_json(),
+            "min": self.min,
+            "max": self.max,
+        })
+    }
 }
 
 #[derive(Clone)]
@@ -56,6 +70,19 @@ pub(crate) enum UnaryType {
     Squeeze,
 }
 
+impl UnaryType {
+    fn registry_name(&self) -> &'static str {
+        match self {
+            Self::Abs => "minecraft:abs",
+            Self::Square => "minecraft:square",
+            Self::Cube => "minecraft:cube",
+            Self::HalfNeg => "minecraft:half_negative",
+            Self::QuartNeg => "minecraft:quarter_negative",
+            Self::Squeeze => "minecraft:squeeze",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct UnaryFunction<'a> {
     action: UnaryType,
@@ -142,4 +169,131 @@ impl<'a> DensityFunctionImpl<'a> for UnaryFunction<'a> {
     fn min(&self) -> f64 {
         self.min
     }
+
+    fn estimated_cost(&self) -> u32 {
+        self.input.estimated_cost() + 1
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": self.action.registry_name(),
+            "argument": self.input.to_json(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::world_gen::noise::density::{
+        cache::CellApplier, Applier, ApplierImpl, DensityFunction, DensityFunctionImpl, NoisePos,
+        NoisePosImpl, UnaryDensityFunction, YClampedFunction,
+    };
+
+    use super::{UnaryFunction, UnaryType};
+
+    fn y() -> DensityFunction<'static> {
+        DensityFunction::ClampedY(YClampedFunction::new(-64, 320, -64f64, 320f64))
+    }
+
+    #[test]
+    fn test_clamp() {
+        let function = y().clamp(-10f64, 10f64);
+        assert_eq!(function.sample(&NoisePos::new(0, -50, 0)), -10f64);
+        assert_eq!(function.sample(&NoisePos::new(0, 50, 0)), 10f64);
+        assert_eq!(function.sample(&NoisePos::new(0, 3, 0)), 3f64);
+
+        let cell = CellApplier::new(0, -16, 0, 1, 32);
+        let mut densities = vec![0f64; cell.size()];
+        function.fill(&mut densities, &Applier::Cell(cell.clone()));
+        for (i, density) in densities.iter().enumerate() {
+            let y = ApplierImpl::at(&cell, i).y();
+            assert_eq!(*density, f64::from(y).clamp(-10f64, 10f64));
+        }
+    }
+
+    #[test]
+    fn test_clamp_bounds() {
+        let function = y().clamp(-10f64, 10f64);
+        assert_eq!(function.min(), -10f64);
+        assert_eq!(function.max(), 10f64);
+
+        // Bounds wider than the input are kept
+        let function = y().clamp(-1000f64, 1000f64);
+        assert_eq!(function.min(), -1000f64);
+        assert_eq!(function.max(), 1000f64);
+    }
+
+    /// Checks `apply_density` for the given inputs and the bounds derived from the `-64..=320` input
+    fn check(action: UnaryType, values: &[(f64, f64)], min: f64, max: f64) {
+        let function = UnaryFunction::create(action, Arc::new(y()));
+        for (input, expected) in values {
+            assert_eq!(function.apply_density(*input), *expected, "input {}", input);
+        }
+        assert_eq!(function.min(), min);
+        assert_eq!(function.max(), max);
+    }
+
+    #[test]
+    fn test_abs() {
+        // Like vanilla, the lower bound is the clamped input bound instead of the real minimum
+        check(
+            UnaryType::Abs,
+            &[(-3.0, 3.0), (2.5, 2.5), (0.0, 0.0)],
+            0.0,
+            320.0,
+        );
+    }
+
+    #[test]
+    fn test_square() {
+        check(
+            UnaryType::Square,
+            &[(-3.0, 9.0), (0.5, 0.25)],
+            0.0,
+            320.0 * 320.0,
+        );
+    }
+
+    #[test]
+    fn test_cube() {
+        check(
+            UnaryType::Cube,
+            &[(-2.0, -8.0), (3.0, 27.0)],
+            -64.0 * 64.0 * 64.0,
+            320.0 * 320.0 * 320.0,
+        );
+    }
+
+    #[test]
+    fn test_half_negative() {
+        check(
+            UnaryType::HalfNeg,
+            &[(-4.0, -2.0), (3.0, 3.0)],
+            -32.0,
+            320.0,
+        );
+    }
+
+    #[test]
+    fn test_quarter_negative() {
+        check(
+            UnaryType::QuartNeg,
+            &[(-4.0, -1.0), (3.0, 3.0)],
+            -16.0,
+            320.0,
+        );
+    }
+
+    #[test]
+    fn test_squeeze() {
+        let edge = 0.5 - 1.0 / 24.0;
+        check(
+            UnaryType::Squeeze,
+            &[(0.5, 0.25 - 0.125 / 24.0), (2.0, edge), (-2.0, -edge)],
+            -edge,
+            edge,
+        );
+    }
 }
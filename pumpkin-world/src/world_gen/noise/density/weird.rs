@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use super::{
     noise::InternalNoise, Applier, ApplierImpl, DensityFunction, DensityFunctionImpl, NoisePos,
-    NoisePosImpl, Visitor, VisitorImpl,
+    NoisePosImpl, Visitor, VisitorImpl, NOISE_COST,
 };
 
 #[derive(Clone)]
@@ -12,6 +12,14 @@ pub enum RarityMapper {
 }
 
 impl RarityMapper {
+    /// The `rarity_value_mapper` field of vanilla's JSON
+    pub fn registry_name(&self) -> &'static str {
+        match self {
+            Self::Tunnels => "type_1",
+            Self::Caves => "type_2",
+        }
+    }
+
     #[inline]
     pub fn max_multiplier(&self) -> f64 {
         match self {
@@ -89,6 +97,10 @@ impl<'a> DensityFunctionImpl<'a> for WierdScaledFunction<'a> {
         0f64
     }
 
+    fn estimated_cost(&self) -> u32 {
+        self.input.estimated_cost() + NOISE_COST
+    }
+
     fn apply(&self, visitor: &Visitor<'a>) -> Arc<DensityFunction<'a>> {
         visitor.apply(Arc::new(DensityFunction::Wierd(WierdScaledFunction {
             input: self.input.apply(visitor),
@@ -107,4 +119,13 @@ impl<'a> DensityFunctionImpl<'a> for WierdScaledFunction<'a> {
             *val = self.apply_loc(&applier.at(i), *val);
         });
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "minecraft:weird_scaled_sampler",
+            "rarity_value_mapper": self.rarity.registry_name(),
+            "input": self.input.to_json(),
+            "noise": self.noise.to_json(),
+        })
+    }
 }
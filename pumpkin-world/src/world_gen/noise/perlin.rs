@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use itertools::Itertools;
 use num_traits::{Pow, Zero};
 use pumpkin_core::random::{RandomDeriverImpl, RandomGenerator, RandomImpl};
@@ -329,19 +331,49 @@ impl OctavePerlinNoiseSampler {
     }
 }
 
+/// Vanilla's noise parameters, the built-in ones borrow their amplitudes and parsed ones own them
 #[derive(Clone)]
 pub struct DoublePerlinNoiseParameters<'a> {
     first_octave: i32,
-    amplitudes: &'a [f64],
+    amplitudes: Cow<'a, [f64]>,
 }
 
 impl<'a> DoublePerlinNoiseParameters<'a> {
     pub fn new(first_octave: i32, amplitudes: &'a [f64]) -> Self {
         Self {
             first_octave,
-            amplitudes,
+            amplitudes: Cow::Borrowed(amplitudes),
         }
     }
+
+    /// Decodes vanilla's JSON format, `{"firstOctave": -7, "amplitudes": [1.0, 1.0]}`
+    pub fn from_json(json: &serde_json::Value) -> Option<Self> {
+        let first_octave = json
+            .get("firstOctave")
+            .and_then(serde_json::Value::as_i64)
+            .and_then(|octave| i32::try_from(octave).ok())?;
+        let amplitudes = json
+            .get("amplitudes")?
+            .as_array()?
+            .iter()
+            .map(serde_json::Value::as_f64)
+            .collect::<Option<Vec<f64>>>()?;
+        if amplitudes.is_empty() {
+            return None;
+        }
+        Some(Self {
+            first_octave,
+            amplitudes: Cow::Owned(amplitudes),
+        })
+    }
+
+    /// Encodes the parameters in vanilla's JSON format, see `from_json`
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "firstOctave": self.first_octave,
+            "amplitudes": self.amplitudes,
+        })
+    }
 }
 
 pub struct DoublePerlinNoiseSampler {
@@ -362,7 +394,7 @@ impl DoublePerlinNoiseSampler {
 
     pub fn new(rand: &mut RandomGenerator, parameters: &DoublePerlinNoiseParameters) -> Self {
         let first_octave = parameters.first_octave;
-        let amplitudes = parameters.amplitudes;
+        let amplitudes = &parameters.amplitudes;
 
         let first_sampler = OctavePerlinNoiseSampler::new(rand, first_octave, amplitudes);
         let second_sampler = OctavePerlinNoiseSampler::new(rand, first_octave, amplitudes);
@@ -411,10 +443,7 @@ mod double_perlin_noise_sampler_test {
         assert_eq!(rand.next_i32(), -1302745855);
 
         let mut rand_gen = RandomGenerator::Legacy(rand);
-        let params = DoublePerlinNoiseParameters {
-            first_octave: 0,
-            amplitudes: &[4f64],
-        };
+        let params = DoublePerlinNoiseParameters::new(0, &[4f64]);
         let sampler = DoublePerlinNoiseSampler::new(&mut rand_gen, &params);
 
         let values = [
@@ -512,10 +541,7 @@ mod double_perlin_noise_sampler_test {
 
         let mut rand_gen = RandomGenerator::Xoroshiro(rand);
 
-        let params = DoublePerlinNoiseParameters {
-            first_octave: 1,
-            amplitudes: &[2f64, 4f64],
-        };
+        let params = DoublePerlinNoiseParameters::new(1, &[2f64, 4f64]);
 
         let sampler = DoublePerlinNoiseSampler::new(&mut rand_gen, &params);
 
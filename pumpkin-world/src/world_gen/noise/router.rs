@@ -1,14 +1,18 @@
 use std::sync::Arc;
 
-use crate::world_gen::sampler::VeinType;
+use crate::{
+    biome::{ClimateOffsets, MultiNoiseSampler},
+    world_gen::sampler::VeinType,
+};
 
 use super::{
     density::{
-        apply_blend_density, lerp_density_static_start,
+        apply_blend_density,
+        cache::{CacheVisitor, InterpolationVisitor},
+        lerp_density_static_start,
         noise::{InternalNoise, NoiseFunction, ShiftedNoiseFunction},
-        veritcal_range_choice, BuiltInNoiseFunctions, ConstantFunction, DensityFunction,
-        DensityFunctionImpl, RangeFunction, Visitor, WrapperFunction, WrapperType,
-        YClampedFunction,
+        veritcal_range_choice, BuiltInNoiseFunctions, DensityFunction, DensityFunctionImpl,
+        MarkerFunction, MarkerType, RangeFunction, Visitor, YClampedFunction,
     },
     BuiltInNoiseParams,
 };
@@ -53,6 +57,28 @@ impl<'a> NoiseRouter<'a> {
         }
     }
 
+    /// A copy of the router with empty caches, for sampling a single chunk.
+    ///
+    /// The caches only hold the last column, position or cell, so every chunk must be sampled with
+    /// its own copy
+    pub fn for_chunk(&self) -> Self {
+        self.apply(&Visitor::Cache(CacheVisitor {}))
+    }
+
+    /// Samples the climate for biome placement, vanilla's vegetation and ridges are the humidity and weirdness.
+    /// The offsets come from `GenerationSettings::climate_offsets`
+    pub fn multi_noise_sampler(&self, offsets: ClimateOffsets) -> MultiNoiseSampler<'a> {
+        MultiNoiseSampler::new(
+            self.temperature.clone(),
+            self.vegetation.clone(),
+            self.continents.clone(),
+            self.erosion.clone(),
+            self.depth.clone(),
+            self.ridges.clone(),
+            offsets,
+        )
+    }
+
     pub fn create_surface_noise_router(
         noise_params: &'a BuiltInNoiseParams<'a>,
         noise_funcs: &'a BuiltInNoiseFunctions<'a>,
@@ -148,11 +174,11 @@ impl<'a> NoiseRouter<'a> {
         };
 
         let mapped_depth_overworld = Arc::new(
-            DensityFunction::Constant(ConstantFunction::new(4f64)).mul(Arc::new(
+            DensityFunction::constant(4f64).mul(Arc::new(
                 depth_overworld
-                    .mul(Arc::new(DensityFunction::Wrapper(WrapperFunction::new(
+                    .mul(Arc::new(DensityFunction::Marker(MarkerFunction::new(
                         factor_overworld,
-                        WrapperType::Cache2D,
+                        MarkerType::Cache2D,
                     ))))
                     .quarter_negative(),
             )),
@@ -166,12 +192,9 @@ impl<'a> NoiseRouter<'a> {
             noise_funcs.sloped_cheese_overworld().clone()
         };
 
-        let cave_entrances_overworld = Arc::new(
-            sloped_cheese_overworld.binary_min(Arc::new(
-                DensityFunction::Constant(ConstantFunction::new(5f64))
-                    .mul(noise_funcs.caves_entrances_overworld().clone()),
-            )),
-        );
+        let cave_entrances_overworld = Arc::new(sloped_cheese_overworld.binary_min(Arc::new(
+            DensityFunction::constant(5f64).mul(noise_funcs.caves_entrances_overworld().clone()),
+        )));
 
         let mapped_cave_entraces_overworld = Arc::new(DensityFunction::Range(RangeFunction::new(
             sloped_cheese_overworld.clone(),
@@ -242,8 +265,7 @@ impl<'a> NoiseRouter<'a> {
         );
 
         let ore_vein = Arc::new(
-            DensityFunction::Constant(ConstantFunction::new(-0.08f64))
-                .add(Arc::new(ore_vein_a.binary_max(ore_vein_b))),
+            DensityFunction::constant(-0.08f64).add(Arc::new(ore_vein_a.binary_max(ore_vein_b))),
         );
 
         let ore_gap = Arc::new(DensityFunction::Noise(NoiseFunction::new(
@@ -252,7 +274,7 @@ impl<'a> NoiseRouter<'a> {
             1f64,
         )));
 
-        Self {
+        let router = Self {
             barrier: aquifier_barrier,
             fluid_level_floodedness: aquifier_fluid_level_floodedness,
             fluid_level_spread: aquifer_fluid_level_spread,
@@ -283,7 +305,11 @@ impl<'a> NoiseRouter<'a> {
             vein_toggle: ore_veininess,
             vein_ridged: ore_vein,
             vein_gap: ore_gap,
-        }
+        };
+        // The overworld is interpolated in cells of 4x8 blocks
+        router
+            .apply(&Visitor::Interpolate(InterpolationVisitor::new(4, 8)))
+            .apply(&Visitor::Cache(CacheVisitor {}))
     }
 }
 
@@ -343,23 +369,21 @@ fn create_caves<'a>(
         1f64,
         8f64,
     )));
-    let scaled_cave_layer = Arc::new(
-        DensityFunction::Constant(ConstantFunction::new(4f64)).mul(Arc::new(cave_layer.square())),
-    );
+    let scaled_cave_layer =
+        Arc::new(DensityFunction::constant(4f64).mul(Arc::new(cave_layer.square())));
     let cave_cheese = Arc::new(DensityFunction::Noise(NoiseFunction::new(
         Arc::new(InternalNoise::new(noise_params.cave_cheese().clone(), None)),
         1f64,
         0.6666666666666666f64,
     )));
     let scaled_cave_cheese = Arc::new(
-        DensityFunction::Constant(ConstantFunction::new(0.27f64))
+        DensityFunction::constant(0.27f64)
             .add(cave_cheese)
             .clamp(-1f64, 1f64)
             .add(Arc::new(
-                DensityFunction::Constant(ConstantFunction::new(1.5f64))
+                DensityFunction::constant(1.5f64)
                     .add(Arc::new(
-                        DensityFunction::Constant(ConstantFunction::new(-0.64f64))
-                            .mul(sloped_cheese),
+                        DensityFunction::constant(-0.64f64).mul(sloped_cheese),
                     ))
                     .clamp(0f64, 0.5f64),
             )),
@@ -373,9 +397,7 @@ fn create_caves<'a>(
         pillars.clone(),
         -1000000f64,
         0.03f64,
-        Arc::new(DensityFunction::Constant(ConstantFunction::new(
-            -1000000f64,
-        ))),
+        Arc::new(DensityFunction::constant(-1000000f64)),
         pillars,
     )));
     cave_entrances.binary_max(scaled_pillars)
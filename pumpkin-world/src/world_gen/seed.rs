@@ -1,15 +1,281 @@
-use std::hash::{DefaultHasher, Hash, Hasher};
+use std::{fmt, fs, io, path::Path};
 
-#[derive(Clone, Copy)]
+use pumpkin_core::random::{
+    java_string_hash,
+    xoroshiro128::{Xoroshiro, XoroshiroSplitter},
+    RandomDeriverImpl, RandomImpl,
+};
+
+/// The file in the world folder which keeps the seed the world was created with
+const SEED_FILE: &str = "seed.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Seed(pub i64);
 
+impl fmt::Display for Seed {
+    /// The signed value, like vanilla's `/seed`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Seed {
+    /// The numeric seed, e.g. for `/seed`. Parse it once with [`Self::from_string`] and keep the `Seed`,
+    /// parsing an empty seed again picks another random one
+    pub const fn as_i64(&self) -> i64 {
+        self.0
+    }
+
+    /// Parses the seed like vanilla's world creation does: empty seeds are random,
+    /// numbers are used as is and anything else is hashed with `String.hashCode`, see `From<&str>`.
+    ///
+    /// `0x`/`0b` prefixed numbers are a Pumpkin extension, vanilla hashes them like other text
+    pub fn from_string(value: &str) -> Self {
+        if value.trim().is_empty() {
+            return Self(rand::random());
+        }
+        Self::from(value)
+    }
+
+    /// The seed the world in `world_folder` was created with. A new world gets the `configured` seed
+    /// (see [`Self::from_string`]), which is saved so random seeds stay the same on the next start.
+    ///
+    /// Like vanilla's `level-seed`, changing the configured seed doesn't affect existing worlds
+    pub fn load_or_save(world_folder: &Path, configured: &str) -> io::Result<Self> {
+        let path = world_folder.join(SEED_FILE);
+        match fs::read_to_string(&path) {
+            Ok(saved) => {
+                let seed = saved.trim().parse().map(Self).map_err(|err| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid seed in {}: {}", path.display(), err),
+                    )
+                })?;
+                if !configured.trim().is_empty() && Self::from(configured) != seed {
+                    log::warn!(
+                        "The world was created with seed {}, the configured seed is ignored",
+                        seed
+                    );
+                }
+                Ok(seed)
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let seed = Self::from_string(configured);
+                fs::create_dir_all(world_folder)?;
+                fs::write(&path, seed.to_string())?;
+                Ok(seed)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// The root of all position based randomness.
+    /// Split it by name (e.g. `minecraft:bedrock_floor`) so every user gets its own random values
+    pub fn positional_factory(&self) -> XoroshiroSplitter {
+        Xoroshiro::from_seed(self.0 as u64).next_splitter()
+    }
+
+    /// A seed of its own for the dimension (e.g. `minecraft:the_nether`), split off like the positional
+    /// randoms, so it stays the same for every world with this seed.
+    ///
+    /// Vanilla generates every dimension from the world seed itself and only gets different terrain
+    /// from the noise settings, this is for generators which would otherwise sample the same noise
+    pub fn for_dimension(&self, dimension: &str) -> Self {
+        Self(self.positional_factory().split_string(dimension).next_i64())
+    }
+
+    /// Parses a `0x` (hexadecimal) or `0b` (binary) prefixed number, optionally negative.
+    /// Values which don't fit into an `i64` wrap around like they would in Java
+    fn parse_prefixed(value: &str) -> Option<i64> {
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(value) => (true, value),
+            None => (false, value),
+        };
+
+        let (radix, digits) = if let Some(digits) = value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+        {
+            (16, digits)
+        } else if let Some(digits) = value
+            .strip_prefix("0b")
+            .or_else(|| value.strip_prefix("0B"))
+        {
+            (2, digits)
+        } else {
+            return None;
+        };
+
+        if digits.is_empty() {
+            return None;
+        }
+
+        let mut result = 0u64;
+        for char in digits.chars() {
+            let digit = char.to_digit(radix)?;
+            result = result.wrapping_mul(radix as u64).wrapping_add(digit as u64);
+        }
+
+        // TODO use cast_signed once the feature is stabilized.
+        let result = result as i64;
+        Some(if negative {
+            result.wrapping_neg()
+        } else {
+            result
+        })
+    }
+}
+
 impl From<&str> for Seed {
+    /// Decimal numbers are used as is, then `0x`/`0b` prefixed numbers,
+    /// anything else gets hashed like vanilla (`String.hashCode`).
+    ///
+    /// Text used to be hashed with Rust's `DefaultHasher`, so worlds created from a text seed before
+    /// get other terrain in new chunks. That hash could change with every Rust release, so there is no
+    /// old value to keep; the seed file (see [`Seed::load_or_save`]) keeps the seed stable from now on
     fn from(value: &str) -> Self {
-        // TODO replace with a deterministic hasher (the same as vanilla?)
-        let mut hasher = DefaultHasher::new();
-        value.hash(&mut hasher);
+        let trimmed = value.trim();
+        if let Ok(seed) = trimmed.parse::<i64>() {
+            return Self(seed);
+        }
+        if let Some(seed) = Self::parse_prefixed(trimmed) {
+            return Self(seed);
+        }
 
         // TODO use cast_signed once the feature is stabilized.
-        Self(hasher.finish() as i64)
+        Self((java_string_hash(trimmed) as i32).into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{Seed, SEED_FILE};
+
+    #[test]
+    fn test_decimal() {
+        assert_eq!(Seed::from("12345").0, 12345);
+        assert_eq!(Seed::from("-4172144997902289642").0, -4172144997902289642);
+        assert_eq!(Seed::from(" 42 ").0, 42);
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(Seed::from("0xDEADBEEF").0, 0xDEADBEEF);
+        assert_eq!(Seed::from("0xffffffffffffffff").0, -1);
+        assert_eq!(Seed::from("-0x10").0, -16);
+        // Wraps like Java instead of failing
+        assert_eq!(Seed::from("0x1ffffffffffffffff").0, -1);
+    }
+
+    #[test]
+    fn test_binary() {
+        assert_eq!(Seed::from("0b1011").0, 11);
+        assert_eq!(
+            Seed::from(format!("0b1{}", "0".repeat(63)).as_str()).0,
+            i64::MIN
+        );
+    }
+
+    #[test]
+    fn test_hash_fallback() {
+        // Decimal overflows and invalid digits are text seeds
+        let overflow = "9223372036854775808";
+        assert_eq!(Seed::from(overflow).0, Seed::from(overflow).0);
+        assert_ne!(Seed::from(overflow).0, i64::MIN);
+        assert_eq!(Seed::from("0xZZ").0, Seed::from("0xZZ").0);
+        assert_ne!(Seed::from("0x").0, 0);
+        assert_eq!(Seed::from("Pumpkin").0, Seed::from("Pumpkin").0);
+    }
+
+    #[test]
+    fn test_from_string() {
+        // The seeds vanilla creates for these inputs
+        assert_eq!(Seed::from_string("glacier"), Seed(108_181_935));
+        assert_eq!(Seed::from_string("minecraft"), Seed(695_073_197));
+        assert_eq!(Seed::from_string("gargamel"), Seed(-1_623_774_494));
+        assert_eq!(Seed::from_string(" glacier "), Seed(108_181_935));
+        assert_eq!(Seed::from_string("+1"), Seed(1));
+        assert_eq!(
+            Seed::from_string("-4172144997902289642"),
+            Seed(-4_172_144_997_902_289_642)
+        );
+
+        // Empty seeds are random
+        assert_ne!(Seed::from_string(""), Seed::from_string(""));
+        assert_ne!(Seed::from_string(" "), Seed(0));
+    }
+
+    #[test]
+    fn test_as_i64() {
+        assert_eq!(Seed::from_string("42").as_i64(), 42);
+        assert_eq!(Seed::from_string("-42").as_i64(), -42);
+
+        // Picked once, the copies keep the value
+        let random = Seed::from_string("");
+        assert_ne!(random.as_i64(), 0);
+        let copy = random;
+        assert_eq!(copy.as_i64(), random.as_i64());
+        assert_eq!(random.as_i64(), random.0);
+    }
+
+    #[test]
+    fn test_for_dimension() {
+        let seed = Seed(-4172144997902289642);
+        let nether = seed.for_dimension("minecraft:the_nether");
+        let end = seed.for_dimension("minecraft:the_end");
+        assert_ne!(nether, end);
+        assert_ne!(nether, seed);
+        assert_eq!(nether, seed.for_dimension("minecraft:the_nether"));
+        assert_eq!(
+            end,
+            Seed(-4172144997902289642).for_dimension("minecraft:the_end")
+        );
+        assert_ne!(nether, Seed(1).for_dimension("minecraft:the_nether"));
+    }
+
+    #[test]
+    fn test_eq_display() {
+        assert_eq!(Seed::from("1"), Seed::from("1"));
+        assert_eq!(Seed::from("1"), Seed(1));
+        assert_eq!(Seed::from("0x10"), Seed::from("16"));
+        assert_ne!(Seed::from("1"), Seed::from("2"));
+
+        assert_eq!(Seed::from("1").to_string(), "1");
+        assert_eq!(
+            Seed(-4172144997902289642).to_string(),
+            "-4172144997902289642"
+        );
+
+        // The values vanilla shows for these text seeds
+        assert_eq!(Seed::from("hello world").to_string(), "1794106052");
+        assert_eq!(Seed::from("Pumpkin").to_string(), "1439567688");
+    }
+
+    #[test]
+    fn test_load_or_save() {
+        let dir = std::env::temp_dir().join(format!("pumpkin-seed-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        // A random seed is saved with the new world and kept afterwards
+        let seed = Seed::load_or_save(&dir, "").unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join(SEED_FILE)).unwrap(),
+            seed.to_string()
+        );
+        assert_eq!(Seed::load_or_save(&dir, "").unwrap(), seed);
+        // The configured seed only matters for new worlds
+        assert_eq!(Seed::load_or_save(&dir, "glacier").unwrap(), seed);
+
+        fs::write(dir.join(SEED_FILE), "glacier").unwrap();
+        assert!(Seed::load_or_save(&dir, "").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            Seed::load_or_save(&dir, "glacier").unwrap(),
+            Seed(108_181_935)
+        );
+        fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,248 @@
+use crate::{
+    biome::Biome, block::BlockState, chunk::ChunkBlocks,
+    coordinates::ChunkRelativeBlockCoordinates, WORLD_LOWEST_Y,
+};
+
+use super::state_id;
+
+/// Where a block of the column is, the input of the surface rules
+#[derive(Clone, Copy, Debug)]
+pub struct SurfaceContext {
+    pub biome: Biome,
+    pub y: i16,
+    /// Like vanilla's `stone_depth`, 1 for the topmost solid block and counting up below it
+    pub depth: i16,
+}
+
+/// Decides whether a surface rule applies to a block
+pub enum SurfaceCondition {
+    /// At most `offset` blocks below the top block, like vanilla's `stone_depth` with the floor surface type
+    StoneDepth {
+        offset: i16,
+    },
+    /// The column is in one of the biomes
+    Biome(Vec<Biome>),
+    /// The block is at or above this y
+    AboveY(i16),
+    Not(Box<SurfaceCondition>),
+}
+
+impl SurfaceCondition {
+    pub fn test(&self, context: &SurfaceContext) -> bool {
+        match self {
+            Self::StoneDepth { offset } => context.depth <= 1 + offset,
+            Self::Biome(biomes) => biomes.contains(&context.biome),
+            Self::AboveY(y) => context.y >= *y,
+            Self::Not(condition) => !condition.test(context),
+        }
+    }
+}
+
+/// Replaces the stone of the terrain with the surface blocks, modeled on vanilla's surface rules
+pub enum SurfaceRule {
+    /// Places the block state
+    Block(u16),
+    /// Only applies the rule where the condition holds
+    Condition(SurfaceCondition, Box<SurfaceRule>),
+    /// The first rule which places a block wins
+    Sequence(Vec<SurfaceRule>),
+}
+
+impl SurfaceRule {
+    fn block(name: &str) -> Self {
+        Self::Block(state_id(name))
+    }
+
+    fn on_depth(offset: i16, rule: Self) -> Self {
+        Self::Condition(SurfaceCondition::StoneDepth { offset }, Box::new(rule))
+    }
+
+    /// Grass on the top block, 2 blocks of dirt below and stone further down
+    pub fn plains() -> Self {
+        Self::Sequence(vec![
+            Self::on_depth(0, Self::block("minecraft:grass_block")),
+            Self::on_depth(2, Self::block("minecraft:dirt")),
+            Self::block("minecraft:stone"),
+        ])
+    }
+
+    /// 4 blocks of sand with sandstone below, held up by stone
+    pub fn desert() -> Self {
+        Self::Sequence(vec![
+            Self::on_depth(3, Self::block("minecraft:sand")),
+            Self::on_depth(6, Self::block("minecraft:sandstone")),
+            Self::block("minecraft:stone"),
+        ])
+    }
+
+    /// The rule for every overworld biome, biomes without their own rule get the plains surface
+    pub fn overworld() -> Self {
+        Self::Sequence(vec![
+            Self::Condition(
+                SurfaceCondition::Biome(vec![Biome::Desert]),
+                Box::new(Self::desert()),
+            ),
+            Self::plains(),
+        ])
+    }
+
+    /// The block state to place, `None` keeps the block
+    pub fn try_apply(&self, context: &SurfaceContext) -> Option<u16> {
+        match self {
+            Self::Block(state_id) => Some(*state_id),
+            Self::Condition(condition, rule) => {
+                if condition.test(context) {
+                    rule.try_apply(context)
+                } else {
+                    None
+                }
+            }
+            Self::Sequence(rules) => rules.iter().find_map(|rule| rule.try_apply(context)),
+        }
+    }
+}
+
+/// Runs a surface rule over the columns of a chunk, the blocks it checks for are looked up once
+pub struct SurfaceSystem {
+    rule: SurfaceRule,
+    stone: u16,
+    water: u16,
+}
+
+impl SurfaceSystem {
+    pub fn new(rule: SurfaceRule) -> Self {
+        Self {
+            rule,
+            stone: state_id("minecraft:stone"),
+            water: state_id("minecraft:water"),
+        }
+    }
+
+    /// See `SurfaceRule::overworld`
+    pub fn overworld() -> Self {
+        Self::new(SurfaceRule::overworld())
+    }
+
+    /// Runs the rule on the stone of the column below `top_y`, from the top down.
+    /// The depth starts over below air and water, so overhangs and caves get their own surface
+    pub fn apply_column(&self, blocks: &mut ChunkBlocks, x: u8, z: u8, biome: Biome, top_y: i16) {
+        let mut depth = 0;
+        for y in (WORLD_LOWEST_Y..top_y).rev() {
+            let coordinates = ChunkRelativeBlockCoordinates {
+                x: x.into(),
+                y: y.into(),
+                z: z.into(),
+            };
+            let block = blocks.get_block(coordinates);
+            if block == BlockState::AIR.state_id || block == self.water {
+                depth = 0;
+                continue;
+            }
+            depth += 1;
+            if block != self.stone {
+                continue;
+            }
+
+            let context = SurfaceContext { biome, y, depth };
+            if let Some(state_id) = self.rule.try_apply(&context) {
+                if state_id != self.stone {
+                    blocks.set_block(coordinates, state_id);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        biome::Biome,
+        block::BlockState,
+        chunk::ChunkBlocks,
+        coordinates::ChunkRelativeBlockCoordinates,
+        world_gen::{state_id, test::block_at},
+        WORLD_LOWEST_Y,
+    };
+
+    use super::{SurfaceCondition, SurfaceContext, SurfaceRule, SurfaceSystem};
+
+    fn stone_column(top_y: i16) -> ChunkBlocks {
+        let stone = state_id("minecraft:stone");
+        let mut blocks = ChunkBlocks::default();
+        for y in WORLD_LOWEST_Y..top_y {
+            blocks.set_block(
+                ChunkRelativeBlockCoordinates {
+                    x: 0u8.into(),
+                    y: y.into(),
+                    z: 0u8.into(),
+                },
+                stone,
+            );
+        }
+        blocks
+    }
+
+    #[test]
+    fn test_plains_column() {
+        let mut blocks = stone_column(64);
+        SurfaceSystem::overworld().apply_column(&mut blocks, 0, 0, Biome::Plains, 100);
+
+        assert_eq!(block_at(&blocks, 0, 64, 0), BlockState::AIR.state_id);
+        assert_eq!(
+            block_at(&blocks, 0, 63, 0),
+            state_id("minecraft:grass_block")
+        );
+        for y in 61..63 {
+            assert_eq!(
+                block_at(&blocks, 0, y, 0),
+                state_id("minecraft:dirt"),
+                "{y}"
+            );
+        }
+        assert_eq!(block_at(&blocks, 0, 60, 0), state_id("minecraft:stone"));
+        assert_eq!(block_at(&blocks, 0, 0, 0), state_id("minecraft:stone"));
+    }
+
+    #[test]
+    fn test_desert_column() {
+        let mut blocks = stone_column(70);
+        SurfaceSystem::overworld().apply_column(&mut blocks, 0, 0, Biome::Desert, 70);
+
+        for y in 66..70 {
+            assert_eq!(
+                block_at(&blocks, 0, y, 0),
+                state_id("minecraft:sand"),
+                "{y}"
+            );
+        }
+        for y in 63..66 {
+            assert_eq!(
+                block_at(&blocks, 0, y, 0),
+                state_id("minecraft:sandstone"),
+                "{y}"
+            );
+        }
+        assert_eq!(block_at(&blocks, 0, 62, 0), state_id("minecraft:stone"));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let context = SurfaceContext {
+            biome: Biome::Forest,
+            y: 10,
+            depth: 2,
+        };
+        assert!(SurfaceCondition::StoneDepth { offset: 1 }.test(&context));
+        assert!(!SurfaceCondition::StoneDepth { offset: 0 }.test(&context));
+        assert!(SurfaceCondition::AboveY(10).test(&context));
+        assert!(!SurfaceCondition::Not(Box::new(SurfaceCondition::AboveY(10))).test(&context));
+        assert!(!SurfaceCondition::Biome(vec![Biome::Desert]).test(&context));
+
+        // Nothing places a block
+        let rule = SurfaceRule::Sequence(vec![SurfaceRule::Condition(
+            SurfaceCondition::AboveY(64),
+            Box::new(SurfaceRule::Block(1)),
+        )]);
+        assert_eq!(rule.try_apply(&context), None);
+    }
+}
@@ -1,13 +1,16 @@
 use std::{collections::HashMap, net::IpAddr};
 
 use base64::{engine::general_purpose, Engine};
-use pumpkin_config::{auth::TextureConfig, ADVANCED_CONFIG};
+use md5::Md5;
+use pumpkin_config::{
+    auth::{AuthenticationConfig, OfflineUuidStrategy, TextureConfig},
+    ADVANCED_CONFIG,
+};
 use pumpkin_core::ProfileAction;
 use pumpkin_protocol::Property;
 use reqwest::{StatusCode, Url};
 use serde::Deserialize;
 use sha1::Digest;
-use sha2::Sha256;
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -57,21 +60,13 @@ pub async fn authenticate(
     ip: &IpAddr,
     auth_client: &reqwest::Client,
 ) -> Result<GameProfile, AuthError> {
-    assert!(ADVANCED_CONFIG.authentication.enabled);
-    let address = if ADVANCED_CONFIG.authentication.prevent_proxy_connections {
-        ADVANCED_CONFIG
-            .authentication
-            .auth_url
-            .replace("{username}", username)
-            .replace("{server_hash}", server_hash)
-            .replace("{}", &ip.to_string())
-    } else {
-        ADVANCED_CONFIG
-            .authentication
-            .auth_url
-            .replace("{username}", username)
-            .replace("{server_hash}", server_hash)
-    };
+    assert!(ADVANCED_CONFIG.get().authentication.enabled);
+    let address = has_joined_url(
+        &ADVANCED_CONFIG.get().authentication,
+        username,
+        server_hash,
+        ip,
+    )?;
 
     let response = auth_client
         .get(address)
@@ -87,6 +82,24 @@ pub async fn authenticate(
     Ok(profile)
 }
 
+/// The session server URL which tells whether `username` joined, see `session_server_url` in the config
+fn has_joined_url(
+    config: &AuthenticationConfig,
+    username: &str,
+    server_hash: &str,
+    ip: &IpAddr,
+) -> Result<Url, AuthError> {
+    let mut url =
+        Url::parse(&config.has_joined_url()).map_err(|_| AuthError::InvalidSessionServerUrl)?;
+    url.query_pairs_mut()
+        .append_pair("username", username)
+        .append_pair("serverId", server_hash);
+    if config.prevent_proxy_connections {
+        url.query_pairs_mut().append_pair("ip", &ip.to_string());
+    }
+    Ok(url)
+}
+
 pub fn validate_textures(property: &Property, config: &TextureConfig) -> Result<(), TextureError> {
     let from64 = general_purpose::STANDARD
         .decode(&property.value)
@@ -121,8 +134,18 @@ pub fn is_texture_url_valid(url: &Url, config: &TextureConfig) -> Result<(), Tex
     Ok(())
 }
 
-pub fn offline_uuid(username: &str) -> Result<Uuid, uuid::Error> {
-    Uuid::from_slice(&Sha256::digest(username)[..16])
+/// Returns the UUID vanilla gives a player in offline mode, which is a V3 UUID of `OfflinePlayer:<name>`
+pub fn offline_uuid(username: &str) -> Uuid {
+    let digest = Md5::digest(format!("OfflinePlayer:{username}"));
+    uuid::Builder::from_md5_bytes(digest.into()).into_uuid()
+}
+
+/// Returns the UUID for an offline player using the configured `offline_uuid_strategy`
+pub fn offline_player_uuid(username: &str) -> Uuid {
+    match ADVANCED_CONFIG.get().authentication.offline_uuid_strategy {
+        OfflineUuidStrategy::Vanilla => offline_uuid(username),
+        OfflineUuidStrategy::Random => Uuid::new_v4(),
+    }
 }
 
 #[derive(Error, Debug)]
@@ -131,6 +154,8 @@ pub enum AuthError {
     MissingAuthClient,
     #[error("Authentication servers are down")]
     FailedResponse,
+    #[error("Invalid session server URL")]
+    InvalidSessionServerUrl,
     #[error("Failed to verify username")]
     UnverifiedUsername,
     #[error("You are banned from Authentication servers")]
@@ -158,3 +183,48 @@ pub enum TextureError {
     #[error("Failed to parse JSON from player texture: {0}")]
     JSONError(String),
 }
+
+#[cfg(test)]
+mod test {
+    use std::net::IpAddr;
+
+    use pumpkin_config::auth::AuthenticationConfig;
+
+    use super::{has_joined_url, offline_uuid};
+
+    #[test]
+    fn test_has_joined_url() {
+        let ip: IpAddr = "192.168.1.5".parse().unwrap();
+        let mut config = AuthenticationConfig::default();
+        assert_eq!(
+            has_joined_url(&config, "Notch", "-4fc5", &ip).unwrap().as_str(),
+            "https://sessionserver.mojang.com/session/minecraft/hasJoined?username=Notch&serverId=-4fc5"
+        );
+
+        config.session_server_url = "https://auth.example.com/api/".to_string();
+        config.prevent_proxy_connections = true;
+        assert_eq!(
+            has_joined_url(&config, "Notch", "-4fc5", &ip).unwrap().as_str(),
+            "https://auth.example.com/api/session/minecraft/hasJoined?username=Notch&serverId=-4fc5&ip=192.168.1.5"
+        );
+    }
+
+    #[test]
+    fn test_offline_uuid() {
+        // Taken from a vanilla server in offline mode
+        for (name, uuid) in [
+            ("Notch", "b50ad385-829d-3141-a216-7e7d7539ba7f"),
+            ("Steve", "5627dd98-e6be-3c21-b8a8-e92344183641"),
+            ("jeb_", "a762f560-4fce-3236-812a-b80efff0b62b"),
+            // Names are case sensitive, unlike online mode
+            ("notch", "42653081-a90e-3475-b3d6-3550cdb43f8e"),
+        ] {
+            let offline = offline_uuid(name);
+            assert_eq!(offline.to_string(), uuid);
+            assert_eq!(offline.get_version(), Some(uuid::Version::Md5));
+            assert_eq!(offline.get_variant(), uuid::Variant::RFC4122);
+        }
+        // Stable across logins
+        assert_eq!(offline_uuid("Notch"), offline_uuid("Notch"));
+    }
+}
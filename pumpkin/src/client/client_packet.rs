@@ -1,24 +1,27 @@
+use std::net::SocketAddr;
+
 use num_traits::FromPrimitive;
-use pumpkin_config::{ADVANCED_CONFIG, BASIC_CONFIG};
-use pumpkin_core::text::TextComponent;
+use pumpkin_config::{proxy::ProxyMode, ADVANCED_CONFIG, BASIC_CONFIG};
 use pumpkin_protocol::{
     client::{
         config::{CConfigAddResourcePack, CFinishConfig, CKnownPacks, CRegistryData},
         login::{CLoginSuccess, CSetCompression},
-        status::CPingResponse,
+        status::{CPingResponse, CStatusResponse},
     },
     server::{
-        config::{SClientInformationConfig, SKnownPacks, SPluginMessage},
+        config::{
+            ResourcePackResponseResult, SClientInformationConfig, SConfigResourcePack, SKnownPacks,
+            SPluginMessage,
+        },
         handshake::SHandShake,
         login::{SEncryptionResponse, SLoginPluginResponse, SLoginStart},
         status::SStatusPingRequest,
     },
     ConnectionState, KnownPack, CURRENT_MC_PROTOCOL,
 };
-use uuid::Uuid;
 
 use crate::{
-    client::authentication::{self, offline_uuid, validate_textures, GameProfile},
+    client::authentication::{self, offline_player_uuid, validate_textures, GameProfile},
     entity::player::{ChatMode, Hand},
     proxy::{
         bungeecord,
@@ -45,11 +48,21 @@ impl Client {
             let protocol = version;
             match protocol.cmp(&(CURRENT_MC_PROTOCOL as i32)) {
                 std::cmp::Ordering::Less => {
-                    self.kick(&format!("Client outdated ({protocol}), Server uses Minecraft {CURRENT_MC_VERSION}, Protocol {CURRENT_MC_PROTOCOL}")).await;
+                    self.kick_component(&ADVANCED_CONFIG.get().messages.outdated_client(
+                        protocol,
+                        CURRENT_MC_VERSION,
+                        CURRENT_MC_PROTOCOL,
+                    ))
+                    .await;
                 }
                 std::cmp::Ordering::Equal => {}
                 std::cmp::Ordering::Greater => {
-                    self.kick(&format!("Server outdated, Server uses Minecraft {CURRENT_MC_VERSION}, Protocol {CURRENT_MC_PROTOCOL}")).await;
+                    self.kick_component(&ADVANCED_CONFIG.get().messages.outdated_server(
+                        protocol,
+                        CURRENT_MC_VERSION,
+                        CURRENT_MC_PROTOCOL,
+                    ))
+                    .await;
                 }
             }
         }
@@ -57,8 +70,8 @@ impl Client {
 
     pub async fn handle_status_request(&self, server: &Server) {
         log::debug!("Handling status request");
-        let status = server.get_status();
-        self.send_packet(&status.lock().await.get_status()).await;
+        let status = server.get_status().lock().await.get_status();
+        self.send_packet(&CStatusResponse::new(&status)).await;
     }
 
     pub async fn handle_ping_request(&self, ping_request: SStatusPingRequest) {
@@ -81,9 +94,9 @@ impl Client {
         // Don't allow new logons when server is full.
         // If max players is set to zero, then there is no max player count enforced.
         // TODO: If client is an operator or otherwise suitable elevated permissions, allow client to bypass this requirement.
-        let max_players = BASIC_CONFIG.max_players;
+        let max_players = BASIC_CONFIG.get().max_players;
         if max_players > 0 && server.get_player_count().await >= max_players as usize {
-            self.kick("The server is currently full, please try again later")
+            self.kick_component(&ADVANCED_CONFIG.get().messages.server_full())
                 .await;
             return;
         }
@@ -95,48 +108,49 @@ impl Client {
         // default game profile, when no online mode
         // TODO: make offline uuid
         let mut gameprofile = self.gameprofile.lock().await;
-        let proxy = &ADVANCED_CONFIG.proxy;
-        if proxy.enabled {
-            if proxy.velocity.enabled {
-                velocity_login(self).await;
-            } else if proxy.bungeecord.enabled {
+        match ADVANCED_CONFIG.get().proxy.mode() {
+            ProxyMode::Velocity { .. } => velocity_login(self).await,
+            ProxyMode::BungeeCord => {
                 match bungeecord::bungeecord_login(self, login_start.name).await {
-                    Ok((_ip, profile)) => {
-                        // self.address.lock() = ip;
-                        self.finish_login(&profile).await;
+                    Ok((ip, profile)) => {
+                        let mut address = self.address.lock().await;
+                        *address = SocketAddr::new(ip, address.port());
+                        drop(address);
+                        self.finish_login(server, &profile).await;
                         *gameprofile = Some(profile);
                     }
                     Err(error) => self.kick(&error.to_string()).await,
                 }
             }
-        } else {
-            let id = if BASIC_CONFIG.online_mode {
-                login_start.uuid
-            } else {
-                offline_uuid(&login_start.name).expect("This is very not safe and bad")
-            };
-
-            let profile = GameProfile {
-                id,
-                name: login_start.name,
-                properties: vec![],
-                profile_actions: None,
-            };
-
-            if BASIC_CONFIG.encryption {
-                let verify_token: [u8; 4] = rand::random();
-                self.send_packet(
-                    &server.encryption_request(&verify_token, BASIC_CONFIG.online_mode),
-                )
-                .await;
-            } else {
-                if ADVANCED_CONFIG.packet_compression.enabled {
-                    self.enable_compression().await;
+            ProxyMode::None => {
+                let id = if BASIC_CONFIG.get().online_mode {
+                    login_start.uuid
+                } else {
+                    offline_player_uuid(&login_start.name)
+                };
+
+                let profile = GameProfile {
+                    id,
+                    name: login_start.name,
+                    properties: vec![],
+                    profile_actions: None,
+                };
+
+                if BASIC_CONFIG.get().encryption {
+                    let verify_token: [u8; 4] = rand::random();
+                    self.send_packet(
+                        &server.encryption_request(&verify_token, BASIC_CONFIG.get().online_mode),
+                    )
+                    .await;
+                } else {
+                    if ADVANCED_CONFIG.get().packet_compression.is_enabled() {
+                        self.enable_compression().await;
+                    }
+                    self.finish_login(server, &profile).await;
                 }
-                self.finish_login(&profile).await;
-            }
 
-            *gameprofile = Some(profile);
+                *gameprofile = Some(profile);
+            }
         }
     }
 
@@ -160,7 +174,7 @@ impl Client {
             return;
         };
 
-        if BASIC_CONFIG.online_mode {
+        if BASIC_CONFIG.get().online_mode {
             // Online mode auth
             match self
                 .authenticate(server, &shared_secret, &profile.name)
@@ -189,20 +203,42 @@ impl Client {
             return;
         }
 
-        if ADVANCED_CONFIG.packet_compression.enabled {
+        if ADVANCED_CONFIG.get().packet_compression.is_enabled() {
             self.enable_compression().await;
         }
-        self.finish_login(profile).await;
+        self.finish_login(server, profile).await;
     }
 
     async fn enable_compression(&self) {
-        let compression = ADVANCED_CONFIG.packet_compression.compression_info.clone();
-        self.send_packet(&CSetCompression::new(compression.threshold.into()))
-            .await;
+        let compression = ADVANCED_CONFIG
+            .get()
+            .packet_compression
+            .compression_info
+            .clone();
+        self.send_packet(&CSetCompression::new(
+            compression.threshold.packet_value().into(),
+        ))
+        .await;
         self.set_compression(Some(compression)).await;
     }
 
-    async fn finish_login(&self, profile: &GameProfile) {
+    async fn finish_login(&self, server: &Server, profile: &GameProfile) {
+        let ip = self.address.lock().await.ip();
+        if let Some(ban) = server.find_ban(&profile.id, ip).await {
+            self.kick_component(
+                &ADVANCED_CONFIG
+                    .get()
+                    .messages
+                    .banned(&ban.reason, &ban.expires_display()),
+            )
+            .await;
+            return;
+        }
+        if !server.is_whitelisted(&profile.id).await {
+            self.kick_component(&ADVANCED_CONFIG.get().messages.whitelist_rejected())
+                .await;
+            return;
+        }
         let packet = CLoginSuccess::new(&profile.id, &profile.name, &profile.properties);
         self.send_packet(&packet).await;
     }
@@ -221,11 +257,13 @@ impl Client {
             // Check if player should join
             if let Some(actions) = &profile.profile_actions {
                 if ADVANCED_CONFIG
+                    .get()
                     .authentication
                     .player_profile
                     .allow_banned_players
                 {
                     for allowed in &ADVANCED_CONFIG
+                        .get()
                         .authentication
                         .player_profile
                         .allowed_actions
@@ -243,7 +281,7 @@ impl Client {
             }
             // validate textures
             for property in &profile.properties {
-                validate_textures(property, &ADVANCED_CONFIG.authentication.textures)
+                validate_textures(property, &ADVANCED_CONFIG.get().authentication.textures)
                     .map_err(AuthError::TextureError)?;
             }
             return Ok(profile);
@@ -251,20 +289,21 @@ impl Client {
         Err(AuthError::MissingAuthClient)
     }
 
-    pub async fn handle_plugin_response(&self, plugin_response: SLoginPluginResponse) {
+    pub async fn handle_plugin_response(
+        &self,
+        server: &Server,
+        plugin_response: SLoginPluginResponse,
+    ) {
         log::debug!("Handling plugin");
-        let velocity_config = &ADVANCED_CONFIG.proxy.velocity;
-        if velocity_config.enabled {
+        if let ProxyMode::Velocity { secret } = ADVANCED_CONFIG.get().proxy.mode() {
             let mut address = self.address.lock().await;
-            match velocity::receive_velocity_plugin_response(
-                address.port(),
-                velocity_config,
-                plugin_response,
-            ) {
-                Ok((profile, new_address)) => {
-                    self.finish_login(&profile).await;
-                    *self.gameprofile.lock().await = Some(profile);
-                    *address = new_address;
+            match velocity::receive_velocity_plugin_response(secret, plugin_response) {
+                Ok(info) => {
+                    // The ban check needs the real address
+                    *address = SocketAddr::new(info.address, address.port());
+                    drop(address);
+                    self.finish_login(server, &info.profile).await;
+                    *self.gameprofile.lock().await = Some(info.profile);
                 }
                 Err(error) => self.kick(&error.to_string()).await,
             }
@@ -276,24 +315,20 @@ impl Client {
         self.connection_state.store(ConnectionState::Config);
         self.send_packet(&server.get_branding()).await;
 
-        let resource_config = &ADVANCED_CONFIG.resource_pack;
+        let advanced_config = ADVANCED_CONFIG.get();
+        let resource_config = &advanced_config.resource_pack;
         if resource_config.enabled {
-            let resource_pack = CConfigAddResourcePack::new(
-                Uuid::new_v3(
-                    &uuid::Uuid::NAMESPACE_DNS,
-                    resource_config.resource_pack_url.as_bytes(),
-                ),
-                &resource_config.resource_pack_url,
-                &resource_config.resource_pack_sha1,
-                resource_config.force,
-                if resource_config.prompt_message.is_empty() {
-                    None
-                } else {
-                    Some(TextComponent::text(&resource_config.prompt_message))
-                },
-            );
-
-            self.send_packet(&resource_pack).await;
+            let prompt = resource_config.prompt();
+            for pack in &resource_config.packs {
+                self.send_packet(&CConfigAddResourcePack::new(
+                    pack.uuid(),
+                    &pack.url,
+                    &pack.sha1,
+                    pack.required,
+                    prompt.clone(),
+                ))
+                .await;
+            }
         }
 
         // known data packs
@@ -342,6 +377,25 @@ impl Client {
         }
     }
 
+    pub async fn handle_resource_pack_response(&self, packet: SConfigResourcePack) {
+        let Some(result) = ResourcePackResponseResult::from_i32(packet.result.0) else {
+            log::warn!(
+                "Client sent an invalid resource pack result {}",
+                packet.result.0
+            );
+            return;
+        };
+        if result.is_failure()
+            && ADVANCED_CONFIG
+                .get()
+                .resource_pack
+                .is_required(&packet.uuid)
+        {
+            self.kick_component(&ADVANCED_CONFIG.get().messages.resource_pack_declined())
+                .await;
+        }
+    }
+
     pub async fn handle_known_packs(&self, server: &Server, _config_acknowledged: SKnownPacks) {
         log::debug!("Handling known packs");
         for registry in &server.cached_registry {
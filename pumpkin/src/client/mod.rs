@@ -22,7 +22,10 @@ use pumpkin_protocol::{
     packet_decoder::PacketDecoder,
     packet_encoder::{PacketEncodeError, PacketEncoder},
     server::{
-        config::{SAcknowledgeFinishConfig, SClientInformationConfig, SKnownPacks, SPluginMessage},
+        config::{
+            SAcknowledgeFinishConfig, SClientInformationConfig, SConfigResourcePack, SKnownPacks,
+            SPluginMessage,
+        },
         handshake::SHandShake,
         login::{SEncryptionResponse, SLoginAcknowledged, SLoginPluginResponse, SLoginStart},
         status::{SStatusPingRequest, SStatusRequest},
@@ -427,7 +430,7 @@ impl Client {
                     .await;
             }
             SLoginPluginResponse::PACKET_ID => {
-                self.handle_plugin_response(SLoginPluginResponse::read(bytebuf)?)
+                self.handle_plugin_response(server, SLoginPluginResponse::read(bytebuf)?)
                     .await;
             }
             SLoginAcknowledged::PACKET_ID => {
@@ -467,6 +470,10 @@ impl Client {
                 self.handle_known_packs(server, SKnownPacks::read(bytebuf)?)
                     .await;
             }
+            SConfigResourcePack::PACKET_ID => {
+                self.handle_resource_pack_response(SConfigResourcePack::read(bytebuf)?)
+                    .await;
+            }
             _ => {
                 log::error!(
                     "Failed to handle client packet id {} in Config State",
@@ -532,20 +539,24 @@ impl Client {
     ///
     /// * `reason`: A string describing the reason for kicking the client.
     pub async fn kick(&self, reason: &str) {
-        log::info!("Kicking Client id {} for {}", self.id, reason);
+        self.kick_component(&TextComponent::text(reason)).await;
+    }
+
+    /// Kicks the Client with a Text Component as reason, e.g. a configured message
+    pub async fn kick_component(&self, reason: &TextComponent<'_>) {
+        log::info!(
+            "Kicking Client id {} for {}",
+            self.id,
+            reason.clone().to_pretty_console()
+        );
         let result = match self.connection_state.load() {
             ConnectionState::Login => {
-                self.try_send_packet(&CLoginDisconnect::new(
-                    &serde_json::to_string_pretty(&reason).unwrap_or_else(|_| String::new()),
-                ))
-                .await
+                self.try_send_packet(&CLoginDisconnect::new(&reason.to_json()))
+                    .await
             }
             ConnectionState::Config => self.try_send_packet(&CConfigDisconnect::new(reason)).await,
             // This way players get kicked when players using client functions (e.g. poll, send_packet)
-            ConnectionState::Play => {
-                self.try_send_packet(&CPlayDisconnect::new(&TextComponent::text(reason)))
-                    .await
-            }
+            ConnectionState::Play => self.try_send_packet(&CPlayDisconnect::new(reason)).await,
             _ => {
                 log::warn!("Can't kick in {:?} State", self.connection_state);
                 Ok(())
@@ -32,6 +32,7 @@ use pumpkin_protocol::{
     },
 };
 use pumpkin_world::block::{block_registry::get_block_by_item, BlockFace};
+use pumpkin_world::{WORLD_LOWEST_Y, WORLD_MAX_Y};
 
 use super::PlayerConfig;
 
@@ -198,8 +199,9 @@ impl Player {
             )
             .await;
         world
-            .broadcast_packet_except(
+            .broadcast_packet_tracking(
                 &[self.gameprofile.id],
+                entity.chunk_pos.load(),
                 &CHeadRot::new(entity_id.into(), yaw as u8),
             )
             .await;
@@ -229,11 +231,11 @@ impl Player {
         let packet =
             CUpdateEntityRot::new(entity_id.into(), yaw as u8, pitch as u8, rotation.ground);
         world
-            .broadcast_packet_except(&[self.gameprofile.id], &packet)
+            .broadcast_packet_tracking(&[self.gameprofile.id], entity.chunk_pos.load(), &packet)
             .await;
         let packet = CHeadRot::new(entity_id.into(), yaw as u8);
         world
-            .broadcast_packet_except(&[self.gameprofile.id], &packet)
+            .broadcast_packet_tracking(&[self.gameprofile.id], entity.chunk_pos.load(), &packet)
             .await;
     }
 
@@ -250,7 +252,7 @@ impl Player {
                 &command.command,
             )
             .await;
-        if ADVANCED_CONFIG.commands.log_console {
+        if ADVANCED_CONFIG.get().commands.log_console {
             log::info!(
                 "Player ({}): executed command /{}",
                 self.gameprofile.name,
@@ -327,8 +329,9 @@ impl Player {
                 let id = self.entity_id();
                 let world = &self.living_entity.entity.world;
                 world
-                    .broadcast_packet_except(
+                    .broadcast_packet_tracking(
                         &[self.gameprofile.id],
+                        self.living_entity.entity.chunk_pos.load(),
                         &CEntityAnimation::new(id.into(), animation as u8),
                     )
                     .await;
@@ -441,13 +444,14 @@ impl Player {
         match action {
             ActionType::Attack => {
                 let entity_id = interact.entity_id;
-                let config = &ADVANCED_CONFIG.pvp;
+                let world = &entity.world;
+                let advanced_config = ADVANCED_CONFIG.get();
+                let config = advanced_config.pvp.pvp_for(world.name());
                 // TODO: do validation and stuff
                 if !config.enabled {
                     return;
                 }
 
-                let world = &entity.world;
                 let victim = world.get_player_by_entityid(entity_id.0).await;
                 let Some(victim) = victim else {
                     self.kick(TextComponent::text("Interacted with invalid entity id"))
@@ -582,24 +586,31 @@ impl Player {
                 if let Some(block) = block {
                     let entity = &self.living_entity.entity;
                     let world = &entity.world;
+                    let world_pos = WorldPosition(location.0 + face.to_offset());
 
-                    // TODO: Config
-                    // Decrease Block count
-                    if self.gamemode.load() != GameMode::Creative {
-                        item.item_count -= 1;
-                        if item.item_count == 0 {
-                            *item_slot = None;
+                    // The client reverts its predicted block once the sequence gets acknowledged
+                    if ADVANCED_CONFIG.get().can_place_at(
+                        world_pos.0.y,
+                        WORLD_LOWEST_Y.into(),
+                        WORLD_MAX_Y.into(),
+                    ) {
+                        // TODO: Config
+                        // Decrease Block count
+                        if self.gamemode.load() != GameMode::Creative {
+                            item.item_count -= 1;
+                            if item.item_count == 0 {
+                                *item_slot = None;
+                            }
                         }
-                    }
 
-                    let world_pos = WorldPosition(location.0 + face.to_offset());
-                    let block_bounding_box = BoundingBox::from_block(&world_pos);
-                    let bounding_box = entity.bounding_box.load();
-                    //TODO: Make this check for every entity in that posistion
-                    if !bounding_box.intersects(&block_bounding_box) {
-                        world
-                            .set_block_state(world_pos, block.default_state_id)
-                            .await;
+                        let block_bounding_box = BoundingBox::from_block(&world_pos);
+                        let bounding_box = entity.bounding_box.load();
+                        //TODO: Make this check for every entity in that posistion
+                        if !bounding_box.intersects(&block_bounding_box) {
+                            world
+                                .set_block_state(world_pos, block.default_state_id)
+                                .await;
+                        }
                     }
                 }
                 self.client
@@ -20,6 +20,9 @@ pub async fn send_c_commands_packet<'a>(
         let Ok(tree) = dispatcher.get_tree(key) else {
             continue;
         };
+        if !cmd_src.can_use(tree) {
+            continue;
+        }
 
         let (is_executable, child_nodes) =
             nodes_to_proto_node_builders(&cmd_src, &tree.nodes, &tree.children);
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use pumpkin_core::text::TextComponent;
+use pumpkin_protocol::compression_stats::GLOBAL_COMPRESSION_STATS;
+
+use crate::command::{
+    args::ConsumedArgs, tree::CommandTree, CommandError, CommandExecutor, CommandSender,
+};
+
+const NAMES: [&str; 1] = ["compression"];
+
+const DESCRIPTION: &str = "Print how much bandwidth packet compression saved.";
+
+struct CompressionExecutor;
+
+#[async_trait]
+impl CommandExecutor for CompressionExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender<'a>,
+        _server: &crate::server::Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let stats = &GLOBAL_COMPRESSION_STATS;
+        let message = match stats.compression_ratio() {
+            None => "No packets were compressed yet.".to_string(),
+            Some(ratio) => format!(
+                "Sent {} bytes as {}, received {} bytes as {}. Compression saved {} bytes ({:.1}%)",
+                stats.sent_uncompressed(),
+                stats.sent_compressed(),
+                stats.received_uncompressed(),
+                stats.received_compressed(),
+                stats.saved_bytes(),
+                (1f64 - ratio) * 100f64
+            ),
+        };
+
+        sender.send_message(TextComponent::text(&message)).await;
+
+        Ok(())
+    }
+}
+
+pub fn init_command_tree<'a>() -> CommandTree<'a> {
+    CommandTree::new(NAMES, DESCRIPTION).execute(&CompressionExecutor)
+}
@@ -3,7 +3,6 @@ use async_trait::async_trait;
 use crate::command::args::arg_gamemode::GamemodeArgumentConsumer;
 use crate::command::args::GetCloned;
 
-use crate::entity::player::PermissionLvl;
 use crate::TextComponent;
 
 use crate::command::args::arg_players::PlayersArgumentConsumer;
@@ -109,12 +108,10 @@ impl CommandExecutor for GamemodeTargetPlayer {
 #[allow(clippy::redundant_closure_for_method_calls)]
 pub fn init_command_tree<'a>() -> CommandTree<'a> {
     CommandTree::new(NAMES, DESCRIPTION).with_child(
-        require(&|sender| sender.has_permission_lvl(PermissionLvl::Two)).with_child(
-            argument(ARG_GAMEMODE, &GamemodeArgumentConsumer)
-                .with_child(require(&|sender| sender.is_player()).execute(&GamemodeTargetSelf))
-                .with_child(
-                    argument(ARG_TARGET, &PlayersArgumentConsumer).execute(&GamemodeTargetPlayer),
-                ),
-        ),
+        argument(ARG_GAMEMODE, &GamemodeArgumentConsumer)
+            .with_child(require(&|sender| sender.is_player()).execute(&GamemodeTargetSelf))
+            .with_child(
+                argument(ARG_TARGET, &PlayersArgumentConsumer).execute(&GamemodeTargetPlayer),
+            ),
     )
 }
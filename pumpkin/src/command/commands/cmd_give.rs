@@ -7,9 +7,8 @@ use crate::command::args::arg_item::ItemArgumentConsumer;
 use crate::command::args::arg_players::PlayersArgumentConsumer;
 use crate::command::args::{ConsumedArgs, FindArg, FindArgDefaultName};
 use crate::command::tree::CommandTree;
-use crate::command::tree_builder::{argument, argument_default_name, require};
+use crate::command::tree_builder::{argument, argument_default_name};
 use crate::command::{CommandError, CommandExecutor, CommandSender};
-use crate::entity::player::PermissionLvl;
 
 const NAMES: [&str; 1] = ["give"];
 
@@ -74,12 +73,10 @@ impl CommandExecutor for GiveExecutor {
 
 pub fn init_command_tree<'a>() -> CommandTree<'a> {
     CommandTree::new(NAMES, DESCRIPTION).with_child(
-        require(&|sender| sender.has_permission_lvl(PermissionLvl::Two)).with_child(
-            argument_default_name(&PlayersArgumentConsumer).with_child(
-                argument(ARG_ITEM, &ItemArgumentConsumer)
-                    .execute(&GiveExecutor)
-                    .with_child(argument_default_name(&ITEM_COUNT_CONSUMER).execute(&GiveExecutor)),
-            ),
+        argument_default_name(&PlayersArgumentConsumer).with_child(
+            argument(ARG_ITEM, &ItemArgumentConsumer)
+                .execute(&GiveExecutor)
+                .with_child(argument_default_name(&ITEM_COUNT_CONSUMER).execute(&GiveExecutor)),
         ),
     )
 }
@@ -34,7 +34,7 @@ impl CommandExecutor for ListExecutor {
             &format!(
                 "There are {} of a max of {} players online: {}",
                 players.len(),
-                BASIC_CONFIG.max_players,
+                BASIC_CONFIG.get().max_players,
                 players
                     .iter()
                     .map(|player| &player.gameprofile.name)
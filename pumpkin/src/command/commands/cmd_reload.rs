@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use itertools::Itertools;
+use pumpkin_config::{AdvancedConfiguration, BASIC_CONFIG};
+use pumpkin_core::text::color::NamedColor;
+use pumpkin_core::text::TextComponent;
+
+use crate::command::args::ConsumedArgs;
+use crate::command::tree::CommandTree;
+use crate::command::tree_builder::literal;
+use crate::command::{CommandError, CommandExecutor, CommandSender};
+
+const NAMES: [&str; 1] = ["reload"];
+
+const DESCRIPTION: &str =
+    "Reload both config files, the features config (features.toml) or the server favicon.";
+
+struct ReloadAllExecutor;
+
+struct ReloadFeaturesExecutor;
+
+struct ReloadFaviconExecutor;
+
+#[async_trait]
+impl CommandExecutor for ReloadFaviconExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender<'a>,
+        server: &crate::server::Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        server
+            .get_status()
+            .lock()
+            .await
+            .reload_favicon(&BASIC_CONFIG.get());
+        sender
+            .send_message(TextComponent::text("Reloaded the server favicon"))
+            .await;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for ReloadAllExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender<'a>,
+        server: &crate::server::Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let message = match server.reload_config().await {
+            Ok(changed) if changed.is_empty() => {
+                TextComponent::text("Reloaded the config, nothing changed")
+            }
+            Ok(changed) => TextComponent::text_string(format!(
+                "Reloaded the config, changed: {}",
+                changed.join(", ")
+            )),
+            Err(errors) => TextComponent::text_string(format!(
+                "Couldn't reload the config. {}",
+                errors.iter().map(ToString::to_string).join(", ")
+            ))
+            .color_named(NamedColor::Red),
+        };
+        sender.send_message(message).await;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommandExecutor for ReloadFeaturesExecutor {
+    async fn execute<'a>(
+        &self,
+        sender: &mut CommandSender<'a>,
+        _server: &crate::server::Server,
+        _args: &ConsumedArgs<'a>,
+    ) -> Result<(), CommandError> {
+        let message = match AdvancedConfiguration::reload() {
+            Ok(changed) if changed.is_empty() => {
+                TextComponent::text("Reloaded features.toml, nothing changed")
+            }
+            Ok(changed) => TextComponent::text_string(format!(
+                "Reloaded features.toml, changed: {}",
+                changed.join(", ")
+            )),
+            Err(errors) => TextComponent::text_string(format!(
+                "Couldn't reload features.toml. {}",
+                errors.iter().map(ToString::to_string).join(", ")
+            ))
+            .color_named(NamedColor::Red),
+        };
+        sender.send_message(message).await;
+
+        Ok(())
+    }
+}
+
+pub fn init_command_tree<'a>() -> CommandTree<'a> {
+    CommandTree::new(NAMES, DESCRIPTION)
+        .with_child(literal("all").execute(&ReloadAllExecutor))
+        .with_child(literal("features").execute(&ReloadFeaturesExecutor))
+        .with_child(literal("favicon").execute(&ReloadFaviconExecutor))
+}
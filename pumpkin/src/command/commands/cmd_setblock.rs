@@ -8,7 +8,6 @@ use crate::command::args::{ConsumedArgs, FindArg};
 use crate::command::tree::CommandTree;
 use crate::command::tree_builder::{argument, literal, require};
 use crate::command::{CommandError, CommandExecutor, CommandSender};
-use crate::entity::player::PermissionLvl;
 
 const NAMES: [&str; 1] = ["setblock"];
 
@@ -79,10 +78,7 @@ impl CommandExecutor for SetblockExecutor {
 
 pub fn init_command_tree<'a>() -> CommandTree<'a> {
     CommandTree::new(NAMES, DESCRIPTION).with_child(
-        require(&|sender| {
-            sender.has_permission_lvl(PermissionLvl::Two) && sender.world().is_some()
-        })
-        .with_child(
+        require(&|sender| sender.world().is_some()).with_child(
             argument(ARG_BLOCK_POS, &BlockPosArgumentConsumer).with_child(
                 argument(ARG_BLOCK, &BlockArgumentConsumer)
                     .with_child(literal("replace").execute(&SetblockExecutor(Mode::Replace)))
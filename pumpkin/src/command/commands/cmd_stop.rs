@@ -1,12 +1,11 @@
 use async_trait::async_trait;
+use pumpkin_config::ADVANCED_CONFIG;
 use pumpkin_core::text::color::NamedColor;
 use pumpkin_core::text::TextComponent;
 
 use crate::command::args::ConsumedArgs;
 use crate::command::tree::CommandTree;
-use crate::command::tree_builder::require;
 use crate::command::{CommandError, CommandExecutor, CommandSender};
-use crate::entity::player::PermissionLvl;
 
 const NAMES: [&str; 1] = ["stop"];
 
@@ -33,12 +32,13 @@ impl CommandExecutor for StopExecutor {
             player.kick(kick_message.clone()).await;
         }
 
+        if ADVANCED_CONFIG.get().world_gen.profile {
+            log::info!("{}", pumpkin_world::density_profile_report());
+        }
         std::process::exit(0)
     }
 }
 
 pub fn init_command_tree<'a>() -> CommandTree<'a> {
-    CommandTree::new(NAMES, DESCRIPTION).with_child(
-        require(&|sender| sender.has_permission_lvl(PermissionLvl::Four)).execute(&StopExecutor),
-    )
+    CommandTree::new(NAMES, DESCRIPTION).execute(&StopExecutor)
 }
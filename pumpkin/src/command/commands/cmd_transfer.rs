@@ -13,7 +13,6 @@ use crate::command::tree_builder::{argument, argument_default_name, require};
 use crate::command::{
     args::ConsumedArgs, tree::CommandTree, CommandError, CommandExecutor, CommandSender,
 };
-use crate::entity::player::PermissionLvl;
 
 const NAMES: [&str; 1] = ["transfer"];
 
@@ -118,19 +117,15 @@ impl CommandExecutor for TransferTargetPlayer {
 
 pub fn init_command_tree<'a>() -> CommandTree<'a> {
     CommandTree::new(NAMES, DESCRIPTION).with_child(
-        require(&|sender| sender.has_permission_lvl(PermissionLvl::Three)).with_child(
-            argument(ARG_HOSTNAME, &SimpleArgConsumer)
-                .with_child(require(&|sender| sender.is_player()).execute(&TransferTargetSelf))
-                .with_child(
-                    argument_default_name(&PORT_CONSUMER)
-                        .with_child(
-                            require(&|sender| sender.is_player()).execute(&TransferTargetSelf),
-                        )
-                        .with_child(
-                            argument(ARG_PLAYERS, &PlayersArgumentConsumer)
-                                .execute(&TransferTargetPlayer),
-                        ),
-                ),
-        ),
+        argument(ARG_HOSTNAME, &SimpleArgConsumer)
+            .with_child(require(&|sender| sender.is_player()).execute(&TransferTargetSelf))
+            .with_child(
+                argument_default_name(&PORT_CONSUMER)
+                    .with_child(require(&|sender| sender.is_player()).execute(&TransferTargetSelf))
+                    .with_child(
+                        argument(ARG_PLAYERS, &PlayersArgumentConsumer)
+                            .execute(&TransferTargetPlayer),
+                    ),
+            ),
     )
 }
@@ -1,4 +1,5 @@
 pub mod cmd_clear;
+pub mod cmd_compression;
 pub mod cmd_craft;
 pub mod cmd_echest;
 pub mod cmd_gamemode;
@@ -8,6 +9,7 @@ pub mod cmd_kick;
 pub mod cmd_kill;
 pub mod cmd_list;
 pub mod cmd_pumpkin;
+pub mod cmd_reload;
 pub mod cmd_say;
 pub mod cmd_setblock;
 pub mod cmd_stop;
@@ -97,6 +97,9 @@ impl<'a> CommandDispatcher<'a> {
         let Ok(tree) = self.get_tree(key) else {
             return Vec::new();
         };
+        if !src.can_use(tree) {
+            return Vec::new();
+        }
 
         let mut suggestions = HashSet::new();
 
@@ -156,6 +159,11 @@ impl<'a> CommandDispatcher<'a> {
         let raw_args: Vec<&str> = parts.rev().collect();
 
         let tree = self.get_tree(key)?;
+        if !src.can_use(tree) {
+            return Err(GeneralCommandIssue(
+                "You don't have permission to use this command".to_string(),
+            ));
+        }
 
         // try paths until fitting path is found
         for path in tree.iter_paths() {
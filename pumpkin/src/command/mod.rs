@@ -9,12 +9,15 @@ use crate::world::World;
 use args::ConsumedArgs;
 use async_trait::async_trait;
 use commands::{
-    cmd_clear, cmd_craft, cmd_echest, cmd_gamemode, cmd_give, cmd_help, cmd_kick, cmd_kill,
-    cmd_list, cmd_pumpkin, cmd_say, cmd_setblock, cmd_stop, cmd_teleport, cmd_worldborder,
+    cmd_clear, cmd_compression, cmd_craft, cmd_echest, cmd_gamemode, cmd_give, cmd_help, cmd_kick,
+    cmd_kill, cmd_list, cmd_pumpkin, cmd_reload, cmd_say, cmd_setblock, cmd_stop, cmd_teleport,
+    cmd_worldborder,
 };
 use dispatcher::CommandError;
+use pumpkin_config::ADVANCED_CONFIG;
 use pumpkin_core::math::vector3::Vector3;
 use pumpkin_core::text::TextComponent;
+use tree::CommandTree;
 
 pub mod args;
 pub mod client_cmd_suggestions;
@@ -79,6 +82,15 @@ impl<'a> CommandSender<'a> {
         }
     }
 
+    /// Whether the sender reaches the level required for the command, see `commands.permission_levels` in the config
+    #[must_use]
+    pub fn can_use(&self, tree: &CommandTree) -> bool {
+        ADVANCED_CONFIG
+            .get()
+            .commands
+            .is_allowed(tree.names[0], self.permission_lvl() as u8)
+    }
+
     #[must_use]
     pub fn has_permission_lvl(&self, lvl: PermissionLvl) -> bool {
         match self {
@@ -124,6 +136,8 @@ pub fn default_dispatcher<'a>() -> Arc<CommandDispatcher<'a>> {
     dispatcher.register(cmd_clear::init_command_tree());
     dispatcher.register(cmd_setblock::init_command_tree());
     dispatcher.register(cmd_transfer::init_command_tree());
+    dispatcher.register(cmd_reload::init_command_tree());
+    dispatcher.register(cmd_compression::init_command_tree());
 
     Arc::new(dispatcher)
 }
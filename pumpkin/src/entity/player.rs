@@ -353,7 +353,8 @@ impl Player {
         let world = &self.living_entity.entity.world;
         let victim_entity = &victim.living_entity.entity;
         let attacker_entity = &self.living_entity.entity;
-        let config = &ADVANCED_CONFIG.pvp;
+        let advanced_config = ADVANCED_CONFIG.get();
+        let config = advanced_config.pvp.pvp_for(world.name());
 
         let pos = victim_entity.pos.load();
 
@@ -0,0 +1,320 @@
+use std::{
+    borrow::Cow,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    net::{Ipv4Addr, Ipv6Addr},
+    path::{Path, PathBuf},
+};
+
+use log::{Log, Metadata, Record};
+use parking_lot::Mutex;
+use simple_logger::SimpleLogger;
+use time::OffsetDateTime;
+
+/// A log file which is moved to `<name>.1` once it would grow beyond `max_size`,
+/// the older files are shifted to `<name>.2` and so on, until `max_files`
+pub struct RollingFile {
+    path: PathBuf,
+    max_size: u64,
+    max_files: u32,
+    file: File,
+    size: u64,
+}
+
+impl RollingFile {
+    /// Appends to the file if it already exists
+    pub fn open(path: &Path, max_size: u64, max_files: u32) -> io::Result<Self> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let file = Self::open_file(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_size,
+            max_files,
+            size: file.metadata()?.len(),
+            file,
+        })
+    }
+
+    fn open_file(path: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_size {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{line}")?;
+        self.size += len;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            // The oldest file falls out, the others move up by one
+            match fs::remove_file(self.rotated(self.max_files)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+            for i in (1..self.max_files).rev() {
+                let from = self.rotated(i);
+                if from.exists() {
+                    fs::rename(from, self.rotated(i + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated(1))?;
+            self.file = Self::open_file(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+
+    fn rotated(&self, i: u32) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{i}"));
+        self.path.with_file_name(name)
+    }
+}
+
+/// Logs to the console and optionally into a [`RollingFile`], see `logging` in the advanced config.
+///
+/// Both get the same messages, with `scrub_ips` the addresses are removed before they reach either of them
+pub struct PumpkinLogger {
+    console: SimpleLogger,
+    file: Option<Mutex<RollingFile>>,
+    timestamps: bool,
+    scrub_ips: bool,
+}
+
+impl PumpkinLogger {
+    #[must_use]
+    pub fn new(console: SimpleLogger, file: Option<RollingFile>, timestamps: bool) -> Self {
+        Self {
+            console,
+            file: file.map(Mutex::new),
+            timestamps,
+            scrub_ips: false,
+        }
+    }
+
+    #[must_use]
+    pub fn with_scrub_ips(mut self, scrub_ips: bool) -> Self {
+        self.scrub_ips = scrub_ips;
+        self
+    }
+
+    pub fn init(self) -> Result<(), log::SetLoggerError> {
+        log::set_max_level(self.console.max_level());
+        log::set_boxed_logger(Box::new(self))
+    }
+
+    fn format_line(&self, record: &Record) -> String {
+        let line = format!(
+            "{:<5} [{}] {}",
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if !self.timestamps {
+            return line;
+        }
+        let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+        match now.format(time::macros::format_description!(
+            "[year]-[month]-[day] [hour]:[minute]:[second]"
+        )) {
+            Ok(timestamp) => format!("{timestamp} {line}"),
+            Err(_) => line,
+        }
+    }
+}
+
+impl Log for PumpkinLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.console.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if self.scrub_ips {
+            let message = record.args().to_string();
+            if let Cow::Owned(scrubbed) = scrub_ips(&message) {
+                self.write(&record.to_builder().args(format_args!("{scrubbed}")).build());
+                return;
+            }
+        }
+        self.write(record);
+    }
+
+    fn flush(&self) {
+        self.console.flush();
+        if let Some(file) = &self.file {
+            let _ = file.lock().flush();
+        }
+    }
+}
+
+impl PumpkinLogger {
+    fn write(&self, record: &Record) {
+        self.console.log(record);
+        if let Some(file) = &self.file {
+            if let Err(err) = file.lock().write_line(&self.format_line(record)) {
+                // Logging the error would end up here again
+                eprintln!("Failed to write to the log file: {err}");
+            }
+        }
+    }
+}
+
+const SCRUBBED_IPV4: &str = "xxx.xxx.xxx.xxx";
+const SCRUBBED_IPV6: &str = "[scrubbed]";
+
+/// Replaces the IPv4 and IPv6 addresses in `message`, ports of socket addresses are kept.
+/// IPv6 addresses may be in brackets and contain an IPv4 address, like `::ffff:192.168.1.5`
+#[must_use]
+pub fn scrub_ips(message: &str) -> Cow<'_, str> {
+    let is_address_char = |ch: char| ch.is_ascii_hexdigit() || ch == '.' || ch == ':';
+
+    let mut scrubbed = String::new();
+    // Everything before this is already in `scrubbed`
+    let mut copied = 0;
+    let mut chars = message.char_indices().peekable();
+    let mut previous = None;
+    while let Some((start, ch)) = chars.next() {
+        let is_start = is_address_char(ch)
+            && !previous
+                .is_some_and(|previous: char| previous.is_alphanumeric() || previous == '_');
+        previous = Some(ch);
+        if !is_start {
+            continue;
+        }
+
+        let mut end = start + ch.len_utf8();
+        while let Some((i, ch)) = chars.next_if(|(_, ch)| is_address_char(*ch)) {
+            end = i + ch.len_utf8();
+            previous = Some(ch);
+        }
+        let Some((len, replacement)) = find_address(&message[start..end]) else {
+            continue;
+        };
+
+        let (mut start, mut end) = (start, start + len);
+        let bracketed = message[..start].ends_with('[') && message[end..].starts_with(']');
+        if bracketed {
+            start -= 1;
+            end += 1;
+        }
+        scrubbed.push_str(&message[copied..start]);
+        scrubbed.push_str(replacement);
+        copied = end;
+    }
+
+    if copied == 0 {
+        return Cow::Borrowed(message);
+    }
+    scrubbed.push_str(&message[copied..]);
+    Cow::Owned(scrubbed)
+}
+
+/// The length of the address at the start of `candidate` and what it gets replaced with
+fn find_address(candidate: &str) -> Option<(usize, &'static str)> {
+    // Punctuation after an address is no part of it
+    let candidate = candidate.trim_end_matches(['.', ':']);
+    if !candidate.contains(['.', ':']) {
+        return None;
+    }
+    if candidate.contains(':') && candidate.parse::<Ipv6Addr>().is_ok() {
+        return Some((candidate.len(), SCRUBBED_IPV6));
+    }
+    // An IPv4 address, possibly followed by a port
+    let address = match candidate.split_once(':') {
+        Some((address, port)) if port.parse::<u16>().is_ok() => address,
+        _ => candidate,
+    };
+    address
+        .parse::<Ipv4Addr>()
+        .ok()
+        .map(|_| (address.len(), SCRUBBED_IPV4))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{scrub_ips, RollingFile};
+
+    #[test]
+    fn test_scrub_ips() {
+        assert_eq!(
+            scrub_ips("Accepted connection from: 192.168.1.5:25565 (id 3)"),
+            "Accepted connection from: xxx.xxx.xxx.xxx:25565 (id 3)"
+        );
+        assert_eq!(
+            scrub_ips("Accepted connection from: [2001:db8::1]:25565 (id 4)"),
+            "Accepted connection from: [scrubbed]:25565 (id 4)"
+        );
+        assert_eq!(
+            scrub_ips("Mapped [::ffff:192.168.1.5]:25565 and ::1, bare 10.0.0.1."),
+            "Mapped [scrubbed]:25565 and [scrubbed], bare xxx.xxx.xxx.xxx."
+        );
+        assert_eq!(
+            scrub_ips("fe80::1 to 2001:0db8:85a3:0000:0000:8a2e:0370:7334"),
+            "[scrubbed] to [scrubbed]"
+        );
+
+        // Nothing which only looks similar
+        for message in [
+            "Running Pumpkin for 1.21.4 at 12:30:45",
+            "Chunk 0.5 of cafe:beef, deadbeef",
+            "Version v1.2.3.4 and 999.1.1.1",
+            "Nothing to see here",
+        ] {
+            assert_eq!(scrub_ips(message), message);
+        }
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = std::env::temp_dir().join(format!("pumpkin-log-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("logs").join("latest.log");
+
+        // Every line is 10 bytes with the newline, so 2 lines fit into a file
+        let mut file = RollingFile::open(&path, 25, 2).unwrap();
+        for i in 0..7 {
+            file.write_line(&format!("line {i:04}")).unwrap();
+        }
+        file.flush().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.join("logs").join(name)).unwrap();
+        assert_eq!(read("latest.log"), "line 0006\n");
+        assert_eq!(read("latest.log.1"), "line 0004\nline 0005\n");
+        assert_eq!(read("latest.log.2"), "line 0002\nline 0003\n");
+        // Beyond max_files
+        assert!(!dir.join("logs").join("latest.log.3").exists());
+
+        // Reopening appends and keeps counting the existing size
+        drop(file);
+        let mut file = RollingFile::open(&path, 25, 2).unwrap();
+        file.write_line("line 0007").unwrap();
+        file.write_line("line 0008").unwrap();
+        file.flush().unwrap();
+        assert_eq!(read("latest.log"), "line 0008\n");
+        assert_eq!(read("latest.log.1"), "line 0006\nline 0007\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
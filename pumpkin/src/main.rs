@@ -31,7 +31,7 @@ use tokio::signal::unix::{signal, SignalKind};
 use std::sync::Arc;
 
 use crate::server::CURRENT_MC_VERSION;
-use pumpkin_config::{ADVANCED_CONFIG, BASIC_CONFIG};
+use pumpkin_config::{ConfigWatcher, ADVANCED_CONFIG, BASIC_CONFIG, HOT_RELOAD_INTERVAL};
 use pumpkin_core::text::{color::NamedColor, TextComponent};
 use pumpkin_protocol::CURRENT_MC_PROTOCOL;
 use rcon::RCONServer;
@@ -43,44 +43,48 @@ pub mod client;
 pub mod command;
 pub mod entity;
 pub mod error;
+pub mod logging;
 pub mod proxy;
 pub mod query;
 pub mod rcon;
 pub mod server;
 pub mod world;
 
-fn scrub_address(ip: &str) -> String {
-    use pumpkin_config::BASIC_CONFIG;
-    if BASIC_CONFIG.scrub_ips {
-        ip.chars()
-            .map(|ch| if ch == '.' || ch == ':' { ch } else { 'x' })
-            .collect()
-    } else {
-        ip.to_string()
-    }
-}
-
 fn init_logger() {
     use pumpkin_config::ADVANCED_CONFIG;
-    if ADVANCED_CONFIG.logging.enabled {
+    let config = ADVANCED_CONFIG.get();
+    if config.logging.enabled {
         let mut logger = simple_logger::SimpleLogger::new();
         logger = logger.with_timestamp_format(time::macros::format_description!(
             "[year]-[month]-[day] [hour]:[minute]:[second]"
         ));
 
-        if !ADVANCED_CONFIG.logging.timestamp {
+        if !config.logging.timestamp {
             logger = logger.without_timestamps();
         }
 
-        if ADVANCED_CONFIG.logging.env {
+        if config.logging.env {
             logger = logger.env();
         }
 
-        logger = logger.with_level(convert_logger_filter(ADVANCED_CONFIG.logging.level));
+        logger = logger.with_level(convert_logger_filter(config.logging.level));
+
+        logger = logger.with_colors(config.logging.color);
+        logger = logger.with_threads(config.logging.threads);
 
-        logger = logger.with_colors(ADVANCED_CONFIG.logging.color);
-        logger = logger.with_threads(ADVANCED_CONFIG.logging.threads);
-        logger.init().unwrap();
+        let file = config.logging.file.as_ref().and_then(|path| {
+            logging::RollingFile::open(
+                path,
+                config.logging.max_file_size(),
+                config.logging.max_files,
+            )
+            .inspect_err(|err| eprintln!("Failed to open the log file {}: {err}", path.display()))
+            .ok()
+        });
+        logging::PumpkinLogger::new(logger, file, config.logging.timestamp)
+            .with_scrub_ips(BASIC_CONFIG.get().scrub_ips)
+            .init()
+            .unwrap();
     }
 }
 
@@ -171,9 +175,53 @@ fn log_system_info() {
     }
 }
 
+/// Prints both config files with the secrets redacted and exits
+fn dump_config() -> ! {
+    match pumpkin_config::dump_all() {
+        Ok(dump) => {
+            println!("{dump}");
+            std::process::exit(0)
+        }
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{error}");
+            }
+            std::process::exit(1)
+        }
+    }
+}
+
+/// Validates the config files without starting the server and exits
+fn check_config() -> ! {
+    match pumpkin_config::validate_all() {
+        Ok(warnings) => {
+            for warning in &warnings {
+                eprintln!("Warning: {warning}");
+            }
+            println!("The config files are valid");
+            std::process::exit(0)
+        }
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{error}");
+            }
+            eprintln!("Found {} config errors", errors.len());
+            std::process::exit(1)
+        }
+    }
+}
+
 #[tokio::main]
 #[expect(clippy::too_many_lines)]
 async fn main() -> io::Result<()> {
+    // Checked before anything touches the configs, as loading them writes the defaults
+    if std::env::args().any(|arg| arg == "--check-config") {
+        check_config();
+    }
+    if std::env::args().any(|arg| arg == "--dump-config") {
+        dump_config();
+    }
+
     init_logger();
 
     // let rt = tokio::runtime::Builder::new_multi_thread()
@@ -218,7 +266,7 @@ async fn main() -> io::Result<()> {
     let time = Instant::now();
 
     // Setup the TCP server socket.
-    let listener = tokio::net::TcpListener::bind(BASIC_CONFIG.server_address)
+    let listener = tokio::net::TcpListener::bind(BASIC_CONFIG.get().server_address)
         .await
         .expect("Failed to start TcpListener");
     // In the event the user puts 0 for their port, this will allow us to know what port it is running on
@@ -226,11 +274,11 @@ async fn main() -> io::Result<()> {
         .local_addr()
         .expect("Unable to get the address of server!");
 
-    let use_console = ADVANCED_CONFIG.commands.use_console;
-    let rcon = ADVANCED_CONFIG.rcon.clone();
+    let use_console = ADVANCED_CONFIG.get().commands.use_console;
+    let rcon = ADVANCED_CONFIG.get().rcon.clone();
 
     let server = Arc::new(Server::new());
-    let mut ticker = Ticker::new(BASIC_CONFIG.tps);
+    let mut ticker = Ticker::new(BASIC_CONFIG.get().tick_duration());
 
     log::info!("Started Server took {}ms", time.elapsed().as_millis());
     log::info!("You now can connect to the server, Listening on {}", addr);
@@ -238,6 +286,9 @@ async fn main() -> io::Result<()> {
     if use_console {
         setup_console(server.clone());
     }
+    if ADVANCED_CONFIG.get().hot_reload {
+        setup_config_watcher(server.clone());
+    }
     if rcon.enabled {
         let server = server.clone();
         tokio::spawn(async move {
@@ -245,9 +296,15 @@ async fn main() -> io::Result<()> {
         });
     }
 
-    if ADVANCED_CONFIG.query.enabled {
+    if ADVANCED_CONFIG.get().query.enabled {
         log::info!("Query protocol enabled. Starting...");
-        tokio::spawn(query::start_query_handler(server.clone(), addr));
+        let server = server.clone();
+        tokio::spawn(async move {
+            match query::QueryServer::bind(server, addr).await {
+                Ok(query) => query.run().await,
+                Err(err) => log::error!("Failed to start the query server: {}", err),
+            }
+        });
     }
 
     {
@@ -262,6 +319,15 @@ async fn main() -> io::Result<()> {
         // Asynchronously wait for an inbound socket.
         let (connection, address) = listener.accept().await?;
 
+        if !ADVANCED_CONFIG
+            .get()
+            .access_control
+            .is_ip_allowed(address.ip())
+        {
+            log::info!("Refused connection from: {address} (access control)");
+            continue;
+        }
+
         if let Err(e) = connection.set_nodelay(true) {
             log::warn!("failed to set TCP_NODELAY {e}");
         }
@@ -269,11 +335,7 @@ async fn main() -> io::Result<()> {
         let id = master_client_id;
         master_client_id = master_client_id.wrapping_add(1);
 
-        log::info!(
-            "Accepted connection from: {} (id {})",
-            scrub_address(&format!("{address}")),
-            id
-        );
+        log::info!("Accepted connection from: {address} (id {id})");
 
         let client = Arc::new(Client::new(connection, addr, id));
 
@@ -295,7 +357,11 @@ async fn main() -> io::Result<()> {
             {
                 let (player, world) = server.add_player(client).await;
                 world
-                    .spawn_player(&BASIC_CONFIG, player.clone(), &server.command_dispatcher)
+                    .spawn_player(
+                        &BASIC_CONFIG.get(),
+                        player.clone(),
+                        &server.command_dispatcher,
+                    )
                     .await;
 
                 // poll Player
@@ -324,6 +390,9 @@ fn handle_interrupt() {
             .color_named(NamedColor::Red)
             .to_pretty_console()
     );
+    if ADVANCED_CONFIG.get().world_gen.profile {
+        log::info!("{}", pumpkin_world::density_profile_report());
+    }
     std::process::exit(0);
 }
 
@@ -355,6 +424,28 @@ async fn setup_sighandler() -> io::Result<()> {
     Ok(())
 }
 
+fn setup_config_watcher(server: Arc<Server>) {
+    tokio::spawn(async move {
+        let mut watcher = ConfigWatcher::new();
+        let mut interval = tokio::time::interval(HOT_RELOAD_INTERVAL);
+        loop {
+            interval.tick().await;
+            if !watcher.poll() {
+                continue;
+            }
+            match server.reload_config().await {
+                Ok(changed) if changed.is_empty() => {}
+                Ok(changed) => log::info!("Reloaded the config, changed: {}", changed.join(", ")),
+                Err(errors) => {
+                    for error in errors {
+                        log::warn!("Couldn't reload the config. {error}");
+                    }
+                }
+            }
+        }
+    });
+}
+
 fn setup_console(server: Arc<Server>) {
     tokio::spawn(async move {
         let stdin = tokio::io::stdin();
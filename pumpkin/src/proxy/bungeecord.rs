@@ -2,9 +2,10 @@ use std::net::IpAddr;
 
 use pumpkin_protocol::Property;
 use thiserror::Error;
+use uuid::Uuid;
 
 use crate::{
-    client::authentication::{offline_uuid, GameProfile},
+    client::authentication::{offline_player_uuid, GameProfile},
     Client,
 };
 
@@ -16,50 +17,106 @@ pub enum BungeeCordError {
     FailedParseUUID,
     #[error("Failed to parse Properties")]
     FailedParseProperties,
-    #[error("Failed to make offline UUID")]
-    FailedMakeOfflineUUID,
 }
 
-pub async fn bungeecord_login(
-    client: &Client,
-    username: String,
-) -> Result<(IpAddr, GameProfile), BungeeCordError> {
-    let server_address = client.server_address.lock().await;
-    let data = server_address.split('\0').take(4).collect::<Vec<_>>();
-
-    // Ip of player, only given if ip_forward on bungee is true
-    let ip = match data.get(1) {
-        Some(ip) => ip
-            .parse()
-            .map_err(|_| BungeeCordError::FailedParseAddress)?,
-        None => client.address.lock().await.ip(),
-    };
+/// The player info BungeeCord appends to the server address of the handshake, separated by `\0`
+pub struct BungeeCordForwarding {
+    /// Only given if `ip_forward` is enabled in BungeeCord
+    pub ip: Option<IpAddr>,
+    /// Only given if `ip_forward` is enabled in BungeeCord
+    pub id: Option<Uuid>,
+    /// Only given if `ip_forward` and `online_mode` are enabled in BungeeCord
+    pub properties: Vec<Property>,
+}
 
-    // Uuid of player, only given if ip_forward on bungee is true
-    let id = match data.get(2) {
-        Some(uuid) => uuid.parse().map_err(|_| BungeeCordError::FailedParseUUID)?,
-        None => {
-            offline_uuid(username.as_str()).map_err(|_| BungeeCordError::FailedMakeOfflineUUID)?
-        }
-    };
+/// Parses the server address of the handshake, e.g. `host\0ip\0uuid\0properties`
+pub fn parse_forwarded_address(
+    server_address: &str,
+) -> Result<BungeeCordForwarding, BungeeCordError> {
+    let data = server_address.split('\0').take(4).collect::<Vec<_>>();
 
-    // Read properties and get textures
-    // Properties of player's game profile, only given if ip_forward and online_mode
-    // on bungee both are true
-    let properties: Vec<Property> = match data.get(3) {
+    let ip = data
+        .get(1)
+        .map(|ip| ip.parse())
+        .transpose()
+        .map_err(|_| BungeeCordError::FailedParseAddress)?;
+    // BungeeCord sends the UUID without dashes
+    let id = data
+        .get(2)
+        .map(|uuid| uuid.parse())
+        .transpose()
+        .map_err(|_| BungeeCordError::FailedParseUUID)?;
+    let properties = match data.get(3) {
         Some(properties) => {
             serde_json::from_str(properties).map_err(|_| BungeeCordError::FailedParseProperties)?
         }
         None => vec![],
     };
 
+    Ok(BungeeCordForwarding { ip, id, properties })
+}
+
+pub async fn bungeecord_login(
+    client: &Client,
+    username: String,
+) -> Result<(IpAddr, GameProfile), BungeeCordError> {
+    let forwarding = parse_forwarded_address(&client.server_address.lock().await)?;
+
+    let ip = match forwarding.ip {
+        Some(ip) => ip,
+        None => client.address.lock().await.ip(),
+    };
+    let id = forwarding
+        .id
+        .unwrap_or_else(|| offline_player_uuid(username.as_str()));
+
     Ok((
         ip,
         GameProfile {
             id,
             name: username,
-            properties,
+            properties: forwarding.properties,
             profile_actions: None,
         },
     ))
 }
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::{parse_forwarded_address, BungeeCordError};
+
+    #[test]
+    fn test_parse_forwarded_address() {
+        let forwarding = parse_forwarded_address(
+            "play.example.com\0203.0.113.7\0069a79f444e94726a5befca90e38aaf5\0[{\"name\":\"textures\",\"value\":\"dGV4dHVyZXM=\",\"signature\":\"c2lnbmF0dXJl\"}]",
+        )
+        .unwrap();
+        assert_eq!(forwarding.ip.unwrap().to_string(), "203.0.113.7");
+        assert_eq!(
+            forwarding.id,
+            Some(Uuid::from_u128(0x069a_79f4_44e9_4726_a5be_fca9_0e38_aaf5))
+        );
+        assert_eq!(forwarding.properties.len(), 1);
+        assert_eq!(forwarding.properties[0].name, "textures");
+
+        // Without `ip_forward` only the host is sent
+        let forwarding = parse_forwarded_address("play.example.com").unwrap();
+        assert!(forwarding.ip.is_none() && forwarding.id.is_none());
+        assert!(forwarding.properties.is_empty());
+
+        assert!(matches!(
+            parse_forwarded_address("host\0not an ip"),
+            Err(BungeeCordError::FailedParseAddress)
+        ));
+        assert!(matches!(
+            parse_forwarded_address("host\0203.0.113.7\0steve"),
+            Err(BungeeCordError::FailedParseUUID)
+        ));
+        assert!(matches!(
+            parse_forwarded_address("host\0203.0.113.7\0069a79f444e94726a5befca90e38aaf5\0{"),
+            Err(BungeeCordError::FailedParseProperties)
+        ));
+    }
+}
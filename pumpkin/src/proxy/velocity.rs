@@ -1,8 +1,7 @@
-use std::net::{IpAddr, SocketAddr};
+use std::net::IpAddr;
 
 use bytes::{BufMut, BytesMut};
 use hmac::{Hmac, Mac};
-use pumpkin_config::proxy::VelocityConfig;
 use pumpkin_protocol::{
     bytebuf::ByteBuffer, client::login::CLoginPluginRequest, server::login::SLoginPluginResponse,
     Property,
@@ -31,7 +30,7 @@ pub enum VelocityError {
     #[error("Failed to read forward version")]
     FailedReadForwardVersion,
     #[error("Unsupported forwarding version {0}. Maximum supported version is {1}")]
-    UnsupportedForwardVersion(u8, u8),
+    UnsupportedForwardVersion(i32, u8),
     #[error("Failed to read address")]
     FailedReadAddress,
     #[error("Failed to parse address")]
@@ -98,43 +97,122 @@ fn read_game_profile(buf: &mut ByteBuffer) -> Result<GameProfile, VelocityError>
     })
 }
 
+/// The player info forwarded by the proxy
+pub struct ForwardedPlayerInfo {
+    /// The address the player connected to the proxy from
+    pub address: IpAddr,
+    pub profile: GameProfile,
+}
+
+/// Checks the HMAC-SHA256 signature in front of the forwarded player info and decodes it
+pub fn verify_velocity_forwarding(
+    secret: &str,
+    data: &[u8],
+) -> Result<ForwardedPlayerInfo, VelocityError> {
+    if data.len() < 32 {
+        return Err(VelocityError::FailedVerifyIntegrity);
+    }
+    let (signature, data_without_signature) = data.split_at(32);
+
+    if !check_integrity((signature, data_without_signature), secret) {
+        return Err(VelocityError::FailedVerifyIntegrity);
+    }
+    let mut buf = ByteBuffer::new(BytesMut::new());
+    buf.put_slice(data_without_signature);
+
+    // check velocity version
+    let version = buf
+        .get_var_int()
+        .map_err(|_| VelocityError::FailedReadForwardVersion)?
+        .0;
+    if version > i32::from(MAX_SUPPORTED_FORWARDING_VERSION) {
+        return Err(VelocityError::UnsupportedForwardVersion(
+            version,
+            MAX_SUPPORTED_FORWARDING_VERSION,
+        ));
+    }
+    let address = buf
+        .get_string()
+        .map_err(|_| VelocityError::FailedReadAddress)?
+        .parse()
+        .map_err(|_| VelocityError::FailedParseAddres)?;
+    let profile = read_game_profile(&mut buf)?;
+    Ok(ForwardedPlayerInfo { address, profile })
+}
+
 pub fn receive_velocity_plugin_response(
-    port: u16,
-    config: &VelocityConfig,
+    secret: &str,
     response: SLoginPluginResponse,
-) -> Result<(GameProfile, SocketAddr), VelocityError> {
+) -> Result<ForwardedPlayerInfo, VelocityError> {
     log::debug!("received velocity response");
-    if let Some(data) = response.data {
-        let (signature, data_without_signature) = data.split_at(32);
+    let data = response.data.ok_or(VelocityError::NoData)?;
+    verify_velocity_forwarding(secret, &data)
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+    use hmac::Mac;
+    use pumpkin_protocol::{bytebuf::ByteBuffer, VarInt};
+    use uuid::Uuid;
+
+    use super::{verify_velocity_forwarding, HmacSha256, VelocityError};
 
-        if !check_integrity((signature, data_without_signature), &config.secret) {
-            return Err(VelocityError::FailedVerifyIntegrity);
-        }
+    fn forwarding_data(secret: &str, version: i32) -> Vec<u8> {
         let mut buf = ByteBuffer::new(BytesMut::new());
-        buf.put_slice(data_without_signature);
-
-        // check velocity version
-        let version = buf
-            .get_var_int()
-            .map_err(|_| VelocityError::FailedReadForwardVersion)?;
-        let version = version.0 as u8;
-        if version > MAX_SUPPORTED_FORWARDING_VERSION {
-            return Err(VelocityError::UnsupportedForwardVersion(
-                version,
-                MAX_SUPPORTED_FORWARDING_VERSION,
-            ));
-        }
-        let addr = buf
-            .get_string()
-            .map_err(|_| VelocityError::FailedReadAddress)?;
-
-        let socket_addr: SocketAddr = SocketAddr::new(
-            addr.parse::<IpAddr>()
-                .map_err(|_| VelocityError::FailedParseAddres)?,
-            port,
+        buf.put_var_int(&VarInt(version));
+        buf.put_string("203.0.113.7");
+        buf.put_uuid(&Uuid::from_u128(42));
+        buf.put_string("Steve");
+        buf.put_var_int(&VarInt(1));
+        buf.put_string("textures");
+        buf.put_string("dGV4dHVyZXM=");
+        buf.put_bool(true);
+        buf.put_string("c2lnbmF0dXJl");
+        let payload = buf.buf().to_vec();
+
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(&payload);
+        let mut data = mac.finalize().into_bytes().to_vec();
+        data.extend_from_slice(&payload);
+        data
+    }
+
+    #[test]
+    fn test_verify_forwarding() {
+        let info = verify_velocity_forwarding("secret", &forwarding_data("secret", 4)).unwrap();
+        assert_eq!(info.address.to_string(), "203.0.113.7");
+        assert_eq!(info.profile.id, Uuid::from_u128(42));
+        assert_eq!(info.profile.name, "Steve");
+        assert_eq!(info.profile.properties.len(), 1);
+        assert_eq!(info.profile.properties[0].name, "textures");
+        assert_eq!(
+            info.profile.properties[0].signature.as_deref(),
+            Some("c2lnbmF0dXJl")
         );
-        let profile = read_game_profile(&mut buf)?;
-        return Ok((profile, socket_addr));
     }
-    Err(VelocityError::NoData)
+
+    #[test]
+    fn test_reject_forwarding() {
+        assert!(matches!(
+            verify_velocity_forwarding("other", &forwarding_data("secret", 4)),
+            Err(VelocityError::FailedVerifyIntegrity)
+        ));
+        // Too short to even hold the signature
+        assert!(matches!(
+            verify_velocity_forwarding("secret", &[0; 8]),
+            Err(VelocityError::FailedVerifyIntegrity)
+        ));
+        assert!(matches!(
+            verify_velocity_forwarding("secret", &forwarding_data("secret", 260)),
+            Err(VelocityError::UnsupportedForwardVersion(260, _))
+        ));
+
+        let mut tampered = forwarding_data("secret", 4);
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            verify_velocity_forwarding("secret", &tampered),
+            Err(VelocityError::FailedVerifyIntegrity)
+        ));
+    }
 }
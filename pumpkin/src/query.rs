@@ -3,7 +3,7 @@ use std::{
     ffi::{CString, NulError},
     net::SocketAddr,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use pumpkin_config::{ADVANCED_CONFIG, BASIC_CONFIG};
@@ -15,159 +15,219 @@ use tokio::{net::UdpSocket, sync::RwLock, time};
 
 use crate::server::{Server, CURRENT_MC_VERSION};
 
-pub async fn start_query_handler(server: Arc<Server>, bound_addr: SocketAddr) {
-    let mut query_addr = bound_addr;
-    if let Some(port) = ADVANCED_CONFIG.query.port {
-        query_addr.set_port(port);
-    }
+/// How long a challenge token can be used after the handshake, like vanilla
+const CHALLENGE_TOKEN_LIFETIME: Duration = Duration::from_secs(30);
 
-    let socket = Arc::new(
-        UdpSocket::bind(query_addr)
-            .await
-            .expect("Unable to bind to address"),
-    );
+/// Query packets are tiny, anything larger is not a valid request
+const MAX_PACKET_SIZE: usize = 1024;
 
-    // Challange tokens are bound to the IP address and port
-    let valid_challange_tokens = Arc::new(RwLock::new(HashMap::new()));
-    let valid_challange_tokens_clone = valid_challange_tokens.clone();
-    // All challange tokens ever created are expired every 30 seconds
-    tokio::spawn(async move {
-        let mut interval = time::interval(Duration::from_secs(30));
+/// The challenge tokens handed out in handshakes, each one is bound to the address which requested it
+#[derive(Default)]
+pub struct ChallengeTokens {
+    tokens: HashMap<i32, (SocketAddr, Instant)>,
+}
 
-        loop {
-            interval.tick().await;
-            valid_challange_tokens_clone.write().await.clear();
-        }
-    });
-
-    log::info!(
-        "Server query running on {}",
-        socket
-            .local_addr()
-            .expect("Unable to find running address!")
-    );
-
-    loop {
-        let socket = socket.clone();
-        let valid_challange_tokens = valid_challange_tokens.clone();
-        let server = server.clone();
-        let mut buf = vec![0; 1024];
-        let (_, addr) = socket.recv_from(&mut buf).await.unwrap();
-
-        tokio::spawn(async move {
-            if let Err(err) = handle_packet(
-                buf,
-                valid_challange_tokens,
-                server,
-                socket,
-                addr,
-                bound_addr,
-            )
-            .await
-            {
-                log::error!("Interior 0 bytes found! Cannot encode query response! {err}");
-            }
-        });
+impl ChallengeTokens {
+    pub fn issue(&mut self, addr: SocketAddr) -> i32 {
+        let token = rand::thread_rng().gen_range(1..=i32::MAX);
+        self.tokens.insert(token, (addr, Instant::now()));
+        token
+    }
+
+    /// Whether `token` was issued to `addr` and has not expired yet
+    #[must_use]
+    pub fn is_valid(&self, token: i32, addr: SocketAddr) -> bool {
+        self.tokens.get(&token).is_some_and(|(bound_addr, issued)| {
+            *bound_addr == addr && issued.elapsed() < CHALLENGE_TOKEN_LIFETIME
+        })
+    }
+
+    pub fn remove_expired(&mut self) {
+        self.tokens
+            .retain(|_, (_, issued)| issued.elapsed() < CHALLENGE_TOKEN_LIFETIME);
     }
 }
 
-// Errors of packets that don't meet the format aren't returned since we won't handle them anyway
-// The only errors that are thrown are because of a null terminator in a CString
-// since those errors need to be corrected by server owner
-#[inline]
-async fn handle_packet(
-    buf: Vec<u8>,
-    clients: Arc<RwLock<HashMap<i32, SocketAddr>>>,
+/// Answers the GameSpy4 query protocol over UDP, see `query` in the advanced config.
+///
+/// Status requests are only answered with a valid challenge token, so spoofed source addresses
+/// can't be used to amplify traffic. Malformed packets are dropped without a response
+pub struct QueryServer {
+    socket: UdpSocket,
     server: Arc<Server>,
-    socket: Arc<UdpSocket>,
-    addr: SocketAddr,
+    /// The address of the Minecraft server, reported in the responses
     bound_addr: SocketAddr,
-) -> Result<(), NulError> {
-    if let Ok(mut raw_packet) = RawQueryPacket::decode(buf).await {
+    challenge_tokens: RwLock<ChallengeTokens>,
+}
+
+impl QueryServer {
+    /// Binds the configured port, defaulting to the port of `bound_addr`
+    pub async fn bind(server: Arc<Server>, bound_addr: SocketAddr) -> std::io::Result<Self> {
+        let mut query_addr = bound_addr;
+        if let Some(port) = ADVANCED_CONFIG.get().query.port {
+            query_addr.set_port(port);
+        }
+
+        Ok(Self {
+            socket: UdpSocket::bind(query_addr).await?,
+            server,
+            bound_addr,
+            challenge_tokens: RwLock::new(ChallengeTokens::default()),
+        })
+    }
+
+    pub async fn run(self) {
+        let query = Arc::new(self);
+
+        {
+            let query = query.clone();
+            tokio::spawn(async move {
+                let mut interval = time::interval(CHALLENGE_TOKEN_LIFETIME);
+                loop {
+                    interval.tick().await;
+                    query.challenge_tokens.write().await.remove_expired();
+                }
+            });
+        }
+
+        if let Ok(addr) = query.socket.local_addr() {
+            log::info!("Server query running on {}", addr);
+        }
+
+        loop {
+            // One extra byte, so oversized packets can be told apart
+            let mut buf = vec![0; MAX_PACKET_SIZE + 1];
+            let (len, addr) = match query.socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(err) => {
+                    log::debug!("Failed to receive query packet: {}", err);
+                    continue;
+                }
+            };
+            if len > MAX_PACKET_SIZE {
+                continue;
+            }
+            buf.truncate(len);
+
+            let query = query.clone();
+            tokio::spawn(async move {
+                if let Err(err) = query.handle_packet(buf, addr).await {
+                    log::error!("Interior 0 bytes found! Cannot encode query response! {err}");
+                }
+            });
+        }
+    }
+
+    // Errors of packets that don't meet the format aren't returned since we won't handle them anyway
+    // The only errors that are thrown are because of a null terminator in a CString
+    // since those errors need to be corrected by server owner
+    async fn handle_packet(&self, buf: Vec<u8>, addr: SocketAddr) -> Result<(), NulError> {
+        let Ok(mut raw_packet) = RawQueryPacket::decode(buf).await else {
+            return Ok(());
+        };
+
+        // Ignore all send errors since we don't want the query handler to crash
+        // Protocol also ignores all errors and just doesn't respond
         match raw_packet.packet_type {
             PacketType::Handshake => {
                 if let Ok(packet) = SHandshake::decode(&mut raw_packet).await {
-                    let challange_token = rand::thread_rng().gen_range(1..=i32::MAX);
                     let response = CHandshake {
                         session_id: packet.session_id,
-                        challange_token,
+                        challange_token: self.challenge_tokens.write().await.issue(addr),
                     };
-
-                    // Ignore all errors since we don't want the query handler to crash
-                    // Protocol also ignores all errors and just doesn't respond
-                    let _ = socket
+                    let _ = self
+                        .socket
                         .send_to(response.encode().await.as_slice(), addr)
                         .await;
-
-                    clients.write().await.insert(challange_token, addr);
                 }
             }
             PacketType::Status => {
-                if let Ok(packet) = SStatusRequest::decode(&mut raw_packet).await {
-                    if clients
-                        .read()
-                        .await
-                        .get(&packet.challange_token)
-                        .is_some_and(|token_bound_ip: &SocketAddr| token_bound_ip == &addr)
-                    {
-                        if packet.is_full_request {
-                            // Get 4 players
-                            let mut players: Vec<CString> = Vec::new();
-                            for world in &server.worlds {
-                                let mut world_players = world
-                                    .current_players
-                                    .lock()
-                                    .await
-                                    // Although there is no documented limit, we will limit to 4 players
-                                    .values()
-                                    .take(4 - players.len())
-                                    .map(|player| {
-                                        CString::new(player.gameprofile.name.as_str()).unwrap()
-                                    })
-                                    .collect::<Vec<_>>();
-
-                                players.append(&mut world_players); // Append players from this world
-
-                                if players.len() >= 4 {
-                                    break; // Stop if we've collected 4 players
-                                }
-                            }
-
-                            let response = CFullStatus {
-                                session_id: packet.session_id,
-                                hostname: CString::new(BASIC_CONFIG.motd.as_str())?,
-                                version: CString::new(CURRENT_MC_VERSION)?,
-                                plugins: CString::new("Pumpkin on 1.21.3")?, // TODO: Fill this with plugins when plugins are working
-                                map: CString::new("world")?, // TODO: Get actual world name
-                                num_players: server.get_player_count().await,
-                                max_players: BASIC_CONFIG.max_players as usize,
-                                host_port: bound_addr.port(),
-                                host_ip: CString::new(bound_addr.ip().to_string())?,
-                                players,
-                            };
-
-                            let _ = socket
-                                .send_to(response.encode().await.as_slice(), addr)
-                                .await;
-                        } else {
-                            let resposne = CBasicStatus {
-                                session_id: packet.session_id,
-                                motd: CString::new(BASIC_CONFIG.motd.as_str())?,
-                                map: CString::new("world")?,
-                                num_players: server.get_player_count().await,
-                                max_players: BASIC_CONFIG.max_players as usize,
-                                host_port: bound_addr.port(),
-                                host_ip: CString::new(bound_addr.ip().to_string())?,
-                            };
-
-                            let _ = socket
-                                .send_to(resposne.encode().await.as_slice(), addr)
-                                .await;
-                        }
-                    }
+                let Ok(packet) = SStatusRequest::decode(&mut raw_packet).await else {
+                    return Ok(());
+                };
+                if !self
+                    .challenge_tokens
+                    .read()
+                    .await
+                    .is_valid(packet.challange_token, addr)
+                {
+                    return Ok(());
                 }
+
+                let response = if packet.is_full_request {
+                    self.full_status(packet.session_id).await?.encode().await
+                } else {
+                    self.basic_status(packet.session_id).await?.encode().await
+                };
+                let _ = self.socket.send_to(response.as_slice(), addr).await;
             }
         }
+        Ok(())
+    }
+
+    async fn basic_status(&self, session_id: i32) -> Result<CBasicStatus, NulError> {
+        Ok(CBasicStatus {
+            session_id,
+            motd: CString::new(BASIC_CONFIG.get().motd.as_str())?,
+            map: CString::new("world")?, // TODO: Get actual world name
+            num_players: self.server.get_player_count().await,
+            max_players: BASIC_CONFIG.get().max_players as usize,
+            host_port: self.bound_addr.port(),
+            host_ip: CString::new(self.bound_addr.ip().to_string())?,
+        })
+    }
+
+    async fn full_status(&self, session_id: i32) -> Result<CFullStatus, NulError> {
+        let mut players: Vec<CString> = Vec::new();
+        for world in &self.server.worlds {
+            for player in world.current_players.lock().await.values() {
+                players.push(CString::new(player.gameprofile.name.as_str())?);
+            }
+        }
+
+        Ok(CFullStatus {
+            session_id,
+            hostname: CString::new(BASIC_CONFIG.get().motd.as_str())?,
+            version: CString::new(CURRENT_MC_VERSION)?,
+            // TODO: Fill this with plugins when plugins are working
+            plugins: CString::new(format!("Pumpkin on {CURRENT_MC_VERSION}"))?,
+            map: CString::new("world")?, // TODO: Get actual world name
+            num_players: players.len(),
+            max_players: BASIC_CONFIG.get().max_players as usize,
+            host_port: self.bound_addr.port(),
+            host_ip: CString::new(self.bound_addr.ip().to_string())?,
+            players,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{net::SocketAddr, time::Instant};
+
+    use super::{ChallengeTokens, CHALLENGE_TOKEN_LIFETIME};
+
+    #[test]
+    fn test_challenge_tokens() {
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let other: SocketAddr = "127.0.0.1:5001".parse().unwrap();
+
+        let mut tokens = ChallengeTokens::default();
+        let token = tokens.issue(addr);
+        assert!(tokens.is_valid(token, addr));
+        // Bound to the address which did the handshake
+        assert!(!tokens.is_valid(token, other));
+        assert!(!tokens.is_valid(token.wrapping_add(1), addr));
+
+        // Expired tokens are rejected, even before they get removed
+        let expired = tokens.issue(other);
+        tokens.tokens.get_mut(&expired).unwrap().1 = Instant::now()
+            .checked_sub(CHALLENGE_TOKEN_LIFETIME)
+            .unwrap();
+        assert!(!tokens.is_valid(expired, other));
+
+        tokens.remove_expired();
+        assert!(tokens.tokens.contains_key(&token));
+        assert!(!tokens.tokens.contains_key(&expired));
     }
-    Ok(())
 }
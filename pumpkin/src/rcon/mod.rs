@@ -1,6 +1,11 @@
-use std::net::SocketAddr;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    time::{Duration, Instant},
+};
 
 use packet::{ClientboundPacket, Packet, PacketError, ServerboundPacket};
+use parking_lot::Mutex;
 use pumpkin_config::{RCONConfig, ADVANCED_CONFIG};
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -17,6 +22,10 @@ impl RCONServer {
         let listener = tokio::net::TcpListener::bind(config.address).await.unwrap();
 
         let password = Arc::new(config.password.clone());
+        let auth_limiter = Arc::new(Mutex::new(AuthLimiter::new(
+            config.max_auth_failures,
+            Duration::from_secs(config.auth_cooldown_secs),
+        )));
 
         let mut connections = 0;
         loop {
@@ -26,9 +35,13 @@ impl RCONServer {
             if config.max_connections != 0 && connections >= config.max_connections {
                 continue;
             }
+            if auth_limiter.lock().is_refused(address.ip()) {
+                log::debug!("RCON ({address}): Refused connection after too many wrong passwords");
+                continue;
+            }
 
             connections += 1;
-            let mut client = RCONClient::new(connection, address);
+            let mut client = RCONClient::new(connection, address, auth_limiter.clone());
 
             let password = password.clone();
             let server = server.clone();
@@ -39,23 +52,76 @@ impl RCONServer {
     }
 }
 
+/// Counts the wrong passwords sent from each IP, see `max_auth_failures` in the RCON config
+pub struct AuthLimiter {
+    max_failures: u32,
+    cooldown: Duration,
+    /// The failures since the last successful login and when the latest one happened
+    failures: HashMap<IpAddr, (u32, Instant)>,
+}
+
+impl AuthLimiter {
+    #[must_use]
+    pub fn new(max_failures: u32, cooldown: Duration) -> Self {
+        Self {
+            max_failures,
+            cooldown,
+            failures: HashMap::new(),
+        }
+    }
+
+    /// Whether connections from `ip` are refused, until the cooldown after its latest failure passed
+    pub fn is_refused(&mut self, ip: IpAddr) -> bool {
+        if self.max_failures == 0 {
+            return false;
+        }
+        let Some((count, last)) = self.failures.get(&ip) else {
+            return false;
+        };
+        if last.elapsed() >= self.cooldown {
+            self.failures.remove(&ip);
+            return false;
+        }
+        *count >= self.max_failures
+    }
+
+    pub fn record_failure(&mut self, ip: IpAddr) {
+        let cooldown = self.cooldown;
+        self.failures
+            .retain(|_, (_, last)| last.elapsed() < cooldown);
+        let (count, last) = self.failures.entry(ip).or_insert((0, Instant::now()));
+        *count += 1;
+        *last = Instant::now();
+    }
+
+    pub fn record_success(&mut self, ip: IpAddr) {
+        self.failures.remove(&ip);
+    }
+}
+
 pub struct RCONClient {
     connection: tokio::net::TcpStream,
     address: SocketAddr,
     logged_in: bool,
     incoming: Vec<u8>,
     closed: bool,
+    auth_limiter: Arc<Mutex<AuthLimiter>>,
 }
 
 impl RCONClient {
     #[must_use]
-    pub const fn new(connection: tokio::net::TcpStream, address: SocketAddr) -> Self {
+    pub const fn new(
+        connection: tokio::net::TcpStream,
+        address: SocketAddr,
+        auth_limiter: Arc<Mutex<AuthLimiter>>,
+    ) -> Self {
         Self {
             connection,
             address,
             logged_in: false,
             incoming: Vec::new(),
             closed: false,
+            auth_limiter,
         }
     }
 
@@ -84,7 +150,8 @@ impl RCONClient {
         let Some(packet) = self.receive_packet().await? else {
             return Ok(());
         };
-        let config = &ADVANCED_CONFIG.rcon;
+        let advanced_config = ADVANCED_CONFIG.get();
+        let config = &advanced_config.rcon;
         match packet.get_type() {
             ServerboundPacket::Auth => {
                 if packet.get_body() == password {
@@ -94,10 +161,12 @@ impl RCONClient {
                         log::info!("RCON ({}): Client logged in successfully", self.address);
                     }
                     self.logged_in = true;
+                    self.auth_limiter.lock().record_success(self.address.ip());
                 } else {
                     if config.logging.log_wrong_password {
                         log::info!("RCON ({}): Client has tried wrong password", self.address);
                     }
+                    self.auth_limiter.lock().record_failure(self.address.ip());
                     self.send(ClientboundPacket::AuthResponse, -1, "").await?;
                     self.closed = true;
                 }
@@ -155,3 +224,50 @@ impl RCONClient {
         Packet::deserialize(&mut self.incoming).await
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{
+        net::IpAddr,
+        time::{Duration, Instant},
+    };
+
+    use super::AuthLimiter;
+
+    #[test]
+    fn test_auth_limiter() {
+        let ip: IpAddr = "192.168.1.5".parse().unwrap();
+        let other: IpAddr = "192.168.1.6".parse().unwrap();
+        let mut limiter = AuthLimiter::new(5, Duration::from_secs(60));
+
+        // 5 connections which sent a wrong password
+        for _ in 0..5 {
+            assert!(!limiter.is_refused(ip));
+            limiter.record_failure(ip);
+        }
+        // The 6th is refused, other IPs are not affected
+        assert!(limiter.is_refused(ip));
+        assert!(!limiter.is_refused(other));
+
+        // Accepted again after the cooldown
+        limiter.failures.get_mut(&ip).unwrap().1 =
+            Instant::now().checked_sub(Duration::from_secs(60)).unwrap();
+        assert!(!limiter.is_refused(ip));
+        assert!(!limiter.failures.contains_key(&ip));
+
+        // A successful login resets the count
+        for _ in 0..4 {
+            limiter.record_failure(ip);
+        }
+        limiter.record_success(ip);
+        limiter.record_failure(ip);
+        assert!(!limiter.is_refused(ip));
+
+        // No limit
+        let mut limiter = AuthLimiter::new(0, Duration::from_secs(60));
+        for _ in 0..10 {
+            limiter.record_failure(ip);
+        }
+        assert!(!limiter.is_refused(ip));
+    }
+}
@@ -7,6 +7,12 @@ use bytes::{BufMut, BytesMut};
 use thiserror::Error;
 use tokio::io::AsyncReadExt;
 
+/// The largest packet accepted from a client including the length field, anything above closes the connection
+pub const MAX_PACKET_SIZE: i32 = 4096;
+
+/// 4 bytes length, 4 bytes id, 4 bytes type and 2 terminating nul bytes
+const MIN_PACKET_SIZE: i32 = 14;
+
 /// Client -> Server
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
@@ -81,10 +87,18 @@ impl Packet {
             return Ok(None);
         }
         let mut buf = Cursor::new(&incoming);
-        let len = buf.read_i32_le().await.map_err(PacketError::FailedRead)? + 4;
-        if !(0..=1460).contains(&len) {
+        let len = buf
+            .read_i32_le()
+            .await
+            .map_err(PacketError::FailedRead)?
+            .saturating_add(4);
+        // Checked before the rest arrives, so a huge declared length can't make us buffer it
+        if !(MIN_PACKET_SIZE..=MAX_PACKET_SIZE).contains(&len) {
             return Err(PacketError::InvalidLength);
         }
+        if incoming.len() < len as usize {
+            return Ok(None);
+        }
         let id = buf.read_i32_le().await.map_err(PacketError::FailedRead)?;
         let ty = buf.read_i32_le().await.map_err(PacketError::FailedRead)?;
         let mut payload = vec![];
@@ -118,3 +132,75 @@ impl Packet {
         self.id
     }
 }
+
+#[cfg(test)]
+mod test {
+    use pumpkin_config::compression::CompressionConfig;
+
+    use super::{ClientboundPacket, Packet, PacketError, ServerboundPacket, MAX_PACKET_SIZE};
+
+    /// RCON has its own framing, game packet compression must never be applied to it
+    #[test]
+    fn test_output_never_compressed() {
+        let compression = CompressionConfig::default();
+        let body = "a".repeat(1000);
+        // Would be compressed if this was a game packet
+        assert!(
+            compression.is_enabled()
+                && compression
+                    .compression_info
+                    .threshold
+                    .compresses(body.len())
+        );
+
+        let buf = ClientboundPacket::Output.write_buf(7, &body);
+        assert_eq!(buf.len(), 14 + body.len());
+        assert_eq!(buf[..4], (10 + body.len() as i32).to_le_bytes());
+        assert_eq!(buf[4..8], 7i32.to_le_bytes());
+        assert_eq!(buf[8..12], (ClientboundPacket::Output as i32).to_le_bytes());
+        assert_eq!(&buf[12..12 + body.len()], body.as_bytes());
+        assert_eq!(buf[12 + body.len()..], [0, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_uncompressed() {
+        let body = "b".repeat(1000);
+        let mut incoming = Vec::new();
+        incoming.extend_from_slice(&(10 + body.len() as i32).to_le_bytes());
+        incoming.extend_from_slice(&3i32.to_le_bytes());
+        incoming.extend_from_slice(&(ServerboundPacket::Auth as i32).to_le_bytes());
+        incoming.extend_from_slice(body.as_bytes());
+        incoming.extend_from_slice(&[0, 0]);
+
+        let packet = Packet::deserialize(&mut incoming).await.unwrap().unwrap();
+        assert_eq!(packet.get_id(), 3);
+        assert_eq!(packet.get_body(), body);
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_partial_and_oversized() {
+        let mut incoming = Vec::new();
+        incoming.extend_from_slice(&14i32.to_le_bytes());
+        incoming.extend_from_slice(&1i32.to_le_bytes());
+        incoming.extend_from_slice(&(ServerboundPacket::ExecCommand as i32).to_le_bytes());
+        incoming.extend_from_slice(b"list");
+        // Waits for the rest
+        assert!(Packet::deserialize(&mut incoming).await.unwrap().is_none());
+        incoming.extend_from_slice(&[0, 0]);
+        let packet = Packet::deserialize(&mut incoming).await.unwrap().unwrap();
+        assert_eq!(packet.get_body(), "list");
+        assert!(incoming.is_empty());
+
+        // Rejected as soon as the length is known
+        let mut incoming = (MAX_PACKET_SIZE - 3).to_le_bytes().to_vec();
+        assert!(matches!(
+            Packet::deserialize(&mut incoming).await,
+            Err(PacketError::InvalidLength)
+        ));
+        let mut incoming = i32::MAX.to_le_bytes().to_vec();
+        assert!(matches!(
+            Packet::deserialize(&mut incoming).await,
+            Err(PacketError::InvalidLength)
+        ));
+    }
+}
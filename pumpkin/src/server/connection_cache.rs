@@ -3,19 +3,29 @@ use std::{
     fs::File,
     io::{Cursor, Read},
     path::Path,
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
 };
 
 use base64::{engine::general_purpose, Engine as _};
-use pumpkin_config::{BasicConfiguration, BASIC_CONFIG};
+use image::{imageops::FilterType, ImageFormat};
+use pumpkin_config::{BasicConfiguration, ADVANCED_CONFIG, BASIC_CONFIG};
 use pumpkin_protocol::{
-    client::{config::CPluginMessage, status::CStatusResponse},
-    Players, StatusResponse, VarInt, Version, CURRENT_MC_PROTOCOL,
+    client::config::CPluginMessage, Players, StatusResponse, VarInt, Version, CURRENT_MC_PROTOCOL,
 };
 
 use super::CURRENT_MC_VERSION;
 
+/// The server list requires icons of exactly this size
+const ICON_SIZE: u32 = 64;
+
 const DEFAULT_ICON: &[u8] = include_bytes!("../../../assets/default_icon.png");
 
+/// The encoded bundled icon, used whenever the configured favicon can't be loaded
+static DEFAULT_FAVICON: LazyLock<String> = LazyLock::new(|| {
+    load_icon_from_bytes(DEFAULT_ICON).expect("The bundled default icon must be valid")
+});
+
 fn load_icon_from_file<P: AsRef<Path>>(path: P) -> Result<String, Box<dyn error::Error>> {
     let mut icon_file = File::open(path)?;
     let mut buf = Vec::new();
@@ -23,24 +33,73 @@ fn load_icon_from_file<P: AsRef<Path>>(path: P) -> Result<String, Box<dyn error:
     load_icon_from_bytes(&buf)
 }
 
-fn load_icon_from_bytes(png_data: &[u8]) -> Result<String, Box<dyn error::Error>> {
-    let icon = png::Decoder::new(Cursor::new(&png_data));
-    let reader = icon.read_info()?;
-    let info = reader.info();
-    assert!(info.width == 64, "Icon width must be 64");
-    assert!(info.height == 64, "Icon height must be 64");
+/// Encodes a PNG, JPEG or WebP image as PNG data URL.
+/// Icons which aren't 64x64 get cropped to a centered square and resized
+fn load_icon_from_bytes(data: &[u8]) -> Result<String, Box<dyn error::Error>> {
+    let format = image::guess_format(data)?;
+    let icon = image::load_from_memory_with_format(data, format)?;
 
-    // Reader consumes the image. Once we verify dimensions, we want to encode the entire raw image
     let mut result = "data:image/png;base64,".to_owned();
-    general_purpose::STANDARD.encode_string(png_data, &mut result);
+    if format == ImageFormat::Png && icon.width() == ICON_SIZE && icon.height() == ICON_SIZE {
+        // Already valid, so the original file can be sent as is
+        general_purpose::STANDARD.encode_string(data, &mut result);
+        return Ok(result);
+    }
+
+    log::debug!(
+        "Converting {}x{} {:?} favicon to a {}x{} PNG",
+        icon.width(),
+        icon.height(),
+        format,
+        ICON_SIZE,
+        ICON_SIZE
+    );
+    let side = icon.width().min(icon.height());
+    let cropped = icon.crop_imm(
+        (icon.width() - side) / 2,
+        (icon.height() - side) / 2,
+        side,
+        side,
+    );
+    let resized = cropped.resize_exact(ICON_SIZE, ICON_SIZE, FilterType::Lanczos3);
+
+    let mut png_data = Vec::new();
+    resized.write_to(&mut Cursor::new(&mut png_data), ImageFormat::Png)?;
+    general_purpose::STANDARD.encode_string(&png_data, &mut result);
     Ok(result)
 }
 
+/// Loads the favicon at `icon_path`, falling back to the bundled icon if it is missing or invalid
+fn load_favicon(icon_path: &str) -> String {
+    load_icon_from_file(icon_path).unwrap_or_else(|err| {
+        match err.downcast_ref::<std::io::Error>() {
+            Some(io_err) if io_err.kind() == std::io::ErrorKind::NotFound => {
+                log::warn!("Favicon '{}' not found; using default icon.", icon_path);
+            }
+            Some(io_err) => log::warn!(
+                "Unable to load favicon at '{}': I/O error - {}; using default icon.",
+                icon_path,
+                io_err
+            ),
+            None => log::warn!(
+                "Unable to load favicon at '{}': other error - {}; using default icon.",
+                icon_path,
+                err
+            ),
+        }
+        DEFAULT_FAVICON.clone()
+    })
+}
+
 pub struct CachedStatus {
     status_response: StatusResponse,
     // We cache the json response here so we don't parse it every time someone makes a Status request.
-    // Keep in mind that we must parse this again, when the StatusResponse changes which usually happen when a player joins or leaves
-    status_response_json: String,
+    // It gets serialized again on the next request once it is older than `ttl` or the response changed,
+    // which usually happens when a player joins or leaves
+    status_response_json: Arc<str>,
+    serialized_at: Instant,
+    changed: bool,
+    ttl: Duration,
 }
 
 pub struct CachedBranding {
@@ -69,76 +128,93 @@ impl CachedBranding {
 
 impl CachedStatus {
     pub fn new() -> Self {
-        let status_response = Self::build_response(&BASIC_CONFIG);
-        let status_response_json = serde_json::to_string(&status_response)
-            .expect("Failed to parse Status response into JSON");
+        Self::from_config(
+            &BASIC_CONFIG.get(),
+            ADVANCED_CONFIG.get().status_cache_ttl(),
+        )
+    }
+
+    /// Builds the response for `config`, the favicon is only read here and on reloads
+    #[must_use]
+    pub fn from_config(config: &BasicConfiguration, ttl: Duration) -> Self {
+        let status_response = Self::build_response(config);
+        let status_response_json = Self::serialize(&status_response);
 
         Self {
             status_response,
             status_response_json,
+            serialized_at: Instant::now(),
+            changed: false,
+            ttl,
         }
     }
 
-    pub fn get_status(&self) -> CStatusResponse<'_> {
-        CStatusResponse::new(&self.status_response_json)
+    /// The serialized response, the same `Arc` is returned until it expires or changes
+    pub fn get_status(&mut self) -> Arc<str> {
+        if self.changed || self.serialized_at.elapsed() >= self.ttl {
+            self.status_response_json = Self::serialize(&self.status_response);
+            self.serialized_at = Instant::now();
+            self.changed = false;
+        }
+        self.status_response_json.clone()
+    }
+
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+    }
+
+    fn serialize(status_response: &StatusResponse) -> Arc<str> {
+        serde_json::to_string(status_response)
+            .expect("Failed to parse Status response into JSON")
+            .into()
     }
 
     // TODO: Player samples
     pub fn add_player(&mut self) {
-        let status_response = &mut self.status_response;
-        if let Some(players) = &mut status_response.players {
+        if let Some(players) = &mut self.status_response.players {
             players.online += 1;
         }
-
-        self.status_response_json = serde_json::to_string(&status_response)
-            .expect("Failed to parse Status response into JSON");
+        self.changed = true;
     }
 
     pub fn remove_player(&mut self) {
-        let status_response = &mut self.status_response;
-        if let Some(players) = &mut status_response.players {
+        if let Some(players) = &mut self.status_response.players {
             players.online -= 1;
         }
+        self.changed = true;
+    }
 
-        self.status_response_json = serde_json::to_string(&status_response)
-            .expect("Failed to parse Status response into JSON");
+    /// Re-reads the favicon, which is otherwise only read once on startup
+    pub fn reload_favicon(&mut self, config: &BasicConfiguration) {
+        self.status_response.favicon = Self::build_favicon(config);
+        self.changed = true;
     }
 
-    pub fn build_response(config: &BasicConfiguration) -> StatusResponse {
-        let icon = if config.use_favicon {
-            let icon_path = &config.favicon_path;
-            log::debug!("Loading server favicon from '{}'", icon_path);
-            match load_icon_from_file(icon_path).or_else(|err| {
-                if let Some(io_err) = err.downcast_ref::<std::io::Error>() {
-                    if io_err.kind() == std::io::ErrorKind::NotFound {
-                        log::info!("Favicon '{}' not found; using default icon.", icon_path);
-                    } else {
-                        log::error!(
-                            "Unable to load favicon at '{}': I/O error - {}; using default icon.",
-                            icon_path,
-                            io_err
-                        );
-                    }
-                } else {
-                    log::error!(
-                        "Unable to load favicon at '{}': other error - {}; using default icon.",
-                        icon_path,
-                        err
-                    );
-                }
-                load_icon_from_bytes(DEFAULT_ICON)
-            }) {
-                Ok(result) => Some(result),
-                Err(err) => {
-                    log::warn!("Failed to load default icon: {}", err);
-                    None
-                }
-            }
+    /// Rebuilds the response after the config was reloaded, keeping the online player count
+    pub fn reload(&mut self, config: &BasicConfiguration) {
+        let online = self
+            .status_response
+            .players
+            .as_ref()
+            .map_or(0, |players| players.online);
+        self.status_response = Self::build_response(config);
+        if let Some(players) = &mut self.status_response.players {
+            players.online = online;
+        }
+        self.changed = true;
+    }
+
+    fn build_favicon(config: &BasicConfiguration) -> Option<String> {
+        if config.use_favicon {
+            log::debug!("Loading server favicon from '{}'", config.favicon_path);
+            Some(load_favicon(&config.favicon_path))
         } else {
             log::info!("Not using a server favicon");
             None
-        };
+        }
+    }
 
+    pub fn build_response(config: &BasicConfiguration) -> StatusResponse {
         StatusResponse {
             version: Some(Version {
                 name: CURRENT_MC_VERSION.into(),
@@ -149,8 +225,8 @@ impl CachedStatus {
                 online: 0,
                 sample: vec![],
             }),
-            description: config.motd.clone(),
-            favicon: icon,
+            description: config.motd_component(),
+            favicon: Self::build_favicon(config),
             enforce_secure_chat: false,
         }
     }
@@ -161,3 +237,101 @@ impl Default for CachedStatus {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{io::Cursor, sync::Arc, time::Duration};
+
+    use base64::{engine::general_purpose, Engine as _};
+    use image::{DynamicImage, ImageFormat, Rgb, RgbImage, RgbaImage};
+    use pumpkin_config::BasicConfiguration;
+
+    use super::{load_favicon, load_icon_from_bytes, CachedStatus, DEFAULT_FAVICON, DEFAULT_ICON};
+
+    #[test]
+    fn test_cached_status() {
+        let config = BasicConfiguration::builder()
+            .use_favicon(false)
+            .build()
+            .unwrap();
+        let mut status = CachedStatus::from_config(&config, Duration::from_secs(60));
+
+        // Repeated pings share one serialized response
+        let first = status.get_status();
+        assert!(Arc::ptr_eq(&first, &status.get_status()));
+        assert!(first.contains(r#""online":0"#));
+
+        // Joins and leaves are visible on the next ping
+        status.add_player();
+        status.add_player();
+        let joined = status.get_status();
+        assert!(!Arc::ptr_eq(&first, &joined));
+        assert!(joined.contains(r#""online":2"#));
+        assert!(Arc::ptr_eq(&joined, &status.get_status()));
+        status.remove_player();
+        assert!(status.get_status().contains(r#""online":1"#));
+
+        // Expired responses are serialized again
+        status.set_ttl(Duration::ZERO);
+        let expired = status.get_status();
+        assert!(!Arc::ptr_eq(&expired, &status.get_status()));
+        assert_eq!(expired, status.get_status());
+    }
+
+    fn decode(favicon: &str) -> RgbaImage {
+        let data = general_purpose::STANDARD
+            .decode(favicon.strip_prefix("data:image/png;base64,").unwrap())
+            .unwrap();
+        image::load_from_memory_with_format(&data, ImageFormat::Png)
+            .unwrap()
+            .to_rgba8()
+    }
+
+    #[test]
+    fn test_missing_favicon() {
+        assert!(DEFAULT_FAVICON.starts_with("data:image/png;base64,"));
+        assert_eq!(
+            load_favicon("this/favicon/does/not/exist.png"),
+            *DEFAULT_FAVICON
+        );
+    }
+
+    #[test]
+    fn test_invalid_favicon() {
+        assert!(load_icon_from_bytes(b"not a png").is_err());
+    }
+
+    #[test]
+    fn test_valid_favicon_unchanged() {
+        let favicon = load_icon_from_bytes(DEFAULT_ICON).unwrap();
+        let data = general_purpose::STANDARD
+            .decode(favicon.strip_prefix("data:image/png;base64,").unwrap())
+            .unwrap();
+        assert_eq!(data, DEFAULT_ICON);
+    }
+
+    #[test]
+    fn test_convert_favicon() {
+        // Red borders on the left and right, which get cropped away.
+        // The edges are aligned to the JPEG blocks, so they stay sharp
+        let image = DynamicImage::ImageRgb8(RgbImage::from_fn(256, 128, |x, _| {
+            if (64..192).contains(&x) {
+                Rgb([0, 0, 255])
+            } else {
+                Rgb([255, 0, 0])
+            }
+        }));
+
+        for format in [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::WebP] {
+            let mut data = Vec::new();
+            image.write_to(&mut Cursor::new(&mut data), format).unwrap();
+
+            let favicon = decode(&load_icon_from_bytes(&data).unwrap());
+            assert_eq!(favicon.dimensions(), (64, 64), "{:?}", format);
+            for (x, y) in [(0, 0), (63, 0), (0, 63), (63, 63), (32, 32)] {
+                let [red, _, blue, _] = favicon.get_pixel(x, y).0;
+                assert!(blue > 200 && red < 50, "{:?} at {} {}", format, x, y);
+            }
+        }
+    }
+}
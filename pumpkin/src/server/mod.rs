@@ -1,16 +1,24 @@
 use connection_cache::{CachedBranding, CachedStatus};
 use key_store::KeyStore;
-use pumpkin_config::BASIC_CONFIG;
-use pumpkin_core::GameMode;
+use pumpkin_config::{
+    bans::{BanInfo, BanList},
+    whitelist::Whitelist,
+    world_gen::WorldGenType as ConfigWorldGenType,
+    ConfigError, ADVANCED_CONFIG, BASIC_CONFIG,
+};
 use pumpkin_entity::EntityId;
 use pumpkin_inventory::drag_handler::DragHandler;
 use pumpkin_inventory::{Container, OpenContainer};
 use pumpkin_protocol::client::login::CEncryptionRequest;
 use pumpkin_protocol::{client::config::CPluginMessage, ClientPacket};
 use pumpkin_registry::Registry;
+use pumpkin_world::biome::Biome;
 use pumpkin_world::dimension::Dimension;
+use pumpkin_world::{generation_pool, FlatLayers, GenerationSettings, Seed, WorldGenType};
 use rand::prelude::SliceRandom;
 use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
 use std::{
     sync::{
         atomic::{AtomicI32, Ordering},
@@ -34,6 +42,101 @@ pub mod ticker;
 
 pub const CURRENT_MC_VERSION: &str = "1.21.3";
 
+/// The folder the dimensions are saved in
+const WORLD_FOLDER: &str = "./world";
+
+/// Maps the configured world type, unknown biomes fall back to the default world type
+fn world_gen_type(world_type: &ConfigWorldGenType) -> WorldGenType {
+    match world_type {
+        ConfigWorldGenType::Default => WorldGenType::Default,
+        ConfigWorldGenType::Flat => WorldGenType::Flat,
+        ConfigWorldGenType::Amplified => WorldGenType::Amplified,
+        ConfigWorldGenType::SingleBiome(name) => Biome::from_name(name).map_or_else(
+            || {
+                log::warn!("Unknown biome {}, using the default world type", name);
+                WorldGenType::Default
+            },
+            WorldGenType::SingleBiome,
+        ),
+    }
+}
+
+/// The config validation already rejects invalid layers, so the default layers are only a fallback
+fn flat_layers(spec: &str) -> FlatLayers {
+    spec.parse().unwrap_or_else(|err| {
+        log::error!(
+            "Invalid world_gen.flat_layers {:?}: {}, using the default layers",
+            spec,
+            err
+        );
+        FlatLayers::default()
+    })
+}
+
+/// The generation settings from the advanced config
+fn generation_settings() -> GenerationSettings {
+    let config = ADVANCED_CONFIG.get();
+    GenerationSettings {
+        sea_level: config.world_gen.sea_level,
+        bedrock_roughness: config.world_gen.bedrock_roughness,
+        nan_guard: config.world_gen.nan_guard,
+        profile: config.world_gen.profile,
+        features: (&config.world_gen.features).into(),
+        flat_layers: flat_layers(&config.world_gen.flat_layers),
+        climate_offsets: (&config.biome_tuning).into(),
+    }
+}
+
+/// Loads the dimension from its folder below `./world`, generating missing chunks with `world_type`
+fn load_world(
+    dimension: Dimension,
+    seed: Seed,
+    world_type: WorldGenType,
+    generation_pool: &Arc<rayon::ThreadPool>,
+) -> World {
+    World::load(
+        dimension
+            .into_level(
+                // TODO: load form config
+                WORLD_FOLDER.parse().unwrap(),
+                seed,
+                world_type,
+                generation_settings(),
+                generation_pool.clone(),
+            )
+            .with_max_concurrent_chunk_gen(ADVANCED_CONFIG.get().max_concurrent_chunk_gen),
+        dimension,
+    )
+}
+
+/// The seed of the world, a broken seed file is logged and replaced by the configured seed
+fn load_seed() -> Seed {
+    Seed::load_or_save(Path::new(WORLD_FOLDER), &BASIC_CONFIG.get().seed).unwrap_or_else(|err| {
+        log::error!("Failed to load the world seed: {}", err);
+        Seed::from_string(&BASIC_CONFIG.get().seed)
+    })
+}
+
+/// Loads the configured whitelist file, a broken file is logged and replaced by an empty whitelist
+fn load_whitelist() -> Whitelist {
+    let config = ADVANCED_CONFIG.get();
+    let path = &config.whitelist.path;
+    Whitelist::load(path).unwrap_or_else(|err| {
+        log::error!("Failed to load the whitelist {:?}: {}", path, err);
+        Whitelist::empty(path)
+    })
+}
+
+/// Loads the configured ban lists, broken files are logged and replaced by empty lists
+fn load_bans() -> BanList {
+    let advanced_config = ADVANCED_CONFIG.get();
+    let config = &advanced_config.bans;
+    BanList::load(&config.players_path, &config.ips_path).unwrap_or_else(|err| {
+        log::error!("Failed to load the ban lists: {}", err);
+        BanList::empty(&config.players_path, &config.ips_path)
+    })
+}
+
 /// Represents a Minecraft server instance.
 pub struct Server {
     /// Handles cryptographic keys for secure communication.
@@ -55,6 +158,12 @@ pub struct Server {
     entity_id: AtomicI32,
     /// Manages authentication with a authentication server, if enabled.
     pub auth_client: Option<reqwest::Client>,
+    /// The players which may join while the whitelist is enabled
+    pub whitelist: RwLock<Whitelist>,
+    /// The banned players and IPs
+    pub bans: RwLock<BanList>,
+    /// The world seed, saved with the world so a random seed is only picked once
+    pub seed: Seed,
 }
 
 impl Server {
@@ -63,7 +172,7 @@ impl Server {
     pub fn new() -> Self {
         // TODO: only create when needed
 
-        let auth_client = if BASIC_CONFIG.online_mode {
+        let auth_client = if BASIC_CONFIG.get().online_mode {
             Some(
                 reqwest::Client::builder()
                     .timeout(Duration::from_millis(5000))
@@ -77,19 +186,43 @@ impl Server {
         // First register default command, after that plugins can put in their own
         let command_dispatcher = default_dispatcher();
 
-        let world = World::load(Dimension::OverWorld.into_level(
-            // TODO: load form config
-            "./world".parse().unwrap(),
-        ));
+        let seed = load_seed();
+        // One pool for all dimensions, so `generation_threads` caps the whole server
+        let generation_pool = Arc::new(generation_pool(ADVANCED_CONFIG.get().generation_threads()));
+        let mut worlds = vec![Arc::new(load_world(
+            Dimension::OverWorld,
+            seed,
+            world_gen_type(&BASIC_CONFIG.get().world_type),
+            &generation_pool,
+        ))];
+        if BASIC_CONFIG.get().allow_nether {
+            worlds.push(Arc::new(load_world(
+                Dimension::Nether,
+                seed,
+                WorldGenType::Nether,
+                &generation_pool,
+            )));
+        }
+        if BASIC_CONFIG.get().allow_end {
+            worlds.push(Arc::new(load_world(
+                Dimension::End,
+                seed,
+                WorldGenType::End,
+                &generation_pool,
+            )));
+        }
         Self {
             cached_registry: Registry::get_synced(),
             open_containers: RwLock::new(HashMap::new()),
             drag_handler: DragHandler::new(),
             // 0 is invalid
             entity_id: 2.into(),
-            worlds: vec![Arc::new(world)],
+            worlds,
             command_dispatcher,
             auth_client,
+            whitelist: RwLock::new(load_whitelist()),
+            bans: RwLock::new(load_bans()),
+            seed,
             key_store: KeyStore::new(),
             server_listing: Mutex::new(CachedStatus::new()),
             server_branding: CachedBranding::new(),
@@ -123,10 +256,8 @@ impl Server {
     /// You still have to spawn the Player in the World to make then to let them Join and make them Visible
     pub async fn add_player(&self, client: Arc<Client>) -> (Arc<Player>, Arc<World>) {
         let entity_id = self.new_entity_id();
-        let gamemode = match BASIC_CONFIG.default_gamemode {
-            GameMode::Undefined => GameMode::Survival,
-            game_mode => game_mode,
-        };
+        // TODO: pass the saved game mode once player data is persisted
+        let gamemode = BASIC_CONFIG.get().join_gamemode(None);
         // Basically the default world
         // TODO: select default from config
         let world = &self.worlds[0];
@@ -282,6 +413,93 @@ impl Server {
         &self.server_listing
     }
 
+    /// Reloads both config files, see [`pumpkin_config::reload_all`], and re-applies the
+    /// cached values which depend on them
+    pub async fn reload_config(&self) -> Result<Vec<String>, Vec<ConfigError>> {
+        let changed = pumpkin_config::reload_all()?;
+        if changed.iter().any(|key| {
+            ["max_players", "motd", "use_favicon", "favicon_path"].contains(&key.as_str())
+        }) {
+            self.server_listing.lock().await.reload(&BASIC_CONFIG.get());
+        }
+        if changed.iter().any(|key| key == "status_cache_ttl_ms") {
+            self.server_listing
+                .lock()
+                .await
+                .set_ttl(ADVANCED_CONFIG.get().status_cache_ttl());
+        }
+        if changed.iter().any(|key| key == "bans") {
+            *self.bans.write().await = load_bans();
+        }
+        if changed.iter().any(|key| key == "whitelist") {
+            *self.whitelist.write().await = load_whitelist();
+            self.enforce_whitelist().await;
+        }
+        Ok(changed)
+    }
+
+    /// The ban of the player or of their IP, if either is banned
+    pub async fn find_ban(&self, uuid: &uuid::Uuid, ip: IpAddr) -> Option<BanInfo> {
+        let (ban, has_expired) = {
+            let bans = self.bans.read().await;
+            let ban = bans
+                .is_banned_player(uuid)
+                .or_else(|| bans.is_banned_ip(ip))
+                .cloned();
+            (ban, bans.has_expired())
+        };
+        if has_expired {
+            self.remove_expired_bans().await;
+        }
+        ban
+    }
+
+    /// Removes the expired bans, the files get saved on a blocking thread
+    async fn remove_expired_bans(&self) {
+        let save = match self.bans.write().await.remove_expired() {
+            Ok(Some(save)) => save,
+            Ok(None) => return,
+            Err(err) => {
+                log::warn!("Failed to save the ban list: {}", err);
+                return;
+            }
+        };
+        tokio::task::spawn_blocking(move || {
+            if let Err(err) = save.write() {
+                log::warn!("Failed to save the ban list: {}", err);
+            }
+        });
+    }
+
+    /// Whether the player may join, always true while the whitelist is disabled
+    pub async fn is_whitelisted(&self, uuid: &uuid::Uuid) -> bool {
+        !ADVANCED_CONFIG.get().whitelist.enabled || self.whitelist.read().await.is_whitelisted(uuid)
+    }
+
+    /// Kicks the online players which aren't on the whitelist, if it is enforced
+    pub async fn enforce_whitelist(&self) {
+        if !ADVANCED_CONFIG.get().whitelist.enforce {
+            return;
+        }
+        for world in &self.worlds {
+            // Kicking removes the player, so the lock can't be held
+            let players: Vec<_> = world
+                .current_players
+                .lock()
+                .await
+                .values()
+                .cloned()
+                .collect();
+            for player in players {
+                if !self.is_whitelisted(&player.gameprofile.id).await {
+                    player
+                        .kick(ADVANCED_CONFIG.get().messages.whitelist_rejected())
+                        .await;
+                }
+            }
+        }
+    }
+
     pub fn encryption_request<'a>(
         &'a self,
         verification_token: &'a [u8; 4],
@@ -11,9 +11,9 @@ pub struct Ticker {
 
 impl Ticker {
     #[must_use]
-    pub fn new(tps: f32) -> Self {
+    pub fn new(tick_interval: Duration) -> Self {
         Self {
-            tick_interval: Duration::from_millis((1000.0 / tps) as u64),
+            tick_interval,
             last_tick: Instant::now(),
         }
     }
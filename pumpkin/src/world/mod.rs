@@ -12,7 +12,7 @@ use crate::{
         Entity,
     },
 };
-use pumpkin_config::BasicConfiguration;
+use pumpkin_config::{BasicConfiguration, ADVANCED_CONFIG};
 use pumpkin_core::math::vector2::Vector2;
 use pumpkin_core::math::{position::WorldPosition, vector3::Vector3};
 use pumpkin_core::text::{color::NamedColor, TextComponent};
@@ -29,6 +29,7 @@ use pumpkin_protocol::{
     ClientPacket, VarInt,
 };
 use pumpkin_world::chunk::ChunkData;
+use pumpkin_world::dimension::Dimension;
 use pumpkin_world::level::Level;
 use pumpkin_world::{
     block::block_registry::{
@@ -71,13 +72,22 @@ pub struct World {
     pub scoreboard: Mutex<Scoreboard>,
     /// The world's worldborder, defining the playable area and controlling its expansion or contraction.
     pub worldborder: Mutex<Worldborder>,
+    /// The dimension this world is in
+    pub dimension: Dimension,
     // TODO: entities
 }
 
 impl World {
+    /// The name of the world's dimension, used to look up per world settings
     #[must_use]
-    pub fn load(level: Level) -> Self {
+    pub fn name(&self) -> &'static str {
+        self.dimension.name()
+    }
+
+    #[must_use]
+    pub fn load(level: Level, dimension: Dimension) -> Self {
         Self {
+            dimension,
             level: Arc::new(level),
             current_players: Arc::new(Mutex::new(HashMap::new())),
             scoreboard: Mutex::new(Scoreboard::new()),
@@ -115,6 +125,31 @@ impl World {
         }
     }
 
+    /// Broadcasts a packet about an entity at `chunk` to the players within the entity tracking range,
+    /// excluding the specified players.
+    ///
+    /// **Note:** This function acquires a lock on the `current_players` map, ensuring thread safety.
+    // TODO: Use this for movement as well, once entities get spawned and removed when they enter and leave the range
+    pub async fn broadcast_packet_tracking<P>(
+        &self,
+        except: &[uuid::Uuid],
+        chunk: Vector2<i32>,
+        packet: &P,
+    ) where
+        P: ClientPacket,
+    {
+        let range = i32::from(ADVANCED_CONFIG.get().entity_tracking_range());
+        let current_players = self.current_players.lock().await;
+        for (_, player) in current_players.iter().filter(|c| !except.contains(c.0)) {
+            let player_chunk = player.living_entity.entity.chunk_pos.load();
+            if (player_chunk.x - chunk.x).abs() <= range
+                && (player_chunk.z - chunk.z).abs() <= range
+            {
+                player.client.send_packet(packet).await;
+            }
+        }
+    }
+
     pub async fn play_sound(
         &self,
         sound_id: u16,
@@ -140,6 +175,9 @@ impl World {
         for player in current_players.values() {
             player.tick().await;
         }
+        // TODO: Apply `ADVANCED_CONFIG.get().random_ticks_per_section` random ticks to every section within the
+        // simulation distance of a player once blocks have random tick behaviours, at the runtime value of the
+        // `randomTickSpeed` game rule rather than the initial `random_tick_speed`
     }
 
     /// Gets the y position of the first non air block from the top down
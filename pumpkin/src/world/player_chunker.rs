@@ -18,7 +18,7 @@ pub async fn get_view_distance(player: &Player) -> u8 {
         .lock()
         .await
         .view_distance
-        .clamp(2, BASIC_CONFIG.view_distance)
+        .clamp(2, BASIC_CONFIG.get().view_distance)
 }
 
 pub async fn player_join(world: &World, player: Arc<Player>) {